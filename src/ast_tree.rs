@@ -0,0 +1,228 @@
+use std::fmt::Write;
+
+use crate::{
+    formatter::printer::{print_event_type, print_import, print_label, quote},
+    metrics::element_kind,
+    parser::ast::{
+        AstDocument, EventDirection, Flow, FlowType, GatewayBranch, GatewayMode, ProcessElement,
+    },
+};
+
+struct TreeNode {
+    label: String,
+    children: Vec<Self>,
+}
+
+impl TreeNode {
+    const fn leaf(label: String) -> Self {
+        Self {
+            label,
+            children: Vec::new(),
+        }
+    }
+}
+
+// Imports and processes as root lines, with everything they contain drawn below
+// them using box-drawing connectors
+#[must_use]
+pub fn render_tree(document: &AstDocument) -> String {
+    let mut output = String::new();
+
+    for import in &document.imports {
+        let _ = writeln!(output, "{}", print_import(import));
+    }
+
+    for process in &document.processes {
+        let mut children: Vec<TreeNode> = process
+            .variables
+            .iter()
+            .map(|variable| {
+                TreeNode::leaf(format!("var {}: {}", variable.name, variable.variable_type))
+            })
+            .collect();
+        children.extend(element_nodes(&process.elements));
+        children.extend(flow_nodes(&process.flows));
+        render(
+            &TreeNode {
+                label: format!("process {}", process.name),
+                children,
+            },
+            &mut output,
+        );
+    }
+
+    output
+}
+
+fn render(root: &TreeNode, output: &mut String) {
+    let _ = writeln!(output, "{}", root.label);
+    render_children(&root.children, "", output);
+}
+
+fn render_children(children: &[TreeNode], prefix: &str, output: &mut String) {
+    for (index, child) in children.iter().enumerate() {
+        let last = index + 1 == children.len();
+        let (connector, indent) = if last {
+            ("└── ", "    ")
+        } else {
+            ("├── ", "│   ")
+        };
+        let _ = writeln!(output, "{prefix}{connector}{}", child.label);
+        render_children(&child.children, &format!("{prefix}{indent}"), output);
+    }
+}
+
+fn element_nodes(elements: &[ProcessElement]) -> Vec<TreeNode> {
+    elements.iter().map(element_node).collect()
+}
+
+fn flow_nodes(flows: &[Flow]) -> impl Iterator<Item = TreeNode> + '_ {
+    flows.iter().map(|flow| {
+        let arrow = match flow.flow_type {
+            FlowType::Sequence => "->",
+            FlowType::Message => "-->",
+            FlowType::Default => "=>",
+            FlowType::Association => "..>",
+        };
+        let mut label = format!("{} {arrow} {}", flow.from, flow.to);
+        if let Some(condition) = &flow.condition {
+            let _ = write!(label, " [{condition}]");
+        }
+        TreeNode::leaf(label)
+    })
+}
+
+fn branch_node(branch: &GatewayBranch) -> TreeNode {
+    if branch.is_default {
+        return TreeNode::leaf(format!("=> {}", branch.target));
+    }
+
+    let condition = branch
+        .condition
+        .as_ref()
+        .map_or_else(|| "true".to_string(), ToString::to_string);
+    TreeNode::leaf(format!("[{condition}] -> {}", branch.target))
+}
+
+#[allow(clippy::too_many_lines)]
+fn element_node(element: &ProcessElement) -> TreeNode {
+    let kind = element_kind(element);
+    let name = |id: Option<&String>| id.map_or_else(String::new, |id| format!(" {id}"));
+
+    match element {
+        ProcessElement::StartEvent { id, event_type, .. }
+        | ProcessElement::EndEvent { id, event_type, .. } => TreeNode::leaf(format!(
+            "{kind}{}{}",
+            name(id.as_ref()),
+            print_event_type(event_type.as_ref())
+        )),
+        ProcessElement::IntermediateEvent {
+            id,
+            event_type,
+            direction,
+            ..
+        } => {
+            let direction = match direction {
+                EventDirection::Throw => " throw",
+                EventDirection::Catch => "",
+            };
+            TreeNode::leaf(format!(
+                "{kind}{direction}{}{}",
+                name(id.as_ref()),
+                print_event_type(Some(event_type))
+            ))
+        }
+        ProcessElement::Task { id, label, .. } => {
+            TreeNode::leaf(format!("{kind} {id}{}", print_label(label.as_deref())))
+        }
+        ProcessElement::CallActivity {
+            id,
+            label,
+            called_element,
+            ..
+        } => {
+            let mut text = format!("{kind} {id}{}", print_label(label.as_deref()));
+            if called_element != id {
+                let _ = write!(text, " ({called_element})");
+            }
+            TreeNode::leaf(text)
+        }
+        ProcessElement::Gateway {
+            id, mode, branches, ..
+        } => {
+            let modifier = match mode {
+                GatewayMode::Split => "",
+                GatewayMode::Join => " join",
+                GatewayMode::Mixed => " mixed",
+            };
+            TreeNode {
+                label: format!("{kind}{}{modifier}", name(id.as_ref())),
+                children: branches.iter().map(branch_node).collect(),
+            }
+        }
+        ProcessElement::Subprocess {
+            id,
+            label,
+            elements,
+            flows,
+            ..
+        } => {
+            let mut children = element_nodes(elements);
+            children.extend(flow_nodes(flows));
+            TreeNode {
+                label: format!("{kind} {id}{}", print_label(label.as_deref())),
+                children,
+            }
+        }
+        ProcessElement::EventSubprocess {
+            id,
+            trigger,
+            elements,
+            flows,
+            ..
+        } => {
+            let mut children = element_nodes(elements);
+            children.extend(flow_nodes(flows));
+            TreeNode {
+                label: format!("{kind} {id}{}", print_event_type(Some(trigger))),
+                children,
+            }
+        }
+        ProcessElement::Pool {
+            name: pool,
+            lanes,
+            elements,
+            flows,
+            ..
+        } => {
+            let mut children: Vec<TreeNode> = lanes
+                .iter()
+                .map(|lane| TreeNode {
+                    label: format!("lane {}", lane.name),
+                    children: element_nodes(&lane.elements),
+                })
+                .collect();
+            children.extend(element_nodes(elements));
+            children.extend(flow_nodes(flows));
+            TreeNode {
+                label: format!("{kind} {pool}"),
+                children,
+            }
+        }
+        ProcessElement::Group {
+            label, elements, ..
+        } => TreeNode {
+            label: format!("{kind} {}", quote(label)),
+            children: element_nodes(elements),
+        },
+        ProcessElement::Annotation { id, text, .. } => {
+            TreeNode::leaf(format!("{kind}{} {}", name(id.as_ref()), quote(text)))
+        }
+        ProcessElement::DataObject {
+            id, label, store, ..
+        } => {
+            let kind = if *store { "data store" } else { kind };
+            TreeNode::leaf(format!("{kind} {id}{}", print_label(label.as_deref())))
+        }
+    }
+}
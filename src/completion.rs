@@ -0,0 +1,133 @@
+use serde::Serialize;
+
+use crate::{
+    diagnostics::{
+        context_validator::{is_flow_arrow, is_statement_start},
+        suggestions::{ATTRIBUTE_NAMES, BPMN_KEYWORDS, EVENT_TYPES},
+    },
+    lexer::{Token, TokenKind},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompletionKind {
+    Keyword,
+    EventType,
+    Attribute,
+    Element,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Completion {
+    pub kind: CompletionKind,
+    pub insert_text: String,
+}
+
+// Candidates for the word at `byte_offset`, chosen by the token in front of it
+// and narrowed to the part of the word already typed
+#[must_use]
+pub fn complete_at(tokens: &[Token], byte_offset: usize) -> Vec<Completion> {
+    let (index, prefix) = word_at(tokens, byte_offset);
+    let previous = previous_token(tokens, index);
+
+    let (kind, candidates) = match previous.map(|token| &token.kind) {
+        Some(TokenKind::At) => (CompletionKind::EventType, owned(EVENT_TYPES)),
+        Some(kind) if is_flow_arrow(kind) => (CompletionKind::Element, element_ids(tokens, index)),
+        Some(TokenKind::LeftParen | TokenKind::Comma) if inside_parens(tokens, index) => {
+            (CompletionKind::Attribute, owned(ATTRIBUTE_NAMES))
+        }
+        _ if is_statement_start(tokens, index) => (CompletionKind::Keyword, owned(BPMN_KEYWORDS)),
+        _ => return Vec::new(),
+    };
+
+    candidates
+        .into_iter()
+        .filter(|candidate| candidate.starts_with(prefix) && candidate != prefix)
+        .map(|insert_text| Completion { kind, insert_text })
+        .collect()
+}
+
+// The index the completed word occupies and the part of it left of the cursor
+fn word_at(tokens: &[Token], byte_offset: usize) -> (usize, &str) {
+    let index = tokens.partition_point(|token| token.span.end < byte_offset);
+
+    if let Some(token) = tokens.get(index)
+        && token.span.start < byte_offset
+        && token
+            .text
+            .starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && let Some(prefix) = token.text.get(..byte_offset - token.span.start)
+    {
+        return (index, prefix);
+    }
+
+    (
+        tokens.partition_point(|token| token.span.start < byte_offset),
+        "",
+    )
+}
+
+fn previous_token(tokens: &[Token], index: usize) -> Option<&Token> {
+    tokens[..index.min(tokens.len())]
+        .iter()
+        .rev()
+        .find(|token| !matches!(token.kind, TokenKind::LineComment | TokenKind::BlockComment))
+}
+
+fn inside_parens(tokens: &[Token], index: usize) -> bool {
+    for token in tokens[..index.min(tokens.len())].iter().rev() {
+        match token.kind {
+            TokenKind::LeftParen => return true,
+            TokenKind::RightParen | TokenKind::LeftBrace | TokenKind::RightBrace => return false,
+            _ => {}
+        }
+    }
+
+    false
+}
+
+// Names declared in the process around `index`
+fn element_ids(tokens: &[Token], index: usize) -> Vec<String> {
+    let start = tokens[..index.min(tokens.len())]
+        .iter()
+        .rposition(|token| token.kind == TokenKind::Process)
+        .unwrap_or(0);
+
+    let mut ids: Vec<String> = Vec::new();
+    let mut depth = 0usize;
+    for pair in tokens[start..].windows(2) {
+        match pair[0].kind {
+            TokenKind::LeftBrace => depth += 1,
+            TokenKind::RightBrace => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    break;
+                }
+            }
+            TokenKind::Task
+            | TokenKind::User
+            | TokenKind::Service
+            | TokenKind::Script
+            | TokenKind::Call
+            | TokenKind::Xor
+            | TokenKind::And
+            | TokenKind::Or
+            | TokenKind::Event
+            | TokenKind::Subprocess
+            | TokenKind::Pool
+            | TokenKind::Data
+            | TokenKind::Note
+                if pair[1].kind == TokenKind::Identifier && !ids.contains(&pair[1].text) =>
+            {
+                ids.push(pair[1].text.clone());
+            }
+            _ => {}
+        }
+    }
+
+    ids
+}
+
+fn owned(names: &[&str]) -> Vec<String> {
+    names.iter().map(ToString::to_string).collect()
+}
@@ -0,0 +1,66 @@
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+pub use crate::diagnostics::LintLevel;
+use thiserror::Error;
+
+pub const CONFIG_FILE_NAME: &str = ".bpmncode.toml";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub format: Option<String>,
+    pub no_source: Option<bool>,
+    pub max_nesting_depth: Option<usize>,
+    pub indent_width: Option<usize>,
+    pub lints: BTreeMap<String, LintLevel>,
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+}
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Cannot read {path}: {source}")]
+    Io { path: PathBuf, source: io::Error },
+
+    #[error("Invalid config file {path}: {source}")]
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+}
+
+impl Config {
+    // Walks up from `start` to the first directory containing `.bpmncode.toml`
+    pub fn load(start: &Path) -> Result<Self, ConfigError> {
+        let start = std::path::absolute(start).map_err(|source| ConfigError::Io {
+            path: start.to_path_buf(),
+            source,
+        })?;
+
+        let Some(path) = start
+            .ancestors()
+            .map(|directory| directory.join(CONFIG_FILE_NAME))
+            .find(|candidate| candidate.is_file())
+        else {
+            return Ok(Self::default());
+        };
+
+        let content = fs::read_to_string(&path).map_err(|source| ConfigError::Io {
+            path: path.clone(),
+            source,
+        })?;
+        let mut config: Self = toml::from_str(&content).map_err(|source| ConfigError::Parse {
+            path: path.clone(),
+            source,
+        })?;
+        config.path = Some(path);
+
+        Ok(config)
+    }
+}
@@ -0,0 +1,85 @@
+use std::{collections::HashMap, fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{DiagnosticError, DiagnosticReport};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BaselineEntry {
+    pub file: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Baseline {
+    pub diagnostics: Vec<BaselineEntry>,
+}
+
+#[derive(Error, Debug)]
+pub enum BaselineError {
+    #[error("Cannot read baseline: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Invalid baseline file: {0}")]
+    Parse(#[from] serde_json::Error),
+}
+
+impl BaselineEntry {
+    #[must_use]
+    pub fn new(file: &str, error: &DiagnosticError) -> Self {
+        Self {
+            file: file.to_string(),
+            message: error.to_string(),
+        }
+    }
+}
+
+impl Baseline {
+    pub fn load(path: &Path) -> Result<Self, BaselineError> {
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), BaselineError> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn from_reports(reports: &[DiagnosticReport]) -> Self {
+        let mut baseline = Self::default();
+        for report in reports {
+            baseline.record(report);
+        }
+        baseline
+    }
+
+    pub fn record(&mut self, report: &DiagnosticReport) {
+        self.diagnostics.extend(
+            report
+                .errors
+                .iter()
+                .map(|error| BaselineEntry::new(&report.file_path, error)),
+        );
+    }
+
+    pub fn retain_new(&self, report: &mut DiagnosticReport) {
+        let mut known: HashMap<&BaselineEntry, usize> = HashMap::new();
+        for entry in &self.diagnostics {
+            *known.entry(entry).or_insert(0) += 1;
+        }
+
+        let file_path = report.file_path.clone();
+        report.errors.retain(|error| {
+            let entry = BaselineEntry::new(&file_path, error);
+            match known.get_mut(&entry) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    false
+                }
+                _ => true,
+            }
+        });
+    }
+}
@@ -20,13 +20,18 @@ impl ContextValidator {
     pub fn validate_tokens(&mut self, tokens: &[Token]) -> Vec<DiagnosticError> {
         self.errors.clear();
 
+        let mut brackets = 0usize;
         for (i, token) in tokens.iter().enumerate() {
             match &token.kind {
+                TokenKind::LeftBracket => brackets += 1,
+                TokenKind::RightBracket => brackets = brackets.saturating_sub(1),
                 TokenKind::Identifier => {
                     self.check_identifier_typo(token, tokens, i);
                 }
-                TokenKind::Unknown => {
-                    self.check_unknown_token(token);
+                // Conditions read `-5` as a negative number
+                TokenKind::Unknown if brackets > 0 && is_minus_sign(tokens, i) => {}
+                TokenKind::Unknown if !continues_unknown_run(tokens, i) => {
+                    self.check_unknown_run(tokens, i);
                 }
                 _ => {}
             }
@@ -41,16 +46,17 @@ impl ContextValidator {
     fn check_identifier_typo(&mut self, token: &Token, tokens: &[Token], index: usize) {
         let identifier = &token.text;
 
-        if self.is_contextual_identifier(tokens, index) {
+        if is_contextual_identifier(tokens, index) {
             return;
         }
 
-        if self.is_statement_start(tokens, index) {
+        if is_statement_start(tokens, index) {
             if let Some(suggestion) = detect_keyword_typo(identifier) {
                 self.errors.push(DiagnosticError::UnexpectedToken {
                     found: identifier.clone(),
                     expected: format!("keyword (did you mean '{suggestion}'?)"),
                     span: token.span.clone(),
+                    severity: Severity::Error,
                     suggestions: vec![suggestion],
                 });
             } else if is_likely_keyword_typo(identifier) {
@@ -59,32 +65,48 @@ impl ContextValidator {
                     found: identifier.clone(),
                     expected: "BPMN keyword".to_string(),
                     span: token.span.clone(),
+                    severity: Severity::Error,
                     suggestions,
                 });
             }
         }
     }
 
-    fn check_unknown_token(&mut self, token: &Token) {
-        if matches!(token.text.as_str(), "<" | ">" | "=" | "!" | "&" | "|") {
-            return;
-        }
+    // A run of adjacent unknown characters is reported once
+    fn check_unknown_run(&mut self, tokens: &[Token], start: usize) {
+        let length = 1 + tokens[start + 1..]
+            .iter()
+            .enumerate()
+            .take_while(|(offset, _)| continues_unknown_run(tokens, start + 1 + offset))
+            .count();
+        let run = &tokens[start..start + length];
+
+        let message = if let [token] = run {
+            format!("Unknown token '{}'", token.text)
+        } else {
+            let text: String = run.iter().map(|token| token.text.as_str()).collect();
+            format!("Unexpected characters '{text}'")
+        };
 
         self.errors.push(DiagnosticError::SyntaxError {
-            message: format!("Unknown token '{}'", token.text),
-            span: token.span.clone(),
+            message,
+            span: Span {
+                end: run[length - 1].span.end,
+                ..run[0].span.clone()
+            },
             severity: Severity::Error,
             suggestions: Vec::new(),
+            lint: "syntax",
         });
     }
 
     fn check_flow_syntax(&mut self, tokens: &[Token]) {
         for (i, token) in tokens.iter().enumerate() {
             if token.text == "-" {
-                if let Some(next_token) = tokens.get(i + 1) {
-                    if next_token.text == ">" {
-                        continue;
-                    }
+                if let Some(next_token) = tokens.get(i + 1)
+                    && next_token.text == ">"
+                {
+                    continue;
                 }
 
                 if self.looks_like_flow_context(tokens, i) {
@@ -93,45 +115,97 @@ impl ContextValidator {
                         span: token.span.clone(),
                         severity: Severity::Error,
                         suggestions: vec!["->".to_string()],
+                        lint: "syntax",
                     });
                 }
             }
         }
     }
 
+    // `and` and `or` also appear inside conditions, only a keyword that begins an
+    // element outside `[...]` opens a gateway
     fn check_missing_braces(&mut self, tokens: &[Token]) {
+        let mut brackets = 0usize;
         for (i, token) in tokens.iter().enumerate() {
-            if matches!(token.kind, TokenKind::Xor | TokenKind::And) {
-                self.check_gateway_braces(tokens, i);
+            match token.kind {
+                TokenKind::LeftBracket => brackets += 1,
+                TokenKind::RightBracket => brackets = brackets.saturating_sub(1),
+                TokenKind::Xor | TokenKind::And | TokenKind::Or
+                    if brackets == 0 && is_element_start(tokens, i) =>
+                {
+                    self.check_gateway_braces(tokens, i);
+                }
+                _ => {}
             }
         }
     }
 
     fn check_gateway_braces(&mut self, tokens: &[Token], gateway_index: usize) {
         let token = &tokens[gateway_index];
-        let gateway_type = if matches!(token.kind, TokenKind::Xor) {
-            "XOR"
-        } else {
-            "AND"
+        let gateway_type = match token.kind {
+            TokenKind::Xor => "XOR",
+            TokenKind::Or => "OR",
+            _ => "AND",
         };
 
         let mut j = gateway_index + 1;
         let mut gateway_name_end = token.span.end;
 
-        if let Some(next) = tokens.get(j) {
-            if matches!(next.kind, TokenKind::Identifier) {
-                gateway_name_end = next.span.end;
-                j += 1;
-            }
+        if let Some(next) = tokens.get(j)
+            && matches!(next.kind, TokenKind::Identifier)
+            && !is_gateway_mode(&next.text)
+        {
+            gateway_name_end = next.span.end;
+            j += 1;
+        }
+
+        // A join only merges incoming flows, so its branch block is optional
+        let mut is_join = false;
+        if let Some(next) = tokens.get(j)
+            && next.kind == TokenKind::Identifier
+            && is_gateway_mode(&next.text)
+        {
+            is_join = next.text == "join";
+            gateway_name_end = next.span.end;
+            j += 1;
+        }
+
+        if let Some(next) = tokens.get(j)
+            && matches!(next.kind, TokenKind::Question)
+        {
+            gateway_name_end = next.span.end;
+            j += 1;
         }
 
-        if let Some(next) = tokens.get(j) {
-            if matches!(next.kind, TokenKind::Question) {
-                gateway_name_end = next.span.end;
+        // Attributes sit between the name and the branches, read the way
+        // `Parser::parse_attributes` reads them
+        while tokens.get(j).is_some_and(|next| next.kind == TokenKind::At) {
+            j += 2;
+            if tokens
+                .get(j)
+                .is_some_and(|next| next.kind == TokenKind::Colon)
+            {
+                j += 2;
+            }
+            if tokens.get(j).is_some_and(|next| {
+                matches!(
+                    next.kind,
+                    TokenKind::StringLiteral | TokenKind::NumberLiteral | TokenKind::Identifier
+                )
+            }) {
                 j += 1;
             }
         }
 
+        if let Some(start) = self.find_next_significant_token(tokens, j)
+            && matches!(tokens[start].kind, TokenKind::LeftParen)
+            && let Some(close) = tokens[start..]
+                .iter()
+                .position(|token| matches!(token.kind, TokenKind::RightParen))
+        {
+            j = start + close + 1;
+        }
+
         let gateway_span = Span {
             start: token.span.start,
             end: gateway_name_end,
@@ -153,10 +227,11 @@ impl ContextValidator {
                         span: gateway_span,
                         severity: Severity::Error,
                         suggestions: vec!["}".to_string()],
+                        lint: "syntax",
                     });
                 }
             }
-        } else if self.has_gateway_conditions_ahead(tokens, j) {
+        } else if !is_join && self.has_gateway_conditions_ahead(tokens, j) {
             self.errors.push(DiagnosticError::SyntaxError {
                 message: format!(
                     "{gateway_type} gateway missing opening brace '{{' before conditions"
@@ -164,6 +239,7 @@ impl ContextValidator {
                 span: gateway_span,
                 severity: Severity::Error,
                 suggestions: vec!["{".to_string()],
+                lint: "syntax",
             });
         }
     }
@@ -209,11 +285,21 @@ impl ContextValidator {
             return None;
         }
 
+        // An empty `{}` is a complete block, the validator warns about it
         let mut brace_count = 1;
-        let mut found_gateway_content = false;
+        let mut brackets = 0usize;
+        let mut found_gateway_content = self
+            .find_next_significant_token(tokens, open_idx + 1)
+            .is_some_and(|idx| tokens[idx].kind == TokenKind::RightBrace);
 
         for i in (open_idx + 1)..tokens.len() {
             match tokens[i].kind {
+                TokenKind::LeftBracket => {
+                    found_gateway_content |= brace_count == 1;
+                    brackets += 1;
+                }
+                TokenKind::RightBracket => brackets = brackets.saturating_sub(1),
+                _ if brackets > 0 => {}
                 TokenKind::LeftBrace => brace_count += 1,
                 TokenKind::RightBrace => {
                     brace_count -= 1;
@@ -221,21 +307,20 @@ impl ContextValidator {
                         return if found_gateway_content { Some(i) } else { None };
                     }
                 }
-                TokenKind::LeftBracket | TokenKind::DefaultFlow | TokenKind::SequenceFlow => {
-                    if brace_count == 1 {
-                        found_gateway_content = true;
-                    }
+                TokenKind::DefaultFlow | TokenKind::SequenceFlow if brace_count == 1 => {
+                    found_gateway_content = true;
                 }
                 TokenKind::Xor
                 | TokenKind::And
+                | TokenKind::Or
                 | TokenKind::Task
                 | TokenKind::User
                 | TokenKind::Service
                 | TokenKind::Script
-                | TokenKind::End => {
-                    if brace_count == 1 {
-                        return None;
-                    }
+                | TokenKind::End
+                    if brace_count == 1 =>
+                {
+                    return None;
                 }
                 _ => {}
             }
@@ -244,82 +329,99 @@ impl ContextValidator {
     }
 
     #[allow(clippy::unused_self)]
-    fn is_contextual_identifier(&self, tokens: &[Token], index: usize) -> bool {
-        if let Some(next) = tokens.get(index + 1) {
-            if matches!(next.kind, TokenKind::LeftParen) {
-                return true;
-            }
+    fn looks_like_flow_context(&self, tokens: &[Token], index: usize) -> bool {
+        if index > 0
+            && let Some(prev) = tokens.get(index - 1)
+            && matches!(prev.kind, TokenKind::Identifier)
+            && let Some(next) = tokens.get(index + 1)
+            && matches!(next.kind, TokenKind::Identifier)
+        {
+            return true;
         }
 
-        if index > 0 {
-            if let Some(prev) = tokens.get(index - 1) {
-                if matches!(
-                    prev.kind,
-                    TokenKind::SequenceFlow
-                        | TokenKind::MessageFlow
-                        | TokenKind::DefaultFlow
-                        | TokenKind::Association
-                ) {
-                    return true;
-                }
-            }
-        }
+        false
+    }
+}
 
-        if let Some(next) = tokens.get(index + 1) {
-            if matches!(
-                next.kind,
-                TokenKind::SequenceFlow
-                    | TokenKind::MessageFlow
-                    | TokenKind::DefaultFlow
-                    | TokenKind::Association
-            ) {
-                return true;
-            }
-        }
+fn is_minus_sign(tokens: &[Token], index: usize) -> bool {
+    tokens[index].text == "-"
+        && !continues_unknown_run(tokens, index)
+        && tokens
+            .get(index + 1)
+            .is_some_and(|next| next.kind == TokenKind::NumberLiteral)
+}
 
-        if let Some(next) = tokens.get(index + 1) {
-            if next.text == "-" {
-                return true;
-            }
-        }
+fn continues_unknown_run(tokens: &[Token], index: usize) -> bool {
+    index > 0
+        && tokens[index].kind == TokenKind::Unknown
+        && tokens[index - 1].kind == TokenKind::Unknown
+        && tokens[index - 1].span.end == tokens[index].span.start
+}
 
-        false
+pub(crate) const fn is_flow_arrow(kind: &TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::SequenceFlow
+            | TokenKind::MessageFlow
+            | TokenKind::DefaultFlow
+            | TokenKind::Association
+    )
+}
+
+// Identifiers next to a flow arrow, an attribute list or a namespace separator are
+// element names, not keywords
+pub(crate) fn is_contextual_identifier(tokens: &[Token], index: usize) -> bool {
+    if let Some(next) = tokens.get(index + 1)
+        && (matches!(next.kind, TokenKind::LeftParen | TokenKind::Namespace)
+            || is_flow_arrow(&next.kind)
+            || next.text == "-")
+    {
+        return true;
     }
 
-    #[allow(clippy::needless_continue)]
-    #[allow(clippy::unused_self)]
-    fn is_statement_start(&self, tokens: &[Token], index: usize) -> bool {
-        if index == 0 {
-            return true;
-        }
+    index > 0
+        && tokens
+            .get(index - 1)
+            .is_some_and(|prev| is_flow_arrow(&prev.kind))
+}
 
-        for i in (0..index).rev() {
-            match &tokens[i].kind {
-                TokenKind::LeftBrace | TokenKind::RightBrace | TokenKind::Newline => return true,
-                TokenKind::Identifier | TokenKind::StringLiteral | TokenKind::NumberLiteral => {
-                    continue;
-                }
-                _ => return false,
-            }
-        }
+fn is_gateway_mode(text: &str) -> bool {
+    matches!(text, "split" | "join" | "mixed")
+}
 
-        false
+// After a line break, a brace or a flow arrow, e.g. the `xor` of `A -> xor Done? {`
+fn is_element_start(tokens: &[Token], index: usize) -> bool {
+    tokens[..index]
+        .iter()
+        .rev()
+        .find(|token| !matches!(token.kind, TokenKind::LineComment | TokenKind::BlockComment))
+        .is_none_or(|token| {
+            is_flow_arrow(&token.kind)
+                || matches!(
+                    token.kind,
+                    TokenKind::Newline
+                        | TokenKind::CarriageReturnNewline
+                        | TokenKind::LeftBrace
+                        | TokenKind::RightBrace
+                )
+        })
+}
+
+#[allow(clippy::needless_continue)]
+pub(crate) fn is_statement_start(tokens: &[Token], index: usize) -> bool {
+    if index == 0 {
+        return true;
     }
 
-    #[allow(clippy::unused_self)]
-    fn looks_like_flow_context(&self, tokens: &[Token], index: usize) -> bool {
-        if index > 0 {
-            if let Some(prev) = tokens.get(index - 1) {
-                if matches!(prev.kind, TokenKind::Identifier) {
-                    if let Some(next) = tokens.get(index + 1) {
-                        if matches!(next.kind, TokenKind::Identifier) {
-                            return true;
-                        }
-                    }
-                }
+    for i in (0..index).rev() {
+        match &tokens[i].kind {
+            TokenKind::LeftBrace | TokenKind::RightBrace | TokenKind::Newline => return true,
+            TokenKind::Identifier | TokenKind::StringLiteral | TokenKind::NumberLiteral => {
+                continue;
             }
+            _ => return false,
         }
-
-        false
     }
+
+    false
 }
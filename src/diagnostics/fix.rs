@@ -0,0 +1,70 @@
+use std::{cmp::Reverse, ops::Range};
+
+use super::{DiagnosticError, DiagnosticReport};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fix {
+    pub range: Range<usize>,
+    pub replacement: String,
+}
+
+impl Fix {
+    // A suggestion replaces the span only when the span holds the very token or name the
+    // error is about, other suggestions (like a missing brace) describe what to add
+    #[must_use]
+    pub fn for_error(error: &DiagnosticError, source: &str) -> Option<Self> {
+        let [replacement] = error.suggestions() else {
+            return None;
+        };
+        let subject = match error {
+            DiagnosticError::UnexpectedToken { found, .. } => found,
+            DiagnosticError::UndefinedReference { name, .. } => name,
+            DiagnosticError::InvalidAttribute { attribute, .. } => attribute,
+            _ => return None,
+        };
+        let span = error.span();
+        let text = source.get(span.start..span.end)?;
+
+        if text != subject || text == replacement {
+            return None;
+        }
+
+        Some(Self {
+            range: span.start..span.end,
+            replacement: replacement.clone(),
+        })
+    }
+}
+
+#[must_use]
+pub fn collect_fixes(report: &DiagnosticReport) -> Vec<Fix> {
+    report
+        .errors
+        .iter()
+        .filter(|error| !report.imported_sources.contains_key(&error.span().file))
+        .filter_map(|error| Fix::for_error(error, &report.source_code))
+        .collect()
+}
+
+// Applies fixes back to front so earlier offsets stay valid, overlapping fixes are skipped
+#[must_use]
+pub fn apply_fixes(source: &str, mut fixes: Vec<Fix>) -> (String, usize) {
+    fixes.sort_by_key(|fix| Reverse(fix.range.start));
+    fixes.dedup();
+
+    let mut output = source.to_string();
+    let mut limit = source.len();
+    let mut applied = 0;
+
+    for fix in fixes {
+        if fix.range.end > limit {
+            continue;
+        }
+
+        output.replace_range(fix.range.clone(), &fix.replacement);
+        limit = fix.range.start;
+        applied += 1;
+    }
+
+    (output, applied)
+}
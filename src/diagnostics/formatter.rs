@@ -1,11 +1,19 @@
-use super::{DiagnosticError, DiagnosticReport, Severity};
+use super::{DiagnosticError, DiagnosticReport, Severity, SourcedDiagnostic};
+use crate::lexer::{Span, line_starts};
 use colored::Colorize;
-use miette::{GraphicalReportHandler, GraphicalTheme, NamedSource};
+use miette::{GraphicalReportHandler, GraphicalTheme, NamedSource, ThemeStyles};
 use serde_json;
+use std::{fmt::Write, path::Path};
+
+const MIN_MESSAGE_WIDTH: usize = 20;
+const SOURCE_GUTTER_WIDTH: usize = 7;
 
 pub struct DiagnosticFormatter {
     use_colors: bool,
     show_source: bool,
+    width: Option<usize>,
+    max_errors: Option<usize>,
+    show_paths: bool,
 }
 
 impl DiagnosticFormatter {
@@ -14,9 +22,41 @@ impl DiagnosticFormatter {
         Self {
             use_colors,
             show_source,
+            width: None,
+            max_errors: None,
+            show_paths: true,
         }
     }
 
+    #[must_use]
+    pub const fn with_width(mut self, width: Option<usize>) -> Self {
+        self.width = width;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_max_errors(mut self, max_errors: Option<usize>) -> Self {
+        self.max_errors = max_errors;
+        self
+    }
+
+    // Without paths, locations in the checked file print as `line:column`. Spans in
+    // other files, such as imports, keep their path
+    #[must_use]
+    pub const fn with_paths(mut self, show_paths: bool) -> Self {
+        self.show_paths = show_paths;
+        self
+    }
+
+    // The diagnostics of `report` to print and how many were left out
+    fn shown<'a>(&self, report: &'a DiagnosticReport) -> (&'a [DiagnosticError], usize) {
+        let limit = self
+            .max_errors
+            .unwrap_or(usize::MAX)
+            .min(report.errors.len());
+        (&report.errors[..limit], report.errors.len() - limit)
+    }
+
     #[allow(clippy::format_push_string)]
     #[must_use]
     pub fn format_cli(&self, report: &DiagnosticReport) -> String {
@@ -36,90 +76,294 @@ impl DiagnosticFormatter {
             output.push_str(&format!("Checking: {}\n", report.file_path));
         }
 
-        for error in &report.errors {
-            output.push_str(&self.format_error_cli(error, &report.source_code));
+        let (shown, hidden) = self.shown(report);
+        for error in shown {
+            output.push_str(&self.format_error_cli(error, report));
             output.push('\n');
         }
+        output.push_str(&format_hidden(hidden));
+
+        output.push_str(&self.format_failure_summary(report));
 
+        output
+    }
+
+    #[allow(clippy::format_push_string)]
+    fn format_failure_summary(&self, report: &DiagnosticReport) -> String {
         let error_count = report.error_count();
         let warning_count = report.warning_count();
 
-        if error_count > 0 {
-            if self.use_colors {
-                output.push_str(&format!(
-                    "\n{} {} - {} found\n",
-                    "✗".red().bold(),
-                    report.file_path.cyan(),
-                    self.format_count_text(error_count, warning_count).red()
-                ));
-            } else {
-                output.push_str(&format!(
-                    "\n✗ {} - {} found\n",
-                    report.file_path,
-                    self.format_count_text(error_count, warning_count)
-                ));
-            }
+        if error_count == 0 {
+            return String::new();
         }
 
-        output
+        if self.use_colors {
+            format!(
+                "\n{} {} - {} found\n",
+                "✗".red().bold(),
+                report.file_path.cyan(),
+                self.format_count_text(error_count, warning_count).red()
+            )
+        } else {
+            format!(
+                "\n✗ {} - {} found\n",
+                report.file_path,
+                self.format_count_text(error_count, warning_count)
+            )
+        }
     }
 
     pub fn format_json(&self, report: &DiagnosticReport) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(&self.json_report(report))
+    }
+
+    // Several files are emitted as one array so the output stays a single JSON document
+    pub fn format_json_reports(
+        &self,
+        reports: &[DiagnosticReport],
+    ) -> Result<String, serde_json::Error> {
+        let json_reports: Vec<JsonDiagnostic> = reports
+            .iter()
+            .map(|report| self.json_report(report))
+            .collect();
+        serde_json::to_string_pretty(&json_reports)
+    }
+
+    fn json_report(&self, report: &DiagnosticReport) -> JsonDiagnostic {
+        let (shown, hidden) = self.shown(report);
+        JsonDiagnostic::from_report(report, shown, hidden)
+    }
+
+    // One `file:line:column: severity: message` line per diagnostic
+    #[must_use]
+    pub fn format_short(&self, report: &DiagnosticReport) -> String {
+        let mut output = String::new();
+
+        let (shown, hidden) = self.shown(report);
+        for error in shown {
+            let span = error.span();
+            let _ = writeln!(
+                output,
+                "{}:{}:{}: {}: {}",
+                span.file.display(),
+                span.line,
+                span.column,
+                error.severity(),
+                error
+            );
+        }
+        output.push_str(&format_hidden(hidden));
+
+        output
+    }
+
+    #[must_use]
+    pub fn format_github(&self, report: &DiagnosticReport) -> String {
+        let mut output = String::new();
+
+        let (shown, hidden) = self.shown(report);
+        for error in shown {
+            let span = error.span();
+            let command = match error.severity() {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+                Severity::Info | Severity::Hint => "notice",
+            };
+
+            let _ = writeln!(
+                output,
+                "::{command} file={},line={},col={},title={}::{}",
+                escape_workflow_property(&diagnostic_file(span, report)),
+                span.line,
+                span.column,
+                error.code(),
+                escape_workflow_data(&error.to_string())
+            );
+        }
+        if hidden > 0 {
+            let _ = writeln!(
+                output,
+                "::notice file={}::{}",
+                escape_workflow_property(&report.file_path),
+                format_hidden(hidden).trim_end()
+            );
+        }
+
+        output
+    }
+
+    pub fn format_sarif(&self, reports: &[DiagnosticReport]) -> Result<String, serde_json::Error> {
+        #[derive(serde::Serialize)]
+        struct SarifLog {
+            #[serde(rename = "$schema")]
+            schema: &'static str,
+            version: &'static str,
+            runs: Vec<SarifRun>,
+        }
+
+        #[derive(serde::Serialize)]
+        struct SarifRun {
+            tool: SarifTool,
+            results: Vec<SarifResult>,
+        }
+
+        #[derive(serde::Serialize)]
+        struct SarifTool {
+            driver: SarifDriver,
+        }
+
+        #[derive(serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct SarifDriver {
+            name: &'static str,
+            semantic_version: &'static str,
+        }
+
+        #[derive(serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct SarifResult {
+            rule_id: &'static str,
+            level: &'static str,
+            message: SarifMessage,
+            locations: Vec<SarifLocation>,
+        }
+
+        #[derive(serde::Serialize)]
+        struct SarifMessage {
+            text: String,
+        }
+
         #[derive(serde::Serialize)]
-        struct JsonDiagnostic {
-            file: String,
-            errors: Vec<JsonError>,
-            summary: JsonSummary,
+        #[serde(rename_all = "camelCase")]
+        struct SarifLocation {
+            physical_location: SarifPhysicalLocation,
         }
 
         #[derive(serde::Serialize)]
-        struct JsonError {
-            severity: String,
-            message: String,
-            line: usize,
-            column: usize,
-            start: usize,
-            end: usize,
-            suggestions: Vec<String>,
-            code: Option<String>,
+        #[serde(rename_all = "camelCase")]
+        struct SarifPhysicalLocation {
+            artifact_location: SarifArtifactLocation,
+            region: SarifRegion,
         }
 
         #[derive(serde::Serialize)]
-        struct JsonSummary {
-            error_count: usize,
-            warning_count: usize,
-            has_errors: bool,
+        struct SarifArtifactLocation {
+            uri: String,
         }
 
-        let json_errors: Vec<JsonError> = report
-            .errors
+        #[derive(serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct SarifRegion {
+            start_line: usize,
+            start_column: usize,
+        }
+
+        let results = reports
             .iter()
-            .map(|error| {
-                let span = error.span();
-                JsonError {
-                    severity: error.severity().to_string(),
-                    message: error.to_string(),
-                    line: span.line,
-                    column: span.column,
-                    start: span.start,
-                    end: span.end,
-                    suggestions: error.suggestions().to_vec(),
-                    code: Some(self.extract_error_code(error)),
-                }
+            .flat_map(|report| {
+                self.shown(report).0.iter().map(|error| {
+                    let span = error.span();
+                    SarifResult {
+                        rule_id: error.code(),
+                        level: match error.severity() {
+                            Severity::Error => "error",
+                            Severity::Warning => "warning",
+                            Severity::Info | Severity::Hint => "note",
+                        },
+                        message: SarifMessage {
+                            text: error.to_string(),
+                        },
+                        locations: vec![SarifLocation {
+                            physical_location: SarifPhysicalLocation {
+                                artifact_location: SarifArtifactLocation {
+                                    uri: diagnostic_file(span, report).replace('\\', "/"),
+                                },
+                                region: SarifRegion {
+                                    start_line: span.line,
+                                    start_column: span.column,
+                                },
+                            },
+                        }],
+                    }
+                })
             })
             .collect();
 
-        let json_report = JsonDiagnostic {
-            file: report.file_path.clone(),
-            errors: json_errors,
-            summary: JsonSummary {
-                error_count: report.error_count(),
-                warning_count: report.warning_count(),
-                has_errors: report.has_errors(),
-            },
+        let log = SarifLog {
+            schema: "https://json.schemastore.org/sarif-2.1.0.json",
+            version: "2.1.0",
+            runs: vec![SarifRun {
+                tool: SarifTool {
+                    driver: SarifDriver {
+                        name: "BPMNCode",
+                        semantic_version: env!("CARGO_PKG_VERSION"),
+                    },
+                },
+                results,
+            }],
         };
 
-        serde_json::to_string_pretty(&json_report)
+        serde_json::to_string_pretty(&log)
+    }
+
+    #[allow(clippy::format_push_string)]
+    #[must_use]
+    pub fn format_review(&self, reports: &[DiagnosticReport]) -> String {
+        let total: usize = reports.iter().map(|report| report.errors.len()).sum();
+
+        if total == 0 {
+            return "### ✅ BPMNCode: no new diagnostics\n".to_string();
+        }
+
+        let mut output = format!(
+            "### BPMNCode: {total} new {}\n",
+            if total == 1 {
+                "diagnostic"
+            } else {
+                "diagnostics"
+            }
+        );
+
+        for report in reports.iter().filter(|report| !report.errors.is_empty()) {
+            output.push_str(&format!(
+                "\n<details open>\n<summary><code>{}</code> — {}</summary>\n\n",
+                report.file_path,
+                self.format_count_text(report.error_count(), report.warning_count())
+            ));
+            output.push_str("| | Code | Location | Message |\n");
+            output.push_str("|---|---|---|---|\n");
+
+            let (shown, hidden) = self.shown(report);
+            for error in shown {
+                let span = error.span();
+                let icon = match error.severity() {
+                    Severity::Error => "❌",
+                    Severity::Warning => "⚠️",
+                    Severity::Info | Severity::Hint => "ℹ️",
+                };
+
+                let mut message = error.to_string();
+                let suggestions = error.suggestions();
+                if !suggestions.is_empty() {
+                    message.push_str(&format!(" (did you mean: {})", suggestions.join(", ")));
+                }
+
+                output.push_str(&format!(
+                    "| {icon} | `{}` | {}:{} | {} |\n",
+                    error.code(),
+                    span.line,
+                    span.column,
+                    message.replace('|', "\\|").replace('\n', " ")
+                ));
+            }
+            if hidden > 0 {
+                output.push_str(&format!("| | | | {} |\n", format_hidden(hidden).trim_end()));
+            }
+
+            output.push_str("\n</details>\n");
+        }
+
+        output
     }
 
     #[must_use]
@@ -128,26 +372,48 @@ impl DiagnosticFormatter {
             return self.format_success_message(&report.file_path);
         }
 
-        let mut output = String::new();
-        let _source = NamedSource::new(&report.file_path, report.source_code.clone());
+        let handler = GraphicalReportHandler::new_themed(if self.use_colors {
+            GraphicalTheme::unicode()
+        } else {
+            GraphicalTheme {
+                styles: ThemeStyles::none(),
+                ..GraphicalTheme::ascii()
+            }
+        })
+        .with_width(self.width.unwrap_or(100));
 
-        let _handler = GraphicalReportHandler::new()
-            .with_theme(if self.use_colors {
-                GraphicalTheme::unicode()
-            } else {
-                GraphicalTheme::ascii()
-            })
-            .with_width(100);
+        let source = report.named_source();
+        let mut output = String::new();
+        let (shown, hidden) = self.shown(report);
+        for error in shown {
+            let file = &error.span().file;
+            let imported = report
+                .imported_sources
+                .get(file)
+                .map(|imported| NamedSource::new(file.display().to_string(), imported.clone()));
+            let diagnostic = SourcedDiagnostic {
+                error,
+                source_code: imported.as_ref().unwrap_or(&source),
+            };
+
+            // Spans that point outside the source cannot be drawn, fall back to the plain line
+            if handler.render_report(&mut output, &diagnostic).is_err() {
+                output.push_str(&self.format_error_cli(error, report));
+            }
+            output.push('\n');
+        }
+        output.push_str(&format_hidden(hidden));
 
-        output.push_str(&self.format_cli(report));
+        output.push_str(&self.format_failure_summary(report));
 
         output
     }
 
     #[allow(clippy::format_push_string)]
     #[allow(clippy::uninlined_format_args)]
-    fn format_error_cli(&self, error: &DiagnosticError, source: &str) -> String {
+    fn format_error_cli(&self, error: &DiagnosticError, report: &DiagnosticReport) -> String {
         let span = error.span();
+        let source = report.source_for(&span.file);
         let severity_icon = match error.severity() {
             Severity::Error => "error",
             Severity::Warning => "warning",
@@ -155,23 +421,52 @@ impl DiagnosticFormatter {
             Severity::Hint => "hint",
         };
 
-        let location = format!("{}:{}:{}", span.file.display(), span.line, span.column);
+        let location = if !self.show_paths && is_same_file(&span.file, Path::new(&report.file_path))
+        {
+            format!("{}:{}", span.line, span.column)
+        } else {
+            format!("{}:{}:{}", span.file.display(), span.line, span.column)
+        };
+
+        let mut message = error.to_string();
+        if let Some(width) = self.width {
+            let prefix_width = severity_icon.len() + location.chars().count() + 5;
+            message = truncate_text(
+                &message,
+                width.saturating_sub(prefix_width).max(MIN_MESSAGE_WIDTH),
+            );
+        }
 
         let mut output = if self.use_colors {
             format!(
                 "  {}: {} {}",
                 severity_icon.red().bold(),
                 location.blue(),
-                error
+                message
             )
         } else {
-            format!("  {}: {} {}", severity_icon, location, error)
+            format!("  {}: {} {}", severity_icon, location, message)
         };
 
-        if self.show_source {
-            if let Some(line) = self.get_source_line(source, span.line) {
-                output.push('\n');
-                output.push_str(&self.format_source_line(line, span.column, span.end - span.start));
+        if self.show_source
+            && let Some(line) = self.get_source_line(source, span.line)
+        {
+            // Only the part of the span on the printed line is underlined
+            let text = source.get(span.start..span.end).unwrap_or_default();
+            let length = text.lines().next().map_or(0, |first| first.chars().count());
+            let continues = text.trim_end_matches(['\r', '\n']).contains('\n');
+
+            output.push('\n');
+            output.push_str(&self.format_source_line(line, span.column, length, continues));
+        }
+
+        if let Some(first) = error.first_definition() {
+            output.push('\n');
+            let note = format!("first defined at line {}", first.line);
+            if self.use_colors {
+                output.push_str(&format!("    {}: {note}", "note".cyan().bold()));
+            } else {
+                output.push_str(&format!("    note: {note}"));
             }
         }
 
@@ -193,23 +488,40 @@ impl DiagnosticFormatter {
     }
 
     #[allow(clippy::format_push_string)]
-    fn format_source_line(&self, line: &str, column: usize, length: usize) -> String {
+    fn format_source_line(
+        &self,
+        line: &str,
+        column: usize,
+        length: usize,
+        continues: bool,
+    ) -> String {
         let mut output = String::new();
 
+        let line_length = line.chars().count();
+        let column = column.clamp(1, line_length + 1);
+        let length = length.min(line_length + 1 - column).max(1);
+
+        let (line, column, length) = self.width.map_or_else(
+            || (line.to_string(), column, length),
+            |width| source_window(line, column, length, width),
+        );
+        let marker = if continues { "..." } else { "" };
+
         if self.use_colors {
             output.push_str(&format!("    {} | {}\n", "".blue(), line));
             output.push_str(&format!(
-                "    {} | {}{}",
+                "    {} | {}{}{}",
                 "".blue(),
                 " ".repeat(column.saturating_sub(1)),
-                "^".repeat(length.max(1)).red().bold()
+                "^".repeat(length).red().bold(),
+                marker
             ));
         } else {
             output.push_str(&format!("    | {line}\n"));
             output.push_str(&format!(
-                "    | {}{}",
+                "    | {}{}{marker}",
                 " ".repeat(column.saturating_sub(1)),
-                "^".repeat(length.max(1))
+                "^".repeat(length)
             ));
         }
 
@@ -218,7 +530,10 @@ impl DiagnosticFormatter {
 
     #[allow(clippy::unused_self)]
     fn get_source_line<'a>(&self, source: &'a str, line_number: usize) -> Option<&'a str> {
-        source.lines().nth(line_number.saturating_sub(1))
+        let starts = line_starts(source);
+        let start = *starts.get(line_number.checked_sub(1)?)?;
+        let end = starts.get(line_number).copied().unwrap_or(source.len());
+        (start < source.len()).then(|| source[start..end].trim_end_matches(['\r', '\n']))
     }
 
     #[allow(clippy::uninlined_format_args)]
@@ -243,22 +558,158 @@ impl DiagnosticFormatter {
             (e, w) => format!("{e} errors, {w} warnings"),
         }
     }
+}
 
-    #[allow(clippy::unused_self)]
-    fn extract_error_code(&self, error: &DiagnosticError) -> String {
-        match error {
-            DiagnosticError::SyntaxError { .. } => "E001".to_string(),
-            DiagnosticError::UnexpectedToken { .. } => "E002".to_string(),
-            DiagnosticError::UndefinedReference { .. } => "E003".to_string(),
-            DiagnosticError::DuplicateIdentifier { .. } => "E004".to_string(),
-            DiagnosticError::InvalidAttribute { .. } => "E005".to_string(),
-            DiagnosticError::MissingElement { .. } => "E006".to_string(),
-            DiagnosticError::InvalidFlow { .. } => "E007".to_string(),
-            DiagnosticError::ImportError { .. } => "E008".to_string(),
+// The lexer records the path it opened, the report the one given on the command
+// line, e.g. `/work/p.bpmn` and `./p.bpmn`
+fn is_same_file(a: &Path, b: &Path) -> bool {
+    a == b || matches!((a.canonicalize(), b.canonicalize()), (Ok(a), Ok(b)) if a == b)
+}
+
+// Diagnostics in imported files name that file, the rest keep the path the report
+// was checked under
+fn diagnostic_file(span: &Span, report: &DiagnosticReport) -> String {
+    if span.file.as_os_str().is_empty() || is_same_file(&span.file, Path::new(&report.file_path)) {
+        report.file_path.clone()
+    } else {
+        span.file.display().to_string()
+    }
+}
+
+// Workflow commands are line based, so newlines and the characters that delimit
+// properties have to be percent-encoded
+fn escape_workflow_data(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+// Closes a list cut short by `--max-errors`, the counts in summaries stay complete
+fn format_hidden(hidden: usize) -> String {
+    if hidden == 0 {
+        String::new()
+    } else {
+        format!("… and {hidden} more\n")
+    }
+}
+
+fn escape_workflow_property(text: &str) -> String {
+    escape_workflow_data(text)
+        .replace(':', "%3A")
+        .replace(',', "%2C")
+}
+
+#[derive(serde::Serialize)]
+struct JsonDiagnostic {
+    file: String,
+    errors: Vec<JsonError>,
+    summary: JsonSummary,
+}
+
+#[derive(serde::Serialize)]
+struct JsonError {
+    severity: String,
+    message: String,
+    line: usize,
+    column: usize,
+    start: usize,
+    end: usize,
+    suggestions: Vec<String>,
+    code: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+struct JsonSummary {
+    error_count: usize,
+    warning_count: usize,
+    info_count: usize,
+    hint_count: usize,
+    has_errors: bool,
+    #[serde(skip_serializing_if = "is_zero")]
+    omitted: usize,
+}
+
+impl JsonDiagnostic {
+    fn from_report(report: &DiagnosticReport, shown: &[DiagnosticError], omitted: usize) -> Self {
+        let errors = shown
+            .iter()
+            .map(|error| {
+                let span = error.span();
+                JsonError {
+                    severity: error.severity().to_string(),
+                    message: error.to_string(),
+                    line: span.line,
+                    column: span.column,
+                    start: span.start,
+                    end: span.end,
+                    suggestions: error.suggestions().to_vec(),
+                    code: Some(error.code().to_string()),
+                }
+            })
+            .collect();
+
+        Self {
+            file: report.file_path.clone(),
+            errors,
+            summary: JsonSummary {
+                error_count: report.error_count(),
+                warning_count: report.warning_count(),
+                info_count: report.info_count(),
+                hint_count: report.hint_count(),
+                has_errors: report.has_errors(),
+                omitted,
+            },
         }
     }
 }
 
+#[allow(clippy::trivially_copy_pass_by_ref)]
+const fn is_zero(count: &usize) -> bool {
+    *count == 0
+}
+
+fn truncate_text(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+
+    let mut truncated: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+fn source_window(line: &str, column: usize, length: usize, width: usize) -> (String, usize, usize) {
+    let chars: Vec<char> = line.chars().collect();
+    let available = width
+        .saturating_sub(SOURCE_GUTTER_WIDTH)
+        .max(MIN_MESSAGE_WIDTH);
+
+    if chars.len() <= available {
+        return (line.to_string(), column, length);
+    }
+
+    let body_width = available - 2;
+    let error_index = column.saturating_sub(1).min(chars.len());
+    let start = error_index
+        .saturating_sub(body_width / 2)
+        .min(chars.len() - body_width);
+    let end = (start + body_width).min(chars.len());
+
+    let mut window = String::new();
+    if start > 0 {
+        window.push('…');
+    }
+    window.extend(&chars[start..end]);
+    if end < chars.len() {
+        window.push('…');
+    }
+
+    let window_column = error_index - start + usize::from(start > 0) + 1;
+    let window_length = length.min(end.saturating_sub(error_index)).max(1);
+
+    (window, window_column, window_length)
+}
+
 impl Default for DiagnosticFormatter {
     fn default() -> Self {
         Self::new(true, true)
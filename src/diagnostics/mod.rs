@@ -1,10 +1,18 @@
-use crate::lexer::Span;
-use miette::Diagnostic;
+use crate::lexer::{Span, TokenKind, error::LexerError};
+use crate::parser::ast::{AstDocument, ErrorSeverity, ParseError, ProcessElement};
+use miette::{Diagnostic, NamedSource};
 use serde::{Deserialize, Serialize};
-use std::fmt;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::{Path, PathBuf},
+};
+use suggestions::{suggest_identifiers, suggest_keywords};
 use thiserror::Error;
 
+pub mod baseline;
 pub mod context_validator;
+pub mod fix;
 pub mod formatter;
 pub mod suggestions;
 
@@ -17,6 +25,8 @@ pub enum DiagnosticError {
         span: Span,
         severity: Severity,
         suggestions: Vec<String>,
+        #[serde(skip_deserializing, default = "crate::parser::ast::syntax_lint")]
+        lint: &'static str,
     },
 
     #[error("Unexpected token '{found}', expected {expected}")]
@@ -25,6 +35,7 @@ pub enum DiagnosticError {
         expected: String,
         #[serde(flatten)]
         span: Span,
+        severity: Severity,
         suggestions: Vec<String>,
     },
 
@@ -33,6 +44,7 @@ pub enum DiagnosticError {
         name: String,
         #[serde(flatten)]
         span: Span,
+        severity: Severity,
         suggestions: Vec<String>,
     },
 
@@ -41,6 +53,7 @@ pub enum DiagnosticError {
         name: String,
         #[serde(flatten)]
         span: Span,
+        severity: Severity,
         first_definition: Option<Span>,
     },
 
@@ -50,7 +63,10 @@ pub enum DiagnosticError {
         element: String,
         #[serde(flatten)]
         span: Span,
+        severity: Severity,
         valid_attributes: Vec<String>,
+        #[serde(skip_deserializing, default = "invalid_attribute_lint")]
+        lint: &'static str,
     },
 
     #[error("Missing required element '{element}'")]
@@ -58,6 +74,7 @@ pub enum DiagnosticError {
         element: String,
         #[serde(flatten)]
         span: Span,
+        severity: Severity,
         suggestions: Vec<String>,
     },
 
@@ -66,6 +83,7 @@ pub enum DiagnosticError {
         message: String,
         #[serde(flatten)]
         span: Span,
+        severity: Severity,
         suggestions: Vec<String>,
     },
 
@@ -74,10 +92,59 @@ pub enum DiagnosticError {
         message: String,
         #[serde(flatten)]
         span: Span,
+        severity: Severity,
         path: String,
     },
 }
 
+pub const UNDECLARED_VARIABLE: &str = "undeclared-variable";
+
+// Every name `--allow`, `--warn`, `--deny` and the `[lints]` table accept
+pub const LINT_NAMES: &[&str] = &[
+    "syntax",
+    "missing-start",
+    "missing-end",
+    "empty-block",
+    "multiple-starts",
+    "unreachable",
+    "dead-end",
+    "duplicate-id",
+    "shadowed-name",
+    "multiple-defaults",
+    "missing-default",
+    "parallel-condition",
+    "gateway-mode",
+    "unnamed-gateway",
+    "unknown-reference",
+    "self-loop",
+    "container-flow",
+    "pool-boundary",
+    "start-incoming",
+    "end-outgoing",
+    "event-subprocess-flow",
+    "data-flow",
+    "association",
+    "duplicate-flow",
+    "unused-import",
+    "timer-duration",
+    "conditional-event",
+    "attribute-placement",
+    "attribute-type",
+    "duplicate-attribute",
+    "loop-marker",
+    "foreign-namespace",
+    "service-implementation",
+    "service-endpoint",
+    "nesting-depth",
+    UNDECLARED_VARIABLE,
+    "unexpected-token",
+    "undefined-reference",
+    "invalid-attribute",
+    "missing-element",
+    "invalid-flow",
+    "import-error",
+];
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum Severity {
     Error,
@@ -97,6 +164,39 @@ impl fmt::Display for Severity {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintLevel {
+    Allow,
+    #[serde(alias = "warn")]
+    Warning,
+    #[serde(alias = "deny")]
+    Error,
+}
+
+// The stable code of each diagnostic kind as returned by `DiagnosticError::code`. Codes
+// are never reused, they appear in every output format and can stand in for lint names
+pub const ERROR_CODES: &[(&str, &str)] = &[
+    ("E001", "syntax error"),
+    ("E002", "unexpected token"),
+    ("E003", "undefined reference"),
+    ("E004", "duplicate identifier"),
+    ("E005", "invalid attribute"),
+    ("E006", "missing element"),
+    ("E007", "invalid flow"),
+    ("E008", "import error"),
+];
+
+const fn invalid_attribute_lint() -> &'static str {
+    "invalid-attribute"
+}
+
+// Accepts a lint name like `unreachable` or an error code like `E003`
+#[must_use]
+pub fn is_known_lint(name: &str) -> bool {
+    LINT_NAMES.contains(&name) || ERROR_CODES.iter().any(|(code, _)| *code == name)
+}
+
 impl DiagnosticError {
     #[must_use]
     pub const fn span(&self) -> &Span {
@@ -115,14 +215,66 @@ impl DiagnosticError {
     #[must_use]
     pub const fn severity(&self) -> Severity {
         match self {
-            Self::SyntaxError { severity, .. } => *severity,
-            Self::UnexpectedToken { .. }
-            | Self::UndefinedReference { .. }
-            | Self::DuplicateIdentifier { .. }
-            | Self::InvalidAttribute { .. }
-            | Self::MissingElement { .. }
-            | Self::InvalidFlow { .. }
-            | Self::ImportError { .. } => Severity::Error,
+            Self::SyntaxError { severity, .. }
+            | Self::UnexpectedToken { severity, .. }
+            | Self::UndefinedReference { severity, .. }
+            | Self::DuplicateIdentifier { severity, .. }
+            | Self::InvalidAttribute { severity, .. }
+            | Self::MissingElement { severity, .. }
+            | Self::InvalidFlow { severity, .. }
+            | Self::ImportError { severity, .. } => *severity,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_severity(mut self, new_severity: Severity) -> Self {
+        match &mut self {
+            Self::SyntaxError { severity, .. }
+            | Self::UnexpectedToken { severity, .. }
+            | Self::UndefinedReference { severity, .. }
+            | Self::DuplicateIdentifier { severity, .. }
+            | Self::InvalidAttribute { severity, .. }
+            | Self::MissingElement { severity, .. }
+            | Self::InvalidFlow { severity, .. }
+            | Self::ImportError { severity, .. } => *severity = new_severity,
+        }
+        self
+    }
+
+    #[must_use]
+    pub const fn code(&self) -> &'static str {
+        match self {
+            Self::SyntaxError { .. } => "E001",
+            Self::UnexpectedToken { .. } => "E002",
+            Self::UndefinedReference { .. } => "E003",
+            Self::DuplicateIdentifier { .. } => "E004",
+            Self::InvalidAttribute { .. } => "E005",
+            Self::MissingElement { .. } => "E006",
+            Self::InvalidFlow { .. } => "E007",
+            Self::ImportError { .. } => "E008",
+        }
+    }
+
+    #[must_use]
+    pub const fn lint_name(&self) -> &'static str {
+        match self {
+            Self::SyntaxError { lint, .. } | Self::InvalidAttribute { lint, .. } => lint,
+            Self::UnexpectedToken { .. } => "unexpected-token",
+            Self::UndefinedReference { .. } => "undefined-reference",
+            Self::DuplicateIdentifier { .. } => "duplicate-id",
+            Self::MissingElement { .. } => "missing-element",
+            Self::InvalidFlow { .. } => "invalid-flow",
+            Self::ImportError { .. } => "import-error",
+        }
+    }
+
+    #[must_use]
+    pub const fn first_definition(&self) -> Option<&Span> {
+        match self {
+            Self::DuplicateIdentifier {
+                first_definition, ..
+            } => first_definition.as_ref(),
+            _ => None,
         }
     }
 
@@ -175,20 +327,131 @@ impl DiagnosticError {
         }
         self
     }
-}
 
-impl Diagnostic for DiagnosticError {
-    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
-        None
+    #[must_use]
+    pub fn from_lexer_error(error: &LexerError) -> Self {
+        let (message, span, suggestions) = match error {
+            LexerError::UnterminatedString { span } => (
+                "Unterminated string literal".to_string(),
+                span,
+                vec!["\"".to_string()],
+            ),
+            LexerError::UnterminatedComment { span } => (
+                "Unterminated block comment".to_string(),
+                span,
+                vec!["*/".to_string()],
+            ),
+            LexerError::UnexpectedCharacter { character, span } => (
+                format!("Unexpected character '{character}'"),
+                span,
+                Vec::new(),
+            ),
+            LexerError::InvalidNumber { suffix, span } => (
+                format!("Invalid number suffix '{suffix}', expected one of ms, s, m, h or d"),
+                span,
+                Vec::new(),
+            ),
+        };
+
+        Self::SyntaxError {
+            message,
+            span: span.clone(),
+            severity: Severity::Error,
+            suggestions,
+            lint: "syntax",
+        }
+    }
+
+    #[must_use]
+    pub fn from_parse_error(error: &ParseError, ast: &AstDocument) -> Self {
+        if let (Some(name), Some(first)) = (&error.element, &error.first_definition) {
+            return Self::DuplicateIdentifier {
+                name: name.clone(),
+                span: error.span.clone(),
+                severity: Severity::Error,
+                first_definition: Some(first.clone()),
+            };
+        }
+
+        let suggestions = if error.message.contains("Unexpected token") {
+            error
+                .message
+                .find('\'')
+                .map_or_else(Vec::new, |token_start| {
+                    error.message[token_start + 1..]
+                        .find('\'')
+                        .map_or_else(Vec::new, |token_end| {
+                            let found_token =
+                                &error.message[token_start + 1..token_start + 1 + token_end];
+                            if found_token == TokenKind::Eof.to_string() {
+                                Vec::new()
+                            } else {
+                                suggest_keywords(found_token)
+                            }
+                        })
+                })
+        } else if error.message.contains("Unknown") {
+            let identifiers: Vec<String> = ast
+                .processes
+                .iter()
+                .flat_map(|p| {
+                    p.elements.iter().filter_map(|e| match e {
+                        ProcessElement::CallActivity { id, .. }
+                        | ProcessElement::Task { id, .. } => Some(id.clone()),
+                        ProcessElement::Gateway { id, .. } => id.clone(),
+                        _ => None,
+                    })
+                })
+                .collect();
+
+            error
+                .message
+                .find('\'')
+                .map_or_else(Vec::new, |name_start| {
+                    error.message[name_start + 1..]
+                        .find('\'')
+                        .map_or_else(Vec::new, |name_end| {
+                            let unknown_name =
+                                &error.message[name_start + 1..name_start + 1 + name_end];
+                            suggest_identifiers(unknown_name, &identifiers)
+                        })
+                })
+        } else {
+            Vec::new()
+        };
+
+        Self::SyntaxError {
+            message: error.message.clone(),
+            span: error.span.clone(),
+            severity: match error.severity {
+                ErrorSeverity::Error => Severity::Error,
+                ErrorSeverity::Warning => Severity::Warning,
+                ErrorSeverity::Info => Severity::Info,
+            },
+            suggestions,
+            lint: error.lint,
+        }
     }
+}
 
+impl Diagnostic for DiagnosticError {
     fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
         let span = self.span();
-        Some(Box::new(std::iter::once(miette::LabeledSpan::new(
-            Some(self.to_string()),
-            span.start,
-            span.end - span.start,
-        ))))
+        let first = self.first_definition().map(|first| {
+            miette::LabeledSpan::new(
+                Some("first defined here".to_string()),
+                first.start,
+                first.end - first.start,
+            )
+        });
+        Some(Box::new(
+            std::iter::once(miette::LabeledSpan::new(
+                Some(self.to_string()),
+                span.start,
+                span.end - span.start,
+            ))
+            .chain(first),
+        ))
     }
 
     fn help<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
@@ -212,11 +475,42 @@ impl Diagnostic for DiagnosticError {
     }
 }
 
+// A diagnostic paired with the file it points into, so miette can draw source snippets
+#[derive(Debug, Error)]
+#[error("{error}")]
+pub struct SourcedDiagnostic<'a> {
+    pub error: &'a DiagnosticError,
+    pub source_code: &'a NamedSource<String>,
+}
+
+impl Diagnostic for SourcedDiagnostic<'_> {
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        Some(self.source_code)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        self.error.labels()
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        self.error.help()
+    }
+
+    fn severity(&self) -> Option<miette::Severity> {
+        Diagnostic::severity(self.error)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DiagnosticReport {
     pub errors: Vec<DiagnosticError>,
     pub file_path: String,
     pub source_code: String,
+    // Text of the imported files, by the path their spans record
+    #[serde(skip)]
+    pub imported_sources: BTreeMap<PathBuf, String>,
+    #[serde(skip)]
+    pub lint_levels: BTreeMap<String, LintLevel>,
 }
 
 impl DiagnosticReport {
@@ -226,11 +520,42 @@ impl DiagnosticReport {
             errors: Vec::new(),
             file_path,
             source_code,
+            imported_sources: BTreeMap::new(),
+            lint_levels: BTreeMap::new(),
         }
     }
 
+    #[must_use]
+    pub fn with_lint_levels(mut self, lint_levels: BTreeMap<String, LintLevel>) -> Self {
+        self.lint_levels = lint_levels;
+        self
+    }
+
+    #[must_use]
+    pub fn named_source(&self) -> NamedSource<String> {
+        NamedSource::new(&self.file_path, self.source_code.clone())
+    }
+
+    // Errors found while loading an import point into that file, not the checked one
+    #[must_use]
+    pub fn source_for(&self, file: &Path) -> &str {
+        self.imported_sources
+            .get(file)
+            .map_or(self.source_code.as_str(), String::as_str)
+    }
+
     pub fn add_error(&mut self, error: DiagnosticError) {
-        self.errors.push(error);
+        let level = self
+            .lint_levels
+            .get(error.lint_name())
+            .or_else(|| self.lint_levels.get(error.code()));
+
+        match level {
+            Some(LintLevel::Allow) => {}
+            Some(LintLevel::Warning) => self.errors.push(error.with_severity(Severity::Warning)),
+            Some(LintLevel::Error) => self.errors.push(error.with_severity(Severity::Error)),
+            None => self.errors.push(error),
+        }
     }
 
     #[must_use]
@@ -255,4 +580,28 @@ impl DiagnosticReport {
             .filter(|e| matches!(e.severity(), Severity::Warning))
             .count()
     }
+
+    #[must_use]
+    pub fn info_count(&self) -> usize {
+        self.errors
+            .iter()
+            .filter(|e| matches!(e.severity(), Severity::Info))
+            .count()
+    }
+
+    #[must_use]
+    pub fn hint_count(&self) -> usize {
+        self.errors
+            .iter()
+            .filter(|e| matches!(e.severity(), Severity::Hint))
+            .count()
+    }
+
+    // Orders diagnostics by file and position, ties keep the order they were found in
+    pub fn sort(&mut self) {
+        self.errors.sort_by(|a, b| {
+            let (a, b) = (a.span(), b.span());
+            (&a.file, a.line, a.column).cmp(&(&b.file, b.line, b.column))
+        });
+    }
 }
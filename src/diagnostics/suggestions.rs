@@ -32,11 +32,14 @@ pub const BPMN_KEYWORDS: &[&str] = &[
     "call",
     "xor",
     "and",
+    "or",
     "event",
     "pool",
     "lane",
     "group",
     "note",
+    "data",
+    "var",
     "subprocess",
     "import",
     "from",
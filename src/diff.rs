@@ -0,0 +1,587 @@
+use std::fmt;
+
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use crate::{
+    formatter::printer::{print_event_type, print_import, print_value, quote},
+    metrics::element_kind,
+    parser::{
+        ast::{
+            AstDocument, AttributeValue, EventDirection, EventType, Flow, FlowType, GatewayMode,
+            Lane, ProcessDeclaration, ProcessElement,
+        },
+        ids::assign_ids,
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Change {
+    // None for imports, which belong to the file rather than a process
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub process: Option<String>,
+    pub kind: ChangeKind,
+    pub subject: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<String>,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ChangeKind::Added => write!(f, "{} added", self.subject),
+            ChangeKind::Removed => write!(f, "{} removed", self.subject),
+            ChangeKind::Modified => write!(f, "{} {}", self.subject, self.details.join(", ")),
+        }
+    }
+}
+
+// Processes are matched by name, elements, lanes and variables by id and flows by their
+// endpoints, so reordering or reformatting the source reports nothing. Anonymous events
+// and gateways are compared under the ids `assign_ids` gives them
+#[must_use]
+pub fn diff_documents(old: &AstDocument, new: &AstDocument) -> Vec<Change> {
+    let (mut old, mut new) = (old.clone(), new.clone());
+    assign_ids(&mut old);
+    assign_ids(&mut new);
+
+    let mut changes = Vec::new();
+    let old_imports: Vec<String> = old.imports.iter().map(print_import).collect();
+    let new_imports: Vec<String> = new.imports.iter().map(print_import).collect();
+    for (kind, imports, others) in [
+        (ChangeKind::Removed, &old_imports, &new_imports),
+        (ChangeKind::Added, &new_imports, &old_imports),
+    ] {
+        for import in imports.iter().filter(|import| !others.contains(import)) {
+            changes.push(Change {
+                process: None,
+                kind,
+                subject: format!("Import `{import}`"),
+                details: Vec::new(),
+            });
+        }
+    }
+
+    for process in &old.processes {
+        match new
+            .processes
+            .iter()
+            .find(|other| other.name == process.name)
+        {
+            Some(other) => diff_process(process, other, &mut changes),
+            None => changes.push(process_change(process, ChangeKind::Removed, Vec::new())),
+        }
+    }
+    for process in &new.processes {
+        if !old.processes.iter().any(|other| other.name == process.name) {
+            changes.push(process_change(process, ChangeKind::Added, Vec::new()));
+        }
+    }
+
+    changes
+}
+
+fn process_change(process: &ProcessDeclaration, kind: ChangeKind, details: Vec<String>) -> Change {
+    Change {
+        process: Some(process.name.clone()),
+        kind,
+        subject: format!("Process '{}'", process.name),
+        details,
+    }
+}
+
+fn diff_process(old: &ProcessDeclaration, new: &ProcessDeclaration, changes: &mut Vec<Change>) {
+    let change = |kind, subject: String, details| Change {
+        process: Some(old.name.clone()),
+        kind,
+        subject,
+        details,
+    };
+
+    let details = attribute_details(&old.attributes, &new.attributes);
+    if !details.is_empty() {
+        changes.push(process_change(old, ChangeKind::Modified, details));
+    }
+
+    for variable in &old.variables {
+        let subject = format!("Variable '{}'", variable.name);
+        match new
+            .variables
+            .iter()
+            .find(|other| other.name == variable.name)
+        {
+            Some(other) if other.variable_type != variable.variable_type => {
+                let detail = format!(
+                    "changed type {} → {}",
+                    variable.variable_type, other.variable_type
+                );
+                changes.push(change(ChangeKind::Modified, subject, vec![detail]));
+            }
+            Some(_) => {}
+            None => changes.push(change(ChangeKind::Removed, subject, Vec::new())),
+        }
+    }
+    for variable in &new.variables {
+        if !old
+            .variables
+            .iter()
+            .any(|other| other.name == variable.name)
+        {
+            let subject = format!("Variable '{}'", variable.name);
+            changes.push(change(ChangeKind::Added, subject, Vec::new()));
+        }
+    }
+
+    let (old_entries, old_flows) = collect_process(old);
+    let (new_entries, new_flows) = collect_process(new);
+
+    for entry in &old_entries {
+        match new_entries.iter().find(|other| other.key == entry.key) {
+            Some(other) => {
+                let details = entry_details(entry, other);
+                if !details.is_empty() {
+                    changes.push(change(ChangeKind::Modified, entry.subject.clone(), details));
+                }
+            }
+            None => changes.push(change(
+                ChangeKind::Removed,
+                entry.subject.clone(),
+                Vec::new(),
+            )),
+        }
+    }
+    for entry in &new_entries {
+        if !old_entries.iter().any(|other| other.key == entry.key) {
+            changes.push(change(ChangeKind::Added, entry.subject.clone(), Vec::new()));
+        }
+    }
+
+    for (kind, subject, details) in diff_flows(old_flows, new_flows) {
+        changes.push(change(kind, subject, details));
+    }
+}
+
+// An element or lane together with the container it's placed in
+struct Entry<'a> {
+    key: String,
+    subject: String,
+    container: String,
+    element: Option<&'a ProcessElement>,
+}
+
+// A sequence, message, default or association flow, gateway branches included
+struct FlowEntry {
+    from: String,
+    to: String,
+    arrow: &'static str,
+    condition: Option<String>,
+}
+
+impl FlowEntry {
+    fn subject(&self) -> String {
+        format!("Flow {} {} {}", self.from, self.arrow, self.to)
+    }
+
+    fn same_endpoints(&self, other: &Self) -> bool {
+        self.from == other.from && self.to == other.to && self.arrow == other.arrow
+    }
+}
+
+fn collect_process(process: &ProcessDeclaration) -> (Vec<Entry<'_>>, Vec<FlowEntry>) {
+    let mut entries = Vec::new();
+    let mut flows = Vec::new();
+    collect(
+        &process.elements,
+        &process.flows,
+        "the process",
+        &mut entries,
+        &mut flows,
+    );
+    (entries, flows)
+}
+
+fn collect<'a>(
+    elements: &'a [ProcessElement],
+    element_flows: &[Flow],
+    container: &str,
+    entries: &mut Vec<Entry<'a>>,
+    flows: &mut Vec<FlowEntry>,
+) {
+    flows.extend(element_flows.iter().map(|flow| FlowEntry {
+        from: flow.from.clone(),
+        to: flow.to.clone(),
+        arrow: match flow.flow_type {
+            FlowType::Sequence => "->",
+            FlowType::Message => "-->",
+            FlowType::Default => "=>",
+            FlowType::Association => "..>",
+        },
+        condition: flow.condition.as_ref().map(ToString::to_string),
+    }));
+
+    for element in elements {
+        let (key, subject) = element_key(element);
+        entries.push(Entry {
+            key: key.clone(),
+            subject,
+            container: container.to_string(),
+            element: Some(element),
+        });
+
+        match element {
+            ProcessElement::Gateway { branches, .. } => {
+                flows.extend(branches.iter().map(|branch| FlowEntry {
+                    from: key.clone(),
+                    to: branch.target.clone(),
+                    arrow: if branch.is_default { "=>" } else { "->" },
+                    condition: branch.condition.as_ref().map(ToString::to_string),
+                }));
+            }
+            ProcessElement::Subprocess {
+                id,
+                elements,
+                flows: inner,
+                ..
+            } => collect(elements, inner, &format!("subprocess {id}"), entries, flows),
+            ProcessElement::EventSubprocess {
+                id,
+                elements,
+                flows: inner,
+                ..
+            } => collect(
+                elements,
+                inner,
+                &format!("event subprocess {id}"),
+                entries,
+                flows,
+            ),
+            ProcessElement::Pool {
+                name,
+                lanes,
+                elements,
+                flows: inner,
+                ..
+            } => {
+                let pool = format!("pool {name}");
+                for lane in lanes {
+                    entries.push(Entry {
+                        key: format!("{name}/{}", lane.name),
+                        subject: format!("Lane '{}'", lane.name),
+                        container: pool.clone(),
+                        element: None,
+                    });
+                    collect(
+                        &lane.elements,
+                        &[],
+                        &format!("lane {}", lane.name),
+                        entries,
+                        flows,
+                    );
+                }
+                // Direct elements of a pool with lanes sit in its default lane
+                let direct = if lanes.is_empty() {
+                    pool
+                } else {
+                    format!("lane {}", Lane::DEFAULT)
+                };
+                collect(elements, inner, &direct, entries, flows);
+            }
+            ProcessElement::Group { elements, .. } => {
+                collect(elements, &[], container, entries, flows);
+            }
+            _ => {}
+        }
+    }
+}
+
+// Groups and unnamed notes have no id, they are matched by their label or text
+fn element_key(element: &ProcessElement) -> (String, String) {
+    let noun = match element {
+        ProcessElement::StartEvent { .. } => "Start event",
+        ProcessElement::EndEvent { .. } => "End event",
+        ProcessElement::Task { .. } => "Task",
+        ProcessElement::Gateway { .. } => "Gateway",
+        ProcessElement::IntermediateEvent { .. } => "Event",
+        ProcessElement::Subprocess { .. } => "Subprocess",
+        ProcessElement::EventSubprocess { .. } => "Event subprocess",
+        ProcessElement::CallActivity { .. } => "Call activity",
+        ProcessElement::Pool { .. } => "Pool",
+        ProcessElement::Group { .. } => "Group",
+        ProcessElement::Annotation { .. } => "Note",
+        ProcessElement::DataObject { store: true, .. } => "Data store",
+        ProcessElement::DataObject { .. } => "Data object",
+    };
+
+    let key = match element {
+        ProcessElement::StartEvent { id, .. } => id.clone().unwrap_or_else(|| "start".into()),
+        ProcessElement::EndEvent { id, .. } => id.clone().unwrap_or_else(|| "end".into()),
+        ProcessElement::Gateway { id, .. } | ProcessElement::IntermediateEvent { id, .. } => {
+            id.clone().unwrap_or_default()
+        }
+        ProcessElement::Task { id, .. }
+        | ProcessElement::Subprocess { id, .. }
+        | ProcessElement::EventSubprocess { id, .. }
+        | ProcessElement::CallActivity { id, .. }
+        | ProcessElement::DataObject { id, .. }
+        | ProcessElement::Annotation { id: Some(id), .. } => id.clone(),
+        ProcessElement::Pool { name, .. } => name.clone(),
+        ProcessElement::Group { label, .. } => {
+            return (format!("group {label}"), format!("{noun} {}", quote(label)));
+        }
+        ProcessElement::Annotation { text, .. } => {
+            return (format!("note {text}"), format!("{noun} {}", quote(text)));
+        }
+    };
+
+    (key.clone(), format!("{noun} '{key}'"))
+}
+
+fn entry_details(old: &Entry<'_>, new: &Entry<'_>) -> Vec<String> {
+    let mut details = Vec::new();
+
+    if let (Some(old), Some(new)) = (old.element, new.element) {
+        let (old_kind, new_kind) = (kind(old), kind(new));
+        if old_kind != new_kind {
+            details.push(format!("changed type {old_kind} → {new_kind}"));
+        }
+
+        let (old_label, new_label) = (label(old), label(new));
+        match (old_label, new_label) {
+            (None, Some(label)) => details.push(format!("added label {}", quote(label))),
+            (Some(label), None) => details.push(format!("removed label {}", quote(label))),
+            (Some(old), Some(new)) if old != new => {
+                details.push(format!("changed label {} → {}", quote(old), quote(new)));
+            }
+            _ => {}
+        }
+
+        let (old_event, new_event) = (event_type(old), event_type(new));
+        if old_event != new_event {
+            details.push(format!(
+                "changed event {} → {}",
+                event_name(old_event),
+                event_name(new_event)
+            ));
+        }
+
+        details.extend(element_details(old, new));
+        if let (Some(old), Some(new)) = (attributes(old), attributes(new)) {
+            details.extend(attribute_details(old, new));
+        }
+    }
+
+    if old.container != new.container {
+        details.push(format!("moved from {} to {}", old.container, new.container));
+    }
+
+    details
+}
+
+fn element_details(old: &ProcessElement, new: &ProcessElement) -> Vec<String> {
+    let mut details = Vec::new();
+    match (old, new) {
+        (
+            ProcessElement::IntermediateEvent {
+                direction: old_direction,
+                payload: old_payload,
+                ..
+            },
+            ProcessElement::IntermediateEvent {
+                direction: new_direction,
+                payload: new_payload,
+                ..
+            },
+        ) => {
+            if old_direction != new_direction {
+                details.push(format!(
+                    "changed direction {} → {}",
+                    direction_name(*old_direction),
+                    direction_name(*new_direction)
+                ));
+            }
+            if old_payload != new_payload {
+                let payload = |payload: &Option<String>| {
+                    payload.as_deref().map_or_else(|| "none".to_string(), quote)
+                };
+                details.push(format!(
+                    "changed payload {} → {}",
+                    payload(old_payload),
+                    payload(new_payload)
+                ));
+            }
+        }
+        (
+            ProcessElement::CallActivity {
+                called_element: old_called,
+                ..
+            },
+            ProcessElement::CallActivity {
+                called_element: new_called,
+                ..
+            },
+        ) if old_called != new_called => {
+            details.push(format!(
+                "changed called element {old_called} → {new_called}"
+            ));
+        }
+        (
+            ProcessElement::Gateway { mode: old_mode, .. },
+            ProcessElement::Gateway { mode: new_mode, .. },
+        ) if old_mode != new_mode => {
+            details.push(format!(
+                "changed mode {} → {}",
+                mode_name(*old_mode),
+                mode_name(*new_mode)
+            ));
+        }
+        _ => {}
+    }
+    details
+}
+
+fn attribute_details(
+    old: &IndexMap<String, AttributeValue>,
+    new: &IndexMap<String, AttributeValue>,
+) -> Vec<String> {
+    let mut details = Vec::new();
+    for (name, value) in old {
+        match new.get(name) {
+            Some(other) if other != value => details.push(format!(
+                "changed {name} {} → {}",
+                print_value(value),
+                print_value(other)
+            )),
+            Some(_) => {}
+            None => details.push(format!("removed {name}")),
+        }
+    }
+    for (name, value) in new {
+        if !old.contains_key(name) {
+            details.push(format!("added {name}={}", print_value(value)));
+        }
+    }
+    details
+}
+
+// Exact matches pair up first, so that of two flows between the same elements only the
+// one whose condition changed is reported
+fn diff_flows(
+    old: Vec<FlowEntry>,
+    mut new: Vec<FlowEntry>,
+) -> Vec<(ChangeKind, String, Vec<String>)> {
+    let mut unmatched = Vec::new();
+    for flow in old {
+        match new
+            .iter()
+            .position(|other| other.same_endpoints(&flow) && other.condition == flow.condition)
+        {
+            Some(index) => {
+                new.remove(index);
+            }
+            None => unmatched.push(flow),
+        }
+    }
+
+    let mut changes = Vec::new();
+    for flow in unmatched {
+        let Some(index) = new.iter().position(|other| other.same_endpoints(&flow)) else {
+            changes.push((ChangeKind::Removed, flow.subject(), Vec::new()));
+            continue;
+        };
+
+        let other = new.remove(index);
+        let detail = match (&flow.condition, &other.condition) {
+            (None, Some(condition)) => format!("added condition [{condition}]"),
+            (Some(condition), None) => format!("removed condition [{condition}]"),
+            (old, new) => format!(
+                "changed condition [{}] → [{}]",
+                old.as_deref().unwrap_or_default(),
+                new.as_deref().unwrap_or_default()
+            ),
+        };
+        changes.push((ChangeKind::Modified, flow.subject(), vec![detail]));
+    }
+
+    changes.extend(
+        new.iter()
+            .map(|flow| (ChangeKind::Added, flow.subject(), Vec::new())),
+    );
+    changes
+}
+
+const fn kind(element: &ProcessElement) -> &'static str {
+    match element {
+        ProcessElement::DataObject { store: true, .. } => "data store",
+        _ => element_kind(element),
+    }
+}
+
+fn label(element: &ProcessElement) -> Option<&str> {
+    match element {
+        ProcessElement::Task { label, .. }
+        | ProcessElement::Subprocess { label, .. }
+        | ProcessElement::CallActivity { label, .. }
+        | ProcessElement::DataObject { label, .. } => label.as_deref(),
+        _ => None,
+    }
+}
+
+const fn event_type(element: &ProcessElement) -> Option<&EventType> {
+    match element {
+        ProcessElement::StartEvent { event_type, .. }
+        | ProcessElement::EndEvent { event_type, .. } => event_type.as_ref(),
+        ProcessElement::IntermediateEvent { event_type, .. }
+        | ProcessElement::EventSubprocess {
+            trigger: event_type,
+            ..
+        } => Some(event_type),
+        _ => None,
+    }
+}
+
+fn event_name(event_type: Option<&EventType>) -> String {
+    let name = print_event_type(event_type);
+    if name.is_empty() {
+        "none".to_string()
+    } else {
+        name.trim_start().to_string()
+    }
+}
+
+const fn attributes(element: &ProcessElement) -> Option<&IndexMap<String, AttributeValue>> {
+    match element {
+        ProcessElement::StartEvent { attributes, .. }
+        | ProcessElement::EndEvent { attributes, .. }
+        | ProcessElement::Task { attributes, .. }
+        | ProcessElement::IntermediateEvent { attributes, .. }
+        | ProcessElement::Subprocess { attributes, .. }
+        | ProcessElement::CallActivity { attributes, .. }
+        | ProcessElement::Gateway { attributes, .. }
+        | ProcessElement::EventSubprocess { attributes, .. }
+        | ProcessElement::Pool { attributes, .. } => Some(attributes),
+        _ => None,
+    }
+}
+
+const fn direction_name(direction: EventDirection) -> &'static str {
+    match direction {
+        EventDirection::Throw => "throw",
+        EventDirection::Catch => "catch",
+    }
+}
+
+const fn mode_name(mode: GatewayMode) -> &'static str {
+    match mode {
+        GatewayMode::Split => "split",
+        GatewayMode::Join => "join",
+        GatewayMode::Mixed => "mixed",
+    }
+}
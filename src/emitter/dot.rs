@@ -0,0 +1,252 @@
+use crate::{
+    emitter::{implicit_flows, node_id, scoped_id, unique_event_id},
+    parser::ast::{
+        ConditionExpr, Flow, FlowType, GatewayType, Lane, ProcessDeclaration, ProcessElement,
+        needs_default_lane,
+    },
+};
+
+#[must_use]
+pub fn to_dot(process: &ProcessDeclaration) -> String {
+    let mut emitter = DotEmitter::default();
+
+    emitter
+        .lines
+        .push(format!("digraph {} {{", quote(&process.name)));
+    emitter.lines.push("    rankdir=LR;".to_string());
+    emitter
+        .lines
+        .push("    node [fontname=\"Helvetica\", fontsize=10];".to_string());
+    emitter.emit_elements(&process.elements, "", 1);
+    emitter.collect_flows(&process.flows, "");
+    emitter.collect_implicit_flows(&process.elements, &process.flows, "");
+
+    let mut output = emitter.lines.join("\n");
+    for edge in &emitter.edges {
+        output.push_str("\n    ");
+        output.push_str(edge);
+    }
+    output.push_str("\n}\n");
+
+    output
+}
+
+#[derive(Default)]
+struct DotEmitter {
+    lines: Vec<String>,
+    edges: Vec<String>,
+    declared: Vec<String>,
+}
+
+impl DotEmitter {
+    fn emit_elements(&mut self, elements: &[ProcessElement], scope: &str, depth: usize) {
+        for element in elements {
+            self.emit_element(element, scope, depth);
+        }
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn emit_element(&mut self, element: &ProcessElement, scope: &str, depth: usize) {
+        let mut id = scoped_id(scope, &node_id(element));
+        if self.declared.contains(&id)
+            && let Some(unique) = unique_event_id(element)
+        {
+            id = scoped_id(scope, &unique);
+        }
+
+        match element {
+            ProcessElement::StartEvent { .. } => {
+                self.declare(&id, "start", "shape=circle", depth);
+            }
+            ProcessElement::EndEvent { .. } => {
+                self.declare(&id, "end", "shape=doublecircle", depth);
+            }
+            ProcessElement::IntermediateEvent { id: name, .. } => {
+                let text = name.as_deref().unwrap_or("event");
+                self.declare(&id, text, "shape=circle", depth);
+            }
+            ProcessElement::Task {
+                id: name, label, ..
+            } => {
+                let text = label.as_deref().unwrap_or(name);
+                self.declare(&id, text, "shape=box, style=rounded", depth);
+            }
+            ProcessElement::CallActivity {
+                id: name, label, ..
+            } => {
+                let text = label.as_deref().unwrap_or(name);
+                self.declare(&id, text, "shape=box, style=\"rounded,bold\"", depth);
+            }
+            ProcessElement::Gateway {
+                id: name,
+                gateway_type,
+                branches,
+                ..
+            } => {
+                let fallback = match gateway_type {
+                    GatewayType::Exclusive => "X",
+                    GatewayType::Parallel => "+",
+                    GatewayType::Inclusive => "O",
+                };
+                let text = name.as_deref().unwrap_or(fallback);
+                self.declare(&id, text, "shape=diamond", depth);
+
+                for branch in branches {
+                    let target = scoped_id(scope, &branch.target);
+                    let edge = if branch.is_default {
+                        edge(&id, &target, &FlowType::Default, None)
+                    } else {
+                        edge(&id, &target, &FlowType::Sequence, branch.condition.as_ref())
+                    };
+                    self.edges.push(edge);
+                }
+            }
+            ProcessElement::Subprocess {
+                id: name,
+                label,
+                elements,
+                flows,
+                ..
+            } => {
+                let text = label.as_deref().unwrap_or(name);
+                self.open_cluster(&id, text, "style=rounded", depth);
+                self.emit_elements(elements, &id, depth + 1);
+                self.close_cluster(depth);
+                self.collect_flows(flows, &id);
+                self.collect_implicit_flows(elements, flows, &id);
+            }
+            ProcessElement::EventSubprocess {
+                id: name,
+                elements,
+                flows,
+                ..
+            } => {
+                self.open_cluster(&id, name, "style=\"rounded,dotted\"", depth);
+                self.emit_elements(elements, &id, depth + 1);
+                self.close_cluster(depth);
+                self.collect_flows(flows, &id);
+                self.collect_implicit_flows(elements, flows, &id);
+            }
+            ProcessElement::Pool {
+                name,
+                lanes,
+                elements,
+                flows,
+                ..
+            } => {
+                self.open_cluster(&id, name, "style=solid", depth);
+                for lane in lanes {
+                    let lane_id = format!("{}_{}", name, lane.name);
+                    self.open_cluster(&lane_id, &lane.name, "style=solid", depth + 1);
+                    self.emit_elements(&lane.elements, scope, depth + 2);
+                    if lane.is_default() {
+                        self.emit_elements(elements, scope, depth + 2);
+                    }
+                    self.close_cluster(depth + 1);
+                }
+                if needs_default_lane(lanes, elements) {
+                    let lane_id = format!("{}_{}", name, Lane::DEFAULT);
+                    self.open_cluster(&lane_id, Lane::DEFAULT, "style=solid", depth + 1);
+                    self.emit_elements(elements, scope, depth + 2);
+                    self.close_cluster(depth + 1);
+                } else if lanes.is_empty() {
+                    self.emit_elements(elements, scope, depth + 1);
+                }
+                self.close_cluster(depth);
+                self.collect_flows(flows, scope);
+            }
+            ProcessElement::Group {
+                label, elements, ..
+            } => {
+                self.open_cluster(&id, label, "style=dashed", depth);
+                self.emit_elements(elements, scope, depth + 1);
+                self.close_cluster(depth);
+            }
+            ProcessElement::Annotation { text, .. } => {
+                self.declare(&id, text, "shape=note", depth);
+            }
+            ProcessElement::DataObject {
+                id: name,
+                label,
+                store,
+                ..
+            } => {
+                let text = label.as_deref().unwrap_or(name);
+                let shape = if *store {
+                    "shape=cylinder"
+                } else {
+                    "shape=folder"
+                };
+                self.declare(&id, text, shape, depth);
+            }
+        }
+    }
+
+    fn declare(&mut self, id: &str, label: &str, attributes: &str, depth: usize) {
+        if self.declared.iter().any(|declared| declared == id) {
+            return;
+        }
+
+        self.declared.push(id.to_string());
+        self.lines.push(format!(
+            "{}{} [label={}, {attributes}];",
+            "    ".repeat(depth),
+            quote(id),
+            quote(label)
+        ));
+    }
+
+    fn open_cluster(&mut self, id: &str, label: &str, style: &str, depth: usize) {
+        let indent = "    ".repeat(depth);
+        self.lines.push(format!(
+            "{indent}subgraph {} {{",
+            quote(&format!("cluster_{id}"))
+        ));
+        self.lines
+            .push(format!("{indent}    label={}; {style};", quote(label)));
+    }
+
+    fn close_cluster(&mut self, depth: usize) {
+        self.lines.push(format!("{}}}", "    ".repeat(depth)));
+    }
+
+    fn collect_flows(&mut self, flows: &[Flow], scope: &str) {
+        for flow in flows {
+            let from = scoped_id(scope, &flow.from);
+            let to = scoped_id(scope, &flow.to);
+            self.edges
+                .push(edge(&from, &to, &flow.flow_type, flow.condition.as_ref()));
+        }
+    }
+
+    fn collect_implicit_flows(&mut self, elements: &[ProcessElement], flows: &[Flow], scope: &str) {
+        for (from, to) in implicit_flows(elements, flows) {
+            let (from, to) = (scoped_id(scope, &from), scoped_id(scope, &to));
+            self.edges.push(edge(&from, &to, &FlowType::Sequence, None));
+        }
+    }
+}
+
+#[allow(clippy::format_push_string)]
+fn edge(from: &str, to: &str, flow_type: &FlowType, condition: Option<&ConditionExpr>) -> String {
+    let style = match flow_type {
+        FlowType::Sequence => "solid",
+        FlowType::Message => "dashed",
+        FlowType::Association => "dotted",
+        FlowType::Default => "bold",
+    };
+
+    let mut attributes = format!("style={style}");
+    if matches!(flow_type, FlowType::Association) {
+        attributes.push_str(", arrowhead=none");
+    }
+    if let Some(condition) = condition {
+        attributes.push_str(&format!(", label={}", quote(&condition.to_string())));
+    }
+
+    format!("{} -> {} [{attributes}];", quote(from), quote(to))
+}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
@@ -0,0 +1,416 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Write,
+};
+
+use crate::{
+    emitter::{implicit_flows, node_id, unique_event_id},
+    parser::ast::{Flow, Lane, ProcessDeclaration, ProcessElement, needs_default_lane},
+};
+
+const ORIGIN: i32 = 50;
+const LABEL_WIDTH: i32 = 30;
+const CONTENT_LEFT: i32 = ORIGIN + 2 * LABEL_WIDTH + 30;
+const COLUMN_WIDTH: i32 = 150;
+const CELL_WIDTH: i32 = 100;
+const ROW_HEIGHT: i32 = 120;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bounds {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shape {
+    pub element: String,
+    pub bounds: Bounds,
+    pub horizontal: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edge {
+    pub element: String,
+    pub source: String,
+    pub target: String,
+    pub waypoints: Vec<(i32, i32)>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Layout {
+    pub shapes: Vec<Shape>,
+    pub edges: Vec<Edge>,
+}
+
+impl Layout {
+    #[must_use]
+    pub fn shape(&self, element: &str) -> Option<&Shape> {
+        self.shapes.iter().find(|shape| shape.element == element)
+    }
+}
+
+// Columns come from the BFS distance to the nearest element without incoming flows,
+// elements sharing a column are stacked top to bottom. Every lane gets its own
+// horizontal band, including the implied default lane, subprocesses are drawn collapsed
+#[must_use]
+pub fn layout_process(process: &ProcessDeclaration) -> Layout {
+    let mut graph = Graph::default();
+    graph.collect(&process.elements, &process.flows, None);
+    graph
+        .links
+        .extend(implicit_flows(&process.elements, &process.flows));
+
+    let ranks = graph.ranks();
+    let columns = ranks.iter().max().map_or(1, |rank| rank + 1);
+    let content_width = CONTENT_LEFT - ORIGIN + i32_of(columns) * COLUMN_WIDTH;
+
+    let mut rows = vec![0; graph.nodes.len()];
+    let mut band_rows = vec![1; graph.bands];
+    let mut used: HashMap<(usize, usize), usize> = HashMap::new();
+    for (index, node) in graph.nodes.iter().enumerate() {
+        let row = used.entry((node.band, ranks[index])).or_default();
+        rows[index] = *row;
+        *row += 1;
+        band_rows[node.band] = band_rows[node.band].max(*row);
+    }
+
+    let mut band_tops = Vec::with_capacity(graph.bands);
+    let mut top = ORIGIN;
+    for rows in &band_rows {
+        band_tops.push(top);
+        top += i32_of(*rows) * ROW_HEIGHT;
+    }
+    let band_height = |band: usize| i32_of(band_rows[band]) * ROW_HEIGHT;
+
+    let mut layout = Layout::default();
+    for container in &graph.containers {
+        let inset = if container.is_lane { LABEL_WIDTH } else { 0 };
+        layout.shapes.push(Shape {
+            element: container.id.clone(),
+            bounds: Bounds {
+                x: ORIGIN + inset,
+                y: band_tops[container.bands.0],
+                width: content_width - inset,
+                height: (container.bands.0..container.bands.1)
+                    .map(band_height)
+                    .sum(),
+            },
+            horizontal: true,
+        });
+    }
+
+    for (index, node) in graph.nodes.iter().enumerate() {
+        let (width, height) = node.size;
+        layout.shapes.push(Shape {
+            element: node.id.clone(),
+            bounds: Bounds {
+                x: CONTENT_LEFT + i32_of(ranks[index]) * COLUMN_WIDTH + (CELL_WIDTH - width) / 2,
+                y: band_tops[node.band]
+                    + i32_of(rows[index]) * ROW_HEIGHT
+                    + (ROW_HEIGHT - height) / 2,
+                width,
+                height,
+            },
+            horizontal: false,
+        });
+    }
+
+    layout.edges = graph.route(&layout);
+    layout
+}
+
+// The `<bpmndi:BPMNDiagram>` block for `process`, sequence flows are referenced as
+// `Flow_<from>_<to>`
+#[must_use]
+pub fn to_bpmndi(process: &ProcessDeclaration) -> String {
+    let layout = layout_process(process);
+    let mut output = String::new();
+
+    let _ = writeln!(
+        output,
+        "<bpmndi:BPMNDiagram id=\"{}_diagram\">",
+        escape(&process.name)
+    );
+    let _ = writeln!(
+        output,
+        "  <bpmndi:BPMNPlane id=\"{0}_plane\" bpmnElement=\"{0}\">",
+        escape(&process.name)
+    );
+
+    for shape in &layout.shapes {
+        let element = escape(&shape.element);
+        let horizontal = if shape.horizontal {
+            " isHorizontal=\"true\""
+        } else {
+            ""
+        };
+        let Bounds {
+            x,
+            y,
+            width,
+            height,
+        } = shape.bounds;
+        let _ = writeln!(
+            output,
+            "    <bpmndi:BPMNShape id=\"{element}_di\" bpmnElement=\"{element}\"{horizontal}>"
+        );
+        let _ = writeln!(
+            output,
+            "      <dc:Bounds x=\"{x}\" y=\"{y}\" width=\"{width}\" height=\"{height}\" />"
+        );
+        output.push_str("    </bpmndi:BPMNShape>\n");
+    }
+
+    for edge in &layout.edges {
+        let element = escape(&edge.element);
+        let _ = writeln!(
+            output,
+            "    <bpmndi:BPMNEdge id=\"{element}_di\" bpmnElement=\"{element}\">"
+        );
+        for (x, y) in &edge.waypoints {
+            let _ = writeln!(output, "      <di:waypoint x=\"{x}\" y=\"{y}\" />");
+        }
+        output.push_str("    </bpmndi:BPMNEdge>\n");
+    }
+
+    output.push_str("  </bpmndi:BPMNPlane>\n");
+    output.push_str("</bpmndi:BPMNDiagram>\n");
+    output
+}
+
+struct Node {
+    id: String,
+    size: (i32, i32),
+    band: usize,
+}
+
+// A pool or lane spanning the bands `bands.0..bands.1`
+struct Container {
+    id: String,
+    bands: (usize, usize),
+    is_lane: bool,
+}
+
+#[derive(Default)]
+struct Graph {
+    nodes: Vec<Node>,
+    links: Vec<(String, String)>,
+    containers: Vec<Container>,
+    bands: usize,
+    top_level_band: Option<usize>,
+}
+
+impl Graph {
+    fn collect(&mut self, elements: &[ProcessElement], flows: &[Flow], band: Option<usize>) {
+        for element in elements {
+            self.collect_element(element, band);
+        }
+        self.links.extend(
+            flows
+                .iter()
+                .map(|flow| (flow.from.clone(), flow.to.clone())),
+        );
+    }
+
+    fn collect_element(&mut self, element: &ProcessElement, band: Option<usize>) {
+        match element {
+            ProcessElement::Pool {
+                name,
+                lanes,
+                elements,
+                flows,
+                ..
+            } => {
+                let first = self.bands;
+                let pool_index = self.containers.len();
+                for lane in lanes {
+                    let lane_band = self.new_lane(name, &lane.name);
+                    self.collect(&lane.elements, &[], Some(lane_band));
+                    if lane.is_default() {
+                        self.collect(elements, &[], Some(lane_band));
+                    }
+                }
+                if needs_default_lane(lanes, elements) {
+                    let lane_band = self.new_lane(name, Lane::DEFAULT);
+                    self.collect(elements, &[], Some(lane_band));
+                } else if lanes.is_empty() {
+                    let pool_band = self.new_band();
+                    self.collect(elements, &[], Some(pool_band));
+                }
+                self.collect(&[], flows, None);
+                self.containers.insert(
+                    pool_index,
+                    Container {
+                        id: name.clone(),
+                        bands: (first, self.bands),
+                        is_lane: false,
+                    },
+                );
+            }
+            ProcessElement::Group { elements, .. } => self.collect(elements, &[], band),
+            _ => {
+                let band = band.unwrap_or_else(|| self.top_level_band());
+                let mut id = node_id(element);
+                if self.nodes.iter().any(|node| node.id == id)
+                    && let Some(unique) = unique_event_id(element)
+                {
+                    id = unique;
+                }
+                if let ProcessElement::Gateway { branches, .. } = element {
+                    self.links.extend(
+                        branches
+                            .iter()
+                            .map(|branch| (id.clone(), branch.target.clone())),
+                    );
+                }
+                self.nodes.push(Node {
+                    id,
+                    size: element_size(element),
+                    band,
+                });
+            }
+        }
+    }
+
+    fn new_lane(&mut self, pool: &str, lane: &str) -> usize {
+        let band = self.new_band();
+        self.containers.push(Container {
+            id: format!("{pool}_{lane}"),
+            bands: (band, band + 1),
+            is_lane: true,
+        });
+        band
+    }
+
+    const fn new_band(&mut self) -> usize {
+        self.bands += 1;
+        self.bands - 1
+    }
+
+    // Elements outside any pool share one band, opened the first time one shows up
+    const fn top_level_band(&mut self) -> usize {
+        if let Some(band) = self.top_level_band {
+            return band;
+        }
+        let band = self.new_band();
+        self.top_level_band = Some(band);
+        band
+    }
+
+    fn index_of(&self, id: &str) -> Option<usize> {
+        self.nodes.iter().position(|node| node.id == id)
+    }
+
+    fn ranks(&self) -> Vec<usize> {
+        let mut outgoing = vec![Vec::new(); self.nodes.len()];
+        let mut has_incoming = vec![false; self.nodes.len()];
+        for (from, to) in &self.links {
+            if let (Some(from), Some(to)) = (self.index_of(from), self.index_of(to)) {
+                outgoing[from].push(to);
+                has_incoming[to] = true;
+            }
+        }
+
+        let mut ranks: Vec<Option<usize>> = vec![None; self.nodes.len()];
+        let roots = (0..self.nodes.len()).filter(|index| !has_incoming[*index]);
+        // Elements only reachable through a cycle start a walk of their own
+        for root in roots.chain(0..self.nodes.len()) {
+            if ranks[root].is_some() {
+                continue;
+            }
+            ranks[root] = Some(0);
+            let mut queue = VecDeque::from([root]);
+            while let Some(current) = queue.pop_front() {
+                let next_rank = ranks[current].map(|rank| rank + 1);
+                for &next in &outgoing[current] {
+                    if ranks[next].is_none() {
+                        ranks[next] = next_rank;
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+
+        ranks.into_iter().map(Option::unwrap_or_default).collect()
+    }
+
+    fn route(&self, layout: &Layout) -> Vec<Edge> {
+        let mut edges: Vec<Edge> = Vec::new();
+        for (from, to) in &self.links {
+            let (Some(source), Some(target)) = (layout.shape(from), layout.shape(to)) else {
+                continue;
+            };
+
+            let mut element = format!("Flow_{from}_{to}");
+            let duplicates = edges
+                .iter()
+                .filter(|edge| edge.source == *from && edge.target == *to)
+                .count();
+            if duplicates > 0 {
+                let _ = write!(element, "_{}", duplicates + 1);
+            }
+
+            edges.push(Edge {
+                element,
+                source: from.clone(),
+                target: to.clone(),
+                waypoints: waypoints(source.bounds, target.bounds),
+            });
+        }
+        edges
+    }
+}
+
+// Forward edges leave on the right and enter on the left with an elbow between the
+// columns, backward edges loop underneath both shapes
+fn waypoints(source: Bounds, target: Bounds) -> Vec<(i32, i32)> {
+    let (source_x, source_y) = (source.x + source.width, source.y + source.height / 2);
+    let (target_x, target_y) = (target.x, target.y + target.height / 2);
+
+    if source_x < target_x {
+        if source_y == target_y {
+            return vec![(source_x, source_y), (target_x, target_y)];
+        }
+        let middle = source_x + (target_x - source_x) / 2;
+        return vec![
+            (source_x, source_y),
+            (middle, source_y),
+            (middle, target_y),
+            (target_x, target_y),
+        ];
+    }
+
+    let source_center = source.x + source.width / 2;
+    let target_center = target.x + target.width / 2;
+    let below = (source.y + source.height).max(target.y + target.height) + 20;
+    vec![
+        (source_center, source.y + source.height),
+        (source_center, below),
+        (target_center, below),
+        (target_center, target.y + target.height),
+    ]
+}
+
+const fn element_size(element: &ProcessElement) -> (i32, i32) {
+    match element {
+        ProcessElement::StartEvent { .. }
+        | ProcessElement::EndEvent { .. }
+        | ProcessElement::IntermediateEvent { .. } => (36, 36),
+        ProcessElement::Gateway { .. } | ProcessElement::DataObject { store: true, .. } => (50, 50),
+        ProcessElement::DataObject { .. } => (36, 50),
+        ProcessElement::Annotation { .. } => (100, 30),
+        _ => (100, 80),
+    }
+}
+
+fn i32_of(value: usize) -> i32 {
+    i32::try_from(value).unwrap_or(i32::MAX)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+}
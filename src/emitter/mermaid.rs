@@ -0,0 +1,257 @@
+use crate::{
+    emitter::{implicit_flows, node_id, scoped_id, unique_event_id},
+    parser::ast::{
+        Flow, FlowType, GatewayType, Lane, ProcessDeclaration, ProcessElement, needs_default_lane,
+    },
+};
+
+#[must_use]
+pub fn to_mermaid(process: &ProcessDeclaration) -> String {
+    let mut emitter = MermaidEmitter::default();
+
+    emitter.lines.push("flowchart TD".to_string());
+    emitter.emit_elements(&process.elements, "", 1);
+    emitter.collect_flows(&process.flows, "");
+    emitter.collect_implicit_flows(&process.elements, &process.flows, "");
+
+    let mut output = emitter.lines.join("\n");
+    for edge in &emitter.edges {
+        output.push_str("\n    ");
+        output.push_str(edge);
+    }
+    output.push('\n');
+
+    output
+}
+
+#[derive(Default)]
+struct MermaidEmitter {
+    lines: Vec<String>,
+    edges: Vec<String>,
+    declared: Vec<String>,
+}
+
+impl MermaidEmitter {
+    fn emit_elements(&mut self, elements: &[ProcessElement], scope: &str, depth: usize) {
+        for element in elements {
+            self.emit_element(element, scope, depth);
+        }
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn emit_element(&mut self, element: &ProcessElement, scope: &str, depth: usize) {
+        let mut id = mermaid_id(scope, &node_id(element));
+        if self.declared.contains(&id)
+            && let Some(unique) = unique_event_id(element)
+        {
+            id = mermaid_id(scope, &unique);
+        }
+
+        match element {
+            ProcessElement::StartEvent { .. } => self.declare(&id, "((start))", depth),
+            ProcessElement::EndEvent { .. } => self.declare(&id, "((end))", depth),
+            ProcessElement::IntermediateEvent { id: name, .. } => {
+                let shape = format!("(({}))", label(name.as_deref().unwrap_or("event")));
+                self.declare(&id, &shape, depth);
+            }
+            ProcessElement::Task {
+                id: name,
+                label: display,
+                ..
+            } => {
+                let text = display.as_deref().unwrap_or(name);
+                self.declare(&id, &format!("[{}]", label(text)), depth);
+            }
+            ProcessElement::CallActivity {
+                id: name,
+                label: display,
+                ..
+            } => {
+                let text = display.as_deref().unwrap_or(name);
+                self.declare(&id, &format!("[[{}]]", label(text)), depth);
+            }
+            ProcessElement::Gateway {
+                id: name,
+                gateway_type,
+                branches,
+                ..
+            } => {
+                let fallback = match gateway_type {
+                    GatewayType::Exclusive => "X",
+                    GatewayType::Parallel => "+",
+                    GatewayType::Inclusive => "O",
+                };
+                let shape = format!("{{{}}}", label(name.as_deref().unwrap_or(fallback)));
+                self.declare(&id, &shape, depth);
+
+                for branch in branches {
+                    let target = mermaid_id(scope, &branch.target);
+                    let edge = if branch.is_default {
+                        format!("{id} ==> {target}")
+                    } else if let Some(condition) = &branch.condition {
+                        format!("{id} -->|{}| {target}", label(&condition.to_string()))
+                    } else {
+                        format!("{id} --> {target}")
+                    };
+                    self.edges.push(edge);
+                }
+            }
+            ProcessElement::Subprocess {
+                id: name,
+                label: display,
+                elements,
+                flows,
+                ..
+            } => {
+                self.open_subgraph(&id, display.as_deref().unwrap_or(name), depth);
+                self.emit_elements(elements, &id, depth + 1);
+                self.close_subgraph(depth);
+                self.collect_flows(flows, &id);
+                self.collect_implicit_flows(elements, flows, &id);
+            }
+            ProcessElement::EventSubprocess {
+                id: name,
+                elements,
+                flows,
+                ..
+            } => {
+                self.open_subgraph(&id, name, depth);
+                self.emit_elements(elements, &id, depth + 1);
+                self.close_subgraph(depth);
+                self.collect_flows(flows, &id);
+                self.collect_implicit_flows(elements, flows, &id);
+            }
+            ProcessElement::Pool {
+                name,
+                lanes,
+                elements,
+                flows,
+                ..
+            } => {
+                self.open_subgraph(&id, name, depth);
+                for lane in lanes {
+                    let lane_id = mermaid_id("", &format!("{}_{}", name, lane.name));
+                    self.open_subgraph(&lane_id, &lane.name, depth + 1);
+                    self.emit_elements(&lane.elements, scope, depth + 2);
+                    if lane.is_default() {
+                        self.emit_elements(elements, scope, depth + 2);
+                    }
+                    self.close_subgraph(depth + 1);
+                }
+                if needs_default_lane(lanes, elements) {
+                    let lane_id = mermaid_id("", &format!("{}_{}", name, Lane::DEFAULT));
+                    self.open_subgraph(&lane_id, Lane::DEFAULT, depth + 1);
+                    self.emit_elements(elements, scope, depth + 2);
+                    self.close_subgraph(depth + 1);
+                } else if lanes.is_empty() {
+                    self.emit_elements(elements, scope, depth + 1);
+                }
+                self.close_subgraph(depth);
+                self.collect_flows(flows, scope);
+            }
+            ProcessElement::Group {
+                label: text,
+                elements,
+                ..
+            } => {
+                self.open_subgraph(&id, text, depth);
+                self.emit_elements(elements, scope, depth + 1);
+                self.close_subgraph(depth);
+            }
+            ProcessElement::Annotation { text, .. } => {
+                self.declare(&id, &format!(">{}]", label(text)), depth);
+            }
+            ProcessElement::DataObject {
+                id: name,
+                label: display,
+                store,
+                ..
+            } => {
+                let text = label(display.as_deref().unwrap_or(name));
+                let shape = if *store {
+                    format!("[({text})]")
+                } else {
+                    format!("[/{text}/]")
+                };
+                self.declare(&id, &shape, depth);
+            }
+        }
+    }
+
+    fn declare(&mut self, id: &str, shape: &str, depth: usize) {
+        if self.declared.iter().any(|declared| declared == id) {
+            return;
+        }
+
+        self.declared.push(id.to_string());
+        self.lines
+            .push(format!("{}{id}{shape}", "    ".repeat(depth)));
+    }
+
+    fn open_subgraph(&mut self, id: &str, title: &str, depth: usize) {
+        self.lines.push(format!(
+            "{}subgraph {id} [{}]",
+            "    ".repeat(depth),
+            label(title)
+        ));
+    }
+
+    fn close_subgraph(&mut self, depth: usize) {
+        self.lines.push(format!("{}end", "    ".repeat(depth)));
+    }
+
+    fn collect_flows(&mut self, flows: &[Flow], scope: &str) {
+        for flow in flows {
+            let arrow = match flow.flow_type {
+                FlowType::Sequence => "-->",
+                FlowType::Message => "-.->",
+                FlowType::Default => "==>",
+                FlowType::Association => "-.-",
+            };
+            let from = mermaid_id(scope, &flow.from);
+            let to = mermaid_id(scope, &flow.to);
+
+            let edge = flow.condition.as_ref().map_or_else(
+                || format!("{from} {arrow} {to}"),
+                |condition| format!("{from} {arrow}|{}| {to}", label(&condition.to_string())),
+            );
+            self.edges.push(edge);
+        }
+    }
+
+    fn collect_implicit_flows(&mut self, elements: &[ProcessElement], flows: &[Flow], scope: &str) {
+        for (from, to) in implicit_flows(elements, flows) {
+            let edge = format!(
+                "{} --> {}",
+                mermaid_id(scope, &from),
+                mermaid_id(scope, &to)
+            );
+            self.edges.push(edge);
+        }
+    }
+}
+
+fn mermaid_id(scope: &str, name: &str) -> String {
+    let id: String = scoped_id(scope, name)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+
+    // `end` closes a subgraph in Mermaid, so it cannot be used as a node id
+    if id == "end" {
+        "end_event".to_string()
+    } else {
+        id
+    }
+}
+
+fn label(text: &str) -> String {
+    if text
+        .chars()
+        .all(|c| c.is_alphanumeric() || matches!(c, '_' | ' ' | '-'))
+    {
+        text.to_string()
+    } else {
+        format!("\"{}\"", text.replace('"', "#quot;"))
+    }
+}
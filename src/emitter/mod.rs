@@ -0,0 +1,97 @@
+pub mod dot;
+pub mod layout;
+pub mod mermaid;
+
+use std::fmt::Write;
+
+use crate::{
+    lexer::Span,
+    parser::{
+        analysis::FlowGraph,
+        ast::{Flow, ProcessElement, parse_duration_millis},
+    },
+};
+
+#[must_use]
+pub fn node_id(element: &ProcessElement) -> String {
+    match element {
+        ProcessElement::StartEvent { id, .. } => id.clone().unwrap_or_else(|| "start".to_string()),
+        ProcessElement::EndEvent { id, .. } => id.clone().unwrap_or_else(|| "end".to_string()),
+        ProcessElement::Gateway { id, span, .. } => {
+            id.clone().unwrap_or_else(|| generated_id("gateway", span))
+        }
+        ProcessElement::IntermediateEvent { id, span, .. } => {
+            id.clone().unwrap_or_else(|| generated_id("event", span))
+        }
+        ProcessElement::Task { id, .. }
+        | ProcessElement::Subprocess { id, .. }
+        | ProcessElement::EventSubprocess { id, .. }
+        | ProcessElement::CallActivity { id, .. }
+        | ProcessElement::DataObject { id, .. } => id.clone(),
+        ProcessElement::Pool { name, .. } => name.clone(),
+        ProcessElement::Group { span, .. } => generated_id("group", span),
+        ProcessElement::Annotation { id, span, .. } => {
+            id.clone().unwrap_or_else(|| generated_id("note", span))
+        }
+    }
+}
+
+// A process may have several anonymous start or end events, only the first one can
+// take the plain `start`/`end` id that flows refer to
+#[must_use]
+pub fn unique_event_id(element: &ProcessElement) -> Option<String> {
+    match element {
+        ProcessElement::StartEvent { id: None, span, .. } => Some(generated_id("start", span)),
+        ProcessElement::EndEvent { id: None, span, .. } => Some(generated_id("end", span)),
+        _ => None,
+    }
+}
+
+// The edges a block gets from elements continuing with the next one declared, as
+// `(from, to)` node ids
+#[must_use]
+pub fn implicit_flows(elements: &[ProcessElement], flows: &[Flow]) -> Vec<(String, String)> {
+    FlowGraph::build(elements, flows)
+        .implicit_edges()
+        .into_iter()
+        .map(|(from, to)| (node_id(from), node_id(to)))
+        .collect()
+}
+
+#[must_use]
+pub fn generated_id(prefix: &str, span: &Span) -> String {
+    format!("{prefix}_{}_{}", span.line, span.column)
+}
+
+#[must_use]
+pub fn scoped_id(scope: &str, name: &str) -> String {
+    if !scope.is_empty() && matches!(name, "start" | "end") {
+        format!("{scope}_{name}")
+    } else {
+        name.to_string()
+    }
+}
+
+#[must_use]
+pub fn to_iso8601_duration(raw: &str) -> Option<String> {
+    let millis = parse_duration_millis(raw)?;
+    let (hours, rest) = (millis / 3_600_000, millis % 3_600_000);
+    let (minutes, rest) = (rest / 60_000, rest % 60_000);
+    let (seconds, fraction) = (rest / 1_000, rest % 1_000);
+
+    let mut output = "PT".to_string();
+    if hours > 0 {
+        let _ = write!(output, "{hours}H");
+    }
+    if minutes > 0 {
+        let _ = write!(output, "{minutes}M");
+    }
+    if fraction > 0 {
+        let fraction = format!("{fraction:03}");
+        let _ = write!(output, "{seconds}.{}S", fraction.trim_end_matches('0'));
+    } else if seconds > 0 || millis == 0 {
+        let _ = write!(output, "{seconds}S");
+    }
+
+    Some(output)
+}
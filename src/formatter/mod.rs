@@ -0,0 +1,65 @@
+pub mod printer;
+pub mod source;
+
+// Above this many table cells the changed region is only reported, not diffed
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+#[must_use]
+#[allow(clippy::format_push_string)]
+pub fn diff_lines(original: &str, formatted: &str) -> String {
+    let old: Vec<&str> = original.lines().collect();
+    let new: Vec<&str> = formatted.lines().collect();
+
+    // Lines the two share at either end are left out of the table
+    let prefix = old
+        .iter()
+        .zip(&new)
+        .take_while(|(old, new)| old == new)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+    let old = &old[prefix..old.len() - suffix];
+    let new = &new[prefix..new.len() - suffix];
+
+    if old.len().saturating_mul(new.len()) > MAX_DIFF_CELLS {
+        return format!(
+            "File differs from line {} on, {} lines changed to {}, too many to compare\n",
+            prefix + 1,
+            old.len(),
+            new.len()
+        );
+    }
+
+    // Longest common subsequence table, filled from the end of both inputs
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut output = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || common[i + 1][j] >= common[i][j + 1]) {
+            output.push_str(&format!("{:>4} - {}\n", prefix + i + 1, old[i]));
+            i += 1;
+        } else {
+            output.push_str(&format!("{:>4} + {}\n", prefix + j + 1, new[j]));
+            j += 1;
+        }
+    }
+
+    output
+}
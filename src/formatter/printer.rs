@@ -0,0 +1,382 @@
+use std::fmt::Write;
+
+use indexmap::IndexMap;
+
+use crate::parser::ast::{
+    AstDocument, AttributeValue, EventDirection, EventType, Flow, FlowType, GatewayMode,
+    GatewayType, ImportDeclaration, ProcessElement, TaskType,
+};
+
+#[must_use]
+pub fn print_document(document: &AstDocument) -> String {
+    let mut printer = SourcePrinter::default();
+
+    for import in &document.imports {
+        printer.line(0, print_import(import));
+    }
+
+    for process in &document.processes {
+        if !printer.lines.is_empty() {
+            printer.line(0, String::new());
+        }
+        printer.line(
+            0,
+            format!(
+                "process {}{} {{",
+                process.name,
+                print_process_attributes(&process.attributes)
+            ),
+        );
+        for variable in &process.variables {
+            printer.line(
+                1,
+                format!("var {}: {}", variable.name, variable.variable_type),
+            );
+        }
+        printer.elements(&process.elements, 1);
+        printer.flows(&process.flows, 1);
+        printer.line(0, "}".to_string());
+    }
+
+    let mut output = printer.lines.join("\n");
+    output.push('\n');
+    output
+}
+
+#[derive(Default)]
+struct SourcePrinter {
+    lines: Vec<String>,
+}
+
+impl SourcePrinter {
+    fn line(&mut self, depth: usize, text: String) {
+        if text.is_empty() {
+            self.lines.push(text);
+        } else {
+            self.lines.push(format!("{}{text}", "    ".repeat(depth)));
+        }
+    }
+
+    fn elements(&mut self, elements: &[ProcessElement], depth: usize) {
+        for element in elements {
+            self.element(element, depth);
+        }
+    }
+
+    fn flows(&mut self, flows: &[Flow], depth: usize) {
+        if !flows.is_empty() {
+            self.line(0, String::new());
+        }
+
+        for flow in flows {
+            let arrow = match flow.flow_type {
+                FlowType::Sequence => "->",
+                FlowType::Message => "-->",
+                FlowType::Default => "=>",
+                FlowType::Association => "..>",
+            };
+            let mut text = format!("{} {arrow} {}", flow.from, flow.to);
+            if let Some(condition) = &flow.condition {
+                let _ = write!(text, " [{condition}]");
+            }
+            self.line(depth, text);
+        }
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn element(&mut self, element: &ProcessElement, depth: usize) {
+        match element {
+            ProcessElement::StartEvent {
+                id,
+                event_type,
+                attributes,
+                ..
+            } => {
+                let text = format!(
+                    "start{}{}{}",
+                    id.as_ref().map_or_else(String::new, |id| format!(" {id}")),
+                    print_event_type(event_type.as_ref()),
+                    print_attributes(attributes)
+                );
+                self.line(depth, text);
+            }
+            ProcessElement::EndEvent {
+                id,
+                event_type,
+                attributes,
+                ..
+            } => {
+                let text = format!(
+                    "end{}{}{}",
+                    id.as_ref().map_or_else(String::new, |id| format!(" {id}")),
+                    print_event_type(event_type.as_ref()),
+                    print_attributes(attributes)
+                );
+                self.line(depth, text);
+            }
+            ProcessElement::Task {
+                id,
+                label,
+                task_type,
+                attributes,
+                ..
+            } => {
+                let keyword = match task_type {
+                    TaskType::Generic => "task",
+                    TaskType::User => "user",
+                    TaskType::Service => "service",
+                    TaskType::Script => "script",
+                };
+                self.line(
+                    depth,
+                    format!(
+                        "{keyword} {id}{}{}",
+                        print_label(label.as_deref()),
+                        print_attributes(attributes)
+                    ),
+                );
+            }
+            ProcessElement::Gateway {
+                id,
+                gateway_type,
+                mode,
+                branches,
+                attributes,
+                ..
+            } => {
+                let keyword = match gateway_type {
+                    GatewayType::Exclusive => "xor",
+                    GatewayType::Parallel => "and",
+                    GatewayType::Inclusive => "or",
+                };
+                let name = id.as_ref().map_or_else(String::new, |id| format!(" {id}"));
+                let modifier = match mode {
+                    GatewayMode::Split => "",
+                    GatewayMode::Join => " join",
+                    GatewayMode::Mixed => " mixed",
+                };
+                let attributes = print_attributes(attributes);
+                if *mode == GatewayMode::Join && branches.is_empty() {
+                    self.line(depth, format!("{keyword}{name}{modifier}{attributes}"));
+                    return;
+                }
+                self.line(depth, format!("{keyword}{name}{modifier}{attributes} {{"));
+                for branch in branches {
+                    let text = if branch.is_default {
+                        format!("=> {}", branch.target)
+                    } else {
+                        let condition = branch
+                            .condition
+                            .as_ref()
+                            .map_or_else(|| "true".to_string(), ToString::to_string);
+                        format!("[{condition}] -> {}", branch.target)
+                    };
+                    self.line(depth + 1, text);
+                }
+                self.line(depth, "}".to_string());
+            }
+            ProcessElement::IntermediateEvent {
+                id,
+                event_type,
+                direction,
+                attributes,
+                ..
+            } => {
+                let direction = match direction {
+                    EventDirection::Throw => " throw",
+                    EventDirection::Catch => "",
+                };
+                let name = id.as_ref().map_or_else(String::new, |id| format!(" {id}"));
+                let text = format!(
+                    "event{direction}{name}{}{}",
+                    print_event_type(Some(event_type)),
+                    print_attributes(attributes)
+                );
+                self.line(depth, text);
+            }
+            ProcessElement::Subprocess {
+                id,
+                label,
+                elements,
+                flows,
+                attributes,
+                ..
+            } => {
+                self.line(
+                    depth,
+                    format!(
+                        "subprocess {id}{}{} {{",
+                        print_label(label.as_deref()),
+                        print_attributes(attributes)
+                    ),
+                );
+                self.elements(elements, depth + 1);
+                self.flows(flows, depth + 1);
+                self.line(depth, "}".to_string());
+            }
+            ProcessElement::EventSubprocess {
+                id,
+                trigger,
+                elements,
+                flows,
+                attributes,
+                ..
+            } => {
+                self.line(
+                    depth,
+                    format!(
+                        "event subprocess {id}{}{} {{",
+                        print_event_type(Some(trigger)),
+                        print_attributes(attributes)
+                    ),
+                );
+                self.elements(elements, depth + 1);
+                self.flows(flows, depth + 1);
+                self.line(depth, "}".to_string());
+            }
+            ProcessElement::CallActivity {
+                id,
+                label,
+                called_element,
+                attributes,
+                ..
+            } => {
+                let target = if called_element.contains("::") {
+                    called_element
+                } else {
+                    id
+                };
+                self.line(
+                    depth,
+                    format!(
+                        "call {target}{}{}",
+                        print_label(label.as_deref()),
+                        print_attributes(attributes)
+                    ),
+                );
+            }
+            ProcessElement::Pool {
+                name,
+                lanes,
+                elements,
+                flows,
+                attributes,
+                ..
+            } => {
+                self.line(
+                    depth,
+                    format!("pool {name}{} {{", print_attributes(attributes)),
+                );
+                for lane in lanes {
+                    self.line(depth + 1, format!("lane {} {{", lane.name));
+                    self.elements(&lane.elements, depth + 2);
+                    self.line(depth + 1, "}".to_string());
+                }
+                self.elements(elements, depth + 1);
+                self.flows(flows, depth + 1);
+                self.line(depth, "}".to_string());
+            }
+            ProcessElement::Group {
+                label, elements, ..
+            } => {
+                self.line(depth, format!("group {} {{", quote(label)));
+                self.elements(elements, depth + 1);
+                self.line(depth, "}".to_string());
+            }
+            ProcessElement::Annotation { id, text, .. } => {
+                let id = id.as_ref().map_or_else(String::new, |id| format!("{id} "));
+                self.line(depth, format!("note {id}{}", quote(text)));
+            }
+            ProcessElement::DataObject {
+                id, label, store, ..
+            } => {
+                let keyword = if *store { "data store" } else { "data" };
+                self.line(
+                    depth,
+                    format!("{keyword} {id}{}", print_label(label.as_deref())),
+                );
+            }
+        }
+    }
+}
+
+pub(crate) fn print_import(import: &ImportDeclaration) -> String {
+    import.alias.as_ref().map_or_else(
+        || {
+            format!(
+                "import {} from {}",
+                import.items.join(", "),
+                quote(&import.path)
+            )
+        },
+        |alias| format!("import {} as {alias}", quote(&import.path)),
+    )
+}
+
+pub(crate) fn print_event_type(event_type: Option<&EventType>) -> String {
+    let (name, payload) = match event_type {
+        None => return String::new(),
+        Some(EventType::Terminate) => return " @terminate".to_string(),
+        Some(EventType::Timer(duration)) if !duration.is_empty() => {
+            return format!(" @timer {duration}");
+        }
+        Some(EventType::Timer(_)) => ("timer", ""),
+        Some(EventType::Message(payload)) => ("message", payload.as_str()),
+        Some(EventType::Error(payload)) => ("error", payload.as_str()),
+        Some(EventType::Signal(payload)) => ("signal", payload.as_str()),
+        Some(EventType::Escalation(payload)) => ("escalation", payload.as_str()),
+        Some(EventType::Compensation(payload)) => ("compensation", payload.as_str()),
+        Some(EventType::Conditional(expression)) => ("conditional", expression.as_str()),
+    };
+
+    if payload.is_empty() {
+        format!(" @{name}")
+    } else {
+        format!(" @{name} {}", quote(payload))
+    }
+}
+
+fn print_process_attributes(attributes: &IndexMap<String, AttributeValue>) -> String {
+    attributes
+        .iter()
+        .fold(String::new(), |mut output, (key, value)| {
+            let _ = write!(output, " @{key} {}", print_value(value));
+            output
+        })
+}
+
+pub(crate) fn print_label(label: Option<&str>) -> String {
+    label.map_or_else(String::new, |label| format!(" {}", quote(label)))
+}
+
+fn print_attributes(attributes: &IndexMap<String, AttributeValue>) -> String {
+    if attributes.is_empty() {
+        return String::new();
+    }
+
+    let pairs: Vec<String> = attributes
+        .iter()
+        .map(|(key, value)| format!("{key}={}", print_value(value)))
+        .collect();
+    format!("({})", pairs.join(", "))
+}
+
+pub(crate) fn print_value(value: &AttributeValue) -> String {
+    match value {
+        AttributeValue::String(text) => quote(text),
+        AttributeValue::Number(number) => number.to_string(),
+        AttributeValue::Boolean(flag) => flag.to_string(),
+        AttributeValue::Duration(duration) => duration.clone(),
+    }
+}
+
+pub(crate) fn quote(text: &str) -> String {
+    format!(
+        "\"{}\"",
+        text.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r")
+            .replace('\t', "\\t")
+    )
+}
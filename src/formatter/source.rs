@@ -0,0 +1,384 @@
+use crate::{
+    lexer::{Lexer, Token, TokenKind},
+    parser::ast::AstDocument,
+};
+
+struct Line<'a> {
+    depth: usize,
+    tokens: Vec<&'a Token>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatOptions {
+    // Spaces per nesting level
+    pub indent_width: usize,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self { indent_width: 4 }
+    }
+}
+
+#[must_use]
+pub fn format_source(document: &AstDocument, source: &str) -> String {
+    format_source_with_options(document, source, &FormatOptions::default())
+}
+
+#[must_use]
+pub fn format_source_with_options(
+    document: &AstDocument,
+    source: &str,
+    options: &FormatOptions,
+) -> String {
+    // Never rewrite input that did not parse, the token layout may be wrong
+    if document.has_errors() {
+        return source.to_string();
+    }
+
+    let tokens = Lexer::new(source, "").tokenize();
+    let lines = assign_depths(split_statements(split_lines(&tokens)));
+
+    let mut rendered: Vec<(usize, String, Option<String>)> = Vec::new();
+    let mut previous_blank = true;
+    for (index, line) in lines.iter().enumerate() {
+        if line.tokens.is_empty() {
+            let next_closes = lines[index + 1..]
+                .iter()
+                .find(|next| !next.tokens.is_empty())
+                .is_none_or(|next| next.tokens[0].kind == TokenKind::RightBrace);
+            if !previous_blank && !next_closes {
+                rendered.push((0, String::new(), None));
+            }
+            previous_blank = true;
+            continue;
+        }
+
+        let (head, attributes) = render_line(&line.tokens);
+        previous_blank = opens_block(&line.tokens);
+        rendered.push((line.depth, head, attributes));
+    }
+
+    let mut output = String::new();
+    let mut index = 0;
+    while index < rendered.len() {
+        // Consecutive elements with attribute lists get their parentheses aligned
+        let run_end = rendered[index..]
+            .iter()
+            .position(|(depth, _, attributes)| attributes.is_none() || *depth != rendered[index].0)
+            .map_or(rendered.len(), |offset| index + offset);
+        let width = rendered[index..run_end]
+            .iter()
+            .map(|(_, head, _)| head.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        for (depth, head, attributes) in &rendered[index..run_end.max(index + 1)] {
+            if !head.is_empty() {
+                output.push_str(&" ".repeat(options.indent_width * depth));
+                output.push_str(head);
+            }
+            if let Some(attributes) = attributes {
+                let padding = width.saturating_sub(head.chars().count());
+                output.push_str(&" ".repeat(padding));
+                output.push_str(attributes);
+            }
+            output.push('\n');
+        }
+
+        index = run_end.max(index + 1);
+    }
+
+    output
+}
+
+fn split_lines(tokens: &[Token]) -> Vec<Vec<&Token>> {
+    let mut lines = vec![Vec::new()];
+    let mut nesting = 0usize;
+
+    for token in tokens {
+        match token.kind {
+            TokenKind::Eof => break,
+            TokenKind::Newline | TokenKind::CarriageReturnNewline => {
+                // Attribute lists and conditions spanning several lines are joined
+                if nesting == 0 {
+                    lines.push(Vec::new());
+                }
+                continue;
+            }
+            TokenKind::LeftParen | TokenKind::LeftBracket => nesting += 1,
+            TokenKind::RightParen | TokenKind::RightBracket => {
+                nesting = nesting.saturating_sub(1);
+            }
+            _ => {}
+        }
+
+        if let Some(line) = lines.last_mut() {
+            line.push(token);
+        }
+    }
+
+    lines
+}
+
+fn split_statements(lines: Vec<Vec<&Token>>) -> Vec<Vec<&Token>> {
+    let mut statements = Vec::new();
+
+    for line in lines {
+        if line.is_empty() {
+            statements.push(line);
+            continue;
+        }
+
+        let mut current: Vec<&Token> = Vec::new();
+        let mut nesting = 0usize;
+
+        for (position, &token) in line.iter().enumerate() {
+            if nesting == 0 && starts_statement(&current, token) {
+                statements.push(std::mem::take(&mut current));
+            }
+
+            match token.kind {
+                TokenKind::LeftParen | TokenKind::LeftBracket => nesting += 1,
+                TokenKind::RightParen | TokenKind::RightBracket => {
+                    nesting = nesting.saturating_sub(1);
+                }
+                _ => {}
+            }
+            current.push(token);
+
+            let followed_by_code = line
+                .get(position + 1)
+                .is_some_and(|next| !is_comment(&next.kind));
+            if nesting == 0
+                && matches!(token.kind, TokenKind::LeftBrace | TokenKind::RightBrace)
+                && followed_by_code
+            {
+                statements.push(std::mem::take(&mut current));
+            }
+        }
+
+        if !current.is_empty() {
+            statements.push(current);
+        }
+    }
+
+    statements
+}
+
+fn starts_statement(current: &[&Token], token: &Token) -> bool {
+    let Some(previous) = current.last() else {
+        return false;
+    };
+
+    if token.kind == TokenKind::RightBrace {
+        return true;
+    }
+
+    // `event subprocess` is a single element keyword
+    if previous.kind == TokenKind::Event && token.kind == TokenKind::Subprocess {
+        return false;
+    }
+
+    if is_element_keyword(&token.kind) {
+        return !matches!(
+            previous.kind,
+            TokenKind::SequenceFlow
+                | TokenKind::MessageFlow
+                | TokenKind::DefaultFlow
+                | TokenKind::Association
+                | TokenKind::Namespace
+                | TokenKind::Equals
+                | TokenKind::Comma
+                | TokenKind::At
+                | TokenKind::Colon
+                | TokenKind::BlockComment
+        );
+    }
+
+    // `A -> B C -> D` holds two flows
+    token.kind == TokenKind::Identifier
+        && previous.kind == TokenKind::Identifier
+        && current.len() >= 2
+        && is_flow_arrow(&current[current.len() - 2].kind)
+}
+
+fn assign_depths(statements: Vec<Vec<&Token>>) -> Vec<Line<'_>> {
+    let mut depth = 0usize;
+
+    statements
+        .into_iter()
+        .map(|tokens| {
+            let mut line_depth = depth;
+            for token in &tokens {
+                match token.kind {
+                    TokenKind::LeftBrace => depth += 1,
+                    TokenKind::RightBrace => depth = depth.saturating_sub(1),
+                    _ => {}
+                }
+            }
+            if tokens
+                .first()
+                .is_some_and(|token| token.kind == TokenKind::RightBrace)
+            {
+                line_depth = line_depth.saturating_sub(1);
+            }
+
+            Line {
+                depth: line_depth,
+                tokens,
+            }
+        })
+        .collect()
+}
+
+fn render_line(tokens: &[&Token]) -> (String, Option<String>) {
+    let split = attribute_list_start(tokens);
+    let mut head = String::new();
+    let mut attributes = String::new();
+    let mut brackets = 0usize;
+    // `var amount: number` separates the type, attribute keys like `camunda:assignee` do not
+    let declaration = tokens
+        .first()
+        .is_some_and(|token| token.kind == TokenKind::Var);
+
+    for (index, token) in tokens.iter().enumerate() {
+        let target = if split.is_some_and(|split| index >= split) {
+            &mut attributes
+        } else {
+            &mut head
+        };
+
+        if index > 0
+            && split != Some(index)
+            && (needs_space(tokens[index - 1], token, brackets)
+                || (declaration && tokens[index - 1].kind == TokenKind::Colon))
+        {
+            target.push(' ');
+        }
+        if token.kind == TokenKind::LineComment {
+            target.push_str(token.text.trim_end());
+        } else {
+            target.push_str(&token.text);
+        }
+
+        match token.kind {
+            TokenKind::LeftBracket => brackets += 1,
+            TokenKind::RightBracket => brackets = brackets.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    (head, split.map(|_| attributes))
+}
+
+fn attribute_list_start(tokens: &[&Token]) -> Option<usize> {
+    if !tokens
+        .first()
+        .is_some_and(|token| is_element_keyword(&token.kind))
+    {
+        return None;
+    }
+
+    tokens
+        .iter()
+        .enumerate()
+        .skip(1)
+        .find_map(|(index, token)| {
+            (token.kind == TokenKind::LeftParen && tokens[index - 1].kind == TokenKind::Identifier)
+                .then_some(index)
+        })
+}
+
+fn needs_space(previous: &Token, next: &Token, brackets: usize) -> bool {
+    use TokenKind::{
+        At, Colon, Comma, Equals, LeftBracket, LeftParen, Namespace, Question, RightBracket,
+        RightParen,
+    };
+
+    if is_comment(&next.kind) {
+        return true;
+    }
+    if previous.kind == LeftBracket || next.kind == RightBracket {
+        return false;
+    }
+    // Conditions are free-form, keep whatever separation the author chose
+    if brackets > 0 || is_free_form(&previous.kind) || is_free_form(&next.kind) {
+        return previous.span.end != next.span.start;
+    }
+    if matches!(
+        next.kind,
+        Comma | RightParen | Question | Colon | Namespace | Equals
+    ) {
+        return false;
+    }
+    if matches!(previous.kind, LeftParen | At | Colon | Namespace | Equals) {
+        return false;
+    }
+
+    !(next.kind == LeftParen && previous.kind == TokenKind::Identifier)
+}
+
+fn opens_block(tokens: &[&Token]) -> bool {
+    tokens
+        .iter()
+        .rev()
+        .find(|token| !is_comment(&token.kind))
+        .is_some_and(|token| token.kind == TokenKind::LeftBrace)
+}
+
+const fn is_free_form(kind: &TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::Unknown
+            | TokenKind::EqualEqual
+            | TokenKind::NotEqual
+            | TokenKind::LessEqual
+            | TokenKind::GreaterEqual
+            | TokenKind::Less
+            | TokenKind::Greater
+            | TokenKind::AndAnd
+            | TokenKind::OrOr
+            | TokenKind::Bang
+    )
+}
+
+const fn is_comment(kind: &TokenKind) -> bool {
+    matches!(kind, TokenKind::LineComment | TokenKind::BlockComment)
+}
+
+const fn is_flow_arrow(kind: &TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::SequenceFlow
+            | TokenKind::MessageFlow
+            | TokenKind::DefaultFlow
+            | TokenKind::Association
+    )
+}
+
+const fn is_element_keyword(kind: &TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::Process
+            | TokenKind::Import
+            | TokenKind::Subprocess
+            | TokenKind::Start
+            | TokenKind::End
+            | TokenKind::Task
+            | TokenKind::User
+            | TokenKind::Service
+            | TokenKind::Script
+            | TokenKind::Call
+            | TokenKind::Xor
+            | TokenKind::And
+            | TokenKind::Or
+            | TokenKind::Event
+            | TokenKind::Group
+            | TokenKind::Pool
+            | TokenKind::Lane
+            | TokenKind::Note
+            | TokenKind::Data
+            | TokenKind::Var
+    )
+}
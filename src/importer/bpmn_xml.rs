@@ -0,0 +1,711 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+};
+
+use indexmap::IndexMap;
+use quick_xml::{
+    escape::resolve_predefined_entity,
+    events::{BytesRef, BytesStart, Event},
+    reader::Reader,
+};
+use thiserror::Error;
+
+use crate::{
+    lexer::Span,
+    parser::{
+        ast::{
+            AstDocument, AttributeValue, ConditionExpr, EventDirection, EventType, Flow, FlowType,
+            GatewayBranch, GatewayMode, GatewayType, ProcessDeclaration, ProcessElement, TaskType,
+        },
+        condition::parse_condition_str,
+        definitions::collect_definitions,
+    },
+};
+
+#[derive(Error, Debug)]
+pub enum ImportError {
+    #[error("Malformed XML at line {line}: {message}")]
+    Malformed { message: String, line: usize },
+
+    #[error("No <process> element found")]
+    NoProcess,
+}
+
+struct XmlElement {
+    name: String,
+    attributes: HashMap<String, String>,
+    children: Vec<Self>,
+    text: String,
+    offset: usize,
+}
+
+impl XmlElement {
+    const fn new(name: String, attributes: HashMap<String, String>, offset: usize) -> Self {
+        Self {
+            name,
+            attributes,
+            children: Vec::new(),
+            text: String::new(),
+            offset,
+        }
+    }
+
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.get(name).map(String::as_str)
+    }
+
+    fn child(&self, name: &str) -> Option<&Self> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    fn collect<'a>(&'a self, name: &str, found: &mut Vec<&'a Self>) {
+        for child in &self.children {
+            if child.name == name {
+                found.push(child);
+            } else {
+                child.collect(name, found);
+            }
+        }
+    }
+}
+
+pub fn from_bpmn_xml(xml: &str) -> Result<AstDocument, ImportError> {
+    let root = parse_xml(xml)?;
+
+    let mut processes = Vec::new();
+    root.collect("process", &mut processes);
+    if processes.is_empty() {
+        return Err(ImportError::NoProcess);
+    }
+
+    let mut document = AstDocument::new();
+    for process in processes {
+        let process = ProcessImporter::new(xml).import(process, &mut document);
+        document.processes.push(process);
+    }
+    collect_definitions(&mut document);
+
+    Ok(document)
+}
+
+struct ProcessImporter<'a> {
+    xml: &'a str,
+    names: HashMap<&'a str, String>,
+    used: HashSet<String>,
+    named_starts: bool,
+    named_ends: bool,
+    // Elements written as notes, by id, with the note text
+    unsupported: IndexMap<&'a str, (String, Span)>,
+}
+
+impl<'a> ProcessImporter<'a> {
+    fn new(xml: &'a str) -> Self {
+        Self {
+            xml,
+            names: HashMap::new(),
+            used: HashSet::new(),
+            named_starts: false,
+            named_ends: false,
+            unsupported: IndexMap::new(),
+        }
+    }
+
+    fn import(mut self, process: &'a XmlElement, document: &mut AstDocument) -> ProcessDeclaration {
+        let count = |name: &str| {
+            process
+                .children
+                .iter()
+                .filter(|child| child.name == name)
+                .count()
+        };
+        self.named_starts = count("startEvent") > 1;
+        self.named_ends = count("endEvent") > 1;
+
+        let mut elements = Vec::new();
+        let mut gateways = HashMap::new();
+        let mut sequence_flows = Vec::new();
+
+        for child in &process.children {
+            if child.name == "sequenceFlow" {
+                sequence_flows.push(child);
+                continue;
+            }
+
+            if let Some(element) = self.import_element(child) {
+                if let (ProcessElement::Gateway { .. }, Some(id)) =
+                    (&element, child.attribute("id"))
+                {
+                    gateways.insert(id, elements.len());
+                }
+                elements.push(element);
+            }
+        }
+
+        let mut flows = Vec::new();
+        let mut start_targets = Vec::new();
+        for flow in &sequence_flows {
+            // Flows out of unsupported elements are followed from the flows into them
+            let Some(from) = flow
+                .attribute("sourceRef")
+                .and_then(|source| self.names.get(source))
+                .cloned()
+            else {
+                continue;
+            };
+            let source = flow.attribute("sourceRef").unwrap_or_default();
+
+            let condition = flow
+                .child("conditionExpression")
+                .map(|expression| condition_text(&expression.text))
+                .filter(|condition| !condition.is_empty())
+                .map(|condition| {
+                    parse_condition_str(&condition).unwrap_or(ConditionExpr::Raw(condition))
+                });
+            let span = self.span(flow.offset);
+
+            for target in self.flow_targets(flow, &sequence_flows) {
+                let Some(to) = self.names.get(target).cloned() else {
+                    continue;
+                };
+
+                // The start is moved right before the element it leads to
+                if from == "start" {
+                    start_targets.push(to.clone());
+                }
+
+                if let Some(&index) = gateways.get(source) {
+                    let default = process
+                        .children
+                        .iter()
+                        .find(|child| child.attribute("id") == Some(source))
+                        .and_then(|gateway| gateway.attribute("default"));
+                    let is_default = default.is_some() && default == flow.attribute("id");
+
+                    if let ProcessElement::Gateway { branches, .. } = &mut elements[index]
+                        && (is_default || condition.is_some())
+                    {
+                        branches.push(GatewayBranch {
+                            condition: if is_default { None } else { condition.clone() },
+                            target: to,
+                            is_default,
+                            span: span.clone(),
+                            target_span: span.clone(),
+                        });
+                        continue;
+                    }
+                }
+
+                flows.push(Flow {
+                    from: from.clone(),
+                    to,
+                    flow_type: FlowType::Sequence,
+                    condition: condition.clone(),
+                    span: span.clone(),
+                    from_span: span.clone(),
+                    to_span: span.clone(),
+                });
+            }
+        }
+
+        order_start_targets(&mut elements, &start_targets);
+        end_open_paths(&elements, &mut flows);
+        self.warn_unsupported(&sequence_flows, document);
+
+        let name = process
+            .attribute("name")
+            .and_then(identifier)
+            .or_else(|| process.attribute("id").and_then(identifier))
+            .unwrap_or_else(|| "ImportedProcess".to_string());
+
+        ProcessDeclaration {
+            name,
+            attributes: IndexMap::new(),
+            elements,
+            flows,
+            variables: Vec::new(),
+            span: self.span(process.offset),
+        }
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn import_element(&mut self, child: &'a XmlElement) -> Option<ProcessElement> {
+        let span = self.span(child.offset);
+
+        let element = match child.name.as_str() {
+            "startEvent" => {
+                let id = self.event_name(child, self.named_starts, "start");
+                ProcessElement::StartEvent {
+                    id,
+                    event_type: event_definition(child),
+                    attributes: IndexMap::new(),
+                    span,
+                }
+            }
+            "endEvent" => {
+                let id = self.event_name(child, self.named_ends, "end");
+                ProcessElement::EndEvent {
+                    id,
+                    event_type: event_definition(child),
+                    attributes: IndexMap::new(),
+                    span,
+                }
+            }
+            "task" | "userTask" | "serviceTask" | "scriptTask" => {
+                let task_type = match child.name.as_str() {
+                    "userTask" => TaskType::User,
+                    "serviceTask" => TaskType::Service,
+                    "scriptTask" => TaskType::Script,
+                    _ => TaskType::Generic,
+                };
+                ProcessElement::Task {
+                    id: self.name(child),
+                    label: display_label(child),
+                    task_type,
+                    attributes: loop_attributes(child),
+                    span,
+                }
+            }
+            "exclusiveGateway" | "parallelGateway" | "inclusiveGateway" => {
+                ProcessElement::Gateway {
+                    id: Some(self.name(child)),
+                    gateway_type: match child.name.as_str() {
+                        "parallelGateway" => GatewayType::Parallel,
+                        "inclusiveGateway" => GatewayType::Inclusive,
+                        _ => GatewayType::Exclusive,
+                    },
+                    mode: match child.attribute("gatewayDirection") {
+                        Some("Converging") => GatewayMode::Join,
+                        Some("Mixed") => GatewayMode::Mixed,
+                        _ => GatewayMode::Split,
+                    },
+                    branches: Vec::new(),
+                    attributes: IndexMap::new(),
+                    span,
+                }
+            }
+            "callActivity" => {
+                let id = self.name(child);
+                let mut attributes = IndexMap::new();
+                if let Some(called) = child.attribute("calledElement") {
+                    attributes.insert(
+                        "process".to_string(),
+                        AttributeValue::String(called.to_string()),
+                    );
+                }
+                attributes.extend(loop_attributes(child));
+                ProcessElement::CallActivity {
+                    called_element: id.clone(),
+                    id,
+                    label: display_label(child),
+                    attributes,
+                    span,
+                }
+            }
+            "textAnnotation" => ProcessElement::Annotation {
+                id: None,
+                text: child
+                    .child("text")
+                    .map_or_else(String::new, |text| text.text.trim().to_string()),
+                span,
+            },
+            "intermediateCatchEvent" | "intermediateThrowEvent"
+                if let Some(event_type) = event_definition(child) =>
+            {
+                ProcessElement::IntermediateEvent {
+                    id: Some(self.name(child)),
+                    event_type,
+                    direction: if child.name == "intermediateThrowEvent" {
+                        EventDirection::Throw
+                    } else {
+                        EventDirection::Catch
+                    },
+                    payload: None,
+                    attributes: IndexMap::new(),
+                    span,
+                }
+            }
+            "dataObjectReference" | "dataStoreReference" => ProcessElement::DataObject {
+                id: self.name(child),
+                label: display_label(child),
+                store: child.name == "dataStoreReference",
+                span,
+            },
+            // Only the references are drawn, the object itself is a definition
+            "laneSet" | "extensionElements" | "documentation" | "association" | "incoming"
+            | "outgoing" | "property" | "ioSpecification" | "dataObject" => return None,
+            other => {
+                let label = child
+                    .attribute("name")
+                    .or_else(|| child.attribute("id"))
+                    .unwrap_or_default();
+                let text = format!("Unsupported {other} '{label}'");
+                if let Some(id) = child.attribute("id") {
+                    self.unsupported.insert(id, (text.clone(), span.clone()));
+                }
+                ProcessElement::Annotation {
+                    id: None,
+                    text,
+                    span,
+                }
+            }
+        };
+
+        Some(element)
+    }
+
+    // Flows into an unsupported element continue to wherever its own flows lead
+    fn flow_targets(&self, flow: &'a XmlElement, flows: &[&'a XmlElement]) -> Vec<&'a str> {
+        let mut targets = Vec::new();
+        let mut pending: Vec<&str> = flow.attribute("targetRef").into_iter().collect();
+        let mut visited = HashSet::new();
+
+        while let Some(target) = pending.pop() {
+            if !self.unsupported.contains_key(target) {
+                if !targets.contains(&target) {
+                    targets.push(target);
+                }
+                continue;
+            }
+            if !visited.insert(target) {
+                continue;
+            }
+            pending.extend(
+                flows
+                    .iter()
+                    .rev()
+                    .filter(|next| next.attribute("sourceRef") == Some(target))
+                    .filter_map(|next| next.attribute("targetRef")),
+            );
+        }
+
+        targets
+    }
+
+    fn warn_unsupported(&self, flows: &[&XmlElement], document: &mut AstDocument) {
+        for (id, (text, span)) in &self.unsupported {
+            let touches = |end: &str| flows.iter().any(|flow| flow.attribute(end) == Some(*id));
+            let message = if touches("targetRef") {
+                format!("{text} became a note, the flows through it now skip it")
+            } else if touches("sourceRef") {
+                format!("{text} became a note, its outgoing flows were dropped")
+            } else {
+                continue;
+            };
+            document.add_warnings(message, span.clone());
+        }
+    }
+
+    // A lone event is written by its keyword, several need their own names to tell
+    // them apart
+    fn event_name(&mut self, event: &'a XmlElement, named: bool, keyword: &str) -> Option<String> {
+        if named {
+            return Some(self.name(event));
+        }
+
+        if let Some(id) = event.attribute("id") {
+            self.names.insert(id, keyword.to_string());
+        }
+        None
+    }
+
+    fn name(&mut self, element: &'a XmlElement) -> String {
+        let id = element.attribute("id").unwrap_or_default();
+        let candidates = [
+            element.attribute("name").and_then(identifier),
+            identifier(id),
+        ];
+
+        let mut name = candidates
+            .into_iter()
+            .flatten()
+            .find(|candidate| !self.used.contains(candidate))
+            .unwrap_or_else(|| "Element".to_string());
+        let base = name.clone();
+        let mut suffix = 2;
+        while self.used.contains(&name) {
+            name = format!("{base}{suffix}");
+            suffix += 1;
+        }
+
+        self.used.insert(name.clone());
+        self.names.insert(id, name.clone());
+        name
+    }
+
+    fn span(&self, offset: usize) -> Span {
+        let before = &self.xml[..offset];
+        let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+
+        Span {
+            start: offset,
+            end: offset,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            file: PathBuf::new(),
+        }
+    }
+}
+
+fn order_start_targets(elements: &mut Vec<ProcessElement>, targets: &[String]) {
+    let Some(start) = elements
+        .iter()
+        .position(|element| matches!(element, ProcessElement::StartEvent { .. }))
+    else {
+        return;
+    };
+
+    let start_event = elements.remove(start);
+    elements.insert(0, start_event);
+
+    for target in targets.iter().rev() {
+        let position = elements.iter().position(|element| match element {
+            ProcessElement::Task { id, .. }
+            | ProcessElement::CallActivity { id, .. }
+            | ProcessElement::Subprocess { id, .. } => id == target,
+            ProcessElement::Gateway { id, .. } => id.as_deref() == Some(target.as_str()),
+            _ => false,
+        });
+        if let Some(position) = position.filter(|&position| position > 1) {
+            let element = elements.remove(position);
+            elements.insert(1, element);
+        }
+    }
+}
+
+// BPMN ends the path at an element without outgoing flows, the DSL would continue
+// with the next element instead
+fn end_open_paths(elements: &[ProcessElement], flows: &mut Vec<Flow>) {
+    // Each flow node with whether it can be continued into, and whether it ever
+    // continues on its own
+    let nodes: Vec<_> = elements
+        .iter()
+        .filter_map(|element| match element {
+            ProcessElement::StartEvent { id, span, .. } => {
+                Some((id.as_deref().unwrap_or("start"), span, false, true))
+            }
+            ProcessElement::EndEvent { id, span, .. } => {
+                Some((id.as_deref().unwrap_or("end"), span, true, false))
+            }
+            ProcessElement::Gateway {
+                id: Some(id),
+                branches,
+                span,
+                ..
+            } => Some((id.as_str(), span, true, branches.is_empty())),
+            ProcessElement::Task { id, span, .. }
+            | ProcessElement::CallActivity { id, span, .. }
+            | ProcessElement::IntermediateEvent {
+                id: Some(id), span, ..
+            } => Some((id.as_str(), span, true, true)),
+            _ => None,
+        })
+        .collect();
+
+    for (position, &(name, span, _, continues)) in nodes.iter().enumerate() {
+        let has_next = nodes[position + 1..]
+            .iter()
+            .any(|&(.., entered, _)| entered);
+        if !continues || !has_next || flows.iter().any(|flow| flow.from == name) {
+            continue;
+        }
+
+        flows.push(Flow {
+            from: name.to_string(),
+            to: "end".to_string(),
+            flow_type: FlowType::Sequence,
+            condition: None,
+            span: span.clone(),
+            from_span: span.clone(),
+            to_span: span.clone(),
+        });
+    }
+}
+
+fn event_definition(event: &XmlElement) -> Option<EventType> {
+    event
+        .children
+        .iter()
+        .find_map(|child| match child.name.as_str() {
+            "messageEventDefinition" => Some(EventType::Message(String::new())),
+            "timerEventDefinition" => Some(EventType::Timer(String::new())),
+            "errorEventDefinition" => Some(EventType::Error(String::new())),
+            "signalEventDefinition" => Some(EventType::Signal(String::new())),
+            "escalationEventDefinition" => Some(EventType::Escalation(String::new())),
+            "compensateEventDefinition" => Some(EventType::Compensation(String::new())),
+            "conditionalEventDefinition" => Some(EventType::Conditional(
+                child
+                    .child("condition")
+                    .map(|condition| condition_text(&condition.text))
+                    .unwrap_or_default(),
+            )),
+            "terminateEventDefinition" => Some(EventType::Terminate),
+            _ => None,
+        })
+}
+
+// Loop characteristics map back to the `loop`, `multiInstance` and `sequential`
+// markers
+fn loop_attributes(activity: &XmlElement) -> IndexMap<String, AttributeValue> {
+    let mut attributes = IndexMap::new();
+    if activity.child("standardLoopCharacteristics").is_some() {
+        attributes.insert("loop".to_string(), AttributeValue::Boolean(true));
+    }
+    if let Some(characteristics) = activity.child("multiInstanceLoopCharacteristics") {
+        attributes.insert("multiInstance".to_string(), AttributeValue::Boolean(true));
+        if characteristics.attribute("isSequential") == Some("true") {
+            attributes.insert("sequential".to_string(), AttributeValue::Boolean(true));
+        }
+    }
+    attributes
+}
+
+fn condition_text(expression: &str) -> String {
+    let expression = expression.trim();
+    expression
+        .strip_prefix("${")
+        .or_else(|| expression.strip_prefix("#{"))
+        .and_then(|inner| inner.strip_suffix('}'))
+        .unwrap_or(expression)
+        .replace(['[', ']', '{', '}'], " ")
+        .trim()
+        .to_string()
+}
+
+fn identifier(text: &str) -> Option<String> {
+    let name: String = text
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first.to_ascii_uppercase().to_string() + chars.as_str()
+            })
+        })
+        .collect();
+
+    name.chars()
+        .next()
+        .filter(|first| first.is_ascii_alphabetic() || *first == '_')
+        .map(|_| name)
+}
+
+// Names that cannot be used as an identifier as-is are kept as the display label
+fn display_label(element: &XmlElement) -> Option<String> {
+    let name = element.attribute("name")?.trim();
+    (!name.is_empty() && identifier(name).as_deref() != Some(name)).then(|| name.to_string())
+}
+
+fn parse_xml(xml: &str) -> Result<XmlElement, ImportError> {
+    let malformed = |message: String, offset: usize| ImportError::Malformed {
+        message,
+        line: xml[..offset.min(xml.len())].matches('\n').count() + 1,
+    };
+    let position = |position: u64| usize::try_from(position).unwrap_or(xml.len());
+
+    let mut reader = Reader::from_str(xml);
+    let mut stack = vec![XmlElement::new(String::new(), HashMap::new(), 0)];
+
+    loop {
+        let start = position(reader.buffer_position());
+        let event = reader
+            .read_event()
+            .map_err(|error| malformed(error.to_string(), position(reader.error_position())))?;
+
+        match event {
+            Event::Start(tag) => {
+                stack.push(element(&tag, start).map_err(|error| malformed(error, start))?);
+            }
+            Event::Empty(tag) => {
+                let element = element(&tag, start).map_err(|error| malformed(error, start))?;
+                if let Some(parent) = stack.last_mut() {
+                    parent.children.push(element);
+                }
+            }
+            Event::End(_) => {
+                let element = stack
+                    .pop()
+                    .filter(|_| !stack.is_empty())
+                    .ok_or_else(|| malformed("unexpected closing tag".to_string(), start))?;
+                if let Some(parent) = stack.last_mut() {
+                    parent.children.push(element);
+                }
+            }
+            Event::Text(text) => {
+                let text = text
+                    .decode()
+                    .map_err(|error| malformed(error.to_string(), start))?;
+                if let Some(parent) = stack.last_mut() {
+                    parent.text.push_str(&text);
+                }
+            }
+            Event::CData(data) => {
+                let text = data
+                    .decode()
+                    .map_err(|error| malformed(error.to_string(), start))?;
+                if let Some(parent) = stack.last_mut() {
+                    parent.text.push_str(&text);
+                }
+            }
+            Event::GeneralRef(reference) => {
+                let text =
+                    resolve_reference(&reference).map_err(|error| malformed(error, start))?;
+                if let Some(parent) = stack.last_mut() {
+                    parent.text.push(text);
+                }
+            }
+            Event::Eof => break,
+            Event::Comment(_) | Event::Decl(_) | Event::PI(_) | Event::DocType(_) => {}
+        }
+    }
+
+    if stack.len() > 1 {
+        let unclosed = stack.pop().map(|element| (element.name, element.offset));
+        if let Some((name, offset)) = unclosed {
+            return Err(malformed(format!("unclosed <{name}>"), offset));
+        }
+    }
+
+    stack
+        .pop()
+        .ok_or_else(|| malformed("empty document".to_string(), 0))
+}
+
+// Element names drop their prefix, attribute names keep it so `xsi:type` stays apart
+// from `type`
+fn element(tag: &BytesStart, offset: usize) -> Result<XmlElement, String> {
+    let mut attributes = HashMap::new();
+    for attribute in tag.attributes() {
+        let attribute = attribute.map_err(|error| error.to_string())?;
+        let value = attribute
+            .unescape_value()
+            .map_err(|error| error.to_string())?;
+        attributes.insert(utf8(attribute.key.as_ref())?, value.into_owned());
+    }
+
+    Ok(XmlElement::new(
+        utf8(tag.local_name().as_ref())?,
+        attributes,
+        offset,
+    ))
+}
+
+fn resolve_reference(reference: &BytesRef) -> Result<char, String> {
+    if let Some(c) = reference
+        .resolve_char_ref()
+        .map_err(|error| error.to_string())?
+    {
+        return Ok(c);
+    }
+
+    let name = reference.decode().map_err(|error| error.to_string())?;
+    resolve_predefined_entity(&name)
+        .and_then(|text| text.chars().next())
+        .ok_or_else(|| format!("unknown entity &{name};"))
+}
+
+fn utf8(bytes: &[u8]) -> Result<String, String> {
+    std::str::from_utf8(bytes)
+        .map(str::to_string)
+        .map_err(|error| error.to_string())
+}
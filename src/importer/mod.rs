@@ -0,0 +1 @@
+pub mod bpmn_xml;
@@ -2,7 +2,16 @@ use thiserror::Error;
 
 use crate::lexer::Span;
 
-#[derive(Error, Debug)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum TokenError {
+    #[default]
+    UnexpectedCharacter,
+    UnterminatedComment,
+    UnterminatedString,
+    InvalidNumberSuffix(String),
+}
+
+#[derive(Error, Debug, Clone)]
 pub enum LexerError {
     #[error("Unexpected character '{character}' at {span}")]
     UnexpectedCharacter { character: char, span: Span },
@@ -13,8 +22,29 @@ pub enum LexerError {
     #[error("Unterminated block comment at {span}")]
     UnterminatedComment { span: Span },
 
-    #[error("Invalid number format '{text}' at {span}")]
-    InvalidNumber { text: String, span: Span },
+    #[error("Invalid number suffix '{suffix}' at {span}")]
+    InvalidNumber { suffix: String, span: Span },
+}
+
+impl LexerError {
+    #[must_use]
+    pub const fn span(&self) -> &Span {
+        match self {
+            Self::UnexpectedCharacter { span, .. }
+            | Self::UnterminatedString { span }
+            | Self::UnterminatedComment { span }
+            | Self::InvalidNumber { span, .. } => span,
+        }
+    }
+
+    pub const fn span_mut(&mut self) -> &mut Span {
+        match self {
+            Self::UnexpectedCharacter { span, .. }
+            | Self::UnterminatedString { span }
+            | Self::UnterminatedComment { span }
+            | Self::InvalidNumber { span, .. } => span,
+        }
+    }
 }
 
 impl std::fmt::Display for Span {
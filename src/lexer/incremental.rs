@@ -0,0 +1,179 @@
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use logos::Logos;
+
+use crate::lexer::{Lexer, Span, Token, TokenKind, error::LexerError, resolve_token_kind};
+
+pub struct IncrementalLexer {
+    source: String,
+    tokens: Vec<Token>,
+    errors: Vec<LexerError>,
+    file_path: PathBuf,
+}
+
+impl IncrementalLexer {
+    pub fn new(source: impl Into<String>, file_path: impl AsRef<Path>) -> Self {
+        let source = source.into();
+        let mut lexer = Lexer::new(&source, file_path.as_ref());
+        let tokens = lexer.tokenize();
+        let errors = std::mem::take(&mut lexer.errors);
+
+        Self {
+            source,
+            tokens,
+            errors,
+            file_path: file_path.as_ref().to_path_buf(),
+        }
+    }
+
+    #[must_use]
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    #[must_use]
+    pub fn tokens(&self) -> &[Token] {
+        &self.tokens
+    }
+
+    #[must_use]
+    pub fn errors(&self) -> &[LexerError] {
+        &self.errors
+    }
+
+    // Re-lexes from the last token that ends before the edit until the token stream
+    // lines up with the old one again at the start of a line, then shifts the rest
+    pub fn edit(&mut self, range: Range<usize>, replacement: &str) {
+        let old_source = std::mem::take(&mut self.source);
+        self.source = format!(
+            "{}{replacement}{}",
+            &old_source[..range.start],
+            &old_source[range.end..]
+        );
+        let edit_end = range.start + replacement.len();
+
+        let keep = self
+            .tokens
+            .partition_point(|token| token.span.end < range.start);
+        let restart = self
+            .tokens
+            .get(keep)
+            .map_or(range.start, |token| token.span.start.min(range.start));
+        let (mut line, mut column) = self.position_before(keep, restart, &old_source);
+
+        let old_tokens = self.tokens.split_off(keep);
+        let old_errors = std::mem::take(&mut self.errors);
+        let (mut errors, later_errors): (Vec<_>, Vec<_>) = old_errors
+            .into_iter()
+            .partition(|error| error.span().start < restart);
+
+        let mut logos = TokenKind::lexer(&self.source[restart..]);
+        let mut cursor = restart;
+        let mut at_line_start = false;
+        let mut resync = None;
+
+        while let Some(result) = logos.next() {
+            let start = restart + logos.span().start;
+            let end = restart + logos.span().end;
+            (line, column) = advance_position(&self.source[cursor..start], line, column);
+            cursor = start;
+
+            if at_line_start && start >= edit_end {
+                let old_start = start - edit_end + range.end;
+                let index = old_tokens.partition_point(|token| token.span.start < old_start);
+                if old_tokens.get(index).is_some_and(|token| {
+                    token.span.start == old_start && token.text == self.source[start..end]
+                }) {
+                    resync = Some((index, old_start, start, line));
+                    break;
+                }
+            }
+
+            let span = Span {
+                start,
+                end,
+                line,
+                column,
+                file: self.file_path.clone(),
+            };
+            let (kind, error) = resolve_token_kind(result, &span);
+            errors.extend(error);
+            at_line_start = matches!(kind, TokenKind::Newline | TokenKind::CarriageReturnNewline)
+                && start >= edit_end;
+            self.tokens.push(Token {
+                kind,
+                span,
+                text: self.source[start..end].to_string(),
+            });
+        }
+
+        if let Some((index, old_start, new_start, new_line)) = resync {
+            let old_line = old_tokens[index].span.line;
+            let shift = |span: &mut Span| {
+                span.start = span.start - old_start + new_start;
+                span.end = span.end - old_start + new_start;
+                span.line = span.line - old_line + new_line;
+            };
+
+            for mut token in old_tokens.into_iter().skip(index) {
+                shift(&mut token.span);
+                self.tokens.push(token);
+            }
+            for mut error in later_errors {
+                if error.span().start >= old_start {
+                    shift(error.span_mut());
+                    errors.push(error);
+                }
+            }
+        } else {
+            (line, column) = advance_position(&self.source[cursor..], line, column);
+            self.tokens.push(Token {
+                kind: TokenKind::Eof,
+                span: Span {
+                    start: self.source.len(),
+                    end: self.source.len(),
+                    line,
+                    column,
+                    file: self.file_path.clone(),
+                },
+                text: String::new(),
+            });
+        }
+
+        self.errors = errors;
+    }
+
+    fn position_before(&self, keep: usize, offset: usize, old_source: &str) -> (usize, usize) {
+        self.tokens[..keep]
+            .last()
+            .or_else(|| self.tokens.get(keep))
+            .filter(|token| token.span.start <= offset)
+            .map_or_else(
+                || advance_position(&old_source[..offset], 1, 1),
+                |token| {
+                    advance_position(
+                        &old_source[token.span.start..offset],
+                        token.span.line,
+                        token.span.column,
+                    )
+                },
+            )
+    }
+}
+
+fn advance_position(text: &str, line: usize, column: usize) -> (usize, usize) {
+    let mut characters = text.chars().peekable();
+    let mut position = (line, column);
+    while let Some(character) = characters.next() {
+        let breaks = character == '\n' || (character == '\r' && characters.peek() != Some(&'\n'));
+        position = if breaks {
+            (position.0 + 1, 1)
+        } else {
+            (position.0, position.1 + 1)
+        };
+    }
+    position
+}
@@ -1,4 +1,5 @@
 pub mod error;
+pub mod incremental;
 pub mod multi_file;
 
 use std::{
@@ -8,6 +9,8 @@ use std::{
 
 use logos::Logos;
 
+use crate::lexer::error::{LexerError, TokenError};
+
 #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct Span {
     pub start: usize,
@@ -17,15 +20,15 @@ pub struct Span {
     pub file: PathBuf,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
 pub struct Token {
     pub kind: TokenKind,
     pub span: Span,
     pub text: String,
 }
 
-#[derive(Logos, Debug, Clone, PartialEq, Eq)]
-#[logos(skip r"[ \t\f]+")]
+#[derive(Logos, Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[logos(skip r"[ \t\f]+", error = TokenError)]
 pub enum TokenKind {
     // Keywords
     #[token("process")]
@@ -57,6 +60,8 @@ pub enum TokenKind {
     Xor,
     #[token("and")]
     And,
+    #[token("or")]
+    Or,
     #[token("event")]
     Event,
     #[token("group")]
@@ -67,6 +72,10 @@ pub enum TokenKind {
     Lane,
     #[token("note")]
     Note,
+    #[token("data")]
+    Data,
+    #[token("var")]
+    Var,
     // Flow arrows
     #[token("->")]
     SequenceFlow,
@@ -78,6 +87,8 @@ pub enum TokenKind {
     Association,
     #[token("::")]
     Namespace,
+    #[token(":", priority = 2)]
+    Colon,
     // Brackets and delimiters
     #[token("{", priority = 2)]
     LeftBrace,
@@ -99,20 +110,40 @@ pub enum TokenKind {
     At,
     #[token("?", priority = 2)]
     Question,
+    // Condition operators
+    #[token("==")]
+    EqualEqual,
+    #[token("!=")]
+    NotEqual,
+    #[token("<=")]
+    LessEqual,
+    #[token(">=")]
+    GreaterEqual,
+    #[token("<", priority = 2)]
+    Less,
+    #[token(">", priority = 2)]
+    Greater,
+    #[token("&&")]
+    AndAnd,
+    #[token("||")]
+    OrOr,
+    #[token("!", priority = 2)]
+    Bang,
     // Literals
-    #[regex(r#""([^"\\]|\\.)*""#)]
+    #[token("\"", string_literal)]
     StringLiteral,
-    #[regex(r"[0-9]+(\.[0-9]+)?[a-zA-Z]*")]
+    #[regex(r"[0-9]+(\.[0-9]+)?([a-zA-Z][a-zA-Z0-9]*)?", number_literal)]
     NumberLiteral,
     #[regex(r"[a-zA-Z_][a-zA-Z0-9_]*")]
     Identifier,
     // Comments
-    #[regex(r"//[^\n]*")]
+    #[regex(r"//[^\r\n]*")]
     LineComment,
-    #[regex(r"/\*([^*]|\*[^/])*\*/")]
+    #[token("/*", block_comment)]
     BlockComment,
-    // Whitespace and newlines
+    // Whitespace and newlines, a lone `\r` breaks the line like `\n`
     #[token("\n")]
+    #[token("\r")]
     Newline,
     #[token("\r\n")]
     CarriageReturnNewline,
@@ -126,9 +157,9 @@ pub enum TokenKind {
 pub struct Lexer<'a> {
     input: &'a str,
     logos: logos::Lexer<'a, TokenKind>,
-    line: usize,
-    column: usize,
+    line_starts: Vec<usize>,
     file_path: PathBuf,
+    errors: Vec<LexerError>,
 }
 
 impl<'a> Lexer<'a> {
@@ -136,51 +167,45 @@ impl<'a> Lexer<'a> {
         Self {
             input,
             logos: TokenKind::lexer(input),
-            line: 1,
-            column: 1,
+            line_starts: Vec::new(),
             file_path: file_path.as_ref().to_path_buf(),
+            errors: Vec::new(),
         }
     }
 
     pub fn tokenize(&mut self) -> Vec<Token> {
         let mut tokens = Vec::new();
 
+        self.line_starts = line_starts(self.input);
+
         while let Some(token_kind) = self.logos.next() {
             let span = self.logos.span();
             let text = self.input[span.clone()].to_string();
             let (line, column) = self.calculate_position(span.start);
-            let token = Token {
-                kind: token_kind.unwrap_or(TokenKind::Unknown),
-                span: Span {
-                    start: span.start,
-                    end: span.end,
-                    line,
-                    column,
-                    file: self.file_path.clone(),
-                },
-                text,
+            let token_span = Span {
+                start: span.start,
+                end: span.end,
+                line,
+                column,
+                file: self.file_path.clone(),
             };
-
-            if matches!(
-                token.kind,
-                TokenKind::Newline | TokenKind::CarriageReturnNewline
-            ) {
-                self.line += 1;
-                self.column = 1;
-            } else {
-                self.column += token.span.end - token.span.start;
-            }
-
-            tokens.push(token);
+            let (kind, error) = resolve_token_kind(token_kind, &token_span);
+            self.errors.extend(error);
+            tokens.push(Token {
+                kind,
+                span: token_span,
+                text,
+            });
         }
 
+        let (line, column) = self.calculate_position(self.input.len());
         tokens.push(Token {
             kind: TokenKind::Eof,
             span: Span {
                 start: self.input.len(),
                 end: self.input.len(),
-                line: self.line,
-                column: self.column,
+                line,
+                column,
                 file: self.file_path.clone(),
             },
             text: String::new(),
@@ -189,37 +214,224 @@ impl<'a> Lexer<'a> {
         tokens
     }
 
+    #[must_use]
+    pub fn errors(&self) -> &[LexerError] {
+        &self.errors
+    }
+
     fn calculate_position(&self, pos: usize) -> (usize, usize) {
-        let mut line = 1;
-        let mut column = 1;
+        let line = self.line_starts.partition_point(|&start| start <= pos);
+        let line_start = self.line_starts[line - 1];
+        let column = self.input[line_start..pos].chars().count() + 1;
+
+        (line, column)
+    }
+}
+
+// Offsets at which each line begins, after `\n`, `\r\n` or a lone `\r`
+pub(crate) fn line_starts(input: &str) -> Vec<usize> {
+    let bytes = input.as_bytes();
+    std::iter::once(0)
+        .chain(
+            bytes
+                .iter()
+                .enumerate()
+                .filter(|&(offset, &byte)| {
+                    byte == b'\n' || (byte == b'\r' && bytes.get(offset + 1) != Some(&b'\n'))
+                })
+                .map(|(offset, _)| offset + 1),
+        )
+        .collect()
+}
+
+pub(crate) fn resolve_token_kind(
+    result: Result<TokenKind, TokenError>,
+    span: &Span,
+) -> (TokenKind, Option<LexerError>) {
+    match result {
+        Ok(kind) => (kind, None),
+        Err(TokenError::UnterminatedComment) => (
+            TokenKind::BlockComment,
+            Some(LexerError::UnterminatedComment {
+                span: Span {
+                    end: span.start + 2,
+                    ..span.clone()
+                },
+            }),
+        ),
+        Err(TokenError::UnterminatedString) => (
+            TokenKind::StringLiteral,
+            Some(LexerError::UnterminatedString {
+                span: Span {
+                    end: span.start + 1,
+                    ..span.clone()
+                },
+            }),
+        ),
+        Err(TokenError::InvalidNumberSuffix(suffix)) => (
+            TokenKind::NumberLiteral,
+            Some(LexerError::InvalidNumber {
+                suffix,
+                span: span.clone(),
+            }),
+        ),
+        Err(TokenError::UnexpectedCharacter) => (TokenKind::Unknown, None),
+    }
+}
+
+// Block comments nest, so `/* a /* b */ c */` is a single comment
+fn block_comment(lexer: &mut logos::Lexer<TokenKind>) -> Result<(), TokenError> {
+    let bytes = lexer.remainder().as_bytes();
+    let mut depth = 1;
+    let mut index = 0;
 
-        for (i, ch) in self.input.char_indices() {
-            if i >= pos {
-                break;
+    while index + 1 < bytes.len() {
+        match (bytes[index], bytes[index + 1]) {
+            (b'/', b'*') => {
+                depth += 1;
+                index += 2;
+            }
+            (b'*', b'/') => {
+                depth -= 1;
+                index += 2;
+                if depth == 0 {
+                    lexer.bump(index);
+                    return Ok(());
+                }
             }
+            _ => index += 1,
+        }
+    }
+
+    lexer.bump(bytes.len());
+    Err(TokenError::UnterminatedComment)
+}
+
+// Numbers are plain or durations such as `1h30m`
+fn number_literal(lexer: &logos::Lexer<TokenKind>) -> Result<(), TokenError> {
+    invalid_number_suffix(lexer.slice()).map_or(Ok(()), |suffix| {
+        Err(TokenError::InvalidNumberSuffix(suffix.to_string()))
+    })
+}
+
+// The first unit of a number literal that is not a duration unit
+pub(crate) fn invalid_number_suffix(text: &str) -> Option<&str> {
+    const UNITS: [&str; 5] = ["ms", "s", "m", "h", "d"];
+
+    if !text.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
 
-            if ch == '\n' {
-                line += 1;
-                column = 1;
-            } else {
-                column += 1;
+    text.split(|c: char| c.is_ascii_digit() || c == '.')
+        .find(|unit| !unit.is_empty() && !UNITS.contains(unit))
+}
+
+// Strings end at the closing quote and may not span lines
+fn string_literal(lexer: &mut logos::Lexer<TokenKind>) -> Result<(), TokenError> {
+    let bytes = lexer.remainder().as_bytes();
+    let mut index = 0;
+
+    while index < bytes.len() {
+        match bytes[index] {
+            b'"' => {
+                lexer.bump(index + 1);
+                return Ok(());
             }
+            b'\\'
+                if bytes
+                    .get(index + 1)
+                    .is_some_and(|&next| next != b'\n' && next != b'\r') =>
+            {
+                index += 2;
+            }
+            b'\n' | b'\r' => break,
+            _ => index += 1,
         }
+    }
 
-        (line, column)
+    lexer.bump(index);
+    Err(TokenError::UnterminatedString)
+}
+
+impl TokenKind {
+    // The exact source spelling of keywords, arrows and punctuation
+    #[must_use]
+    pub const fn literal(&self) -> Option<&'static str> {
+        Some(match self {
+            Self::Process => "process",
+            Self::Import => "import",
+            Self::From => "from",
+            Self::As => "as",
+            Self::Subprocess => "subprocess",
+            Self::Start => "start",
+            Self::End => "end",
+            Self::Task => "task",
+            Self::User => "user",
+            Self::Service => "service",
+            Self::Script => "script",
+            Self::Call => "call",
+            Self::Xor => "xor",
+            Self::And => "and",
+            Self::Or => "or",
+            Self::Event => "event",
+            Self::Group => "group",
+            Self::Pool => "pool",
+            Self::Lane => "lane",
+            Self::Note => "note",
+            Self::Data => "data",
+            Self::Var => "var",
+            Self::SequenceFlow => "->",
+            Self::MessageFlow => "-->",
+            Self::DefaultFlow => "=>",
+            Self::Association => "..>",
+            Self::Namespace => "::",
+            Self::Colon => ":",
+            Self::LeftBrace => "{",
+            Self::RightBrace => "}",
+            Self::LeftParen => "(",
+            Self::RightParen => ")",
+            Self::LeftBracket => "[",
+            Self::RightBracket => "]",
+            Self::Comma => ",",
+            Self::Equals => "=",
+            Self::At => "@",
+            Self::Question => "?",
+            Self::EqualEqual => "==",
+            Self::NotEqual => "!=",
+            Self::LessEqual => "<=",
+            Self::GreaterEqual => ">=",
+            Self::Less => "<",
+            Self::Greater => ">",
+            Self::AndAnd => "&&",
+            Self::OrOr => "||",
+            Self::Bang => "!",
+            Self::StringLiteral
+            | Self::NumberLiteral
+            | Self::Identifier
+            | Self::LineComment
+            | Self::BlockComment
+            | Self::Newline
+            | Self::CarriageReturnNewline
+            | Self::Unknown
+            | Self::Eof => return None,
+        })
     }
 }
 
 impl fmt::Display for TokenKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(literal) = self.literal() {
+            return write!(f, "{literal}");
+        }
+
         match self {
-            Self::Process => write!(f, "process"),
-            Self::Import => write!(f, "import"),
-            Self::Identifier => write!(f, "identifier"),
             Self::StringLiteral => write!(f, "string"),
-            Self::SequenceFlow => write!(f, "->"),
-            Self::Unknown => write!(f, "unknown token"),
-            _ => write!(f, "{self:?}"),
+            Self::NumberLiteral => write!(f, "number"),
+            Self::Identifier => write!(f, "identifier"),
+            Self::LineComment | Self::BlockComment => write!(f, "comment"),
+            Self::Newline | Self::CarriageReturnNewline => write!(f, "newline"),
+            Self::Eof => write!(f, "end of file"),
+            _ => write!(f, "unknown token"),
         }
     }
 }
@@ -1,5 +1,5 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeSet, HashMap},
     fs,
     path::{Path, PathBuf},
 };
@@ -10,14 +10,18 @@ use crate::lexer::{Lexer, Token, error::LexerError};
 
 pub struct MultiFileLexer {
     file_cache: HashMap<PathBuf, String>,
+    requested: BTreeSet<PathBuf>,
     base_dir: PathBuf,
+    errors: Vec<LexerError>,
 }
 
 impl MultiFileLexer {
     pub fn new(base_dir: impl AsRef<Path>) -> Self {
         Self {
             file_cache: HashMap::new(),
+            requested: BTreeSet::new(),
             base_dir: base_dir.as_ref().to_path_buf(),
+            errors: Vec::new(),
         }
     }
 
@@ -27,6 +31,23 @@ impl MultiFileLexer {
         self.tokenize_file_recursive(&resolved_path)
     }
 
+    // Lexer errors from every file tokenized since the last call
+    pub fn take_errors(&mut self) -> Vec<LexerError> {
+        std::mem::take(&mut self.errors)
+    }
+
+    // Every file asked for so far, including the ones that could not be read
+    #[must_use]
+    pub const fn dependencies(&self) -> &BTreeSet<PathBuf> {
+        &self.requested
+    }
+
+    // The text of every file read so far, by the path its spans record
+    #[must_use]
+    pub const fn sources(&self) -> &HashMap<PathBuf, String> {
+        &self.file_cache
+    }
+
     fn resolve_path(&self, path: impl AsRef<Path>) -> PathBuf {
         let path = path.as_ref();
 
@@ -38,6 +59,7 @@ impl MultiFileLexer {
     }
 
     fn tokenize_file_recursive(&mut self, file_path: &Path) -> Result<Vec<Token>, MultiFileError> {
+        self.requested.insert(file_path.to_path_buf());
         if !self.file_cache.contains_key(file_path) {
             let content =
                 fs::read_to_string(file_path).map_err(|_| MultiFileError::FileNotFound {
@@ -49,8 +71,11 @@ impl MultiFileLexer {
 
         let content = &self.file_cache[file_path];
         let mut lexer = Lexer::new(content, file_path);
+        let tokens = lexer.tokenize();
+
+        self.errors.extend_from_slice(lexer.errors());
 
-        Ok(lexer.tokenize())
+        Ok(tokens)
     }
 }
 
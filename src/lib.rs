@@ -1,3 +1,191 @@
+pub mod ast_tree;
+pub mod completion;
+pub mod config;
 pub mod diagnostics;
+pub mod diff;
+pub mod emitter;
+pub mod formatter;
+pub mod importer;
 pub mod lexer;
+#[cfg(feature = "lsp")]
+pub mod lsp;
+pub mod metrics;
+pub mod navigation;
 pub mod parser;
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use thiserror::Error;
+
+use crate::{
+    diagnostics::{
+        DiagnosticError, DiagnosticReport, LintLevel, UNDECLARED_VARIABLE,
+        context_validator::ContextValidator,
+    },
+    lexer::{
+        Lexer, Token,
+        multi_file::{MultiFileError, MultiFileLexer},
+    },
+    parser::{
+        Parser,
+        ast::AstDocument,
+        resolver::{
+            check_condition_variables, check_local_calls, check_service_endpoints, resolve_imports,
+        },
+        rules::Profile,
+    },
+};
+
+#[derive(Error, Debug)]
+pub enum CompileError {
+    #[error("Cannot read {path}: {source}")]
+    Io { path: PathBuf, source: io::Error },
+
+    #[error(transparent)]
+    MultiFile(#[from] MultiFileError),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CompileOptions {
+    pub profile: Profile,
+    pub max_nesting_depth: Option<usize>,
+    pub lint_levels: BTreeMap<String, LintLevel>,
+}
+
+#[derive(Debug)]
+pub struct Compilation {
+    pub report: DiagnosticReport,
+    pub document: AstDocument,
+    // The compiled file and the imports it loaded, as absolute paths
+    pub dependencies: BTreeSet<PathBuf>,
+}
+
+pub fn compile(path: &Path) -> Result<DiagnosticReport, CompileError> {
+    compile_with_options(path, &CompileOptions::default()).map(|compilation| compilation.report)
+}
+
+#[must_use]
+pub fn compile_str(source: &str, name: &str) -> DiagnosticReport {
+    compile_str_with_options(source, name, &CompileOptions::default()).report
+}
+
+pub fn compile_with_options(
+    path: &Path,
+    options: &CompileOptions,
+) -> Result<Compilation, CompileError> {
+    let base_dir = std::env::current_dir().map_err(|source| CompileError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let mut lexer = MultiFileLexer::new(base_dir);
+
+    let source_code = fs::read_to_string(path).map_err(|source| CompileError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let tokens = lexer.tokenize_file(path)?;
+    let lexer_errors = lexer
+        .take_errors()
+        .iter()
+        .map(DiagnosticError::from_lexer_error)
+        .collect();
+
+    Ok(run_pipeline(
+        path.display().to_string(),
+        source_code,
+        tokens,
+        lexer_errors,
+        &mut lexer,
+        options,
+    ))
+}
+
+#[must_use]
+pub fn compile_str_with_options(source: &str, name: &str, options: &CompileOptions) -> Compilation {
+    let mut lexer = MultiFileLexer::new(std::env::current_dir().unwrap_or_default());
+    let mut source_lexer = Lexer::new(source, name);
+    let tokens = source_lexer.tokenize();
+    let lexer_errors = source_lexer
+        .errors()
+        .iter()
+        .map(DiagnosticError::from_lexer_error)
+        .collect();
+
+    run_pipeline(
+        name.to_string(),
+        source.to_string(),
+        tokens,
+        lexer_errors,
+        &mut lexer,
+        options,
+    )
+}
+
+fn run_pipeline(
+    file_path: String,
+    source_code: String,
+    tokens: Vec<Token>,
+    lexer_errors: Vec<DiagnosticError>,
+    lexer: &mut MultiFileLexer,
+    options: &CompileOptions,
+) -> Compilation {
+    let mut report = DiagnosticReport::new(file_path, source_code.clone())
+        .with_lint_levels(options.lint_levels.clone());
+    let checked_file = tokens.first().map(|token| token.span.file.clone());
+
+    for error in lexer_errors {
+        report.add_error(error);
+    }
+
+    // Context validation on tokens (catch typos and syntax errors)
+    let mut context_validator = ContextValidator::new(source_code);
+    for error in context_validator.validate_tokens(&tokens) {
+        report.add_error(error);
+    }
+
+    let mut parser = Parser::new(tokens);
+    if let Some(max_depth) = options.max_nesting_depth {
+        parser = parser.with_max_depth(max_depth);
+    }
+    let document = parser.parse_with_profile(options.profile);
+
+    for error in &document.errors {
+        report.add_error(DiagnosticError::from_parse_error(error, &document));
+    }
+
+    for error in resolve_imports(&document, lexer) {
+        report.add_error(error);
+    }
+    for error in lexer.take_errors() {
+        report.add_error(DiagnosticError::from_lexer_error(&error));
+    }
+    report.imported_sources = lexer
+        .sources()
+        .iter()
+        .filter(|(path, _)| Some(*path) != checked_file.as_ref())
+        .map(|(path, source)| (path.clone(), source.clone()))
+        .collect();
+    for error in check_local_calls(&document) {
+        report.add_error(error);
+    }
+    for error in check_service_endpoints(&document) {
+        report.add_error(error);
+    }
+    let every_process = options
+        .lint_levels
+        .get(UNDECLARED_VARIABLE)
+        .is_some_and(|level| *level != LintLevel::Allow);
+    for error in check_condition_variables(&document, every_process) {
+        report.add_error(error);
+    }
+
+    Compilation {
+        report,
+        document,
+        dependencies: lexer.dependencies().clone(),
+    }
+}
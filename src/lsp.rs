@@ -0,0 +1,373 @@
+use std::{collections::HashMap, error::Error};
+
+use lsp_server::{Connection, Message, Notification, Request, RequestId, Response};
+use lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionOptions, CompletionParams, CompletionResponse,
+    Diagnostic, DiagnosticSeverity, DidChangeTextDocumentParams, DidCloseTextDocumentParams,
+    DidOpenTextDocumentParams, GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverContents,
+    HoverParams, HoverProviderCapability, InitializeParams, Location, MarkupContent, MarkupKind,
+    NumberOrString, OneOf, Position, PublishDiagnosticsParams, Range, ReferenceParams,
+    ServerCapabilities, TextDocumentPositionParams, TextDocumentSyncCapability,
+    TextDocumentSyncKind, Uri,
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+        Notification as LspNotification, PublishDiagnostics,
+    },
+    request::{Completion, GotoDefinition, HoverRequest, References, Request as LspRequest},
+};
+
+use crate::{
+    CompileOptions, compile_str_with_options,
+    completion::{CompletionKind, complete_at},
+    diagnostics::{DiagnosticError, Severity},
+    lexer::{Lexer, Span, TokenKind},
+    navigation::{definition_span, hover_at, references},
+};
+
+pub type ServerError = Box<dyn Error + Send + Sync>;
+
+// Serves the editor on stdin/stdout until it sends `shutdown`
+pub fn run() -> Result<(), ServerError> {
+    let (connection, io_threads) = Connection::stdio();
+    serve(&connection)?;
+    drop(connection);
+    io_threads.join()?;
+    Ok(())
+}
+
+pub fn serve(connection: &Connection) -> Result<(), ServerError> {
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec!["@".to_string(), ">".to_string(), "(".to_string()]),
+            ..CompletionOptions::default()
+        }),
+        definition_provider: Some(OneOf::Left(true)),
+        references_provider: Some(OneOf::Left(true)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        ..ServerCapabilities::default()
+    };
+    let params = connection.initialize(serde_json::to_value(capabilities)?)?;
+    let _: InitializeParams = serde_json::from_value(params)?;
+
+    let mut server = Server::default();
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? {
+                    return Ok(());
+                }
+                let response = server.handle_request(request);
+                connection.sender.send(Message::Response(response))?;
+            }
+            Message::Notification(notification) => {
+                let method = notification.method.clone();
+                match server.handle_notification(notification) {
+                    Ok(Some(published)) => {
+                        connection.sender.send(Message::Notification(published))?;
+                    }
+                    Ok(None) => {}
+                    // Notifications get no reply, so a bad one is logged and the session goes on
+                    Err(err) => eprintln!("Ignoring malformed '{method}' notification: {err}"),
+                }
+            }
+            Message::Response(_) => {}
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Default)]
+struct Server {
+    documents: HashMap<Uri, String>,
+}
+
+impl Server {
+    fn handle_notification(
+        &mut self,
+        notification: Notification,
+    ) -> Result<Option<Notification>, ServerError> {
+        let uri = match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: DidOpenTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                let uri = params.text_document.uri;
+                self.documents
+                    .insert(uri.clone(), params.text_document.text);
+                uri
+            }
+            DidChangeTextDocument::METHOD => {
+                let params: DidChangeTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                let uri = params.text_document.uri;
+                // Full sync: the last change carries the whole document
+                if let Some(change) = params.content_changes.into_iter().last() {
+                    self.documents.insert(uri.clone(), change.text);
+                }
+                uri
+            }
+            DidCloseTextDocument::METHOD => {
+                let params: DidCloseTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                self.documents.remove(&params.text_document.uri);
+                return Ok(Some(publish(params.text_document.uri, Vec::new())));
+            }
+            _ => return Ok(None),
+        };
+
+        let diagnostics = self.diagnostics(&uri);
+        Ok(Some(publish(uri, diagnostics)))
+    }
+
+    fn handle_request(&self, request: Request) -> Response {
+        let id = request.id.clone();
+        let result = match request.method.as_str() {
+            Completion::METHOD => self.completion(request),
+            GotoDefinition::METHOD => self.definition(request),
+            References::METHOD => self.references(request),
+            HoverRequest::METHOD => self.hover(request),
+            _ => {
+                return Response::new_err(
+                    id,
+                    lsp_server::ErrorCode::MethodNotFound as i32,
+                    format!("Unsupported request '{}'", request.method),
+                );
+            }
+        };
+
+        match result {
+            Ok(value) => Response::new_ok(id, value),
+            Err(err) => Response::new_err(
+                id,
+                lsp_server::ErrorCode::InvalidParams as i32,
+                err.to_string(),
+            ),
+        }
+    }
+
+    fn diagnostics(&self, uri: &Uri) -> Vec<Diagnostic> {
+        let Some(source) = self.documents.get(uri) else {
+            return Vec::new();
+        };
+
+        let compilation =
+            compile_str_with_options(source, &file_path(uri), &CompileOptions::default());
+        compilation
+            .report
+            .errors
+            .iter()
+            .map(|error| to_diagnostic(error, source))
+            .collect()
+    }
+
+    fn completion(&self, request: Request) -> Result<serde_json::Value, ServerError> {
+        let (_, params) = extract::<CompletionParams>(request, Completion::METHOD)?;
+        let position = params.text_document_position;
+        let Some(source) = self.documents.get(&position.text_document.uri) else {
+            return Ok(serde_json::Value::Null);
+        };
+
+        let tokens = Lexer::new(source, file_path(&position.text_document.uri)).tokenize();
+        let items = complete_at(&tokens, offset_at(source, position.position))
+            .into_iter()
+            .map(|completion| CompletionItem {
+                kind: Some(match completion.kind {
+                    CompletionKind::Keyword => CompletionItemKind::KEYWORD,
+                    CompletionKind::EventType => CompletionItemKind::ENUM_MEMBER,
+                    CompletionKind::Attribute => CompletionItemKind::PROPERTY,
+                    CompletionKind::Element => CompletionItemKind::REFERENCE,
+                }),
+                label: completion.insert_text,
+                ..CompletionItem::default()
+            })
+            .collect();
+
+        Ok(serde_json::to_value(CompletionResponse::Array(items))?)
+    }
+
+    fn definition(&self, request: Request) -> Result<serde_json::Value, ServerError> {
+        let (_, params) = extract::<GotoDefinitionParams>(request, GotoDefinition::METHOD)?;
+        let Some((uri, source, name)) = self.name_at(&params.text_document_position_params) else {
+            return Ok(serde_json::Value::Null);
+        };
+
+        let compilation =
+            compile_str_with_options(source, &file_path(uri), &CompileOptions::default());
+        let location = definition_span(&compilation.document, &name).map(|span| Location {
+            uri: uri.clone(),
+            range: to_range(&span, source),
+        });
+
+        Ok(serde_json::to_value(
+            location.map(GotoDefinitionResponse::Scalar),
+        )?)
+    }
+
+    fn references(&self, request: Request) -> Result<serde_json::Value, ServerError> {
+        let (_, params) = extract::<ReferenceParams>(request, References::METHOD)?;
+        let Some((uri, source, name)) = self.name_at(&params.text_document_position) else {
+            return Ok(serde_json::Value::Null);
+        };
+
+        let compilation =
+            compile_str_with_options(source, &file_path(uri), &CompileOptions::default());
+        let locations: Vec<Location> = references(&compilation.document, &name)
+            .iter()
+            .map(|span| Location {
+                uri: uri.clone(),
+                range: to_range(span, source),
+            })
+            .collect();
+
+        Ok(serde_json::to_value(locations)?)
+    }
+
+    // Hovering a reference describes the element it points at
+    fn hover(&self, request: Request) -> Result<serde_json::Value, ServerError> {
+        let (_, params) = extract::<HoverParams>(request, HoverRequest::METHOD)?;
+        let position = params.text_document_position_params;
+        let Some(source) = self.documents.get(&position.text_document.uri) else {
+            return Ok(serde_json::Value::Null);
+        };
+
+        let uri = &position.text_document.uri;
+        let compilation =
+            compile_str_with_options(source, &file_path(uri), &CompileOptions::default());
+        let offset = self
+            .name_at(&position)
+            .and_then(|(_, _, name)| definition_span(&compilation.document, &name))
+            .map_or_else(|| offset_at(source, position.position), |span| span.start);
+
+        let hover = hover_at(&compilation.document, offset).map(|markdown| Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: markdown,
+            }),
+            range: None,
+        });
+
+        Ok(serde_json::to_value(hover)?)
+    }
+
+    // The identifier under the cursor together with the document it is in
+    fn name_at(&self, position: &TextDocumentPositionParams) -> Option<(&Uri, &str, String)> {
+        let (uri, source) = self.documents.get_key_value(&position.text_document.uri)?;
+        let offset = offset_at(source, position.position);
+
+        let tokens = Lexer::new(source, file_path(uri)).tokenize();
+        let token = tokens.iter().find(|token| {
+            token.kind == TokenKind::Identifier
+                && token.span.start <= offset
+                && offset <= token.span.end
+        })?;
+
+        Some((uri, source, token.text.clone()))
+    }
+}
+
+fn extract<P: serde::de::DeserializeOwned>(
+    request: Request,
+    method: &str,
+) -> Result<(RequestId, P), ServerError> {
+    request
+        .extract(method)
+        .map_err(|err| format!("Invalid '{method}' request: {err:?}").into())
+}
+
+fn publish(uri: Uri, diagnostics: Vec<Diagnostic>) -> Notification {
+    Notification::new(
+        PublishDiagnostics::METHOD.to_string(),
+        PublishDiagnosticsParams {
+            uri,
+            diagnostics,
+            version: None,
+        },
+    )
+}
+
+fn to_diagnostic(error: &DiagnosticError, source: &str) -> Diagnostic {
+    Diagnostic {
+        range: to_range(error.span(), source),
+        severity: Some(match error.severity() {
+            Severity::Error => DiagnosticSeverity::ERROR,
+            Severity::Warning => DiagnosticSeverity::WARNING,
+            Severity::Info => DiagnosticSeverity::INFORMATION,
+            Severity::Hint => DiagnosticSeverity::HINT,
+        }),
+        code: Some(NumberOrString::String(error.lint_name().to_string())),
+        source: Some("bpmncode".to_string()),
+        message: error.to_string(),
+        ..Diagnostic::default()
+    }
+}
+
+// Spans count lines and columns from 1 in characters, LSP positions from 0 in UTF-16
+// code units
+fn to_range(span: &Span, source: &str) -> Range {
+    let line = span.line.saturating_sub(1);
+    let before: usize = source
+        .lines()
+        .nth(line)
+        .unwrap_or_default()
+        .chars()
+        .take(span.column.saturating_sub(1))
+        .map(char::len_utf16)
+        .sum();
+    let start = Position {
+        line: to_u32(line),
+        character: to_u32(before),
+    };
+
+    let text = source.get(span.start..span.end).unwrap_or_default();
+    let end = text.chars().fold(start, |position, character| {
+        if character == '\n' {
+            Position {
+                line: position.line + 1,
+                character: 0,
+            }
+        } else {
+            Position {
+                line: position.line,
+                character: position.character + to_u32(character.len_utf16()),
+            }
+        }
+    });
+
+    Range { start, end }
+}
+
+fn offset_at(source: &str, position: Position) -> usize {
+    let mut line = 0;
+    let mut character = 0;
+
+    for (offset, current) in source.char_indices() {
+        if line == position.line && character >= position.character {
+            return offset;
+        }
+        if current == '\n' {
+            if line == position.line {
+                return offset;
+            }
+            line += 1;
+            character = 0;
+        } else {
+            character += to_u32(current.len_utf16());
+        }
+    }
+
+    source.len()
+}
+
+// Editors send percent-encoded URIs, the compiler wants the plain path
+fn file_path(uri: &Uri) -> String {
+    uri.path()
+        .as_estr()
+        .decode()
+        .into_string_lossy()
+        .into_owned()
+}
+
+fn to_u32(value: usize) -> u32 {
+    u32::try_from(value).unwrap_or(u32::MAX)
+}
@@ -1,14 +1,31 @@
-use bpmncode::diagnostics::context_validator::ContextValidator;
+use bpmncode::ast_tree::render_tree;
+use bpmncode::config::{CONFIG_FILE_NAME, Config};
+use bpmncode::diagnostics::baseline::Baseline;
+use bpmncode::diagnostics::fix::{apply_fixes, collect_fixes};
 use bpmncode::diagnostics::formatter::DiagnosticFormatter;
-use bpmncode::diagnostics::suggestions::{suggest_identifiers, suggest_keywords};
-use bpmncode::diagnostics::{DiagnosticError, DiagnosticReport, Severity};
-use bpmncode::lexer::multi_file::MultiFileLexer;
-use bpmncode::parser::ast::ProcessElement;
-use bpmncode::parser::parse_tokens_with_validation;
-use clap::{Parser, Subcommand, ValueEnum};
+use bpmncode::diagnostics::suggestions::{ATTRIBUTE_NAMES, BPMN_KEYWORDS, EVENT_TYPES, FLOW_TYPES};
+use bpmncode::diagnostics::{ERROR_CODES, LINT_NAMES, LintLevel, Severity, is_known_lint};
+use bpmncode::diff::{Change, ChangeKind, diff_documents};
+use bpmncode::emitter::dot::to_dot;
+use bpmncode::emitter::layout::to_bpmndi;
+use bpmncode::emitter::mermaid::to_mermaid;
+use bpmncode::formatter::diff_lines;
+use bpmncode::formatter::printer::print_document;
+use bpmncode::formatter::source::{FormatOptions, format_source, format_source_with_options};
+use bpmncode::importer::bpmn_xml::from_bpmn_xml;
+use bpmncode::lexer::Lexer;
+use bpmncode::metrics::{ProcessMetrics, document_metrics, element_kind};
+use bpmncode::parser::Parser as DocumentParser;
+use bpmncode::parser::ids::assign_ids;
+use bpmncode::parser::rules::Profile;
+use bpmncode::{Compilation, CompileOptions, compile_str_with_options, compile_with_options};
+use clap::builder::{PossibleValue, PossibleValuesParser, TypedValueParser};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use colored::Colorize;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
-use std::path::PathBuf;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
 use std::process;
 
 #[derive(Parser)]
@@ -23,27 +40,24 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     /// Check BPMN source files for errors
-    Check {
-        /// Input BPMN source file(s)
-        #[arg(value_name = "INPUT")]
-        input: Vec<PathBuf>,
-
-        /// Show detailed error information
-        #[arg(short, long)]
-        verbose: bool,
-
-        /// Output format for diagnostics
-        #[arg(long, default_value = "human")]
-        format: DiagnosticFormat,
-
-        /// Disable colored output
-        #[arg(long)]
-        no_color: bool,
-
-        /// Hide source code context
-        #[arg(long)]
-        no_source: bool,
-    },
+    Check(CheckArgs),
+    /// Export processes to other diagram formats
+    Export(ExportArgs),
+    /// Format BPMN source files
+    Fmt(FmtArgs),
+    /// Print the parsed syntax tree as JSON
+    Ast(AstArgs),
+    /// Print size and complexity metrics for each process
+    Stats(StatsArgs),
+    /// Compare the processes of two BPMN source files element by element
+    Diff(DiffArgs),
+    /// Print the token stream produced by the lexer
+    Tokens(TokensArgs),
+    /// Convert BPMN 2.0 XML into `BPMNCode` source
+    Decompile(DecompileArgs),
+    /// Run a language server over stdin/stdout
+    #[cfg(feature = "lsp")]
+    Lsp,
     /// Show information about `BPMNCode`
     Info {
         /// Show version information
@@ -57,9 +71,213 @@ enum Commands {
         /// Show examples
         #[arg(long)]
         examples: bool,
+
+        /// Output format for the syntax reference
+        #[arg(long, default_value = "human", requires = "syntax")]
+        format: InfoFormat,
     },
 }
 
+#[derive(Args)]
+#[allow(clippy::struct_excessive_bools)]
+struct CheckArgs {
+    /// Input BPMN source file(s), `-` reads from stdin
+    #[arg(value_name = "INPUT")]
+    input: Vec<PathBuf>,
+
+    /// Read source from stdin in addition to any input files
+    #[arg(long)]
+    stdin: bool,
+
+    /// Show detailed error information
+    #[arg(short, long)]
+    verbose: bool,
+
+    /// Output format for diagnostics [default: human]
+    #[arg(long)]
+    format: Option<DiagnosticFormat>,
+
+    /// Disable colored output
+    #[arg(long)]
+    no_color: bool,
+
+    /// Hide source code context
+    #[arg(long)]
+    no_source: bool,
+
+    /// Maximum nesting depth of subprocesses, pools and groups
+    #[arg(long, value_name = "DEPTH")]
+    max_nesting_depth: Option<usize>,
+
+    /// Drop diagnostics of this lint or error code (repeatable)
+    #[arg(long, value_name = "LINT")]
+    allow: Vec<String>,
+
+    /// Report this lint or error code as a warning (repeatable)
+    #[arg(long, value_name = "LINT")]
+    warn: Vec<String>,
+
+    /// Report this lint or error code as an error (repeatable)
+    #[arg(long, value_name = "LINT")]
+    deny: Vec<String>,
+
+    /// Execution engine whose constraints should be checked
+    #[arg(long, default_value_t = Profile::Generic, value_parser = profile_parser())]
+    profile: Profile,
+
+    /// Maximum line width for human output (defaults to the terminal width)
+    #[arg(long, value_name = "COLUMNS")]
+    width: Option<usize>,
+
+    /// Print at most this many diagnostics per file, totals still count all of them
+    #[arg(long, value_name = "N")]
+    max_errors: Option<usize>,
+
+    /// Check again whenever an input file or one of its imports changes
+    #[cfg(feature = "watch")]
+    #[arg(long)]
+    watch: bool,
+
+    /// Apply fixes for diagnostics with a single suggestion in place
+    #[arg(long)]
+    fix: bool,
+
+    /// Only report diagnostics that are not recorded in this baseline file
+    #[arg(long, value_name = "FILE")]
+    baseline: Option<PathBuf>,
+
+    /// Record all current diagnostics into a baseline file
+    #[arg(long, value_name = "FILE")]
+    write_baseline: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct ExportArgs {
+    /// Input BPMN source file
+    #[arg(value_name = "INPUT")]
+    input: PathBuf,
+
+    /// Output diagram format
+    #[arg(long, default_value = "mermaid")]
+    format: ExportFormat,
+
+    /// Only export the process with this name
+    #[arg(long, value_name = "NAME")]
+    process: Option<String>,
+}
+
+#[derive(Args)]
+struct FmtArgs {
+    /// Input BPMN source file(s)
+    #[arg(value_name = "INPUT")]
+    input: Vec<PathBuf>,
+
+    /// Exit with an error and print a diff if a file is not formatted
+    #[arg(long, conflicts_with = "write")]
+    check: bool,
+
+    /// Rewrite the files in place
+    #[arg(long)]
+    write: bool,
+
+    /// Spaces per indentation level (defaults to 4)
+    #[arg(long, value_name = "WIDTH")]
+    indent: Option<usize>,
+}
+
+#[derive(Args)]
+struct AstArgs {
+    /// Input BPMN source file
+    #[arg(value_name = "INPUT")]
+    input: PathBuf,
+
+    /// Pretty-print the JSON output
+    #[arg(long)]
+    pretty: bool,
+
+    /// Draw the document as an indented tree instead of JSON
+    #[arg(long, conflicts_with = "pretty")]
+    tree: bool,
+}
+
+#[derive(Args)]
+struct StatsArgs {
+    /// Input BPMN source file, `-` reads from stdin
+    #[arg(value_name = "INPUT")]
+    input: PathBuf,
+
+    /// Output format for the metrics
+    #[arg(long, default_value = "human")]
+    format: StatsFormat,
+}
+
+#[derive(Args)]
+struct DiffArgs {
+    /// The earlier version of the source file
+    #[arg(value_name = "OLD")]
+    old: PathBuf,
+
+    /// The later version of the source file
+    #[arg(value_name = "NEW")]
+    new: PathBuf,
+
+    /// Output format for the changes
+    #[arg(long, default_value = "human")]
+    format: DiffFormat,
+}
+
+#[derive(Args)]
+struct TokensArgs {
+    /// Input BPMN source file
+    #[arg(value_name = "INPUT")]
+    input: PathBuf,
+
+    /// Print the tokens as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Args)]
+struct DecompileArgs {
+    /// Input BPMN 2.0 XML file
+    #[arg(value_name = "INPUT")]
+    input: PathBuf,
+}
+
+#[derive(ValueEnum, Clone, Debug)]
+enum ExportFormat {
+    /// Mermaid flowchart for Markdown previews
+    Mermaid,
+    /// Graphviz DOT graph with clustered pools and lanes
+    Dot,
+    /// BPMN diagram interchange block with laid out shapes and edges
+    Bpmndi,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum InfoFormat {
+    /// Human-readable reference
+    Human,
+    /// Keyword, event, flow and attribute vocabulary as JSON for editor tooling
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum StatsFormat {
+    /// One block of counts per process
+    Human,
+    /// An array with one object per process
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum DiffFormat {
+    /// One line per change, grouped by process
+    Human,
+    /// An array with one object per change
+    Json,
+}
+
 #[derive(ValueEnum, Clone, Debug)]
 enum DiagnosticFormat {
     /// Human-readable format with colors and source highlighting
@@ -70,25 +288,35 @@ enum DiagnosticFormat {
     Json,
     /// Fancy format using miette
     Fancy,
+    /// Markdown summary of new diagnostics for pull request comments
+    Review,
+    /// SARIF 2.1.0 log for code scanning in CI
+    Sarif,
+    /// GitHub Actions workflow commands that annotate the changed lines
+    Github,
 }
 
 fn main() {
     let cli = Cli::parse();
 
     let result = match cli.command {
-        Commands::Check {
-            input,
-            verbose,
-            format,
-            no_color,
-            no_source,
-        } => check_command(input, verbose, &format, no_color, no_source),
+        Commands::Check(args) => check_command(&args),
+        Commands::Export(args) => export_command(&args),
+        Commands::Fmt(args) => fmt_command(&args),
+        Commands::Ast(args) => ast_command(&args),
+        Commands::Stats(args) => stats_command(&args),
+        Commands::Diff(args) => diff_command(&args),
+        Commands::Tokens(args) => tokens_command(&args),
+        Commands::Decompile(args) => decompile_command(&args),
+        #[cfg(feature = "lsp")]
+        Commands::Lsp => lsp_command(),
         Commands::Info {
             version,
             syntax,
             examples,
+            format,
         } => {
-            info_command(version, syntax, examples);
+            info_command(version, syntax, examples, format);
             return;
         }
     };
@@ -99,40 +327,171 @@ fn main() {
     }
 }
 
-fn check_command(
-    inputs: Vec<PathBuf>,
-    verbose: bool,
-    format: &DiagnosticFormat,
-    no_color: bool,
-    no_source: bool,
+const STDIN_NAME: &str = "<stdin>";
+
+// `-` stands for piped source, which is linted under a synthetic name with imports
+// resolved from the current directory
+fn compile_input(
+    input: &Path,
+    fix: bool,
+    options: &CompileOptions,
+) -> Result<Compilation, Box<dyn std::error::Error>> {
+    if input == Path::new("-") {
+        if fix {
+            return Err("Cannot apply --fix to source read from stdin".into());
+        }
+
+        let mut source = String::new();
+        io::stdin().read_to_string(&mut source)?;
+        return Ok(compile_str_with_options(&source, STDIN_NAME, options));
+    }
+
+    if fix {
+        compile_and_fix(input, options)
+    } else {
+        Ok(compile_with_options(input, options)?)
+    }
+}
+
+fn compile_and_fix(
+    input: &Path,
+    options: &CompileOptions,
+) -> Result<Compilation, Box<dyn std::error::Error>> {
+    let compilation = compile_with_options(input, options)?;
+    let fixes = collect_fixes(&compilation.report);
+    if fixes.is_empty() {
+        return Ok(compilation);
+    }
+
+    let (source, applied) = apply_fixes(&compilation.report.source_code, fixes);
+    fs::write(input, source)?;
+    eprintln!(
+        "Applied {applied} {} to {}",
+        if applied == 1 { "fix" } else { "fixes" },
+        input.display()
+    );
+
+    Ok(compile_with_options(input, options)?)
+}
+
+fn merge_lint_levels(
+    args: &CheckArgs,
+    config: &Config,
+) -> Result<BTreeMap<String, LintLevel>, String> {
+    let mut lint_levels = config.lints.clone();
+    for (names, level) in [
+        (&args.allow, LintLevel::Allow),
+        (&args.warn, LintLevel::Warning),
+        (&args.deny, LintLevel::Error),
+    ] {
+        for name in names {
+            lint_levels.insert(name.clone(), level);
+        }
+    }
+
+    if let Some(name) = lint_levels.keys().find(|name| !is_known_lint(name)) {
+        return Err(format!("Unknown lint '{name}'"));
+    }
+
+    Ok(lint_levels)
+}
+
+// The library keeps `Profile` free of clap, the names and help come from it
+fn profile_parser() -> impl TypedValueParser<Value = Profile> {
+    PossibleValuesParser::new(
+        Profile::ALL
+            .iter()
+            .map(|profile| PossibleValue::new(profile.name()).help(profile.description())),
+    )
+    .map(|name| {
+        name.parse::<Profile>()
+            .expect("every listed profile parses")
+    })
+}
+
+fn check_command(args: &CheckArgs) -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(feature = "watch")]
+    if args.watch {
+        return watch_command(args);
+    }
+
+    check_inputs(args, &mut BTreeSet::new())
+}
+
+// `-` and `--stdin` both name standard input, which can only be read once
+fn input_paths(args: &CheckArgs) -> Vec<PathBuf> {
+    let mut inputs = args.input.clone();
+    if args.stdin {
+        inputs.push(PathBuf::from("-"));
+    }
+
+    let mut read_stdin = false;
+    inputs.retain(|input| input != Path::new("-") || !std::mem::replace(&mut read_stdin, true));
+    inputs
+}
+
+// Collects the files the check read into `dependencies`
+fn check_inputs(
+    args: &CheckArgs,
+    dependencies: &mut BTreeSet<PathBuf>,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let use_colors = !no_color && atty::is(atty::Stream::Stdout);
-    let show_source = !no_source;
-    let formatter = DiagnosticFormatter::new(use_colors, show_source);
+    let config = Config::load(
+        args.input
+            .first()
+            .map_or_else(|| Path::new("."), PathBuf::as_path),
+    )?;
+    let config_format = config
+        .format
+        .as_deref()
+        .map(|name| {
+            DiagnosticFormat::from_str(name, true)
+                .map_err(|_| format!("Invalid format '{name}' in {CONFIG_FILE_NAME}"))
+        })
+        .transpose()?;
+
+    let verbose = args.verbose;
+    let format = &args
+        .format
+        .clone()
+        .or(config_format)
+        .unwrap_or(DiagnosticFormat::Human);
+    let use_colors = !args.no_color && atty::is(atty::Stream::Stdout);
+    let show_source = !(args.no_source || config.no_source.unwrap_or(false));
+    let width = args
+        .width
+        .or_else(|| terminal_size::terminal_size().map(|(width, _)| usize::from(width.0)));
+    let inputs = input_paths(args);
+    let single_input = inputs.len() == 1;
+    let formatter = DiagnosticFormatter::new(use_colors, show_source)
+        .with_width(width)
+        .with_max_errors(args.max_errors)
+        .with_paths(!single_input);
+
+    let options = CompileOptions {
+        profile: args.profile,
+        max_nesting_depth: args.max_nesting_depth.or(config.max_nesting_depth),
+        lint_levels: merge_lint_levels(args, &config)?,
+    };
+
+    let baseline = args.baseline.as_deref().map(Baseline::load).transpose()?;
+    let mut recorded = Baseline::default();
+    let mut collected_reports = Vec::new();
 
     let mut total_errors = 0;
     let mut total_warnings = 0;
 
-    for input in inputs {
-        let source_code = fs::read_to_string(&input)?;
-        let mut report = DiagnosticReport::new(input.display().to_string(), source_code.clone());
-
-        let base_dir = std::env::current_dir()?;
-        let mut lexer = MultiFileLexer::new(base_dir);
-        let tokens = lexer.tokenize_file(&input)?;
-        
-        // Context validation on tokens (catch typos and syntax errors)
-        let mut context_validator = ContextValidator::new(source_code.clone());
-        let context_errors = context_validator.validate_tokens(&tokens);
-        for error in context_errors {
-            report.add_error(error);
-        }
-
-        let ast = parse_tokens_with_validation(tokens);
+    for input in &inputs {
+        let Compilation {
+            mut report,
+            document: ast,
+            dependencies: loaded,
+        } = compile_input(input, args.fix, &options)?;
+        report.sort();
+        dependencies.extend(loaded);
 
-        for error in &ast.errors {
-            let diagnostic_error = convert_parser_error_to_diagnostic(error, &ast);
-            report.add_error(diagnostic_error);
+        recorded.record(&report);
+        if let Some(baseline) = &baseline {
+            baseline.retain_new(&mut report);
         }
 
         total_errors += report.error_count();
@@ -147,23 +506,40 @@ fn check_command(
                 }
             }
             DiagnosticFormat::Short => {
-                print_short_format(&report);
-            }
-            DiagnosticFormat::Json => {
-                println!("{}", formatter.format_json(&report)?);
+                print!("{}", formatter.format_short(&report));
             }
             DiagnosticFormat::Fancy => {
                 print!("{}", formatter.format_fancy(&report));
             }
+            DiagnosticFormat::Github => {
+                print!("{}", formatter.format_github(&report));
+            }
+            DiagnosticFormat::Json | DiagnosticFormat::Review | DiagnosticFormat::Sarif => {
+                collected_reports.push(report);
+            }
         }
 
-        if verbose && !matches!(format, DiagnosticFormat::Json) {
+        if verbose
+            && !matches!(
+                format,
+                DiagnosticFormat::Json | DiagnosticFormat::Review | DiagnosticFormat::Sarif
+            )
+        {
             print_ast_debug_info(&ast, use_colors);
         }
     }
 
-    if !matches!(format, DiagnosticFormat::Json) {
-        print_summary(total_errors, total_warnings, use_colors)?;
+    if let Some(path) = &args.write_baseline {
+        recorded.save(path)?;
+    }
+
+    match format {
+        DiagnosticFormat::Json => {
+            println!("{}", formatter.format_json_reports(&collected_reports)?);
+        }
+        DiagnosticFormat::Review => print!("{}", formatter.format_review(&collected_reports)),
+        DiagnosticFormat::Sarif => println!("{}", formatter.format_sarif(&collected_reports)?),
+        _ => print_summary(total_errors, total_warnings, use_colors)?,
     }
 
     if total_errors > 0 {
@@ -173,62 +549,349 @@ fn check_command(
     }
 }
 
-fn convert_parser_error_to_diagnostic(
-    error: &bpmncode::parser::ast::ParseError,
-    ast: &bpmncode::parser::ast::AstDocument,
-) -> DiagnosticError {
-    let suggestions = if error.message.contains("Unexpected token") {
-        error
-            .message
-            .find('\'')
-            .map_or_else(Vec::new, |token_start| {
-                error.message[token_start + 1..]
-                    .find('\'')
-                    .map_or_else(Vec::new, |token_end| {
-                        let found_token =
-                            &error.message[token_start + 1..token_start + 1 + token_end];
-                        suggest_keywords(found_token)
-                    })
-            })
-    } else if error.message.contains("Unknown") {
-        let identifiers: Vec<String> =
-            ast.processes
-                .iter()
-                .flat_map(|p| {
-                    p.elements.iter().filter_map(|e| match e {
-                        ProcessElement::CallActivity { id, .. }
-                        | ProcessElement::Task { id, .. } => Some(id.clone()),
-                        ProcessElement::Gateway { id, .. } => id.clone(),
-                        _ => None,
-                    })
-                })
-                .collect();
-
-        error
-            .message
-            .find('\'')
-            .map_or_else(Vec::new, |name_start| {
-                error.message[name_start + 1..]
-                    .find('\'')
-                    .map_or_else(Vec::new, |name_end| {
-                        let unknown_name =
-                            &error.message[name_start + 1..name_start + 1 + name_end];
-                        suggest_identifiers(unknown_name, &identifiers)
-                    })
-            })
+// Re-runs the check whenever an input or a file it imports changes. Parent
+// directories are watched so editors that save by replacing the file are seen too.
+#[cfg(feature = "watch")]
+fn watch_command(args: &CheckArgs) -> Result<(), Box<dyn std::error::Error>> {
+    use notify::{EventKind, RecursiveMode, Watcher};
+    use std::{io::Write, sync::mpsc, time::Duration};
+
+    if args.stdin || args.input.iter().any(|input| input == Path::new("-")) {
+        return Err("Cannot watch source read from stdin".into());
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender)?;
+    let mut watched_dirs = BTreeSet::new();
+
+    loop {
+        print!("\x1b[2J\x1b[H");
+        let mut dependencies: BTreeSet<PathBuf> = args
+            .input
+            .iter()
+            .map(std::path::absolute)
+            .collect::<Result<_, _>>()?;
+        if let Err(error) = check_inputs(args, &mut dependencies) {
+            println!("{} {error}", "error:".red().bold());
+        }
+        // Imports reach one file through several spellings (`./a.bpmn`, `sub/../a.bpmn`,
+        // symlinks), while the watcher reports it under its resolved directory
+        let dependencies: BTreeSet<PathBuf> = dependencies
+            .into_iter()
+            .map(|path| path.canonicalize().unwrap_or(path))
+            .collect();
+        println!(
+            "\nWatching {} {} for changes...",
+            dependencies.len(),
+            if dependencies.len() == 1 {
+                "file"
+            } else {
+                "files"
+            }
+        );
+        io::stdout().flush()?;
+
+        let dirs: BTreeSet<PathBuf> = dependencies
+            .iter()
+            .filter_map(|path| path.parent().map(Path::to_path_buf))
+            .collect();
+        for dir in watched_dirs.difference(&dirs) {
+            let _ = watcher.unwatch(dir);
+        }
+        for dir in dirs.difference(&watched_dirs) {
+            watcher.watch(dir, RecursiveMode::NonRecursive)?;
+        }
+        watched_dirs = dirs;
+
+        loop {
+            let event = receiver.recv()??;
+            if !matches!(event.kind, EventKind::Access(_))
+                && event.paths.iter().any(|path| dependencies.contains(path))
+            {
+                break;
+            }
+        }
+        // A single save often arrives as several events
+        while receiver.recv_timeout(Duration::from_millis(100)).is_ok() {}
+    }
+}
+
+fn export_command(args: &ExportArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let Compilation {
+        report,
+        document: mut ast,
+        ..
+    } = compile_with_options(&args.input, &CompileOptions::default())?;
+
+    if report.has_errors() {
+        for error in report
+            .errors
+            .iter()
+            .filter(|error| error.severity() == Severity::Error)
+        {
+            let span = error.span();
+            eprintln!(
+                "{}:{}:{}: {}",
+                span.file.display(),
+                span.line,
+                span.column,
+                error
+            );
+        }
+        return Err(format!("Cannot export {}", args.input.display()).into());
+    }
+    assign_ids(&mut ast);
+
+    let processes: Vec<_> = ast
+        .processes
+        .iter()
+        .filter(|process| {
+            args.process
+                .as_ref()
+                .is_none_or(|name| &process.name == name)
+        })
+        .collect();
+
+    if processes.is_empty() {
+        return Err(args.process.as_ref().map_or_else(
+            || "No processes to export".into(),
+            |name| format!("Process '{name}' not found").into(),
+        ));
+    }
+
+    let rendered: Vec<String> = processes
+        .iter()
+        .map(|process| match args.format {
+            ExportFormat::Mermaid => to_mermaid(process),
+            ExportFormat::Dot => to_dot(process),
+            ExportFormat::Bpmndi => to_bpmndi(process),
+        })
+        .collect();
+
+    print!("{}", rendered.join("\n"));
+
+    Ok(())
+}
+
+fn fmt_command(args: &FmtArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let config = Config::load(
+        args.input
+            .first()
+            .map_or_else(|| Path::new("."), PathBuf::as_path),
+    )?;
+    let mut options = FormatOptions::default();
+    if let Some(indent_width) = args.indent.or(config.indent_width) {
+        options.indent_width = indent_width;
+    }
+
+    let mut unformatted = 0;
+
+    for input in &args.input {
+        let source = fs::read_to_string(input)?;
+        let tokens = Lexer::new(&source, input).tokenize();
+        let document = DocumentParser::new(tokens).parse();
+
+        if document.has_errors() {
+            return Err(
+                format!("Cannot format {}: fix syntax errors first", input.display()).into(),
+            );
+        }
+
+        let formatted = format_source_with_options(&document, &source, &options);
+
+        if args.check {
+            if formatted != source {
+                unformatted += 1;
+                println!("Diff in {}:", input.display());
+                print!("{}", diff_lines(&source, &formatted));
+            }
+        } else if args.write {
+            if formatted != source {
+                fs::write(input, &formatted)?;
+            }
+        } else {
+            print!("{formatted}");
+        }
+    }
+
+    if unformatted > 0 {
+        Err(format!("{unformatted} file(s) need formatting").into())
+    } else {
+        Ok(())
+    }
+}
+
+fn ast_command(args: &AstArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let Compilation { document, .. } =
+        compile_with_options(&args.input, &CompileOptions::default())?;
+
+    if args.tree {
+        print!("{}", render_tree(&document));
+        return Ok(());
+    }
+
+    let json = if args.pretty {
+        serde_json::to_string_pretty(&document)?
     } else {
-        Vec::new()
+        serde_json::to_string(&document)?
+    };
+    println!("{json}");
+
+    Ok(())
+}
+
+fn stats_command(args: &StatsArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let Compilation { document, .. } =
+        compile_input(&args.input, false, &CompileOptions::default())?;
+    let metrics = document_metrics(&document);
+
+    match args.format {
+        StatsFormat::Json => println!("{}", serde_json::to_string_pretty(&metrics)?),
+        StatsFormat::Human => {
+            for (index, process) in metrics.iter().enumerate() {
+                if index > 0 {
+                    println!();
+                }
+                print_metrics(process);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn print_metrics(metrics: &ProcessMetrics) {
+    let breakdown = |counts: &BTreeMap<&str, usize>| {
+        let total: usize = counts.values().sum();
+        let parts: Vec<String> = counts
+            .iter()
+            .map(|(kind, count)| format!("{kind} {count}"))
+            .collect();
+        if parts.is_empty() {
+            total.to_string()
+        } else {
+            format!("{total} ({})", parts.join(", "))
+        }
     };
 
-    DiagnosticError::SyntaxError {
-        message: error.message.clone(),
-        span: error.span.clone(),
-        severity: match error.severity {
-            bpmncode::parser::ast::ErrorSeverity::Error => Severity::Error,
-            bpmncode::parser::ast::ErrorSeverity::Warning => Severity::Warning,
-        },
-        suggestions,
+    println!("{}", metrics.name);
+    println!("  tasks:       {}", breakdown(&metrics.tasks));
+    println!("  gateways:    {}", breakdown(&metrics.gateways));
+    println!("  events:      {}", breakdown(&metrics.events));
+    println!("  flows:       {}", metrics.flows);
+    println!("  complexity:  {}", metrics.complexity);
+    println!("  max depth:   {}", metrics.max_depth);
+    println!("  unreachable: {}", metrics.unreachable);
+    println!("  dead ends:   {}", metrics.dead_ends);
+}
+
+fn diff_command(args: &DiffArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let parse =
+        |input: &Path| -> Result<bpmncode::parser::ast::AstDocument, Box<dyn std::error::Error>> {
+            let source = fs::read_to_string(input)?;
+            let tokens = Lexer::new(&source, input).tokenize();
+            let document = DocumentParser::new(tokens).parse();
+            if document.has_errors() {
+                return Err(
+                    format!("Cannot diff {}: fix syntax errors first", input.display()).into(),
+                );
+            }
+            Ok(document)
+        };
+    let changes = diff_documents(&parse(&args.old)?, &parse(&args.new)?);
+
+    match args.format {
+        DiffFormat::Json => println!("{}", serde_json::to_string_pretty(&changes)?),
+        DiffFormat::Human => print_changes(&changes),
+    }
+
+    Ok(())
+}
+
+fn print_changes(changes: &[Change]) {
+    if changes.is_empty() {
+        println!("No changes");
+        return;
+    }
+
+    let mut heading = None;
+    for change in changes {
+        if heading != Some(&change.process) {
+            if heading.is_some() {
+                println!();
+            }
+            heading = Some(&change.process);
+            let title = change
+                .process
+                .as_ref()
+                .map_or_else(|| "imports".to_string(), |name| format!("process {name}"));
+            println!("{}", title.bold());
+        }
+
+        let marker = match change.kind {
+            ChangeKind::Added => "+".green(),
+            ChangeKind::Removed => "-".red(),
+            ChangeKind::Modified => "~".yellow(),
+        };
+        println!("  {marker} {change}");
+    }
+}
+
+fn tokens_command(args: &TokensArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let source = fs::read_to_string(&args.input)?;
+    let mut lexer = Lexer::new(&source, &args.input);
+    let tokens = lexer.tokenize();
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&tokens)?);
+        return Ok(());
+    }
+
+    for token in &tokens {
+        println!(
+            "{:>4}:{:<4} {:>6}..{:<6} {:<20} {:?}",
+            token.span.line,
+            token.span.column,
+            token.span.start,
+            token.span.end,
+            format!("{:?}", token.kind),
+            token.text
+        );
+    }
+
+    for error in lexer.errors() {
+        eprintln!("{error}");
     }
+
+    Ok(())
+}
+
+#[cfg(feature = "lsp")]
+fn lsp_command() -> Result<(), Box<dyn std::error::Error>> {
+    bpmncode::lsp::run().map_err(|err| err as Box<dyn std::error::Error>)
+}
+
+fn decompile_command(args: &DecompileArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let xml = fs::read_to_string(&args.input)?;
+    let imported = from_bpmn_xml(&xml)?;
+    for warning in &imported.errors {
+        eprintln!(
+            "{}:{}:{}: {} {}",
+            args.input.display(),
+            warning.span.line,
+            warning.span.column,
+            "warning:".yellow().bold(),
+            warning.message
+        );
+    }
+
+    let source = print_document(&imported);
+    let tokens = Lexer::new(&source, &args.input).tokenize();
+    let document = DocumentParser::new(tokens).parse();
+
+    print!("{}", format_source(&document, &source));
+
+    Ok(())
 }
 
 fn print_verbose_success_info(ast: &bpmncode::parser::ast::AstDocument, use_colors: bool) {
@@ -258,20 +921,6 @@ fn print_verbose_success_info(ast: &bpmncode::parser::ast::AstDocument, use_colo
     }
 }
 
-fn print_short_format(report: &DiagnosticReport) {
-    for error in &report.errors {
-        let span = error.span();
-        println!(
-            "{}:{}:{}: {}: {}",
-            span.file.display(),
-            span.line,
-            span.column,
-            error.severity(),
-            error
-        );
-    }
-}
-
 fn print_ast_debug_info(ast: &bpmncode::parser::ast::AstDocument, use_colors: bool) {
     if use_colors {
         println!("{} AST structure:", "Debug:".yellow().bold());
@@ -279,6 +928,9 @@ fn print_ast_debug_info(ast: &bpmncode::parser::ast::AstDocument, use_colors: bo
         println!("Debug: AST structure:");
     }
     print_ast_summary(ast, use_colors);
+    for line in render_tree(ast).lines() {
+        println!("  {line}");
+    }
 }
 
 #[allow(clippy::unnecessary_wraps)]
@@ -328,29 +980,9 @@ fn print_ast_summary(ast: &bpmncode::parser::ast::AstDocument, use_colors: bool)
             process.flows.len()
         );
 
-        let mut element_counts = std::collections::HashMap::new();
+        let mut element_counts = BTreeMap::new();
         for element in &process.elements {
-            let element_type = match element {
-                ProcessElement::StartEvent { .. } => "start",
-                ProcessElement::EndEvent { .. } => "end",
-                ProcessElement::Task { task_type, .. } => match task_type {
-                    bpmncode::parser::ast::TaskType::Generic => "task",
-                    bpmncode::parser::ast::TaskType::User => "user",
-                    bpmncode::parser::ast::TaskType::Service => "service",
-                    bpmncode::parser::ast::TaskType::Script => "script",
-                },
-                ProcessElement::Gateway { gateway_type, .. } => match gateway_type {
-                    bpmncode::parser::ast::GatewayType::Exclusive => "xor",
-                    bpmncode::parser::ast::GatewayType::Parallel => "and",
-                },
-                ProcessElement::IntermediateEvent { .. } => "event",
-                ProcessElement::Subprocess { .. } => "subprocess",
-                ProcessElement::CallActivity { .. } => "call",
-                ProcessElement::Pool { .. } => "pool",
-                ProcessElement::Group { .. } => "group",
-                ProcessElement::Annotation { .. } => "note",
-            };
-            *element_counts.entry(element_type).or_insert(0) += 1;
+            *element_counts.entry(element_kind(element)).or_insert(0) += 1;
         }
 
         for (element_type, count) in element_counts {
@@ -370,14 +1002,17 @@ fn print_ast_summary(ast: &bpmncode::parser::ast::AstDocument, use_colors: bool)
     }
 }
 
-fn info_command(version: bool, syntax: bool, examples: bool) {
+fn info_command(version: bool, syntax: bool, examples: bool, format: InfoFormat) {
     if version {
         show_version();
         return;
     }
 
     if syntax {
-        show_syntax();
+        match format {
+            InfoFormat::Human => show_syntax(),
+            InfoFormat::Json => show_syntax_json(),
+        }
         return;
     }
 
@@ -406,6 +1041,11 @@ fn show_syntax() {
     println!("  process ProcessName @version \"1.0\" {{ ... }}");
     println!();
 
+    println!("{}", "Variables:".green().bold());
+    println!("  var amount: number       - Name read by conditions (number, string or boolean)");
+    println!("  Enable the undeclared-variable lint to warn on conditions using other names");
+    println!();
+
     println!("{}", "Elements:".green().bold());
 
     println!("  start                    - Start event");
@@ -419,6 +1059,7 @@ fn show_syntax() {
     println!("{}", "Gateways:".green().bold());
     println!("  xor GatewayName? {{ ... }}  - Exclusive gateway");
     println!("  and GatewayName {{ ... }}   - Parallel gateway");
+    println!("  or GatewayName? {{ ... }}   - Inclusive gateway");
     println!();
 
     println!("{}", "Flows:".green().bold());
@@ -432,6 +1073,9 @@ fn show_syntax() {
     println!("  pool PoolName {{ ... }}    - Pool");
     println!("  lane LaneName {{ ... }}    - Lane");
     println!("  subprocess Name {{ ... }}  - Subprocess");
+    println!("  event subprocess Name @error \"E1\" {{ ... }} - Event subprocess");
+    println!("  Elements written directly in a pool that has lanes belong to its");
+    println!("  implicit lane 'default', or to a lane declared with that name");
     println!();
 
     println!("{}", "Imports:".green().bold());
@@ -441,6 +1085,8 @@ fn show_syntax() {
 
     println!("{}", "Attributes:".green().bold());
     println!("  task Name (async=true retries=3)");
+    println!("  xor Name (priority=high) {{ ... }}  - also on pools and event subprocesses");
+    println!("  call Name (multiInstance=true sequential=true)  - or loop=true");
     println!("  @version \"1.0\" @author \"Developer\"");
 }
 
@@ -479,6 +1125,24 @@ fn show_examples() {
     println!("  }}");
 }
 
+fn show_syntax_json() {
+    let grammar = serde_json::json!({
+        "keywords": BPMN_KEYWORDS,
+        "event_types": EVENT_TYPES,
+        "flow_types": FLOW_TYPES,
+        "attribute_names": ATTRIBUTE_NAMES,
+        "lints": LINT_NAMES,
+        "error_codes": ERROR_CODES
+            .iter()
+            .map(|(code, kind)| serde_json::json!({ "code": code, "kind": kind }))
+            .collect::<Vec<_>>(),
+    });
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&grammar).unwrap_or_default()
+    );
+}
+
 fn show_general_info() {
     println!("{}", "BPMNCode - Textual DSL for BPMN 2.0".blue().bold());
     println!();
@@ -487,6 +1151,8 @@ fn show_general_info() {
 
     println!("{}", "Available Commands:".green().bold());
     println!("  {}    Check source files for errors", "check".cyan());
+    println!("  {}       Format source files", "fmt".cyan());
+    println!("  {}       Print the syntax tree as JSON", "ast".cyan());
     println!("  {}      Show information and help", "info".cyan());
     println!();
 
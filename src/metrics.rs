@@ -0,0 +1,116 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::parser::{
+    analysis::{check_reachability, check_termination},
+    ast::{AstDocument, Flow, GatewayType, ProcessDeclaration, ProcessElement, TaskType},
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ProcessMetrics {
+    pub name: String,
+    pub tasks: BTreeMap<&'static str, usize>,
+    pub gateways: BTreeMap<&'static str, usize>,
+    pub events: BTreeMap<&'static str, usize>,
+    pub flows: usize,
+    pub complexity: usize,
+    pub max_depth: usize,
+    pub unreachable: usize,
+    pub dead_ends: usize,
+}
+
+#[must_use]
+pub fn document_metrics(document: &AstDocument) -> Vec<ProcessMetrics> {
+    document.processes.iter().map(process_metrics).collect()
+}
+
+// Counts cover nested subprocesses, pools and groups as well as the top level
+#[must_use]
+pub fn process_metrics(process: &ProcessDeclaration) -> ProcessMetrics {
+    let mut metrics = ProcessMetrics {
+        name: process.name.clone(),
+        unreachable: check_reachability(&process.elements, &process.flows).len(),
+        dead_ends: check_termination(&process.elements, &process.flows).len(),
+        ..ProcessMetrics::default()
+    };
+
+    metrics.count(&process.elements, &process.flows, 0);
+    metrics.complexity = metrics.gateways.values().sum::<usize>() + 1;
+    metrics
+}
+
+// The keyword an element is written with
+#[must_use]
+pub const fn element_kind(element: &ProcessElement) -> &'static str {
+    match element {
+        ProcessElement::StartEvent { .. } => "start",
+        ProcessElement::EndEvent { .. } => "end",
+        ProcessElement::Task { task_type, .. } => match task_type {
+            TaskType::Generic => "task",
+            TaskType::User => "user",
+            TaskType::Service => "service",
+            TaskType::Script => "script",
+        },
+        ProcessElement::Gateway { gateway_type, .. } => match gateway_type {
+            GatewayType::Exclusive => "xor",
+            GatewayType::Parallel => "and",
+            GatewayType::Inclusive => "or",
+        },
+        ProcessElement::IntermediateEvent { .. } => "event",
+        ProcessElement::Subprocess { .. } => "subprocess",
+        ProcessElement::EventSubprocess { .. } => "event subprocess",
+        ProcessElement::CallActivity { .. } => "call",
+        ProcessElement::Pool { .. } => "pool",
+        ProcessElement::Group { .. } => "group",
+        ProcessElement::Annotation { .. } => "note",
+        ProcessElement::DataObject { .. } => "data",
+    }
+}
+
+impl ProcessMetrics {
+    fn count(&mut self, elements: &[ProcessElement], flows: &[Flow], depth: usize) {
+        self.flows += flows.len();
+        self.max_depth = self.max_depth.max(depth);
+
+        for element in elements {
+            let kind = element_kind(element);
+            match element {
+                ProcessElement::Task { .. } | ProcessElement::CallActivity { .. } => {
+                    *self.tasks.entry(kind).or_default() += 1;
+                }
+                ProcessElement::Gateway { branches, .. } => {
+                    *self.gateways.entry(kind).or_default() += 1;
+                    self.flows += branches.len();
+                }
+                ProcessElement::StartEvent { .. }
+                | ProcessElement::EndEvent { .. }
+                | ProcessElement::IntermediateEvent { .. } => {
+                    *self.events.entry(kind).or_default() += 1;
+                }
+                ProcessElement::Subprocess {
+                    elements, flows, ..
+                }
+                | ProcessElement::EventSubprocess {
+                    elements, flows, ..
+                } => {
+                    *self.tasks.entry(kind).or_default() += 1;
+                    self.count(elements, flows, depth + 1);
+                }
+                ProcessElement::Pool {
+                    lanes,
+                    elements,
+                    flows,
+                    ..
+                } => {
+                    for lane in lanes {
+                        self.count(&lane.elements, &[], depth + 1);
+                    }
+                    self.count(elements, flows, depth + 1);
+                }
+                ProcessElement::Group { elements, .. } => self.count(elements, &[], depth + 1),
+                ProcessElement::Annotation { .. } | ProcessElement::DataObject { .. } => {}
+            }
+        }
+    }
+}
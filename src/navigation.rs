@@ -0,0 +1,355 @@
+use std::fmt::Write;
+
+use indexmap::IndexMap;
+
+use crate::{
+    formatter::printer::{print_event_type, print_value},
+    lexer::Span,
+    parser::ast::{
+        AstDocument, AttributeValue, EventDirection, Flow, GatewayType, ProcessElement, TaskType,
+    },
+};
+
+// Where the element, pool, lane or process called `name` is declared
+#[must_use]
+pub fn definition_span(document: &AstDocument, name: &str) -> Option<Span> {
+    document.processes.iter().find_map(|process| {
+        if process.name == name {
+            return Some(process.span.clone());
+        }
+        find_definition(&process.elements, name)
+    })
+}
+
+// Where flows and gateway branches name `name`, in source order
+#[must_use]
+pub fn references(document: &AstDocument, name: &str) -> Vec<Span> {
+    let mut spans = Vec::new();
+    for process in &document.processes {
+        collect_references(&process.elements, &process.flows, name, &mut spans);
+    }
+    // A name between two chained links is the target of one and the source of the next
+    spans.sort_by_key(|span| span.start);
+    spans.dedup();
+    spans
+}
+
+fn find_definition(elements: &[ProcessElement], name: &str) -> Option<Span> {
+    elements.iter().find_map(|element| match element {
+        ProcessElement::StartEvent { id, span, .. }
+        | ProcessElement::EndEvent { id, span, .. }
+        | ProcessElement::Gateway { id, span, .. }
+        | ProcessElement::IntermediateEvent { id, span, .. }
+        | ProcessElement::Annotation { id, span, .. } => {
+            (id.as_deref() == Some(name)).then(|| span.clone())
+        }
+        ProcessElement::Task { id, span, .. }
+        | ProcessElement::CallActivity { id, span, .. }
+        | ProcessElement::DataObject { id, span, .. } => (id == name).then(|| span.clone()),
+        ProcessElement::Subprocess {
+            id, elements, span, ..
+        }
+        | ProcessElement::EventSubprocess {
+            id, elements, span, ..
+        } => {
+            if id == name {
+                return Some(span.clone());
+            }
+            find_definition(elements, name)
+        }
+        ProcessElement::Pool {
+            name: pool,
+            lanes,
+            elements,
+            span,
+            ..
+        } => {
+            if pool == name {
+                return Some(span.clone());
+            }
+            lanes
+                .iter()
+                .find_map(|lane| {
+                    if lane.name == name {
+                        return Some(lane.span.clone());
+                    }
+                    find_definition(&lane.elements, name)
+                })
+                .or_else(|| find_definition(elements, name))
+        }
+        ProcessElement::Group { elements, .. } => find_definition(elements, name),
+    })
+}
+
+fn collect_references(
+    elements: &[ProcessElement],
+    flows: &[Flow],
+    name: &str,
+    spans: &mut Vec<Span>,
+) {
+    for flow in flows {
+        if flow.from == name {
+            spans.push(flow.from_span.clone());
+        }
+        if flow.to == name {
+            spans.push(flow.to_span.clone());
+        }
+    }
+
+    for element in elements {
+        match element {
+            ProcessElement::Gateway { branches, .. } => spans.extend(
+                branches
+                    .iter()
+                    .filter(|branch| branch.target == name)
+                    .map(|branch| branch.target_span.clone()),
+            ),
+            ProcessElement::Subprocess {
+                elements, flows, ..
+            }
+            | ProcessElement::EventSubprocess {
+                elements, flows, ..
+            } => collect_references(elements, flows, name, spans),
+            ProcessElement::Pool {
+                lanes,
+                elements,
+                flows,
+                ..
+            } => {
+                for lane in lanes {
+                    collect_references(&lane.elements, &[], name, spans);
+                }
+                collect_references(elements, flows, name, spans);
+            }
+            ProcessElement::Group { elements, .. } => {
+                collect_references(elements, &[], name, spans);
+            }
+            _ => {}
+        }
+    }
+}
+
+// Markdown summary of the innermost element whose span contains `offset`
+#[must_use]
+pub fn hover_at(document: &AstDocument, offset: usize) -> Option<String> {
+    document
+        .processes
+        .iter()
+        .find_map(|process| element_at(&process.elements, offset))
+        .map(describe)
+}
+
+fn element_at(elements: &[ProcessElement], offset: usize) -> Option<&ProcessElement> {
+    elements.iter().find_map(|element| {
+        let nested = match element {
+            ProcessElement::Subprocess { elements, .. }
+            | ProcessElement::EventSubprocess { elements, .. }
+            | ProcessElement::Group { elements, .. } => element_at(elements, offset),
+            ProcessElement::Pool {
+                lanes, elements, ..
+            } => lanes
+                .iter()
+                .find_map(|lane| element_at(&lane.elements, offset))
+                .or_else(|| element_at(elements, offset)),
+            _ => None,
+        };
+
+        let span = element_span(element);
+        nested.or_else(|| (span.start <= offset && offset <= span.end).then_some(element))
+    })
+}
+
+const fn element_span(element: &ProcessElement) -> &Span {
+    match element {
+        ProcessElement::StartEvent { span, .. }
+        | ProcessElement::EndEvent { span, .. }
+        | ProcessElement::Task { span, .. }
+        | ProcessElement::Gateway { span, .. }
+        | ProcessElement::IntermediateEvent { span, .. }
+        | ProcessElement::Subprocess { span, .. }
+        | ProcessElement::EventSubprocess { span, .. }
+        | ProcessElement::CallActivity { span, .. }
+        | ProcessElement::Pool { span, .. }
+        | ProcessElement::Group { span, .. }
+        | ProcessElement::Annotation { span, .. }
+        | ProcessElement::DataObject { span, .. } => span,
+    }
+}
+
+#[allow(clippy::too_many_lines)]
+fn describe(element: &ProcessElement) -> String {
+    let mut lines = Vec::new();
+    let mut attributes = None;
+
+    match element {
+        ProcessElement::StartEvent {
+            id,
+            event_type,
+            attributes: element_attributes,
+            ..
+        }
+        | ProcessElement::EndEvent {
+            id,
+            event_type,
+            attributes: element_attributes,
+            ..
+        } => {
+            let kind = if matches!(element, ProcessElement::StartEvent { .. }) {
+                "Start event"
+            } else {
+                "End event"
+            };
+            lines.push(heading(kind, id.as_deref()));
+            if event_type.is_some() {
+                lines.push(format!(
+                    "- trigger: `{}`",
+                    print_event_type(event_type.as_ref()).trim()
+                ));
+            }
+            attributes = Some(element_attributes);
+        }
+        ProcessElement::Task {
+            id,
+            label,
+            task_type,
+            attributes: element_attributes,
+            ..
+        } => {
+            let kind = match task_type {
+                TaskType::Generic => "Task",
+                TaskType::User => "User task",
+                TaskType::Service => "Service task",
+                TaskType::Script => "Script task",
+            };
+            lines.push(heading(kind, Some(id)));
+            if let Some(label) = label {
+                lines.push(format!("- label: {label}"));
+            }
+            attributes = Some(element_attributes);
+        }
+        ProcessElement::Gateway {
+            id,
+            gateway_type,
+            branches,
+            ..
+        } => {
+            let kind = match gateway_type {
+                GatewayType::Exclusive => "Exclusive gateway",
+                GatewayType::Parallel => "Parallel gateway",
+                GatewayType::Inclusive => "Inclusive gateway",
+            };
+            lines.push(heading(kind, id.as_deref()));
+            lines.push(format!("- branches: {}", branches.len()));
+        }
+        ProcessElement::IntermediateEvent {
+            id,
+            event_type,
+            direction,
+            attributes: element_attributes,
+            ..
+        } => {
+            let kind = match direction {
+                EventDirection::Throw => "Throwing intermediate event",
+                EventDirection::Catch => "Catching intermediate event",
+            };
+            lines.push(heading(kind, id.as_deref()));
+            lines.push(format!(
+                "- trigger: `{}`",
+                print_event_type(Some(event_type)).trim()
+            ));
+            attributes = Some(element_attributes);
+        }
+        ProcessElement::Subprocess {
+            id,
+            label,
+            elements,
+            attributes: element_attributes,
+            ..
+        } => {
+            lines.push(heading("Subprocess", Some(id)));
+            if let Some(label) = label {
+                lines.push(format!("- label: {label}"));
+            }
+            lines.push(format!("- elements: {}", elements.len()));
+            attributes = Some(element_attributes);
+        }
+        ProcessElement::EventSubprocess {
+            id,
+            trigger,
+            elements,
+            ..
+        } => {
+            lines.push(heading("Event subprocess", Some(id)));
+            lines.push(format!(
+                "- trigger: `{}`",
+                print_event_type(Some(trigger)).trim()
+            ));
+            lines.push(format!("- elements: {}", elements.len()));
+        }
+        ProcessElement::CallActivity {
+            id,
+            label,
+            called_element,
+            attributes: element_attributes,
+            ..
+        } => {
+            lines.push(heading("Call activity", Some(id)));
+            if let Some(label) = label {
+                lines.push(format!("- label: {label}"));
+            }
+            lines.push(format!("- calls: `{called_element}`"));
+            attributes = Some(element_attributes);
+        }
+        ProcessElement::Pool {
+            name,
+            lanes,
+            elements,
+            ..
+        } => {
+            lines.push(heading("Pool", Some(name)));
+            if !lanes.is_empty() {
+                lines.push(format!("- lanes: {}", lanes.len()));
+            }
+            lines.push(format!("- elements: {}", elements.len()));
+        }
+        ProcessElement::Group {
+            label, elements, ..
+        } => {
+            lines.push(heading("Group", Some(label)));
+            lines.push(format!("- elements: {}", elements.len()));
+        }
+        ProcessElement::Annotation { id, text, .. } => {
+            lines.push(heading("Note", id.as_deref()));
+            lines.push(text.clone());
+        }
+        ProcessElement::DataObject {
+            id, label, store, ..
+        } => {
+            let kind = if *store { "Data store" } else { "Data object" };
+            lines.push(heading(kind, Some(id)));
+            if let Some(label) = label {
+                lines.push(format!("- label: {label}"));
+            }
+        }
+    }
+
+    let mut markdown = lines.join("\n");
+    if let Some(attributes) = attributes {
+        write_attributes(&mut markdown, attributes);
+    }
+    markdown.truncate(markdown.trim_end().len());
+    markdown
+}
+
+fn heading(kind: &str, id: Option<&str>) -> String {
+    id.map_or_else(
+        || format!("**{kind}**\n"),
+        |id| format!("**{kind}** `{id}`\n"),
+    )
+}
+
+fn write_attributes(markdown: &mut String, attributes: &IndexMap<String, AttributeValue>) {
+    for (key, value) in attributes {
+        let _ = write!(markdown, "\n- @{key}: `{}`", print_value(value));
+    }
+}
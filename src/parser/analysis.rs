@@ -0,0 +1,436 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::{
+    lexer::Span,
+    parser::{
+        ast::{ErrorSeverity, Flow, FlowType, GatewayMode, GatewayType, ProcessElement},
+        validator::SyntaxError,
+    },
+};
+
+struct FlowNode<'a> {
+    name: String,
+    label: String,
+    element: &'a ProcessElement,
+}
+
+pub struct FlowGraph<'a> {
+    nodes: Vec<FlowNode<'a>>,
+    successors: Vec<Vec<usize>>,
+    exits: Vec<bool>,
+    // The next-element edges of `successors`, in declaration order
+    implicit: Vec<(usize, usize)>,
+}
+
+impl<'a> FlowGraph<'a> {
+    #[must_use]
+    pub fn build(elements: &'a [ProcessElement], flows: &'a [Flow]) -> Self {
+        let mut graph = Self {
+            nodes: Vec::new(),
+            successors: Vec::new(),
+            exits: Vec::new(),
+            implicit: Vec::new(),
+        };
+
+        let mut sequences = vec![Vec::new()];
+        let mut all_flows: Vec<&Flow> = flows.iter().collect();
+        graph.collect_nodes(elements, 0, &mut sequences, &mut all_flows);
+
+        let mut by_name: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (index, node) in graph.nodes.iter().enumerate() {
+            by_name.entry(node.name.as_str()).or_default().push(index);
+        }
+
+        let mut edges = Vec::new();
+        let mut has_outgoing = vec![false; graph.nodes.len()];
+        let implicit_end = !by_name.contains_key("end");
+        graph.exits = vec![false; graph.nodes.len()];
+
+        for flow in all_flows {
+            if matches!(flow.flow_type, FlowType::Association) {
+                continue;
+            }
+
+            let sources = by_name.get(flow.from.as_str()).cloned().unwrap_or_default();
+            let targets = by_name.get(flow.to.as_str()).cloned().unwrap_or_default();
+
+            for &source in &sources {
+                if !matches!(flow.flow_type, FlowType::Message) {
+                    has_outgoing[source] = true;
+                    graph.exits[source] |= leaves_process(&flow.to, implicit_end);
+                }
+                edges.extend(targets.iter().map(|&target| (source, target)));
+            }
+        }
+
+        for (index, node) in graph.nodes.iter().enumerate() {
+            if let ProcessElement::Gateway { branches, .. } = node.element {
+                for branch in branches {
+                    has_outgoing[index] = true;
+                    graph.exits[index] |= leaves_process(&branch.target, implicit_end);
+                    if let Some(targets) = by_name.get(branch.target.as_str()) {
+                        edges.extend(targets.iter().map(|&target| (index, target)));
+                    }
+                }
+            }
+        }
+
+        // Elements without an explicit outgoing flow continue with the next
+        // element declared in the same block
+        for sequence in &sequences {
+            for (position, &current) in sequence.iter().enumerate() {
+                if has_outgoing[current]
+                    || matches!(
+                        graph.nodes[current].element,
+                        ProcessElement::EndEvent { .. }
+                    )
+                {
+                    continue;
+                }
+
+                let next = sequence[position + 1..].iter().find(|&&next| {
+                    !matches!(graph.nodes[next].element, ProcessElement::StartEvent { .. })
+                });
+                if let Some(&next) = next {
+                    edges.push((current, next));
+                    graph.implicit.push((current, next));
+                }
+            }
+        }
+
+        graph.successors = vec![Vec::new(); graph.nodes.len()];
+        for (source, target) in edges {
+            if !graph.successors[source].contains(&target) {
+                graph.successors[source].push(target);
+            }
+        }
+
+        graph
+    }
+
+    // Elements without an outgoing flow and the element each one continues with
+    #[must_use]
+    pub fn implicit_edges(&self) -> Vec<(&'a ProcessElement, &'a ProcessElement)> {
+        self.implicit
+            .iter()
+            .map(|&(from, to)| (self.nodes[from].element, self.nodes[to].element))
+            .collect()
+    }
+
+    fn collect_nodes(
+        &mut self,
+        elements: &'a [ProcessElement],
+        sequence: usize,
+        sequences: &mut Vec<Vec<usize>>,
+        flows: &mut Vec<&'a Flow>,
+    ) {
+        for element in elements {
+            let (name, label) = match element {
+                ProcessElement::StartEvent { id, .. } => {
+                    let name = id.clone().unwrap_or_else(|| "start".to_string());
+                    (name.clone(), name)
+                }
+                ProcessElement::EndEvent { id, .. } => {
+                    let name = id.clone().unwrap_or_else(|| "end".to_string());
+                    (name.clone(), name)
+                }
+                ProcessElement::Gateway {
+                    id,
+                    gateway_type,
+                    span,
+                    ..
+                } => {
+                    let kind = match gateway_type {
+                        GatewayType::Exclusive => "xor",
+                        GatewayType::Parallel => "and",
+                        GatewayType::Inclusive => "or",
+                    };
+                    id.as_ref().map_or_else(
+                        || (anonymous_name(kind, span), kind.to_string()),
+                        |id| (id.clone(), id.clone()),
+                    )
+                }
+                ProcessElement::IntermediateEvent { id, span, .. } => id.as_ref().map_or_else(
+                    || (anonymous_name("event", span), "event".to_string()),
+                    |id| (id.clone(), id.clone()),
+                ),
+                ProcessElement::Task { id, .. }
+                | ProcessElement::Subprocess { id, .. }
+                | ProcessElement::CallActivity { id, .. } => (id.clone(), id.clone()),
+                ProcessElement::Pool {
+                    lanes,
+                    elements,
+                    flows: pool_flows,
+                    ..
+                } => {
+                    let mut default_sequence = None;
+                    for lane in lanes {
+                        sequences.push(Vec::new());
+                        let lane_sequence = sequences.len() - 1;
+                        self.collect_nodes(&lane.elements, lane_sequence, sequences, flows);
+                        if lane.is_default() {
+                            default_sequence = Some(lane_sequence);
+                        }
+                    }
+                    let pool_sequence = default_sequence.unwrap_or_else(|| {
+                        sequences.push(Vec::new());
+                        sequences.len() - 1
+                    });
+                    self.collect_nodes(elements, pool_sequence, sequences, flows);
+                    flows.extend(pool_flows);
+                    continue;
+                }
+                ProcessElement::Group { elements, .. } => {
+                    self.collect_nodes(elements, sequence, sequences, flows);
+                    continue;
+                }
+                ProcessElement::EventSubprocess { id, .. } => {
+                    // Started by its trigger, so it never joins the implicit sequence
+                    self.nodes.push(FlowNode {
+                        name: id.clone(),
+                        label: id.clone(),
+                        element,
+                    });
+                    continue;
+                }
+                ProcessElement::Annotation { .. } | ProcessElement::DataObject { .. } => continue,
+            };
+
+            sequences[sequence].push(self.nodes.len());
+            self.nodes.push(FlowNode {
+                name,
+                label,
+                element,
+            });
+        }
+    }
+
+    fn reachable_from(&self, roots: impl Iterator<Item = usize>, forward: bool) -> Vec<bool> {
+        let mut adjacency = vec![Vec::new(); self.nodes.len()];
+        for (source, targets) in self.successors.iter().enumerate() {
+            for &target in targets {
+                if forward {
+                    adjacency[source].push(target);
+                } else {
+                    adjacency[target].push(source);
+                }
+            }
+        }
+
+        let mut visited = vec![false; self.nodes.len()];
+        let mut queue: VecDeque<usize> = roots.collect();
+        for &root in &queue {
+            visited[root] = true;
+        }
+
+        while let Some(node) = queue.pop_front() {
+            for &next in &adjacency[node] {
+                if !visited[next] {
+                    visited[next] = true;
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        visited
+    }
+
+    fn start_nodes(&self) -> impl Iterator<Item = usize> + '_ {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| {
+                matches!(
+                    node.element,
+                    ProcessElement::StartEvent { .. } | ProcessElement::EventSubprocess { .. }
+                )
+            })
+            .map(|(index, _)| index)
+    }
+
+    fn sink_nodes(&self) -> impl Iterator<Item = usize> + '_ {
+        self.successors
+            .iter()
+            .enumerate()
+            .filter(|(_, targets)| targets.is_empty())
+            .map(|(index, _)| index)
+    }
+
+    fn end_nodes(&self) -> impl Iterator<Item = usize> + '_ {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter(|(index, node)| {
+                self.exits[*index]
+                    || matches!(
+                        node.element,
+                        ProcessElement::EndEvent { .. } | ProcessElement::EventSubprocess { .. }
+                    )
+            })
+            .map(|(index, _)| index)
+    }
+}
+
+fn anonymous_name(kind: &str, span: &Span) -> String {
+    format!("<{kind}@{}:{}>", span.line, span.column)
+}
+
+pub(crate) const fn element_span(element: &ProcessElement) -> &Span {
+    match element {
+        ProcessElement::StartEvent { span, .. }
+        | ProcessElement::EndEvent { span, .. }
+        | ProcessElement::Task { span, .. }
+        | ProcessElement::Gateway { span, .. }
+        | ProcessElement::IntermediateEvent { span, .. }
+        | ProcessElement::Subprocess { span, .. }
+        | ProcessElement::EventSubprocess { span, .. }
+        | ProcessElement::CallActivity { span, .. }
+        | ProcessElement::Pool { span, .. }
+        | ProcessElement::Group { span, .. }
+        | ProcessElement::Annotation { span, .. }
+        | ProcessElement::DataObject { span, .. } => span,
+    }
+}
+
+#[must_use]
+pub fn check_reachability(elements: &[ProcessElement], flows: &[Flow]) -> Vec<SyntaxError> {
+    let graph = FlowGraph::build(elements, flows);
+    let mut errors = Vec::new();
+
+    if graph.start_nodes().next().is_some() {
+        let reachable = graph.reachable_from(graph.start_nodes(), true);
+
+        for (node, _) in graph
+            .nodes
+            .iter()
+            .zip(&reachable)
+            .filter(|(_, reached)| !**reached)
+        {
+            errors.push(SyntaxError {
+                message: format!("Element '{}' is unreachable", node.label),
+                span: element_span(node.element).clone(),
+                severity: ErrorSeverity::Warning,
+                lint: "unreachable",
+                element: Some(node.name.clone()),
+                first_definition: None,
+            });
+        }
+    }
+
+    for node in &graph.nodes {
+        if let ProcessElement::Subprocess {
+            elements, flows, ..
+        }
+        | ProcessElement::EventSubprocess {
+            elements, flows, ..
+        } = node.element
+        {
+            errors.extend(check_reachability(elements, flows));
+        }
+    }
+
+    errors
+}
+
+#[must_use]
+pub fn check_termination(elements: &[ProcessElement], flows: &[Flow]) -> Vec<SyntaxError> {
+    let graph = FlowGraph::build(elements, flows);
+    let mut errors = Vec::new();
+
+    if graph.start_nodes().next().is_some() {
+        let live = graph.reachable_from(graph.start_nodes(), true);
+        // Without an end event, running out of elements ends the process, so only a
+        // cycle with no way out is reported
+        let terminating = if graph.end_nodes().next().is_some() {
+            graph.reachable_from(graph.end_nodes(), false)
+        } else {
+            graph.reachable_from(graph.sink_nodes(), false)
+        };
+
+        for (index, node) in graph.nodes.iter().enumerate() {
+            if live[index] && !terminating[index] {
+                errors.push(SyntaxError {
+                    message: format!("Element '{}' cannot reach an end event", node.label),
+                    span: element_span(node.element).clone(),
+                    severity: ErrorSeverity::Warning,
+                    lint: "dead-end",
+                    element: Some(node.name.clone()),
+                    first_definition: None,
+                });
+            }
+        }
+    }
+
+    for node in &graph.nodes {
+        if let ProcessElement::Subprocess {
+            elements, flows, ..
+        }
+        | ProcessElement::EventSubprocess {
+            elements, flows, ..
+        } = node.element
+        {
+            errors.extend(check_termination(elements, flows));
+        }
+    }
+
+    errors
+}
+
+// A split gateway should have one way in and a join one way out, anything else is `mixed`
+#[must_use]
+pub fn check_gateway_modes(elements: &[ProcessElement], flows: &[Flow]) -> Vec<SyntaxError> {
+    let graph = FlowGraph::build(elements, flows);
+    let mut errors = Vec::new();
+
+    let mut incoming = vec![0usize; graph.nodes.len()];
+    for &target in graph.successors.iter().flatten() {
+        incoming[target] += 1;
+    }
+
+    for (index, node) in graph.nodes.iter().enumerate() {
+        let ProcessElement::Gateway { mode, span, .. } = node.element else {
+            continue;
+        };
+
+        let outgoing = graph.successors[index].len() + usize::from(graph.exits[index]);
+        let message = match mode {
+            GatewayMode::Split if incoming[index] > 1 => format!(
+                "Split gateway '{}' has {} incoming flows, mark it `join` or `mixed`",
+                node.label, incoming[index]
+            ),
+            GatewayMode::Join if outgoing > 1 => format!(
+                "Join gateway '{}' has {outgoing} outgoing flows, mark it `split` or `mixed`",
+                node.label
+            ),
+            _ => continue,
+        };
+        errors.push(SyntaxError {
+            message,
+            span: span.clone(),
+            severity: ErrorSeverity::Warning,
+            lint: "gateway-mode",
+            element: None,
+            first_definition: None,
+        });
+    }
+
+    for node in &graph.nodes {
+        if let ProcessElement::Subprocess {
+            elements, flows, ..
+        }
+        | ProcessElement::EventSubprocess {
+            elements, flows, ..
+        } = node.element
+        {
+            errors.extend(check_gateway_modes(elements, flows));
+        }
+    }
+
+    errors
+}
+
+// Control passed to the implicit end or to an element of an imported file
+// does not come back
+fn leaves_process(target: &str, implicit_end: bool) -> bool {
+    (implicit_end && target == "end") || target.contains("::")
+}
@@ -1,15 +1,28 @@
-use std::collections::HashMap;
+use std::{collections::BTreeSet, fmt};
+
+use indexmap::IndexMap;
+
+use serde::{Deserialize, Serialize};
 
 use crate::lexer::Span;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AstDocument {
     pub imports: Vec<ImportDeclaration>,
     pub processes: Vec<ProcessDeclaration>,
     pub errors: Vec<ParseError>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub messages: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub signals: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub error_codes: Vec<String>,
+    // Ids error recovery invented, the error that caused them is reported on its own
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub synthetic_ids: BTreeSet<String>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ImportDeclaration {
     pub path: String,
     pub alias: Option<String>,
@@ -17,80 +30,143 @@ pub struct ImportDeclaration {
     pub span: Span,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ProcessDeclaration {
     pub name: String,
-    pub attributes: HashMap<String, AttributeValue>,
+    pub attributes: IndexMap<String, AttributeValue>,
     pub elements: Vec<ProcessElement>,
     pub flows: Vec<Flow>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variables: Vec<VariableDeclaration>,
+    pub span: Span,
+}
+
+// `var amount: number`, names that conditions in the process may read
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VariableDeclaration {
+    pub name: String,
+    pub variable_type: VariableType,
     pub span: Span,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VariableType {
+    Number,
+    String,
+    Boolean,
+}
+
+impl VariableType {
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "number" => Some(Self::Number),
+            "string" => Some(Self::String),
+            "boolean" => Some(Self::Boolean),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ProcessElement {
     StartEvent {
         id: Option<String>,
         event_type: Option<EventType>,
-        attributes: HashMap<String, AttributeValue>,
+        attributes: IndexMap<String, AttributeValue>,
         span: Span,
     },
     EndEvent {
         id: Option<String>,
         event_type: Option<EventType>,
-        attributes: HashMap<String, AttributeValue>,
+        attributes: IndexMap<String, AttributeValue>,
         span: Span,
     },
     Task {
         id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        label: Option<String>,
         task_type: TaskType,
-        attributes: HashMap<String, AttributeValue>,
+        attributes: IndexMap<String, AttributeValue>,
         span: Span,
     },
     Gateway {
         id: Option<String>,
         gateway_type: GatewayType,
+        #[serde(default)]
+        mode: GatewayMode,
         branches: Vec<GatewayBranch>,
+        #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+        attributes: IndexMap<String, AttributeValue>,
         span: Span,
     },
     IntermediateEvent {
         id: Option<String>,
         event_type: EventType,
+        direction: EventDirection,
         payload: Option<String>,
-        attributes: HashMap<String, AttributeValue>,
+        attributes: IndexMap<String, AttributeValue>,
         span: Span,
     },
     Subprocess {
         id: String,
-        elements: Vec<ProcessElement>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        label: Option<String>,
+        elements: Vec<Self>,
+        flows: Vec<Flow>,
+        attributes: IndexMap<String, AttributeValue>,
+        span: Span,
+    },
+    EventSubprocess {
+        id: String,
+        trigger: EventType,
+        elements: Vec<Self>,
         flows: Vec<Flow>,
-        attributes: HashMap<String, AttributeValue>,
+        #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+        attributes: IndexMap<String, AttributeValue>,
         span: Span,
     },
     CallActivity {
         id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        label: Option<String>,
         called_element: String,
-        attributes: HashMap<String, AttributeValue>,
+        attributes: IndexMap<String, AttributeValue>,
         span: Span,
     },
     Pool {
         name: String,
         lanes: Vec<Lane>,
-        elements: Vec<ProcessElement>,
+        elements: Vec<Self>,
         flows: Vec<Flow>,
+        #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+        attributes: IndexMap<String, AttributeValue>,
         span: Span,
     },
     Group {
         label: String,
-        elements: Vec<ProcessElement>,
+        elements: Vec<Self>,
         span: Span,
     },
     Annotation {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
         text: String,
         span: Span,
     },
+    DataObject {
+        id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        label: Option<String>,
+        // A data store reference rather than a data object
+        #[serde(default)]
+        store: bool,
+        span: Span,
+    },
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TaskType {
     Generic,
     User,
@@ -98,46 +174,117 @@ pub enum TaskType {
     Script,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum GatewayType {
     Exclusive,
     Parallel,
+    Inclusive,
+}
+
+// Whether a gateway forks the flow, merges it, or does both
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum GatewayMode {
+    #[default]
+    Split,
+    Join,
+    Mixed,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct GatewayBranch {
-    pub condition: Option<String>,
+    pub condition: Option<ConditionExpr>,
     pub target: String,
     pub is_default: bool,
     pub span: Span,
+    // The target name as written
+    pub target_span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConditionExpr {
+    Identifier(String),
+    Number(String),
+    String(String),
+    Boolean(bool),
+    Not(Box<Self>),
+    Compare {
+        left: Box<Self>,
+        op: CompareOp,
+        right: Box<Self>,
+    },
+    And(Box<Self>, Box<Self>),
+    Or(Box<Self>, Box<Self>),
+    // Imported expressions that are not in the condition syntax are kept verbatim
+    Raw(String),
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum EventType {
     Message(String),
     Timer(String),
     Error(String),
     Signal(String),
+    Escalation(String),
+    Compensation(String),
+    Conditional(String),
     Terminate,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum EventDirection {
+    Throw,
+    #[default]
+    Catch,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Lane {
     pub name: String,
     pub elements: Vec<ProcessElement>,
     pub span: Span,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+impl Lane {
+    // Elements written directly in a pool that also has lanes belong to the lane of this
+    // name, it's implied unless the pool declares one
+    pub const DEFAULT: &'static str = "default";
+
+    #[must_use]
+    pub fn is_default(&self) -> bool {
+        self.name == Self::DEFAULT
+    }
+}
+
+// Whether the elements written directly in a pool need the implied default lane, a pool
+// without lanes holds them itself
+#[must_use]
+pub fn needs_default_lane(lanes: &[Lane], elements: &[ProcessElement]) -> bool {
+    !lanes.is_empty() && !elements.is_empty() && !lanes.iter().any(Lane::is_default)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Flow {
     pub from: String,
     pub to: String,
     pub flow_type: FlowType,
-    pub condition: Option<String>,
+    pub condition: Option<ConditionExpr>,
     pub span: Span,
+    // The source and target names as written
+    pub from_span: Span,
+    pub to_span: Span,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum FlowType {
     Sequence,
     Message,
@@ -145,25 +292,44 @@ pub enum FlowType {
     Association,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum AttributeValue {
+    // Durations keep a `{"duration": ...}` tag so they stay distinct from strings
+    #[serde(rename = "duration")]
+    Duration(String),
+    #[serde(untagged)]
     String(String),
+    #[serde(untagged)]
     Number(f64),
+    #[serde(untagged)]
     Boolean(bool),
-    Duration(String),
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ParseError {
     pub message: String,
     pub span: Span,
     pub severity: ErrorSeverity,
+    // The lint that can silence or promote this error, `syntax` when it has none
+    #[serde(skip_deserializing, default = "syntax_lint")]
+    pub lint: &'static str,
+    // The element the error is about, when it names one
+    #[serde(default)]
+    pub element: Option<String>,
+    // Where a duplicated id was declared first
+    #[serde(default)]
+    pub first_definition: Option<Span>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) const fn syntax_lint() -> &'static str {
+    "syntax"
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ErrorSeverity {
     Error,
     Warning,
+    Info,
 }
 
 impl AstDocument {
@@ -173,6 +339,10 @@ impl AstDocument {
             imports: Vec::new(),
             processes: Vec::new(),
             errors: Vec::new(),
+            messages: Vec::new(),
+            signals: Vec::new(),
+            error_codes: Vec::new(),
+            synthetic_ids: BTreeSet::new(),
         }
     }
 
@@ -188,6 +358,9 @@ impl AstDocument {
             message,
             span,
             severity: ErrorSeverity::Error,
+            lint: "syntax",
+            element: None,
+            first_definition: None,
         });
     }
 
@@ -196,12 +369,183 @@ impl AstDocument {
             message,
             span,
             severity: ErrorSeverity::Warning,
+            lint: "syntax",
+            element: None,
+            first_definition: None,
         });
     }
 }
 
+impl AttributeValue {
+    #[must_use]
+    pub fn as_millis(&self) -> Option<u64> {
+        match self {
+            Self::Duration(raw) => parse_duration_millis(raw),
+            Self::String(_) | Self::Number(_) | Self::Boolean(_) => None,
+        }
+    }
+}
+
+// Accepts `ms`, `s`, `m` and `h` components in descending order, e.g. `1h30m` or `2s500ms`.
+// Amounts may be fractional, `1.5h`, and are rounded to the nearest millisecond
+#[must_use]
+pub fn parse_duration_millis(raw: &str) -> Option<u64> {
+    const UNITS: [(&str, u64); 5] = [
+        ("d", 86_400_000),
+        ("h", 3_600_000),
+        ("m", 60_000),
+        ("s", 1_000),
+        ("ms", 1),
+    ];
+
+    let mut rest = raw;
+    let mut total: u64 = 0;
+    let mut next_unit = 0;
+
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(rest.len());
+        let amount = &rest[..digits];
+        rest = &rest[digits..];
+
+        let suffix = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let position = UNITS[next_unit..]
+            .iter()
+            .position(|(unit, _)| *unit == &rest[..suffix])?;
+        let factor = UNITS[next_unit + position].1;
+        rest = &rest[suffix..];
+
+        total = total.checked_add(scale_amount(amount, factor)?)?;
+        next_unit += position + 1;
+    }
+
+    (next_unit > 0).then_some(total)
+}
+
+fn scale_amount(amount: &str, factor: u64) -> Option<u64> {
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, "0"));
+    if fraction.is_empty() || !fraction.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+
+    // Digits past the ninth stay below a millisecond for every unit
+    let fraction = &fraction[..fraction.len().min(9)];
+    let scale = 10_u64.pow(u32::try_from(fraction.len()).ok()?);
+    let fraction: u64 = fraction.parse().ok()?;
+    let part = (fraction * factor * 2 + scale) / (2 * scale);
+
+    whole
+        .parse::<u64>()
+        .ok()?
+        .checked_mul(factor)?
+        .checked_add(part)
+}
+
 impl Default for AstDocument {
     fn default() -> Self {
         Self::new()
     }
 }
+
+impl ConditionExpr {
+    const fn precedence(&self) -> u8 {
+        match self {
+            Self::Or(..) => 1,
+            Self::And(..) => 2,
+            Self::Not(_) => 3,
+            Self::Compare { .. } => 4,
+            Self::Identifier(_)
+            | Self::Number(_)
+            | Self::String(_)
+            | Self::Boolean(_)
+            | Self::Raw(_) => 5,
+        }
+    }
+
+    fn fmt_operand(&self, f: &mut fmt::Formatter<'_>, min_precedence: u8) -> fmt::Result {
+        if self.precedence() < min_precedence {
+            write!(f, "({self})")
+        } else {
+            write!(f, "{self}")
+        }
+    }
+}
+
+impl ConditionExpr {
+    // Every name the expression reads, paths like `order.total` are returned whole
+    #[must_use]
+    pub fn identifiers(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        self.collect_identifiers(&mut names);
+        names
+    }
+
+    fn collect_identifiers<'a>(&'a self, names: &mut Vec<&'a str>) {
+        match self {
+            Self::Identifier(name) => names.push(name),
+            Self::Not(operand) => operand.collect_identifiers(names),
+            Self::Compare { left, right, .. } | Self::And(left, right) | Self::Or(left, right) => {
+                left.collect_identifiers(names);
+                right.collect_identifiers(names);
+            }
+            Self::Number(_) | Self::String(_) | Self::Boolean(_) | Self::Raw(_) => {}
+        }
+    }
+}
+
+impl fmt::Display for ConditionExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Identifier(name) | Self::Number(name) | Self::Raw(name) => write!(f, "{name}"),
+            Self::String(text) => {
+                write!(f, "\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+            }
+            Self::Boolean(value) => write!(f, "{value}"),
+            Self::Not(operand) => {
+                write!(f, "!")?;
+                operand.fmt_operand(f, 3)
+            }
+            Self::Compare { left, op, right } => {
+                left.fmt_operand(f, 5)?;
+                write!(f, " {op} ")?;
+                right.fmt_operand(f, 5)
+            }
+            Self::And(left, right) => {
+                left.fmt_operand(f, 2)?;
+                write!(f, " && ")?;
+                right.fmt_operand(f, 3)
+            }
+            Self::Or(left, right) => {
+                left.fmt_operand(f, 1)?;
+                write!(f, " || ")?;
+                right.fmt_operand(f, 2)
+            }
+        }
+    }
+}
+
+impl fmt::Display for CompareOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Eq => write!(f, "=="),
+            Self::Ne => write!(f, "!="),
+            Self::Lt => write!(f, "<"),
+            Self::Le => write!(f, "<="),
+            Self::Gt => write!(f, ">"),
+            Self::Ge => write!(f, ">="),
+        }
+    }
+}
+
+impl fmt::Display for VariableType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Number => write!(f, "number"),
+            Self::String => write!(f, "string"),
+            Self::Boolean => write!(f, "boolean"),
+        }
+    }
+}
@@ -1,10 +1,10 @@
-use std::collections::HashMap;
+use indexmap::IndexMap;
 
 use crate::{
     lexer::Span,
     parser::ast::{
-        AttributeValue, EventType, Flow, FlowType, GatewayBranch, GatewayType, ProcessDeclaration,
-        ProcessElement, TaskType,
+        AttributeValue, ConditionExpr, EventType, Flow, FlowType, GatewayBranch, GatewayMode,
+        GatewayType, ProcessDeclaration, ProcessElement, TaskType,
     },
 };
 
@@ -23,9 +23,10 @@ impl AstBuilder {
     pub fn start_process(&mut self, name: String, span: Span) -> &mut Self {
         self.current_process = Some(ProcessDeclaration {
             name,
-            attributes: HashMap::new(),
+            attributes: IndexMap::new(),
             elements: Vec::new(),
             flows: Vec::new(),
+            variables: Vec::new(),
             span,
         });
 
@@ -65,7 +66,7 @@ impl AstBuilder {
         &self,
         id: Option<String>,
         event_type: Option<EventType>,
-        attributes: HashMap<String, AttributeValue>,
+        attributes: IndexMap<String, AttributeValue>,
         span: Span,
     ) -> ProcessElement {
         ProcessElement::StartEvent {
@@ -81,11 +82,12 @@ impl AstBuilder {
         &self,
         id: String,
         task_type: TaskType,
-        attributes: HashMap<String, AttributeValue>,
+        attributes: IndexMap<String, AttributeValue>,
         span: Span,
     ) -> ProcessElement {
         ProcessElement::Task {
             id,
+            label: None,
             task_type,
             attributes,
             span,
@@ -93,7 +95,7 @@ impl AstBuilder {
     }
 
     #[must_use]
-    pub const fn create_gateway(
+    pub fn create_gateway(
         &self,
         id: Option<String>,
         gateway_type: GatewayType,
@@ -103,18 +105,20 @@ impl AstBuilder {
         ProcessElement::Gateway {
             id,
             gateway_type,
+            mode: GatewayMode::Split,
             branches,
+            attributes: IndexMap::new(),
             span,
         }
     }
 
     #[must_use]
-    pub const fn create_flow(
+    pub fn create_flow(
         &self,
         from: String,
         to: String,
         flow_type: FlowType,
-        condition: Option<String>,
+        condition: Option<ConditionExpr>,
         span: Span,
     ) -> Flow {
         Flow {
@@ -122,7 +126,9 @@ impl AstBuilder {
             to,
             flow_type,
             condition,
-            span,
+            span: span.clone(),
+            from_span: span.clone(),
+            to_span: span,
         }
     }
 }
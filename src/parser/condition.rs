@@ -0,0 +1,233 @@
+use crate::{
+    lexer::{Lexer, Token, TokenKind},
+    parser::{
+        DEFAULT_MAX_DEPTH,
+        ast::{CompareOp, ConditionExpr},
+        unescape_string_literal,
+    },
+};
+
+// Parentheses and `!` deeper than `max_depth` are an error rather than a stack overflow
+pub fn parse_condition(tokens: &[Token], max_depth: usize) -> Result<ConditionExpr, String> {
+    let tokens: Vec<&Token> = tokens
+        .iter()
+        .filter(|token| {
+            !matches!(
+                token.kind,
+                TokenKind::Newline
+                    | TokenKind::CarriageReturnNewline
+                    | TokenKind::LineComment
+                    | TokenKind::BlockComment
+                    | TokenKind::Eof
+            )
+        })
+        .collect();
+
+    let mut parser = ConditionParser {
+        tokens: &tokens,
+        position: 0,
+        depth: 0,
+        max_depth,
+    };
+    let expression = parser.parse_or()?;
+
+    if let Some(token) = parser.peek() {
+        return Err(format!("unexpected '{}'", token.text));
+    }
+
+    Ok(expression)
+}
+
+pub fn parse_condition_str(text: &str) -> Result<ConditionExpr, String> {
+    let tokens = Lexer::new(text, "<condition>").tokenize();
+    parse_condition(&tokens, DEFAULT_MAX_DEPTH)
+}
+
+struct ConditionParser<'a> {
+    tokens: &'a [&'a Token],
+    position: usize,
+    depth: usize,
+    max_depth: usize,
+}
+
+impl<'a> ConditionParser<'a> {
+    fn parse_or(&mut self) -> Result<ConditionExpr, String> {
+        let depth = self.depth;
+        let mut left = self.parse_and()?;
+        while self.eat_operator("||") {
+            self.chain()?;
+            let right = self.parse_and()?;
+            left = ConditionExpr::Or(Box::new(left), Box::new(right));
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<ConditionExpr, String> {
+        let depth = self.depth;
+        let mut left = self.parse_unary()?;
+        while self.eat_operator("&&") {
+            self.chain()?;
+            let right = self.parse_unary()?;
+            left = ConditionExpr::And(Box::new(left), Box::new(right));
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+
+    // Every operator in a chain nests the terms before it one level deeper, so long
+    // chains count against the same limit as parentheses
+    fn chain(&mut self) -> Result<(), String> {
+        if self.depth >= self.max_depth {
+            return Err(format!("more than {} operators in a row", self.max_depth));
+        }
+
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn parse_unary(&mut self) -> Result<ConditionExpr, String> {
+        if self.eat_operator("!") {
+            let operand = self.nested(Self::parse_unary)?;
+            return Ok(ConditionExpr::Not(Box::new(operand)));
+        }
+
+        self.parse_comparison()
+    }
+
+    fn parse_comparison(&mut self) -> Result<ConditionExpr, String> {
+        let left = self.parse_operand()?;
+
+        let op = match self.peek_operator() {
+            Some("==") => CompareOp::Eq,
+            Some("!=") => CompareOp::Ne,
+            Some("<") => CompareOp::Lt,
+            Some("<=") => CompareOp::Le,
+            Some(">") => CompareOp::Gt,
+            Some(">=") => CompareOp::Ge,
+            _ => return Ok(left),
+        };
+        self.position += 1;
+
+        let right = self.parse_operand()?;
+        Ok(ConditionExpr::Compare {
+            left: Box::new(left),
+            op,
+            right: Box::new(right),
+        })
+    }
+
+    fn parse_operand(&mut self) -> Result<ConditionExpr, String> {
+        let Some(token) = self.peek() else {
+            return Err("expected a value".to_string());
+        };
+
+        match token.kind {
+            TokenKind::LeftParen => {
+                self.position += 1;
+                let expression = self.nested(Self::parse_or)?;
+                if self.peek().map(|token| &token.kind) != Some(&TokenKind::RightParen) {
+                    return Err("expected ')'".to_string());
+                }
+                self.position += 1;
+                Ok(expression)
+            }
+            TokenKind::StringLiteral => {
+                self.position += 1;
+                unescape_string_literal(&token.text)
+                    .map(ConditionExpr::String)
+                    .map_err(|escape| format!("invalid escape sequence '{escape}'"))
+            }
+            TokenKind::NumberLiteral => {
+                self.position += 1;
+                Ok(ConditionExpr::Number(token.text.clone()))
+            }
+            TokenKind::Unknown if token.text == "-" => {
+                self.position += 1;
+                match self.peek() {
+                    Some(number) if number.kind == TokenKind::NumberLiteral => {
+                        self.position += 1;
+                        Ok(ConditionExpr::Number(format!("-{}", number.text)))
+                    }
+                    _ => Err("expected a number after '-'".to_string()),
+                }
+            }
+            _ if is_word(&token.text) => {
+                self.position += 1;
+                match token.text.as_str() {
+                    "true" => Ok(ConditionExpr::Boolean(true)),
+                    "false" => Ok(ConditionExpr::Boolean(false)),
+                    _ => self.parse_path(token.text.clone()),
+                }
+            }
+            _ => Err(format!("unexpected '{}'", token.text)),
+        }
+    }
+
+    fn nested(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<ConditionExpr, String>,
+    ) -> Result<ConditionExpr, String> {
+        if self.depth >= self.max_depth {
+            return Err(format!("nested deeper than {} levels", self.max_depth));
+        }
+
+        self.depth += 1;
+        let expression = parse(self);
+        self.depth -= 1;
+        expression
+    }
+
+    fn parse_path(&mut self, mut path: String) -> Result<ConditionExpr, String> {
+        while self.peek().is_some_and(|token| token.text == ".") {
+            self.position += 1;
+            match self.peek() {
+                Some(token) if is_word(&token.text) => {
+                    path.push('.');
+                    path.push_str(&token.text);
+                    self.position += 1;
+                }
+                _ => return Err(format!("expected a name after '{path}.'")),
+            }
+        }
+
+        Ok(ConditionExpr::Identifier(path))
+    }
+
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.position).copied()
+    }
+
+    fn peek_operator(&self) -> Option<&'static str> {
+        match self.peek()?.kind {
+            TokenKind::EqualEqual => Some("=="),
+            TokenKind::NotEqual => Some("!="),
+            TokenKind::LessEqual => Some("<="),
+            TokenKind::GreaterEqual => Some(">="),
+            TokenKind::Less => Some("<"),
+            TokenKind::Greater => Some(">"),
+            TokenKind::AndAnd => Some("&&"),
+            TokenKind::OrOr => Some("||"),
+            TokenKind::Bang => Some("!"),
+            _ => None,
+        }
+    }
+
+    fn eat_operator(&mut self, operator: &str) -> bool {
+        if self.peek_operator() != Some(operator) {
+            return false;
+        }
+
+        self.position += 1;
+        true
+    }
+}
+
+fn is_word(text: &str) -> bool {
+    text.chars()
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && text
+            .chars()
+            .all(|character| character.is_ascii_alphanumeric() || character == '_')
+}
@@ -0,0 +1,75 @@
+use crate::parser::ast::{AstDocument, EventType, ProcessElement};
+
+// Gathers the distinct message, signal and error names used by events so they can be
+// declared once per document, in order of first use
+pub fn collect_definitions(document: &mut AstDocument) {
+    let mut definitions = Definitions::default();
+    for process in &document.processes {
+        definitions.collect(&process.elements);
+    }
+
+    document.messages = definitions.messages;
+    document.signals = definitions.signals;
+    document.error_codes = definitions.error_codes;
+}
+
+#[derive(Default)]
+struct Definitions {
+    messages: Vec<String>,
+    signals: Vec<String>,
+    error_codes: Vec<String>,
+}
+
+impl Definitions {
+    fn collect(&mut self, elements: &[ProcessElement]) {
+        for element in elements {
+            match element {
+                ProcessElement::StartEvent { event_type, .. }
+                | ProcessElement::EndEvent { event_type, .. } => {
+                    if let Some(event_type) = event_type {
+                        self.add(event_type);
+                    }
+                }
+                ProcessElement::IntermediateEvent { event_type, .. } => self.add(event_type),
+                ProcessElement::EventSubprocess {
+                    trigger, elements, ..
+                } => {
+                    self.add(trigger);
+                    self.collect(elements);
+                }
+                ProcessElement::Subprocess { elements, .. }
+                | ProcessElement::Group { elements, .. } => self.collect(elements),
+                ProcessElement::Pool {
+                    lanes, elements, ..
+                } => {
+                    for lane in lanes {
+                        self.collect(&lane.elements);
+                    }
+                    self.collect(elements);
+                }
+                ProcessElement::Task { .. }
+                | ProcessElement::Gateway { .. }
+                | ProcessElement::CallActivity { .. }
+                | ProcessElement::Annotation { .. }
+                | ProcessElement::DataObject { .. } => {}
+            }
+        }
+    }
+
+    fn add(&mut self, event_type: &EventType) {
+        let (names, name) = match event_type {
+            EventType::Message(name) => (&mut self.messages, name),
+            EventType::Signal(name) => (&mut self.signals, name),
+            EventType::Error(name) => (&mut self.error_codes, name),
+            EventType::Timer(_)
+            | EventType::Escalation(_)
+            | EventType::Compensation(_)
+            | EventType::Conditional(_)
+            | EventType::Terminate => return,
+        };
+
+        if !name.is_empty() && !names.contains(name) {
+            names.push(name.clone());
+        }
+    }
+}
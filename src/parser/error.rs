@@ -2,35 +2,40 @@ use thiserror::Error;
 
 use crate::lexer::Span;
 
+// Messages leave out the location, every error carries its span and the
+// diagnostic formatter decides how to render it
 #[derive(Error, Debug, Clone, PartialEq, Eq)]
 pub enum ParserError {
-    #[error("Unexpected token '{found}' at {span}, expected {expected}")]
+    #[error("Unexpected token '{found}', expected {expected}")]
     UnexpectedToken {
         found: String,
         expected: String,
         span: Span,
     },
 
-    #[error("Missing closing brace for block starting at {start_span}")]
+    #[error("Missing closing brace for block starting at line {}", .start_span.line)]
     UnclosedBlock {
         start_span: Span,
         current_span: Span,
     },
 
-    #[error("Invalid attribute value '{value}' at {span}")]
+    #[error("Invalid attribute value '{value}'")]
     InvalidAttributeValue { value: String, span: Span },
 
-    #[error("Duplicate element ID '{id}' at {span}, first defined at {first_span}")]
+    #[error("Invalid escape sequence '{escape}'")]
+    InvalidEscape { escape: String, span: Span },
+
+    #[error("Duplicate element ID '{id}', first defined at line {}", .first_span.line)]
     DuplicateId {
         id: String,
         span: Span,
         first_span: Span,
     },
 
-    #[error("Undefined reference '{reference}' at {span}")]
+    #[error("Undefined reference '{reference}'")]
     UndefinedReference { reference: String, span: Span },
 
-    #[error("Invalid flow: {message} at {span}")]
+    #[error("Invalid flow: {message}")]
     InvalidFlow { message: String, span: Span },
 
     #[error("Unexpected end of input, expected {expected}")]
@@ -43,6 +48,7 @@ impl ParserError {
         match self {
             Self::UnclosedBlock { current_span, .. } => current_span,
             Self::InvalidAttributeValue { span, .. }
+            | Self::InvalidEscape { span, .. }
             | Self::UnexpectedToken { span, .. }
             | Self::DuplicateId { span, .. }
             | Self::UndefinedReference { span, .. }
@@ -0,0 +1,186 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::parser::ast::{AstDocument, Flow, GatewayBranch, ProcessElement};
+
+#[derive(Default)]
+struct IdAssigner {
+    taken: HashSet<String>,
+    counters: HashMap<&'static str, usize>,
+}
+
+// Names in a scope that refer to its anonymous start and end events
+#[derive(Default)]
+struct ScopeAliases {
+    start: Option<String>,
+    end: Option<String>,
+}
+
+// Gives every unnamed start, end, gateway and intermediate event a deterministic id
+// (`StartEvent_1`, `Gateway_3`, ...) and points `start`/`end` references at them
+pub fn assign_ids(document: &mut AstDocument) {
+    let mut assigner = IdAssigner::default();
+    for process in &document.processes {
+        collect_ids(&process.elements, &mut assigner.taken);
+    }
+
+    for process in &mut document.processes {
+        assigner.assign_scope(&mut process.elements, &mut process.flows);
+    }
+}
+
+fn collect_ids(elements: &[ProcessElement], taken: &mut HashSet<String>) {
+    for element in elements {
+        match element {
+            ProcessElement::StartEvent { id, .. }
+            | ProcessElement::EndEvent { id, .. }
+            | ProcessElement::Gateway { id, .. }
+            | ProcessElement::IntermediateEvent { id, .. }
+            | ProcessElement::Annotation { id, .. } => {
+                taken.extend(id.clone());
+            }
+            ProcessElement::Task { id, .. }
+            | ProcessElement::CallActivity { id, .. }
+            | ProcessElement::DataObject { id, .. } => {
+                taken.insert(id.clone());
+            }
+            ProcessElement::Subprocess { id, elements, .. }
+            | ProcessElement::EventSubprocess { id, elements, .. } => {
+                taken.insert(id.clone());
+                collect_ids(elements, taken);
+            }
+            ProcessElement::Pool {
+                name,
+                lanes,
+                elements,
+                ..
+            } => {
+                taken.insert(name.clone());
+                for lane in lanes {
+                    collect_ids(&lane.elements, taken);
+                }
+                collect_ids(elements, taken);
+            }
+            ProcessElement::Group { elements, .. } => collect_ids(elements, taken),
+        }
+    }
+}
+
+impl IdAssigner {
+    fn assign_scope(&mut self, elements: &mut [ProcessElement], flows: &mut [Flow]) {
+        let mut aliases = ScopeAliases::default();
+        self.assign_elements(elements, &mut aliases);
+
+        rewrite_flows(flows, &aliases);
+        rewrite_scope(elements, &aliases);
+    }
+
+    fn assign_elements(&mut self, elements: &mut [ProcessElement], aliases: &mut ScopeAliases) {
+        for element in elements {
+            match element {
+                ProcessElement::StartEvent { id, .. } => {
+                    if id.is_none() {
+                        let name = self.next_id("StartEvent");
+                        aliases.start.get_or_insert_with(|| name.clone());
+                        *id = Some(name);
+                    }
+                }
+                ProcessElement::EndEvent { id, .. } => {
+                    if id.is_none() {
+                        let name = self.next_id("EndEvent");
+                        aliases.end.get_or_insert_with(|| name.clone());
+                        *id = Some(name);
+                    }
+                }
+                ProcessElement::Gateway { id, .. } => {
+                    if id.is_none() {
+                        *id = Some(self.next_id("Gateway"));
+                    }
+                }
+                ProcessElement::IntermediateEvent { id, .. } => {
+                    if id.is_none() {
+                        *id = Some(self.next_id("IntermediateEvent"));
+                    }
+                }
+                ProcessElement::Subprocess {
+                    elements, flows, ..
+                }
+                | ProcessElement::EventSubprocess {
+                    elements, flows, ..
+                } => self.assign_scope(elements, flows),
+                ProcessElement::Pool {
+                    lanes, elements, ..
+                } => {
+                    for lane in lanes {
+                        self.assign_elements(&mut lane.elements, aliases);
+                    }
+                    self.assign_elements(elements, aliases);
+                }
+                ProcessElement::Group { elements, .. } => self.assign_elements(elements, aliases),
+                ProcessElement::Task { .. }
+                | ProcessElement::CallActivity { .. }
+                | ProcessElement::Annotation { .. }
+                | ProcessElement::DataObject { .. } => {}
+            }
+        }
+    }
+
+    fn next_id(&mut self, kind: &'static str) -> String {
+        let counter = self.counters.entry(kind).or_default();
+        loop {
+            *counter += 1;
+            let id = format!("{kind}_{counter}");
+            if self.taken.insert(id.clone()) {
+                return id;
+            }
+        }
+    }
+}
+
+// Pools, lanes and groups share the scope of their parent, subprocesses were
+// already handled by their own `assign_scope`
+fn rewrite_scope(elements: &mut [ProcessElement], aliases: &ScopeAliases) {
+    for element in elements {
+        match element {
+            ProcessElement::Gateway { branches, .. } => rewrite_branches(branches, aliases),
+            ProcessElement::Pool {
+                lanes,
+                elements,
+                flows,
+                ..
+            } => {
+                for lane in lanes {
+                    rewrite_scope(&mut lane.elements, aliases);
+                }
+                rewrite_scope(elements, aliases);
+                rewrite_flows(flows, aliases);
+            }
+            ProcessElement::Group { elements, .. } => rewrite_scope(elements, aliases),
+            _ => {}
+        }
+    }
+}
+
+fn rewrite_flows(flows: &mut [Flow], aliases: &ScopeAliases) {
+    for flow in flows {
+        rewrite_reference(&mut flow.from, aliases);
+        rewrite_reference(&mut flow.to, aliases);
+    }
+}
+
+fn rewrite_branches(branches: &mut [GatewayBranch], aliases: &ScopeAliases) {
+    for branch in branches {
+        rewrite_reference(&mut branch.target, aliases);
+    }
+}
+
+fn rewrite_reference(name: &mut String, aliases: &ScopeAliases) {
+    let alias = match name.as_str() {
+        "start" => &aliases.start,
+        "end" => &aliases.end,
+        _ => return,
+    };
+
+    if let Some(alias) = alias {
+        name.clone_from(alias);
+    }
+}
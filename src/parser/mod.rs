@@ -1,28 +1,44 @@
-use std::collections::HashMap;
+use indexmap::IndexMap;
 
 use crate::{
-    lexer::{Span, Token, TokenKind},
+    diagnostics::{context_validator::is_flow_arrow, suggestions::EVENT_TYPES},
+    lexer::{Span, Token, TokenKind, invalid_number_suffix},
     parser::{
         ast::{
-            AstDocument, AttributeValue, ErrorSeverity, EventType, Flow, FlowType, GatewayBranch,
-            GatewayType, ImportDeclaration, Lane, ParseError, ProcessDeclaration, ProcessElement,
-            TaskType,
+            AstDocument, AttributeValue, ConditionExpr, ErrorSeverity, EventDirection, EventType,
+            Flow, FlowType, GatewayBranch, GatewayMode, GatewayType, ImportDeclaration, Lane,
+            ParseError, ProcessDeclaration, ProcessElement, TaskType, VariableDeclaration,
+            VariableType, parse_duration_millis,
         },
+        condition::parse_condition,
+        definitions::collect_definitions,
         error::ParserError,
         recovery::ErrorRecovery,
-        validator::validate_syntax,
+        rules::Profile,
+        validator::validate_syntax_with_profile,
     },
 };
 
+pub mod analysis;
 pub mod ast;
 pub mod builder;
+pub mod condition;
+pub mod definitions;
 pub mod error;
+pub mod ids;
 pub mod recovery;
+pub mod resolver;
+pub mod rules;
 pub mod validator;
 
+pub const DEFAULT_MAX_DEPTH: usize = 256;
+
 pub struct Parser {
     tokens: Vec<Token>,
     position: usize,
+    errors: Vec<ParseError>,
+    depth: usize,
+    max_depth: usize,
 }
 
 impl Parser {
@@ -31,51 +47,52 @@ impl Parser {
         Self {
             tokens,
             position: 0,
+            errors: Vec::new(),
+            depth: 0,
+            max_depth: DEFAULT_MAX_DEPTH,
         }
     }
 
+    #[must_use]
+    pub const fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
     pub fn parse_with_recovery(&mut self) -> AstDocument {
         let mut document = AstDocument::new();
         let mut recovery = ErrorRecovery::new();
 
         self.skip_whitespace_and_comments();
 
-        while self.check_token(&TokenKind::Import) {
-            match self.parse_import() {
-                Ok(import) => document.imports.push(import),
-                Err(err) => {
-                    document.add_error(err.to_string(), self.current_span());
-
-                    let sync_pos = recovery.find_sync_point(&self.tokens, self.position);
-                    self.position = sync_pos;
-                }
-            }
-            self.skip_whitespace_and_comments();
-        }
-
-        while self.check_token(&TokenKind::Process) {
-            match self.parse_process_with_recovery(&mut recovery) {
-                Ok(process) => document.processes.push(process),
-                Err(err) => {
-                    document.add_error(err.to_string(), self.current_span());
+        while !self.is_at_end() {
+            let current_pos = self.position;
+            let parsed = if self.check_token(&TokenKind::Import) {
+                self.parse_import()
+                    .map(|import| document.imports.push(import))
+            } else if self.check_token(&TokenKind::Process) {
+                self.parse_process_with_recovery(&mut recovery)
+                    .map(|process| document.processes.push(process))
+            } else {
+                self.skip_to_next_declaration(&mut document);
+                Ok(())
+            };
+            if let Err(err) = parsed {
+                document.add_error(err.to_string(), err.span().clone());
 
-                    let sync_pos = recovery.find_sync_point(&self.tokens, self.position);
-                    self.position = sync_pos;
-                }
+                let sync_pos = recovery.find_sync_point(&self.tokens, self.position);
+                self.position = sync_pos;
             }
+            self.ensure_progress(current_pos);
             self.skip_whitespace_and_comments();
         }
 
+        document.errors.append(&mut self.errors);
+        collect_definitions(&mut document);
         for error in recovery.errors {
             document.errors.push(error);
         }
-
-        if !self.is_at_end() && !self.check_token(&TokenKind::Eof) {
-            document.add_error(
-                format!("Unexpected token '{}'", self.current_token().text),
-                self.current_span(),
-            );
-        }
+        document.synthetic_ids.extend(recovery.synthetic_ids);
 
         document
     }
@@ -94,47 +111,47 @@ impl Parser {
 
         let mut elements = Vec::new();
         let mut flows = Vec::new();
+        let mut chained = Vec::new();
+        let mut variables = Vec::new();
 
         self.skip_whitespace_and_comments();
 
         while !self.check_token(&TokenKind::RightBrace) && !self.is_at_end() {
             let current_pos = self.position;
 
-            if let Ok(element) = self.parse_process_element() {
-                elements.push(element);
-            } else {
-                self.position = current_pos;
-                if let Ok(flow) = self.parse_flow() {
-                    flows.push(flow);
+            if self.check_token(&TokenKind::Var) {
+                variables.extend(self.parse_variable());
+            } else if !self.parse_statement(&mut elements, &mut flows, &mut chained) {
+                if let Some((recovered_element, new_pos)) =
+                    recovery.recover_process_element(&self.tokens, self.position)
+                {
+                    elements.push(recovered_element);
+                    self.position = new_pos;
+                } else if let Some((recovered_flow, new_pos)) =
+                    recovery.recover_flow(&self.tokens, self.position)
+                {
+                    flows.push(recovered_flow);
+                    self.position = new_pos;
                 } else {
-                    self.position = current_pos;
-
-                    if let Some((recovered_element, new_pos)) =
-                        recovery.recover_process_element(&self.tokens, self.position)
-                    {
-                        elements.push(recovered_element);
-                        self.position = new_pos;
-                    } else if let Some((recovered_flow, new_pos)) =
-                        recovery.recover_flow(&self.tokens, self.position)
-                    {
-                        flows.push(recovered_flow);
-                        self.position = new_pos;
-                    } else {
-                        recovery.errors.push(ParseError {
-                            message: format!(
-                                "Skipping unexpected token '{}'",
-                                self.current_token().text
-                            ),
-                            span: self.current_span(),
-                            severity: ErrorSeverity::Warning,
-                        });
-                        self.advance();
-                    }
+                    recovery.errors.push(ParseError {
+                        message: format!(
+                            "Skipping unexpected token '{}'",
+                            self.current_token().text
+                        ),
+                        span: self.current_span(),
+                        severity: ErrorSeverity::Warning,
+                        lint: "syntax",
+                        element: None,
+                        first_definition: None,
+                    });
+                    self.advance();
                 }
             }
 
+            self.ensure_progress(current_pos);
             self.skip_whitespace_and_comments();
         }
+        merge_chained(&mut flows, chained);
 
         if self.check_token(&TokenKind::RightBrace) {
             self.advance();
@@ -143,6 +160,9 @@ impl Parser {
                 message: "Missing closing brace for process".to_string(),
                 span: self.current_span(),
                 severity: ErrorSeverity::Error,
+                lint: "syntax",
+                element: None,
+                first_definition: None,
             });
         }
 
@@ -151,6 +171,7 @@ impl Parser {
             attributes,
             elements,
             flows,
+            variables,
             span: start_span,
         })
     }
@@ -160,45 +181,41 @@ impl Parser {
 
         self.skip_whitespace_and_comments();
 
-        while self.check_token(&TokenKind::Import) {
-            match self.parse_import() {
-                Ok(import) => document.imports.push(import),
-                Err(err) => {
-                    document.add_error(err.to_string(), self.current_span());
-
-                    self.recover_to_next_statement();
-                }
-            }
-            self.skip_whitespace_and_comments();
-        }
-
-        while self.check_token(&TokenKind::Process) {
-            match self.parse_process() {
-                Ok(process) => document.processes.push(process),
-                Err(err) => {
-                    document.add_error(err.to_string(), self.current_span());
+        while !self.is_at_end() {
+            let current_pos = self.position;
+            let parsed = if self.check_token(&TokenKind::Import) {
+                self.parse_import()
+                    .map(|import| document.imports.push(import))
+            } else if self.check_token(&TokenKind::Process) {
+                self.parse_process()
+                    .map(|process| document.processes.push(process))
+            } else {
+                self.skip_to_next_declaration(&mut document);
+                Ok(())
+            };
+            if let Err(err) = parsed {
+                document.add_error(err.to_string(), err.span().clone());
 
-                    self.recover_to_next_statement();
-                }
+                self.recover_to_next_statement();
             }
-
+            self.ensure_progress(current_pos);
             self.skip_whitespace_and_comments();
         }
 
-        if !self.is_at_end() && !self.check_token(&TokenKind::Eof) {
-            document.add_error(
-                format!("Unexpected token '{}'", self.current_token().text),
-                self.current_span(),
-            );
-        }
+        document.errors.append(&mut self.errors);
+        collect_definitions(&mut document);
 
         document
     }
 
     pub fn parse_with_validation(&mut self) -> AstDocument {
+        self.parse_with_profile(Profile::Generic)
+    }
+
+    pub fn parse_with_profile(&mut self, profile: Profile) -> AstDocument {
         let mut document = self.parse();
 
-        if let Err(syntax_errors) = validate_syntax(&document) {
+        if let Err(syntax_errors) = validate_syntax_with_profile(&document, profile) {
             document.errors.extend(syntax_errors);
         }
 
@@ -266,26 +283,24 @@ impl Parser {
 
         let mut elements = Vec::new();
         let mut flows = Vec::new();
+        let mut chained = Vec::new();
+        let mut variables = Vec::new();
 
         self.skip_whitespace_and_comments();
 
         while !self.check_token(&TokenKind::RightBrace) && !self.is_at_end() {
             let current_pos = self.position;
-            
-            if let Ok(element) = self.parse_process_element() {
-                elements.push(element);
-            } else {
-                self.position = current_pos;
-                if let Ok(flow) = self.parse_flow() {
-                    flows.push(flow);
-                } else {
-                    self.position = current_pos;
-                    self.advance();
-                }
+
+            if self.check_token(&TokenKind::Var) {
+                variables.extend(self.parse_variable());
+            } else if !self.parse_statement(&mut elements, &mut flows, &mut chained) {
+                self.advance();
             }
 
+            self.ensure_progress(current_pos);
             self.skip_whitespace_and_comments();
         }
+        merge_chained(&mut flows, chained);
 
         self.consume_token(&TokenKind::RightBrace)?;
 
@@ -294,12 +309,66 @@ impl Parser {
             attributes,
             elements,
             flows,
+            variables,
             span: start_span,
         };
 
         Ok(process)
     }
 
+    // `var amount: number`, a malformed declaration is reported and the rest of its
+    // line skipped
+    fn parse_variable(&mut self) -> Option<VariableDeclaration> {
+        let span = self.current_span();
+        self.advance();
+
+        match self.parse_variable_body(span) {
+            Ok(variable) => Some(variable),
+            Err(err) => {
+                self.errors.push(ParseError {
+                    message: err.to_string(),
+                    span: err.span().clone(),
+                    severity: ErrorSeverity::Error,
+                    lint: "syntax",
+                    element: None,
+                    first_definition: None,
+                });
+                while !self.is_at_end()
+                    && !matches!(
+                        self.current_token().kind,
+                        TokenKind::Newline
+                            | TokenKind::CarriageReturnNewline
+                            | TokenKind::RightBrace
+                    )
+                {
+                    self.advance();
+                }
+                None
+            }
+        }
+    }
+
+    fn parse_variable_body(&mut self, span: Span) -> Result<VariableDeclaration, Box<ParserError>> {
+        let name = self.parse_identifier()?;
+        self.consume_token(&TokenKind::Colon)?;
+
+        let type_span = self.current_span();
+        let Some(variable_type) = VariableType::from_name(&self.current_token().text) else {
+            return Err(Box::new(ParserError::UnexpectedToken {
+                found: self.found_text(),
+                expected: "number, string or boolean".to_string(),
+                span: type_span,
+            }));
+        };
+        self.advance();
+
+        Ok(VariableDeclaration {
+            name,
+            variable_type,
+            span,
+        })
+    }
+
     #[allow(clippy::too_many_lines)]
     fn parse_process_element(&mut self) -> Result<ProcessElement, Box<ParserError>> {
         let span = self.current_span();
@@ -307,23 +376,33 @@ impl Parser {
         match &self.current_token().kind {
             TokenKind::Start => {
                 self.advance();
+                let id = self.parse_event_name()?;
                 let event_type = self.parse_event_type()?;
                 let attributes = self.parse_attributes()?;
 
                 Ok(ProcessElement::StartEvent {
-                    id: None,
+                    id,
                     event_type,
                     attributes,
                     span,
                 })
             }
+            // `end -> Next` is a flow out of the end event, left for the validator to reject
+            TokenKind::End if self.next_is_flow_arrow() => {
+                Err(Box::new(ParserError::UnexpectedToken {
+                    found: self.found_text(),
+                    expected: "process element".to_string(),
+                    span,
+                }))
+            }
             TokenKind::End => {
                 self.advance();
+                let id = self.parse_event_name()?;
                 let event_type = self.parse_event_type()?;
                 let attributes = self.parse_attributes()?;
 
                 Ok(ProcessElement::EndEvent {
-                    id: None,
+                    id,
                     event_type,
                     attributes,
                     span,
@@ -332,10 +411,12 @@ impl Parser {
             TokenKind::Task => {
                 self.advance();
                 let id = self.parse_identifier()?;
+                let label = self.parse_label()?;
                 let attributes = self.parse_attributes()?;
 
                 let task = ProcessElement::Task {
                     id,
+                    label,
                     task_type: TaskType::Generic,
                     attributes,
                     span,
@@ -346,10 +427,12 @@ impl Parser {
             TokenKind::User => {
                 self.advance();
                 let id = self.parse_identifier()?;
+                let label = self.parse_label()?;
                 let attributes = self.parse_attributes()?;
 
                 let task = ProcessElement::Task {
                     id,
+                    label,
                     task_type: TaskType::User,
                     attributes,
                     span,
@@ -360,10 +443,12 @@ impl Parser {
             TokenKind::Service => {
                 self.advance();
                 let id = self.parse_identifier()?;
+                let label = self.parse_label()?;
                 let attributes = self.parse_attributes()?;
 
                 Ok(ProcessElement::Task {
                     id,
+                    label,
                     task_type: TaskType::Service,
                     attributes,
                     span,
@@ -372,10 +457,12 @@ impl Parser {
             TokenKind::Script => {
                 self.advance();
                 let id = self.parse_identifier()?;
+                let label = self.parse_label()?;
                 let attributes = self.parse_attributes()?;
 
                 Ok(ProcessElement::Task {
                     id,
+                    label,
                     task_type: TaskType::Script,
                     attributes,
                     span,
@@ -383,17 +470,17 @@ impl Parser {
             }
             TokenKind::Call => {
                 self.advance();
-                let id = self.parse_identifier()?;
-                let called_element = if self.check_token(&TokenKind::Namespace) {
-                    self.advance();
-                    format!("{}::{}", id, self.parse_identifier()?)
-                } else {
-                    id.clone()
-                };
+                let called_element = self.parse_qualified_name()?;
+                let id = called_element
+                    .split_once("::")
+                    .map_or(called_element.as_str(), |(namespace, _)| namespace)
+                    .to_string();
+                let label = self.parse_label()?;
                 let attributes = self.parse_attributes()?;
 
                 Ok(ProcessElement::CallActivity {
                     id,
+                    label,
                     called_element,
                     attributes,
                     span,
@@ -401,52 +488,32 @@ impl Parser {
             }
             TokenKind::Xor => {
                 self.advance();
-                let id = if self.check_token(&TokenKind::Identifier) {
-                    Some(self.parse_identifier()?)
-                } else {
-                    None
-                };
-
-                if self.check_token(&TokenKind::Question) {
-                    self.advance();
-                }
-
-                self.consume_token(&TokenKind::LeftBrace)?;
-                let branches = self.parse_gateway_branches()?;
-                self.consume_token(&TokenKind::RightBrace)?;
-
-                Ok(ProcessElement::Gateway {
-                    id,
-                    gateway_type: GatewayType::Exclusive,
-                    branches,
-                    span,
-                })
+                self.parse_gateway(GatewayType::Exclusive, span)
             }
             TokenKind::And => {
                 self.advance();
-                let id = if self.check_token(&TokenKind::Identifier) {
-                    Some(self.parse_identifier()?)
-                } else {
-                    None
-                };
-
-                self.consume_token(&TokenKind::LeftBrace)?;
-                let branches = self.parse_gateway_branches()?;
-                self.consume_token(&TokenKind::RightBrace)?;
-
-                Ok(ProcessElement::Gateway {
-                    id,
-                    gateway_type: GatewayType::Parallel,
-                    branches,
-                    span,
-                })
+                self.parse_gateway(GatewayType::Parallel, span)
+            }
+            TokenKind::Or => {
+                self.advance();
+                self.parse_gateway(GatewayType::Inclusive, span)
+            }
+            TokenKind::Event
+                if self
+                    .tokens
+                    .get(self.position + 1)
+                    .is_some_and(|token| token.kind == TokenKind::Subprocess) =>
+            {
+                self.parse_event_subprocess(span)
             }
             TokenKind::Event => {
                 self.advance();
+                let direction = self.parse_event_direction();
+                let id = self.parse_event_name()?;
                 let event_type =
                     self.parse_event_type()?
                         .ok_or_else(|| ParserError::UnexpectedToken {
-                            found: self.current_token().text,
+                            found: self.found_text(),
                             expected: "event type (timer, message, etc.)".to_string(),
                             span: self.current_span(),
                         })?;
@@ -467,238 +534,535 @@ impl Parser {
                 let attributes = self.parse_attributes()?;
 
                 Ok(ProcessElement::IntermediateEvent {
-                    id: None,
+                    id,
                     event_type,
+                    direction,
                     payload,
                     attributes,
                     span,
                 })
             }
-            TokenKind::Subprocess => {
-                self.advance();
-                let id = self.parse_identifier()?;
-                let attributes = self.parse_attributes()?;
-
-                self.consume_token(&TokenKind::LeftBrace)?;
-
-                let mut elements = Vec::new();
-                let mut flows = Vec::new();
-
-                self.skip_whitespace_and_comments();
-
-                while !self.check_token(&TokenKind::RightBrace) && !self.is_at_end() {
-                    if let Ok(element) = self.parse_process_element() {
-                        elements.push(element);
-                    } else if let Ok(flow) = self.parse_flow() {
-                        flows.push(flow);
-                    } else {
-                        self.advance();
-                    }
-                    self.skip_whitespace_and_comments();
-                }
-
-                self.consume_token(&TokenKind::RightBrace)?;
-
-                Ok(ProcessElement::Subprocess {
-                    id,
-                    elements,
-                    flows,
-                    attributes,
-                    span,
-                })
-            }
-            TokenKind::Pool => {
+            TokenKind::Subprocess => self.parse_subprocess(span),
+            TokenKind::Pool => self.parse_pool(span),
+            TokenKind::Group => self.parse_group(span),
+            TokenKind::Note => {
                 self.advance();
-                let name = self.parse_identifier()?;
-
-                self.consume_token(&TokenKind::LeftBrace)?;
-
-                let mut lanes = Vec::new();
-                let mut elements = Vec::new();
-                let mut flows = Vec::new();
-
-                self.skip_whitespace_and_comments();
-
-                while !self.check_token(&TokenKind::RightBrace) && !self.is_at_end() {
-                    if self.check_token(&TokenKind::Lane) {
-                        lanes.push(self.parse_lane()?);
-                    } else if let Ok(element) = self.parse_process_element() {
-                        elements.push(element);
-                    } else if let Ok(flow) = self.parse_flow() {
-                        flows.push(flow);
-                    } else {
-                        self.advance();
-                    }
-                    self.skip_whitespace_and_comments();
-                }
-
-                self.consume_token(&TokenKind::RightBrace)?;
+                let id = if self.check_token(&TokenKind::Identifier) {
+                    Some(self.parse_identifier()?)
+                } else {
+                    None
+                };
+                let text = self.parse_string_literal()?;
 
-                Ok(ProcessElement::Pool {
-                    name,
-                    lanes,
-                    elements,
-                    flows,
-                    span,
-                })
+                Ok(ProcessElement::Annotation { id, text, span })
             }
-            TokenKind::Group => {
+            TokenKind::Data => {
                 self.advance();
-                let label = self.parse_string_literal()?;
-
-                self.consume_token(&TokenKind::LeftBrace)?;
-
-                let mut elements = Vec::new();
-                self.skip_whitespace_and_comments();
-
-                while !self.check_token(&TokenKind::RightBrace) && !self.is_at_end() {
-                    if let Ok(element) = self.parse_process_element() {
-                        elements.push(element);
-                    } else {
-                        self.advance();
-                    }
-                    self.skip_whitespace_and_comments();
+                // `data store Inventory` refers to a data store instead of an object
+                let store = self.check_token(&TokenKind::Identifier)
+                    && self.current_token().text == "store"
+                    && self
+                        .tokens
+                        .get(self.position + 1)
+                        .is_some_and(|token| token.kind == TokenKind::Identifier);
+                if store {
+                    self.advance();
                 }
+                let id = self.parse_identifier()?;
+                let label = self.parse_label()?;
 
-                self.consume_token(&TokenKind::RightBrace)?;
-
-                Ok(ProcessElement::Group {
+                Ok(ProcessElement::DataObject {
+                    id,
                     label,
-                    elements,
+                    store,
                     span,
                 })
             }
-            TokenKind::Note => {
-                self.advance();
-                let text = self.parse_string_literal()?;
-
-                Ok(ProcessElement::Annotation { text, span })
-            }
             _ => Err(Box::new(ParserError::UnexpectedToken {
-                found: self.current_token().text,
+                found: self.found_text(),
                 expected: "process element".to_string(),
                 span: self.current_span(),
             })),
         }
     }
 
-    fn parse_flow(&mut self) -> Result<Flow, Box<ParserError>> {
-        let span = self.current_span();
-        let from = self.parse_identifier()?;
+    fn parse_subprocess(&mut self, span: Span) -> Result<ProcessElement, Box<ParserError>> {
+        self.advance();
+        let id = self.parse_identifier()?;
+        let label = self.parse_label()?;
+        let attributes = self.parse_attributes()?;
 
-        let flow_type = match &self.current_token().kind {
-            TokenKind::SequenceFlow => {
-                self.advance();
-                FlowType::Sequence
-            }
-            TokenKind::MessageFlow => {
-                self.advance();
-                FlowType::Message
-            }
-            TokenKind::DefaultFlow => {
-                self.advance();
-                FlowType::Default
-            }
-            TokenKind::Association => {
-                self.advance();
-                FlowType::Association
-            }
-            _ => {
-                return Err(Box::new(ParserError::UnexpectedToken {
-                    found: self.current_token().text,
-                    expected: "flow arrow (-> --> => ..>)".to_string(),
-                    span: self.current_span(),
-                }));
-            }
-        };
+        let (elements, flows) = self.parse_subprocess_body()?;
 
-        let to = if self.check_token(&TokenKind::End) {
-            self.advance();
-            "end".to_string()
-        } else {
-            self.parse_identifier()?
-        };
+        Ok(ProcessElement::Subprocess {
+            id,
+            label,
+            elements,
+            flows,
+            attributes,
+            span,
+        })
+    }
 
-        let condition = if self.check_token(&TokenKind::LeftBracket) {
-            self.advance();
-            let cond = self.parse_condition_expression()?;
-            self.consume_token(&TokenKind::RightBracket)?;
-            Some(cond)
-        } else {
-            None
-        };
+    fn parse_event_subprocess(&mut self, span: Span) -> Result<ProcessElement, Box<ParserError>> {
+        self.advance();
+        self.advance();
+        let id = self.parse_identifier()?;
+        let trigger = self
+            .parse_event_type()?
+            .ok_or_else(|| ParserError::UnexpectedToken {
+                found: self.found_text(),
+                expected: "event subprocess trigger (@message, @error, etc.)".to_string(),
+                span: self.current_span(),
+            })?;
+        let attributes = self.parse_attributes()?;
 
-        Ok(Flow {
-            from,
-            to,
-            flow_type,
-            condition,
+        let (elements, flows) = self.parse_subprocess_body()?;
+
+        Ok(ProcessElement::EventSubprocess {
+            id,
+            trigger,
+            elements,
+            flows,
+            attributes,
             span,
         })
     }
 
-    fn parse_gateway_branches(&mut self) -> Result<Vec<GatewayBranch>, Box<ParserError>> {
-        let mut branches = Vec::new();
-
-        self.skip_whitespace_and_comments();
+    fn parse_subprocess_body(
+        &mut self,
+    ) -> Result<(Vec<ProcessElement>, Vec<Flow>), Box<ParserError>> {
+        self.consume_token(&TokenKind::LeftBrace)?;
 
-        while !self.check_token(&TokenKind::RightBrace) && !self.is_at_end() {
-            let span = self.current_span();
+        let (elements, flows) = self.parse_nested(|parser| {
+            let mut elements = Vec::new();
+            let mut flows = Vec::new();
+            let mut chained = Vec::new();
 
-            let (condition, is_default) = if self.check_token(&TokenKind::LeftBracket) {
-                self.advance();
-                let cond = self.parse_condition_expression()?;
-                self.consume_token(&TokenKind::RightBracket)?;
-                (Some(cond), false)
-            } else if self.check_token(&TokenKind::DefaultFlow) {
-                self.advance();
-                (None, true)
-            } else {
-                let cond = self.parse_identifier()?;
-                (Some(cond), false)
-            };
+            parser.skip_whitespace_and_comments();
 
-            if !is_default {
-                if !self.check_token(&TokenKind::SequenceFlow)
-                    && !self.check_token(&TokenKind::DefaultFlow)
-                {
-                    return Err(Box::new(ParserError::UnexpectedToken {
-                        found: self.current_token().text,
-                        expected: "-> or =>".to_string(),
-                        span: self.current_span(),
-                    }));
+            while !parser.check_token(&TokenKind::RightBrace) && !parser.is_at_end() {
+                if !parser.parse_statement(&mut elements, &mut flows, &mut chained) {
+                    parser.advance();
                 }
-                self.advance();
+                parser.skip_whitespace_and_comments();
             }
+            merge_chained(&mut flows, chained);
 
-            let target = self.parse_identifier()?;
-
-            branches.push(GatewayBranch {
-                condition,
-                target,
-                is_default,
-                span,
-            });
+            Ok((elements, flows))
+        })?;
 
-            self.skip_whitespace_and_comments();
-        }
+        self.consume_token(&TokenKind::RightBrace)?;
 
-        Ok(branches)
+        Ok((elements, flows))
     }
 
-    fn parse_lane(&mut self) -> Result<Lane, Box<ParserError>> {
-        let span = self.current_span();
-        self.consume_token(&TokenKind::Lane)?;
+    fn parse_pool(&mut self, span: Span) -> Result<ProcessElement, Box<ParserError>> {
+        self.advance();
         let name = self.parse_identifier()?;
+        let attributes = self.parse_attributes()?;
 
         self.consume_token(&TokenKind::LeftBrace)?;
 
-        let mut elements = Vec::new();
-        self.skip_whitespace_and_comments();
+        let (lanes, elements, flows) = self.parse_nested(|parser| {
+            let mut lanes = Vec::new();
+            let mut elements = Vec::new();
+            let mut flows = Vec::new();
+            let mut chained = Vec::new();
 
-        while !self.check_token(&TokenKind::RightBrace) && !self.is_at_end() {
-            if let Ok(element) = self.parse_process_element() {
-                elements.push(element);
+            parser.skip_whitespace_and_comments();
+
+            while !parser.check_token(&TokenKind::RightBrace) && !parser.is_at_end() {
+                if parser.check_token(&TokenKind::Lane) {
+                    lanes.push(parser.parse_lane()?);
+                } else if !parser.parse_statement(&mut elements, &mut flows, &mut chained) {
+                    parser.advance();
+                }
+                parser.skip_whitespace_and_comments();
+            }
+            merge_chained(&mut flows, chained);
+
+            Ok((lanes, elements, flows))
+        })?;
+
+        self.consume_token(&TokenKind::RightBrace)?;
+
+        Ok(ProcessElement::Pool {
+            name,
+            lanes,
+            elements,
+            flows,
+            attributes,
+            span,
+        })
+    }
+
+    fn parse_group(&mut self, span: Span) -> Result<ProcessElement, Box<ParserError>> {
+        self.advance();
+        let label = self.parse_string_literal()?;
+
+        self.consume_token(&TokenKind::LeftBrace)?;
+
+        let elements = self.parse_nested(|parser| {
+            let mut elements = Vec::new();
+            parser.skip_whitespace_and_comments();
+
+            while !parser.check_token(&TokenKind::RightBrace) && !parser.is_at_end() {
+                if let Ok(element) = parser.parse_process_element() {
+                    elements.push(element);
+                } else {
+                    parser.advance();
+                }
+                parser.skip_whitespace_and_comments();
+            }
+
+            Ok(elements)
+        })?;
+
+        self.consume_token(&TokenKind::RightBrace)?;
+
+        Ok(ProcessElement::Group {
+            label,
+            elements,
+            span,
+        })
+    }
+
+    // An element or flow together with the `-> Next` links chained onto it
+    fn parse_statement(
+        &mut self,
+        elements: &mut Vec<ProcessElement>,
+        flows: &mut Vec<Flow>,
+        chained: &mut Vec<Flow>,
+    ) -> bool {
+        let start = self.position;
+
+        // A bare `start ->` links from the start event already declared
+        if self.check_token(&TokenKind::Start)
+            && elements.iter().any(is_start_event)
+            && self.parse_reference_chain(elements, chained)
+        {
+            return true;
+        }
+
+        let from = if let Ok(element) = self.parse_process_element() {
+            let name = chain_name(&element);
+            let head = name.map(|name| {
+                let span = self.name_span(start, &name);
+                (name, span)
+            });
+            elements.push(element);
+            head
+        } else {
+            self.position = start;
+            let Ok(flow) = self.parse_flow() else {
+                self.position = start;
+                return self.parse_reference_chain(elements, chained);
+            };
+            let head = (flow.to.clone(), flow.to_span.clone());
+            flows.push(flow);
+            Some(head)
+        };
+
+        if let Some((from, from_span)) = from {
+            self.parse_chain(from, from_span, elements, chained);
+        }
+        true
+    }
+
+    // `Known -> task Next` chains on from an element declared elsewhere
+    fn parse_reference_chain(
+        &mut self,
+        elements: &mut Vec<ProcessElement>,
+        chained: &mut Vec<Flow>,
+    ) -> bool {
+        let start = self.position;
+        let head = matches!(
+            self.current_token().kind,
+            TokenKind::Identifier | TokenKind::Start
+        );
+        if !head || !self.next_is_flow_arrow() {
+            return false;
+        }
+        let from = self.current_token();
+        self.advance();
+
+        let (linked, declared) = (chained.len(), elements.len());
+        self.parse_chain(from.text, from.span, elements, chained);
+        if chained.len() == linked && elements.len() == declared {
+            self.position = start;
+            return false;
+        }
+        true
+    }
+
+    // A link that fails to parse is left in place for the caller to skip
+    fn parse_chain(
+        &mut self,
+        mut from: String,
+        mut from_span: Span,
+        elements: &mut Vec<ProcessElement>,
+        chained: &mut Vec<Flow>,
+    ) {
+        while is_flow_arrow(&self.current_token().kind) {
+            let start = self.position;
+            let Ok((flow, element)) = self.parse_link(from.clone(), from_span.clone()) else {
+                self.position = start;
+                return;
+            };
+            elements.extend(element);
+            let Some(flow) = flow else {
+                return;
+            };
+
+            // `-> end` declares the end event when the block has none yet
+            if flow.to == "end" && !elements.iter().any(is_end_event) {
+                elements.push(ProcessElement::EndEvent {
+                    id: None,
+                    event_type: None,
+                    attributes: IndexMap::new(),
+                    span: flow.to_span.clone(),
+                });
+            }
+
+            from.clone_from(&flow.to);
+            from_span.clone_from(&flow.to_span);
+            chained.push(flow);
+        }
+    }
+
+    // `-> task Next` declares the next element, `-> Next` refers to one. Once declared
+    // the element is kept even if the link fails, parsing it again as a statement would
+    // report its errors twice
+    fn parse_link(
+        &mut self,
+        from: String,
+        from_span: Span,
+    ) -> Result<(Option<Flow>, Option<ProcessElement>), Box<ParserError>> {
+        let span = self.current_span();
+        let flow_type = self.parse_flow_arrow()?;
+
+        let target_start = self.position;
+        let (to, element) = if matches!(
+            self.current_token().kind,
+            TokenKind::Identifier | TokenKind::Start | TokenKind::End
+        ) {
+            (self.parse_flow_target()?, None)
+        } else {
+            let element = self.parse_process_element()?;
+            let Some(to) = chain_name(&element) else {
+                return Ok((None, Some(element)));
+            };
+            (to, Some(element))
+        };
+        let to_span = self.name_span(target_start, &to);
+
+        let condition = match self.parse_flow_condition() {
+            Ok(condition) => condition,
+            Err(err) if element.is_some() => {
+                self.errors.push(ParseError {
+                    message: err.to_string(),
+                    span: err.span().clone(),
+                    severity: ErrorSeverity::Error,
+                    lint: "syntax",
+                    element: None,
+                    first_definition: None,
+                });
+                self.skip_to_end_of_branch();
+                return Ok((None, element));
+            }
+            Err(err) => return Err(err),
+        };
+
+        Ok((
+            Some(Flow {
+                from,
+                to,
+                flow_type,
+                condition,
+                span,
+                from_span,
+                to_span,
+            }),
+            element,
+        ))
+    }
+
+    fn parse_flow(&mut self) -> Result<Flow, Box<ParserError>> {
+        let span = self.current_span();
+        let from_start = self.position;
+        let from = if self.check_token(&TokenKind::End) {
+            self.advance();
+            "end".to_string()
+        } else {
+            self.parse_qualified_name()?
+        };
+        let from_span = self.span_since(from_start);
+
+        let flow_type = self.parse_flow_arrow()?;
+        let to_start = self.position;
+        let to = self.parse_flow_target()?;
+        let to_span = self.span_since(to_start);
+        let condition = self.parse_flow_condition()?;
+
+        Ok(Flow {
+            from,
+            to,
+            flow_type,
+            condition,
+            span,
+            from_span,
+            to_span,
+        })
+    }
+
+    fn parse_flow_arrow(&mut self) -> Result<FlowType, Box<ParserError>> {
+        let flow_type = match &self.current_token().kind {
+            TokenKind::SequenceFlow => {
+                self.advance();
+                FlowType::Sequence
+            }
+            TokenKind::MessageFlow => {
+                self.advance();
+                FlowType::Message
+            }
+            TokenKind::DefaultFlow => {
+                self.advance();
+                FlowType::Default
+            }
+            TokenKind::Association => {
+                self.advance();
+                FlowType::Association
+            }
+            _ => {
+                return Err(Box::new(ParserError::UnexpectedToken {
+                    found: self.found_text(),
+                    expected: "flow arrow (-> --> => ..>)".to_string(),
+                    span: self.current_span(),
+                }));
+            }
+        };
+
+        Ok(flow_type)
+    }
+
+    fn parse_flow_target(&mut self) -> Result<String, Box<ParserError>> {
+        if self.check_token(&TokenKind::End) {
+            self.advance();
+            Ok("end".to_string())
+        } else if self.check_token(&TokenKind::Start) {
+            self.advance();
+            Ok("start".to_string())
+        } else {
+            self.parse_qualified_name()
+        }
+    }
+
+    fn parse_flow_condition(&mut self) -> Result<Option<ConditionExpr>, Box<ParserError>> {
+        if !self.check_token(&TokenKind::LeftBracket) {
+            return Ok(None);
+        }
+
+        self.advance();
+        let condition = self.parse_condition_expression()?;
+        self.consume_token(&TokenKind::RightBracket)?;
+        Ok(Some(condition))
+    }
+
+    // A bad branch is reported and skipped to the end of its line so the
+    // remaining branches are still parsed
+    fn parse_gateway_branches(&mut self) -> Vec<GatewayBranch> {
+        let mut branches = Vec::new();
+
+        self.skip_whitespace_and_comments();
+
+        while !self.check_token(&TokenKind::RightBrace) && !self.is_at_end() {
+            match self.parse_gateway_branch() {
+                Ok(branch) => branches.push(branch),
+                Err(err) => {
+                    self.errors.push(ParseError {
+                        message: err.to_string(),
+                        span: err.span().clone(),
+                        severity: ErrorSeverity::Error,
+                        lint: "syntax",
+                        element: None,
+                        first_definition: None,
+                    });
+                    self.skip_to_end_of_branch();
+                }
+            }
+
+            self.skip_whitespace_and_comments();
+        }
+
+        branches
+    }
+
+    fn parse_gateway_branch(&mut self) -> Result<GatewayBranch, Box<ParserError>> {
+        let span = self.current_span();
+
+        let (condition, is_default) = if self.check_token(&TokenKind::LeftBracket) {
+            self.advance();
+            let cond = self.parse_condition_expression()?;
+            self.consume_token(&TokenKind::RightBracket)?;
+            (Some(cond), false)
+        } else if self.check_token(&TokenKind::DefaultFlow) {
+            self.advance();
+            (None, true)
+        } else {
+            let cond = self.parse_identifier()?;
+            (Some(ConditionExpr::Identifier(cond)), false)
+        };
+
+        if !is_default {
+            if !self.check_token(&TokenKind::SequenceFlow)
+                && !self.check_token(&TokenKind::DefaultFlow)
+            {
+                return Err(Box::new(ParserError::UnexpectedToken {
+                    found: self.found_text(),
+                    expected: "-> or =>".to_string(),
+                    span: self.current_span(),
+                }));
+            }
+            self.advance();
+        }
+
+        let target_start = self.position;
+        let target = self.parse_qualified_name()?;
+
+        Ok(GatewayBranch {
+            condition,
+            target,
+            is_default,
+            span,
+            target_span: self.span_since(target_start),
+        })
+    }
+
+    fn skip_to_end_of_branch(&mut self) {
+        while !self.is_at_end()
+            && !matches!(
+                self.current_token().kind,
+                TokenKind::Newline | TokenKind::CarriageReturnNewline | TokenKind::RightBrace
+            )
+        {
+            self.advance();
+        }
+    }
+
+    fn parse_lane(&mut self) -> Result<Lane, Box<ParserError>> {
+        let span = self.current_span();
+        self.consume_token(&TokenKind::Lane)?;
+        let name = self.parse_identifier()?;
+
+        self.consume_token(&TokenKind::LeftBrace)?;
+
+        let mut elements = Vec::new();
+        self.skip_whitespace_and_comments();
+
+        while !self.check_token(&TokenKind::RightBrace) && !self.is_at_end() {
+            if let Ok(element) = self.parse_process_element() {
+                elements.push(element);
             } else {
                 self.advance();
             }
@@ -714,22 +1078,106 @@ impl Parser {
         })
     }
 
+    fn parse_gateway(
+        &mut self,
+        gateway_type: GatewayType,
+        span: Span,
+    ) -> Result<ProcessElement, Box<ParserError>> {
+        let id = if self.check_token(&TokenKind::Identifier)
+            && gateway_mode(&self.current_token().text).is_none()
+        {
+            Some(self.parse_identifier()?)
+        } else {
+            None
+        };
+
+        let mode = if self.check_token(&TokenKind::Identifier)
+            && let Some(mode) = gateway_mode(&self.current_token().text)
+        {
+            self.advance();
+            mode
+        } else {
+            GatewayMode::default()
+        };
+
+        if gateway_type != GatewayType::Parallel && self.check_token(&TokenKind::Question) {
+            self.advance();
+        }
+        let attributes = self.parse_attributes()?;
+
+        // A join only merges incoming flows, so its branch block is optional
+        let branches = if mode == GatewayMode::Join && !self.check_token(&TokenKind::LeftBrace) {
+            Vec::new()
+        } else {
+            self.consume_token(&TokenKind::LeftBrace)?;
+            let branches = self.parse_gateway_branches();
+            self.consume_token(&TokenKind::RightBrace)?;
+            branches
+        };
+
+        // Ids are generated for emitters, but a flow still has no name to point at
+        if id.is_none() {
+            let keyword = match gateway_type {
+                GatewayType::Exclusive => "xor",
+                GatewayType::Parallel => "and",
+                GatewayType::Inclusive => "or",
+            };
+            self.errors.push(ParseError {
+                message: format!(
+                    "Gateway has no name; flows cannot reference it, name it like `{keyword} Decide`"
+                ),
+                span: span.clone(),
+                severity: ErrorSeverity::Warning,
+                lint: "unnamed-gateway",
+                element: None,
+                first_definition: None,
+            });
+        }
+
+        Ok(ProcessElement::Gateway {
+            id,
+            gateway_type,
+            mode,
+            branches,
+            attributes,
+            span,
+        })
+    }
+
+    fn parse_event_direction(&mut self) -> EventDirection {
+        let direction = match self.current_token().text.as_str() {
+            "throw" if self.check_token(&TokenKind::Identifier) => EventDirection::Throw,
+            "catch" if self.check_token(&TokenKind::Identifier) => EventDirection::Catch,
+            _ => return EventDirection::default(),
+        };
+
+        self.advance();
+        direction
+    }
+
     fn parse_event_type(&mut self) -> Result<Option<EventType>, Box<ParserError>> {
         if !self.check_token(&TokenKind::At) {
             return Ok(None);
         }
 
+        let at_position = self.position;
         self.advance();
 
         if !self.check_token(&TokenKind::Identifier) {
             return Err(Box::new(ParserError::UnexpectedToken {
-                found: self.current_token().text,
+                found: self.found_text(),
                 expected: "event type identifier".to_string(),
                 span: self.current_span(),
             }));
         }
 
         let event_type_name = self.current_token().text;
+
+        if !EVENT_TYPES.contains(&event_type_name.as_str()) {
+            self.position = at_position;
+            return Ok(None);
+        }
+
         self.advance();
 
         match event_type_name.as_str() {
@@ -769,31 +1217,58 @@ impl Parser {
                 };
                 Ok(Some(EventType::Signal(signal_name)))
             }
+            "escalation" => {
+                let escalation_code = if self.check_token(&TokenKind::StringLiteral) {
+                    self.parse_string_literal()?
+                } else {
+                    String::new()
+                };
+                Ok(Some(EventType::Escalation(escalation_code)))
+            }
+            "compensation" => {
+                let activity = if self.check_token(&TokenKind::StringLiteral) {
+                    self.parse_string_literal()?
+                } else {
+                    String::new()
+                };
+                Ok(Some(EventType::Compensation(activity)))
+            }
+            "conditional" => {
+                let expression = if self.check_token(&TokenKind::StringLiteral) {
+                    self.parse_string_literal()?
+                } else {
+                    String::new()
+                };
+                Ok(Some(EventType::Conditional(expression)))
+            }
             "terminate" => Ok(Some(EventType::Terminate)),
             _ => Err(Box::new(ParserError::UnexpectedToken {
                 found: event_type_name,
-                expected: "event type (message, timer, error, signal, terminate)".to_string(),
+                expected: "event type (message, timer, error, signal, escalation, compensation, terminate)".to_string(),
                 span: self.current_span(),
             })),
         }
     }
 
-    fn parse_attributes(&mut self) -> Result<HashMap<String, AttributeValue>, Box<ParserError>> {
-        let mut attributes = HashMap::new();
+    fn parse_attributes(&mut self) -> Result<IndexMap<String, AttributeValue>, Box<ParserError>> {
+        let mut attributes = IndexMap::new();
 
         while self.check_token(&TokenKind::At) {
             self.advance();
-            let key = self.parse_identifier()?;
+            let key_span = self.current_span();
+            let key = self.parse_attribute_key()?;
 
             let value = if self.check_token(&TokenKind::StringLiteral)
                 || self.check_token(&TokenKind::NumberLiteral)
                 || self.check_token(&TokenKind::Identifier)
             {
-                self.parse_attribute_value()?
+                self.parse_checked_attribute_value()?
             } else {
-                AttributeValue::Boolean(true)
+                Some(AttributeValue::Boolean(true))
             };
-            attributes.insert(key, value);
+            if let Some(value) = value {
+                self.insert_attribute(&mut attributes, key, value, key_span);
+            }
         }
 
         if self.check_token(&TokenKind::LeftParen) {
@@ -801,20 +1276,21 @@ impl Parser {
             self.skip_whitespace_and_comments();
 
             while !self.check_token(&TokenKind::RightParen) && !self.is_at_end() {
-                let key = self.parse_identifier()?;
+                let key_span = self.current_span();
+                let key = self.parse_attribute_key()?;
 
                 if !self.check_token(&TokenKind::Equals) {
                     return Err(Box::new(ParserError::UnexpectedToken {
-                        found: self.current_token().text,
+                        found: self.found_text(),
                         expected: "=".to_string(),
                         span: self.current_span(),
                     }));
                 }
                 self.advance();
 
-                let value = self.parse_attribute_value()?;
-
-                attributes.insert(key.clone(), value);
+                if let Some(value) = self.parse_checked_attribute_value()? {
+                    self.insert_attribute(&mut attributes, key, value, key_span);
+                }
                 self.skip_whitespace_and_comments();
 
                 if self.check_token(&TokenKind::Comma) {
@@ -829,7 +1305,7 @@ impl Parser {
                 self.advance();
             } else {
                 return Err(Box::new(ParserError::UnexpectedToken {
-                    found: self.current_token().text,
+                    found: self.found_text(),
                     expected: ")".to_string(),
                     span: self.current_span(),
                 }));
@@ -839,6 +1315,77 @@ impl Parser {
         Ok(attributes)
     }
 
+    // The later value wins, but the shadowed one should not disappear silently
+    fn insert_attribute(
+        &mut self,
+        attributes: &mut IndexMap<String, AttributeValue>,
+        key: String,
+        value: AttributeValue,
+        span: Span,
+    ) {
+        if attributes.contains_key(&key) {
+            self.errors.push(ParseError {
+                message: format!("Attribute '{key}' specified twice"),
+                span,
+                severity: ErrorSeverity::Warning,
+                lint: "duplicate-attribute",
+                element: None,
+                first_definition: None,
+            });
+        }
+        attributes.insert(key, value);
+    }
+
+    fn parse_attribute_key(&mut self) -> Result<String, Box<ParserError>> {
+        let key = self.parse_attribute_name()?;
+
+        if self.check_token(&TokenKind::Colon) {
+            self.advance();
+            return Ok(format!("{key}:{}", self.parse_attribute_name()?));
+        }
+
+        Ok(key)
+    }
+
+    // Keywords double as keys, e.g. `(note=...)`
+    fn parse_attribute_name(&mut self) -> Result<String, Box<ParserError>> {
+        let token = self.current_token();
+        if token.kind != TokenKind::Identifier && is_keyword_text(&token.text) {
+            self.advance();
+            return Ok(token.text);
+        }
+
+        self.parse_identifier()
+    }
+
+    fn parse_checked_attribute_value(
+        &mut self,
+    ) -> Result<Option<AttributeValue>, Box<ParserError>> {
+        match self.parse_attribute_value() {
+            Ok(value) => Ok(Some(value)),
+            // The lexer already reported numbers with an unknown unit
+            Err(err)
+                if matches!(&*err, ParserError::InvalidAttributeValue { value, .. }
+                    if invalid_number_suffix(value).is_some()) =>
+            {
+                Ok(None)
+            }
+            Err(err) if matches!(*err, ParserError::InvalidAttributeValue { .. }) => {
+                let error = ParseError {
+                    message: err.to_string(),
+                    span: err.span().clone(),
+                    severity: ErrorSeverity::Error,
+                    lint: "syntax",
+                    element: None,
+                    first_definition: None,
+                };
+                self.errors.push(error);
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
     fn parse_attribute_value(&mut self) -> Result<AttributeValue, Box<ParserError>> {
         match &self.current_token().kind {
             TokenKind::StringLiteral => {
@@ -847,21 +1394,17 @@ impl Parser {
             }
             TokenKind::NumberLiteral => {
                 let text = self.current_token().text;
+                let span = self.current_span();
                 self.advance();
 
-                if text.ends_with('m')
-                    || text.ends_with('s')
-                    || text.ends_with("ms")
-                    || text.ends_with('h')
-                {
+                if parse_duration_millis(&text).is_some() {
                     Ok(AttributeValue::Duration(text))
-                } else if let Ok(num) = text.parse::<f64>() {
-                    Ok(AttributeValue::Number(num))
                 } else {
-                    Err(Box::new(ParserError::InvalidAttributeValue {
-                        value: text,
-                        span: self.current_span(),
-                    }))
+                    text.parse::<f64>()
+                        .map(AttributeValue::Number)
+                        .map_err(|_| {
+                            Box::new(ParserError::InvalidAttributeValue { value: text, span })
+                        })
                 }
             }
             TokenKind::Identifier => {
@@ -875,30 +1418,27 @@ impl Parser {
                 }
             }
             _ => Err(Box::new(ParserError::UnexpectedToken {
-                found: self.current_token().text,
+                found: self.found_text(),
                 expected: "attribute value (string, number, boolean)".to_string(),
                 span: self.current_span(),
             })),
         }
     }
 
-    fn parse_condition_expression(&mut self) -> Result<String, Box<ParserError>> {
-        let mut condition = String::new();
-        let mut token_count = 0;
-
-        while !self.check_token(&TokenKind::RightBracket) && !self.is_at_end() && token_count < 50 {
-            if !condition.is_empty() {
-                let current_text = &self.current_token().text;
-                if !matches!(current_text.as_str(), "=" | "!" | "<" | ">" | "&" | "|") {
-                    condition.push(' ');
-                }
-            }
-            condition.push_str(&self.current_token().text);
+    fn parse_condition_expression(&mut self) -> Result<ConditionExpr, Box<ParserError>> {
+        let span = self.current_span();
+        let start = self.position;
+        while !self.check_token(&TokenKind::RightBracket) && !self.is_at_end() {
             self.advance();
-            token_count += 1;
         }
-
-        if condition.is_empty() {
+        let tokens = &self.tokens[start..self.position];
+
+        if tokens.iter().all(|token| {
+            matches!(
+                token.kind,
+                TokenKind::Newline | TokenKind::CarriageReturnNewline
+            )
+        }) {
             return Err(Box::new(ParserError::UnexpectedToken {
                 found: "]".to_string(),
                 expected: "condition expression".to_string(),
@@ -906,13 +1446,44 @@ impl Parser {
             }));
         }
 
-        Ok(condition)
+        // Keep the flow with its raw text so one bad condition does not drop the element
+        match parse_condition(tokens, self.max_depth) {
+            Ok(condition) => Ok(condition),
+            Err(reason) => {
+                let text = tokens
+                    .iter()
+                    .map(|token| token.text.trim())
+                    .filter(|text| !text.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                self.errors.push(ParseError {
+                    message: format!("Invalid condition '{text}': {reason}"),
+                    span,
+                    severity: ErrorSeverity::Error,
+                    lint: "syntax",
+                    element: None,
+                    first_definition: None,
+                });
+                Ok(ConditionExpr::Raw(text))
+            }
+        }
+    }
+
+    // `Name` or `namespace::Name` for elements of an imported file
+    fn parse_qualified_name(&mut self) -> Result<String, Box<ParserError>> {
+        let name = self.parse_identifier()?;
+        if !self.check_token(&TokenKind::Namespace) {
+            return Ok(name);
+        }
+
+        self.advance();
+        Ok(format!("{name}::{}", self.parse_identifier()?))
     }
 
     fn parse_identifier(&mut self) -> Result<String, Box<ParserError>> {
         if !self.check_token(&TokenKind::Identifier) {
             return Err(Box::new(ParserError::UnexpectedToken {
-                found: self.current_token().text,
+                found: self.found_text(),
                 expected: "identifier".to_string(),
                 span: self.current_span(),
             }));
@@ -926,24 +1497,48 @@ impl Parser {
     fn parse_string_literal(&mut self) -> Result<String, Box<ParserError>> {
         if !self.check_token(&TokenKind::StringLiteral) {
             return Err(Box::new(ParserError::UnexpectedToken {
-                found: self.current_token().text,
+                found: self.found_text(),
                 expected: "string literal".to_string(),
                 span: self.current_span(),
             }));
         }
 
-        let mut literal = self.current_token().text;
+        let text = self.current_token().text;
+        let literal = unescape_string_literal(&text).unwrap_or_else(|escape| {
+            let error = ParserError::InvalidEscape {
+                escape,
+                span: self.current_span(),
+            };
+            self.errors.push(ParseError {
+                message: error.to_string(),
+                span: error.span().clone(),
+                severity: ErrorSeverity::Error,
+                lint: "syntax",
+                element: None,
+                first_definition: None,
+            });
+            text.trim_matches('"').to_string()
+        });
         self.advance();
 
-        if literal.len() >= 2 && literal.starts_with('"') && literal.ends_with('"') {
-            literal = literal[1..literal.len() - 1].to_string();
-            literal = literal.replace("\\\"", "\"");
-            literal = literal.replace("\\\\", "\\");
-            literal = literal.replace("\\n", "\n");
-            literal = literal.replace("\\t", "\t");
+        Ok(literal)
+    }
+
+    // `start Web` names the event when a scope has several of them
+    fn parse_event_name(&mut self) -> Result<Option<String>, Box<ParserError>> {
+        if self.check_token(&TokenKind::Identifier) {
+            self.parse_identifier().map(Some)
+        } else {
+            Ok(None)
         }
+    }
 
-        Ok(literal)
+    fn parse_label(&mut self) -> Result<Option<String>, Box<ParserError>> {
+        if self.check_token(&TokenKind::StringLiteral) {
+            self.parse_string_literal().map(Some)
+        } else {
+            Ok(None)
+        }
     }
 
     fn current_token(&self) -> Token {
@@ -967,6 +1562,43 @@ impl Parser {
         self.current_token().span
     }
 
+    // The current token as an error names it, tokens without text (end of file) by their kind
+    fn found_text(&self) -> String {
+        let token = self.current_token();
+        if token.text.is_empty() {
+            token.kind.to_string()
+        } else {
+            token.text
+        }
+    }
+
+    // The tokens from `start` up to the current position, as one span
+    fn span_since(&self, start: usize) -> Span {
+        let mut span = self.tokens[start].span.clone();
+        if let Some(last) = self.tokens[start..self.position].last() {
+            span.end = last.span.end;
+        }
+        span
+    }
+
+    // Where `name` is written among the tokens parsed since `start`
+    fn name_span(&self, start: usize, name: &str) -> Span {
+        let last = name.rsplit("::").next().unwrap_or(name);
+        self.tokens[start..self.position]
+            .iter()
+            .find(|token| token.text == last)
+            .map_or_else(
+                || self.tokens[start].span.clone(),
+                |token| token.span.clone(),
+            )
+    }
+
+    fn next_is_flow_arrow(&self) -> bool {
+        self.tokens
+            .get(self.position + 1)
+            .is_some_and(|token| is_flow_arrow(&token.kind))
+    }
+
     fn check_token(&self, kind: &TokenKind) -> bool {
         &self.current_token().kind == kind
     }
@@ -987,8 +1619,10 @@ impl Parser {
             Ok(self.advance())
         } else {
             Err(Box::new(ParserError::UnexpectedToken {
-                found: self.current_token().text,
-                expected: format!("{expected:?}"),
+                found: self.found_text(),
+                expected: expected
+                    .literal()
+                    .map_or_else(|| expected.to_string(), |literal| format!("'{literal}'")),
                 span: self.current_span(),
             }))
         }
@@ -1007,6 +1641,81 @@ impl Parser {
         }
     }
 
+    // A loop iteration that consumed nothing would retry the same token forever
+    fn ensure_progress(&mut self, start: usize) {
+        if self.position > start || self.is_at_end() {
+            return;
+        }
+
+        self.position = start;
+        self.errors.push(ParseError {
+            message: format!("Skipping unexpected token '{}'", self.current_token().text),
+            span: self.current_span(),
+            severity: ErrorSeverity::Warning,
+            lint: "syntax",
+            element: None,
+            first_definition: None,
+        });
+        self.advance();
+    }
+
+    fn parse_nested<T: Default>(
+        &mut self,
+        body: impl FnOnce(&mut Self) -> Result<T, Box<ParserError>>,
+    ) -> Result<T, Box<ParserError>> {
+        if self.depth >= self.max_depth {
+            self.errors.push(ParseError {
+                message: "Maximum nesting depth exceeded".to_string(),
+                span: self.current_span(),
+                severity: ErrorSeverity::Error,
+                lint: "nesting-depth",
+                element: None,
+                first_definition: None,
+            });
+            self.skip_to_closing_brace();
+            return Ok(T::default());
+        }
+
+        self.depth += 1;
+        let result = body(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn skip_to_closing_brace(&mut self) {
+        let mut open = 0usize;
+
+        while !self.is_at_end() {
+            match self.current_token().kind {
+                TokenKind::LeftBrace => open += 1,
+                TokenKind::RightBrace if open == 0 => break,
+                TokenKind::RightBrace => open -= 1,
+                _ => {}
+            }
+            self.advance();
+        }
+    }
+
+    // Stray tokens between declarations are reported once and skipped, so the
+    // processes after them still parse
+    fn skip_to_next_declaration(&mut self, document: &mut AstDocument) {
+        document.add_error(
+            format!(
+                "Unexpected token '{}', expected '{}' or '{}'",
+                self.current_token().text,
+                TokenKind::Process,
+                TokenKind::Import
+            ),
+            self.current_span(),
+        );
+        while !self.is_at_end()
+            && !self.check_token(&TokenKind::Process)
+            && !self.check_token(&TokenKind::Import)
+        {
+            self.advance();
+        }
+    }
+
     fn recover_to_next_statement(&mut self) {
         while !self.is_at_end() {
             match self.current_token().kind {
@@ -1031,3 +1740,119 @@ pub fn parse_tokens_with_validation(tokens: Vec<Token>) -> AstDocument {
     let mut parser = Parser::new(tokens);
     parser.parse_with_validation()
 }
+
+#[must_use]
+pub fn parse_tokens_with_profile(tokens: Vec<Token>, profile: Profile) -> AstDocument {
+    let mut parser = Parser::new(tokens);
+    parser.parse_with_profile(profile)
+}
+
+// The name flows use to refer to `element`, if it can be referred to at all
+fn chain_name(element: &ProcessElement) -> Option<String> {
+    match element {
+        ProcessElement::StartEvent { id, .. } => {
+            Some(id.clone().unwrap_or_else(|| "start".to_string()))
+        }
+        ProcessElement::EndEvent { id, .. } => {
+            Some(id.clone().unwrap_or_else(|| "end".to_string()))
+        }
+        ProcessElement::Gateway { id, .. }
+        | ProcessElement::IntermediateEvent { id, .. }
+        | ProcessElement::Annotation { id, .. } => id.clone(),
+        ProcessElement::Task { id, .. }
+        | ProcessElement::Subprocess { id, .. }
+        | ProcessElement::EventSubprocess { id, .. }
+        | ProcessElement::CallActivity { id, .. }
+        | ProcessElement::DataObject { id, .. } => Some(id.clone()),
+        ProcessElement::Pool { .. } | ProcessElement::Group { .. } => None,
+    }
+}
+
+const fn is_start_event(element: &ProcessElement) -> bool {
+    matches!(element, ProcessElement::StartEvent { .. })
+}
+
+// The event `end` names, named end events are told apart by their own names
+const fn is_end_event(element: &ProcessElement) -> bool {
+    matches!(element, ProcessElement::EndEvent { id: None, .. })
+}
+
+// A link written inline gives way to the same flow written out on its own line
+fn merge_chained(flows: &mut Vec<Flow>, chained: Vec<Flow>) {
+    for link in chained {
+        let written = flows.iter().any(|flow| {
+            flow.from == link.from
+                && flow.to == link.to
+                && flow.flow_type == link.flow_type
+                && flow.condition == link.condition
+        });
+        if !written {
+            flows.push(link);
+        }
+    }
+    flows.sort_by_key(|flow| flow.span.start);
+}
+
+fn is_keyword_text(text: &str) -> bool {
+    text.starts_with(|c: char| c.is_ascii_alphabetic())
+        && text.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn gateway_mode(text: &str) -> Option<GatewayMode> {
+    match text {
+        "split" => Some(GatewayMode::Split),
+        "join" => Some(GatewayMode::Join),
+        "mixed" => Some(GatewayMode::Mixed),
+        _ => None,
+    }
+}
+
+// Unknown escapes are kept as written, a malformed `\u` escape is returned as the error
+pub(crate) fn unescape_string_literal(text: &str) -> Result<String, String> {
+    if text.len() < 2 || !text.starts_with('"') || !text.ends_with('"') {
+        return Ok(text.to_string());
+    }
+
+    let mut unescaped = String::new();
+    let mut chars = text[1..text.len() - 1].chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('"') => unescaped.push('"'),
+            Some('\\') | None => unescaped.push('\\'),
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some('t') => unescaped.push('\t'),
+            Some('u') => {
+                let braced = chars.next_if_eq(&'{').is_some();
+                let mut digits = String::new();
+                while digits.len() < if braced { 6 } else { 4 }
+                    && let Some(digit) = chars.next_if(char::is_ascii_hexdigit)
+                {
+                    digits.push(digit);
+                }
+                let closed = !braced || chars.next_if_eq(&'}').is_some();
+
+                let decoded = u32::from_str_radix(&digits, 16)
+                    .ok()
+                    .filter(|_| closed && (braced || digits.len() == 4))
+                    .and_then(char::from_u32);
+                match decoded {
+                    Some(decoded) => unescaped.push(decoded),
+                    None if braced => return Err(format!("\\u{{{digits}")),
+                    None => return Err(format!("\\u{digits}")),
+                }
+            }
+            Some(other) => {
+                unescaped.push('\\');
+                unescaped.push(other);
+            }
+        }
+    }
+
+    Ok(unescaped)
+}
@@ -1,10 +1,14 @@
-use std::collections::HashMap;
+use indexmap::IndexMap;
 
 use crate::{
     lexer::{Token, TokenKind},
-    parser::ast::{
-        ErrorSeverity, Flow, FlowType, GatewayBranch, GatewayType, ParseError, ProcessElement,
-        TaskType,
+    parser::{
+        DEFAULT_MAX_DEPTH,
+        ast::{
+            ConditionExpr, ErrorSeverity, Flow, FlowType, GatewayBranch, GatewayMode, GatewayType,
+            ParseError, ProcessElement, TaskType,
+        },
+        condition::parse_condition,
     },
 };
 
@@ -12,6 +16,8 @@ pub struct ErrorRecovery {
     pub recovered_elements: Vec<ProcessElement>,
     pub recovered_flows: Vec<Flow>,
     pub errors: Vec<ParseError>,
+    // Placeholder names made up for missing ids and targets
+    pub synthetic_ids: Vec<String>,
 }
 
 impl ErrorRecovery {
@@ -21,6 +27,7 @@ impl ErrorRecovery {
             recovered_elements: Vec::new(),
             recovered_flows: Vec::new(),
             errors: Vec::new(),
+            synthetic_ids: Vec::new(),
         }
     }
 
@@ -41,7 +48,7 @@ impl ErrorRecovery {
                 let element = ProcessElement::StartEvent {
                     id: None,
                     event_type: None,
-                    attributes: std::collections::HashMap::new(),
+                    attributes: IndexMap::new(),
                     span,
                 };
                 Some((element, start_pos + 1))
@@ -50,7 +57,7 @@ impl ErrorRecovery {
                 let element = ProcessElement::EndEvent {
                     id: None,
                     event_type: None,
-                    attributes: std::collections::HashMap::new(),
+                    attributes: IndexMap::new(),
                     span,
                 };
                 Some((element, start_pos + 1))
@@ -58,12 +65,17 @@ impl ErrorRecovery {
             TokenKind::Task | TokenKind::User | TokenKind::Service | TokenKind::Script => {
                 self.recover_task(tokens, start_pos)
             }
-            TokenKind::Xor | TokenKind::And => self.recover_gateway(tokens, start_pos),
+            TokenKind::Xor | TokenKind::And | TokenKind::Or => {
+                self.recover_gateway(tokens, start_pos)
+            }
             _ => {
                 self.errors.push(ParseError {
                     message: format!("Cannot recover from token '{}'", token.text),
                     span,
                     severity: ErrorSeverity::Error,
+                    lint: "syntax",
+                    element: None,
+                    first_definition: None,
                 });
                 None
             }
@@ -97,16 +109,20 @@ impl ErrorRecovery {
                 message: "Missing task identifier, using default".to_string(),
                 span: span.clone(),
                 severity: ErrorSeverity::Warning,
+                lint: "syntax",
+                element: None,
+                first_definition: None,
             });
-            format!("Task_{start_pos}")
+            self.synthesize(format!("Task_{start_pos}"))
         };
 
         pos = self.skip_malformed_attributes(tokens, pos);
 
         let element = ProcessElement::Task {
             id,
+            label: None,
             task_type,
-            attributes: HashMap::new(),
+            attributes: IndexMap::new(),
             span,
         };
 
@@ -124,6 +140,7 @@ impl ErrorRecovery {
         let gateway_type = match &tokens[pos].kind {
             TokenKind::Xor => GatewayType::Exclusive,
             TokenKind::And => GatewayType::Parallel,
+            TokenKind::Or => GatewayType::Inclusive,
             _ => return None,
         };
 
@@ -156,6 +173,9 @@ impl ErrorRecovery {
                 message: "Gateway missing branches block".to_string(),
                 span: span.clone(),
                 severity: ErrorSeverity::Error,
+                lint: "syntax",
+                element: None,
+                first_definition: None,
             });
             Vec::new()
         };
@@ -163,7 +183,9 @@ impl ErrorRecovery {
         let element = ProcessElement::Gateway {
             id,
             gateway_type,
+            mode: GatewayMode::Split,
             branches,
+            attributes: IndexMap::new(),
             span,
         };
 
@@ -199,23 +221,13 @@ impl ErrorRecovery {
         let span = tokens[pos].span.clone();
 
         let (condition, is_default) = if tokens[pos].kind == TokenKind::LeftBracket {
-            pos += 1;
-            let mut cond = String::new();
-            while pos < tokens.len() && tokens[pos].kind != TokenKind::RightBracket {
-                if !cond.is_empty() {
-                    cond.push(' ');
-                }
-                cond.push_str(&tokens[pos].text);
-                pos += 1;
-            }
-            if pos < tokens.len() {
-                pos += 1;
-            }
+            let (cond, next) = recover_condition(tokens, pos + 1);
+            pos = next;
             (Some(cond), false)
         } else if tokens[pos].kind == TokenKind::DefaultFlow {
             (None, true)
         } else if tokens[pos].kind == TokenKind::Identifier {
-            let cond = tokens[pos].text.clone();
+            let cond = ConditionExpr::Identifier(tokens[pos].text.clone());
             pos += 1;
             (Some(cond), false)
         } else {
@@ -232,29 +244,40 @@ impl ErrorRecovery {
                 message: "Missing arrow in gateway branch".to_string(),
                 span,
                 severity: ErrorSeverity::Error,
+                lint: "syntax",
+                element: None,
+                first_definition: None,
             });
             return None;
         }
         pos += 1;
 
-        let target = if pos < tokens.len() && tokens[pos].kind == TokenKind::Identifier {
-            let target = tokens[pos].text.clone();
-            pos += 1;
-            target
-        } else {
-            self.errors.push(ParseError {
-                message: "Missing target in gateway branch".to_string(),
-                span: span.clone(),
-                severity: ErrorSeverity::Error,
-            });
-            format!("UnknownTarget_{pos}")
-        };
+        let (target, target_span) =
+            if pos < tokens.len() && tokens[pos].kind == TokenKind::Identifier {
+                let target = (tokens[pos].text.clone(), tokens[pos].span.clone());
+                pos += 1;
+                target
+            } else {
+                self.errors.push(ParseError {
+                    message: "Missing target in gateway branch".to_string(),
+                    span: span.clone(),
+                    severity: ErrorSeverity::Error,
+                    lint: "syntax",
+                    element: None,
+                    first_definition: None,
+                });
+                (
+                    self.synthesize(format!("UnknownTarget_{pos}")),
+                    span.clone(),
+                )
+            };
 
         let branch = GatewayBranch {
             condition,
             target,
             is_default,
             span,
+            target_span,
         };
 
         Some((branch, pos))
@@ -280,6 +303,7 @@ impl ErrorRecovery {
                         | TokenKind::Script
                         | TokenKind::Xor
                         | TokenKind::And
+                        | TokenKind::Or
                         | TokenKind::RightBrace
                 )
             {
@@ -338,8 +362,8 @@ impl ErrorRecovery {
             return None;
         };
 
-        let to = if pos < tokens.len() && tokens[pos].kind == TokenKind::Identifier {
-            let to = tokens[pos].text.clone();
+        let (to, to_span) = if pos < tokens.len() && tokens[pos].kind == TokenKind::Identifier {
+            let to = (tokens[pos].text.clone(), tokens[pos].span.clone());
             pos += 1;
             to
         } else {
@@ -347,23 +371,19 @@ impl ErrorRecovery {
                 message: "Missing target in flow".to_string(),
                 span: tokens[start_pos].span.clone(),
                 severity: ErrorSeverity::Error,
+                lint: "syntax",
+                element: None,
+                first_definition: None,
             });
-            format!("UnknownTarget_{pos}")
+            (
+                self.synthesize(format!("UnknownTarget_{pos}")),
+                tokens[start_pos].span.clone(),
+            )
         };
 
         let condition = if pos < tokens.len() && tokens[pos].kind == TokenKind::LeftBracket {
-            pos += 1;
-            let mut cond = String::new();
-            while pos < tokens.len() && tokens[pos].kind != TokenKind::RightBracket {
-                if !cond.is_empty() {
-                    cond.push(' ');
-                }
-                cond.push_str(&tokens[pos].text);
-                pos += 1;
-            }
-            if pos < tokens.len() {
-                pos += 1;
-            }
+            let (cond, next) = recover_condition(tokens, pos + 1);
+            pos = next;
             Some(cond)
         } else {
             None
@@ -375,11 +395,18 @@ impl ErrorRecovery {
             flow_type,
             condition,
             span: tokens[start_pos].span.clone(),
+            from_span: tokens[start_pos].span.clone(),
+            to_span,
         };
 
         Some((flow, pos))
     }
 
+    fn synthesize(&mut self, id: String) -> String {
+        self.synthetic_ids.push(id.clone());
+        id
+    }
+
     #[must_use]
     pub fn find_sync_point(&self, tokens: &[Token], start_pos: usize) -> usize {
         let mut pos = start_pos;
@@ -396,6 +423,7 @@ impl ErrorRecovery {
                 | TokenKind::Script
                 | TokenKind::Xor
                 | TokenKind::And
+                | TokenKind::Or
                 | TokenKind::Event
                 | TokenKind::Process
                 | TokenKind::Import
@@ -416,3 +444,23 @@ impl Default for ErrorRecovery {
         Self::new()
     }
 }
+
+// Reads a bracketed condition starting after `[`, returning the position after `]`
+fn recover_condition(tokens: &[Token], start: usize) -> (ConditionExpr, usize) {
+    let mut end = start;
+    while end < tokens.len() && tokens[end].kind != TokenKind::RightBracket {
+        end += 1;
+    }
+
+    let condition = parse_condition(&tokens[start..end], DEFAULT_MAX_DEPTH).unwrap_or_else(|_| {
+        ConditionExpr::Raw(
+            tokens[start..end]
+                .iter()
+                .map(|token| token.text.as_str())
+                .collect::<Vec<_>>()
+                .join(" "),
+        )
+    });
+
+    (condition, (end + 1).min(tokens.len()))
+}
@@ -0,0 +1,508 @@
+use std::{collections::HashMap, net::Ipv6Addr, path::Path};
+
+use crate::{
+    diagnostics::{
+        DiagnosticError, Severity, UNDECLARED_VARIABLE, suggestions::suggest_identifiers,
+    },
+    lexer::{Span, multi_file::MultiFileLexer},
+    parser::{
+        ast::{
+            AstDocument, AttributeValue, ConditionExpr, EventType, Flow, ImportDeclaration,
+            ProcessElement, TaskType,
+        },
+        condition::parse_condition_str,
+        parse_tokens,
+    },
+};
+
+pub fn resolve_imports(document: &AstDocument, lexer: &mut MultiFileLexer) -> Vec<DiagnosticError> {
+    let mut errors = Vec::new();
+    let mut namespaces: HashMap<&str, Option<Vec<String>>> = HashMap::new();
+
+    for import in &document.imports {
+        let exported = match load_exported_names(import, lexer) {
+            Ok(names) => Some(names),
+            Err(error) => {
+                errors.push(*error);
+                None
+            }
+        };
+
+        if let Some(names) = &exported {
+            for item in &import.items {
+                if !names.contains(item) {
+                    errors.push(DiagnosticError::UndefinedReference {
+                        name: item.clone(),
+                        span: import.span.clone(),
+                        severity: Severity::Error,
+                        suggestions: suggest_identifiers(item, names),
+                    });
+                }
+            }
+        }
+
+        if let Some(alias) = &import.alias {
+            namespaces.insert(alias, exported);
+        }
+    }
+
+    // Calls, flow endpoints and gateway branches may all name imported elements
+    let mut references = Vec::new();
+    for process in &document.processes {
+        collect_calls(&process.elements, &mut references);
+        collect_flow_references(&process.elements, &process.flows, &mut references);
+    }
+
+    for (called_element, span) in references {
+        let Some((namespace, name)) = called_element.split_once("::") else {
+            continue;
+        };
+
+        match namespaces.get(namespace) {
+            Some(Some(names)) if !names.iter().any(|exported| exported == name) => {
+                errors.push(DiagnosticError::UndefinedReference {
+                    name: called_element.to_string(),
+                    span: span.clone(),
+                    severity: Severity::Error,
+                    suggestions: suggest_identifiers(name, names)
+                        .into_iter()
+                        .map(|suggestion| format!("{namespace}::{suggestion}"))
+                        .collect(),
+                });
+            }
+            Some(_) => {}
+            None => {
+                errors.push(DiagnosticError::ImportError {
+                    message: format!("Unknown import namespace '{namespace}'"),
+                    span: span.clone(),
+                    severity: Severity::Error,
+                    path: String::new(),
+                });
+            }
+        }
+    }
+
+    errors
+}
+
+// Calls without a namespace must name a process or subprocess of this document,
+// or an item imported by name
+#[must_use]
+pub fn check_local_calls(document: &AstDocument) -> Vec<DiagnosticError> {
+    let mut names: Vec<String> = document
+        .processes
+        .iter()
+        .map(|process| process.name.clone())
+        .chain(
+            document
+                .imports
+                .iter()
+                .flat_map(|import| import.items.clone()),
+        )
+        .collect();
+    let mut calls = Vec::new();
+    for process in &document.processes {
+        collect_subprocess_ids(&process.elements, &mut names);
+        collect_calls(&process.elements, &mut calls);
+    }
+
+    calls
+        .into_iter()
+        .filter(|(called_element, _)| {
+            !called_element.contains("::") && !names.iter().any(|name| name == called_element)
+        })
+        .map(
+            |(called_element, span)| DiagnosticError::UndefinedReference {
+                name: called_element.to_string(),
+                span: span.clone(),
+                severity: Severity::Error,
+                suggestions: suggest_identifiers(called_element, &names),
+            },
+        )
+        .collect()
+}
+
+// Service task endpoints must be an absolute path or an http(s) URL. Expressions
+// such as `${baseUrl}/orders` are resolved by the engine and left alone
+#[must_use]
+pub fn check_service_endpoints(document: &AstDocument) -> Vec<DiagnosticError> {
+    let mut endpoints = Vec::new();
+    for process in &document.processes {
+        collect_endpoints(&process.elements, &mut endpoints);
+    }
+
+    endpoints
+        .into_iter()
+        .filter(|(_, value, _)| !value.is_some_and(is_valid_endpoint))
+        .map(|(id, value, span)| DiagnosticError::InvalidAttribute {
+            attribute: value.map_or_else(
+                || "endpoint".to_string(),
+                |value| format!("endpoint=\"{value}\""),
+            ),
+            element: id.to_string(),
+            span: span.clone(),
+            severity: Severity::Warning,
+            valid_attributes: value.map(endpoint_suggestions).unwrap_or_default(),
+            lint: "service-endpoint",
+        })
+        .collect()
+}
+
+fn is_valid_endpoint(value: &str) -> bool {
+    if value.contains("${") {
+        return true;
+    }
+    if value.chars().any(char::is_whitespace) {
+        return false;
+    }
+    if let Some(path) = value.strip_prefix('/') {
+        return !path.starts_with('/');
+    }
+
+    match value.split_once("://") {
+        Some((scheme, rest)) => {
+            matches!(scheme.to_ascii_lowercase().as_str(), "http" | "https") && is_valid_host(rest)
+        }
+        None => false,
+    }
+}
+
+fn is_valid_host(rest: &str) -> bool {
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    // Credentials come before the host, e.g. `http://user@host/`
+    let authority = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+
+    // An IPv6 address is bracketed, e.g. `http://[::1]:8080/`
+    if let Some(literal) = authority.strip_prefix('[') {
+        return literal.split_once(']').is_some_and(|(address, port)| {
+            address.parse::<Ipv6Addr>().is_ok()
+                && (port.is_empty() || port.strip_prefix(':').is_some_and(is_valid_port))
+        });
+    }
+
+    let (host, port) = authority
+        .rsplit_once(':')
+        .map_or((authority, None), |(host, port)| (host, Some(port)));
+
+    !host.is_empty()
+        && !host.starts_with(['.', '-'])
+        && !host.ends_with(['.', '-'])
+        && host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+        && port.is_none_or(is_valid_port)
+}
+
+fn is_valid_port(port: &str) -> bool {
+    !port.is_empty() && port.chars().all(|c| c.is_ascii_digit())
+}
+
+// A misspelled scheme gets both http and https offered, never a single
+// suggestion, since the diagnostic points at the task keyword
+fn endpoint_suggestions(value: &str) -> Vec<String> {
+    match value.split_once("://") {
+        Some((_, rest)) if is_valid_host(rest) => ["http", "https"]
+            .iter()
+            .map(|scheme| format!("{scheme}://{rest}"))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+// Names read by conditions that the process does not declare with `var`. A process
+// that declares nothing is only checked with `every_process`, when the
+// `undeclared-variable` lint is raised
+#[must_use]
+pub fn check_condition_variables(
+    document: &AstDocument,
+    every_process: bool,
+) -> Vec<DiagnosticError> {
+    let mut errors = Vec::new();
+
+    for process in &document.processes {
+        if process.variables.is_empty() && !every_process {
+            continue;
+        }
+        let declared: Vec<String> = process
+            .variables
+            .iter()
+            .map(|variable| variable.name.clone())
+            .collect();
+        let mut conditions = Vec::new();
+        collect_conditions(&process.elements, &process.flows, &mut conditions);
+
+        for (condition, span) in &conditions {
+            for name in condition.identifiers() {
+                let root = name.split('.').next().unwrap_or(name);
+                if declared.iter().any(|declared| declared == root) {
+                    continue;
+                }
+                errors.push(DiagnosticError::SyntaxError {
+                    message: format!("Undeclared variable '{root}' in condition"),
+                    span: (*span).clone(),
+                    severity: Severity::Warning,
+                    suggestions: suggest_identifiers(root, &declared),
+                    lint: UNDECLARED_VARIABLE,
+                });
+            }
+        }
+    }
+
+    errors
+}
+
+// Conditions on flows and branches, and conditional event expressions that parse
+fn collect_conditions<'a>(
+    elements: &'a [ProcessElement],
+    flows: &'a [Flow],
+    conditions: &mut Vec<(ConditionExpr, &'a Span)>,
+) {
+    conditions.extend(
+        flows
+            .iter()
+            .filter_map(|flow| Some((flow.condition.clone()?, &flow.span))),
+    );
+
+    for element in elements {
+        match element {
+            ProcessElement::Gateway { branches, .. } => conditions.extend(
+                branches
+                    .iter()
+                    .filter_map(|branch| Some((branch.condition.clone()?, &branch.span))),
+            ),
+            ProcessElement::StartEvent {
+                event_type: Some(EventType::Conditional(expression)),
+                span,
+                ..
+            }
+            | ProcessElement::IntermediateEvent {
+                event_type: EventType::Conditional(expression),
+                span,
+                ..
+            } => conditions.extend(
+                parse_condition_str(expression)
+                    .ok()
+                    .map(|condition| (condition, span)),
+            ),
+            ProcessElement::Subprocess {
+                elements, flows, ..
+            } => collect_conditions(elements, flows, conditions),
+            ProcessElement::EventSubprocess {
+                trigger,
+                elements,
+                flows,
+                span,
+                ..
+            } => {
+                if let EventType::Conditional(expression) = trigger
+                    && let Ok(condition) = parse_condition_str(expression)
+                {
+                    conditions.push((condition, span));
+                }
+                collect_conditions(elements, flows, conditions);
+            }
+            ProcessElement::Pool {
+                lanes,
+                elements,
+                flows,
+                ..
+            } => {
+                for lane in lanes {
+                    collect_conditions(&lane.elements, &[], conditions);
+                }
+                collect_conditions(elements, flows, conditions);
+            }
+            ProcessElement::Group { elements, .. } => {
+                collect_conditions(elements, &[], conditions);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn load_exported_names(
+    import: &ImportDeclaration,
+    lexer: &mut MultiFileLexer,
+) -> Result<Vec<String>, Box<DiagnosticError>> {
+    let importing_dir = import.span.file.parent().unwrap_or_else(|| Path::new(""));
+    let path = importing_dir.join(&import.path);
+
+    let tokens = lexer.tokenize_file(&path).map_err(|error| {
+        Box::new(DiagnosticError::ImportError {
+            message: format!("Cannot load '{}': {error}", import.path),
+            span: import.span.clone(),
+            severity: Severity::Error,
+            path: import.path.clone(),
+        })
+    })?;
+
+    let imported = parse_tokens(tokens);
+    let mut names = Vec::new();
+    for process in &imported.processes {
+        names.push(process.name.clone());
+        collect_element_ids(&process.elements, &mut names);
+    }
+    names.sort();
+    names.dedup();
+
+    Ok(names)
+}
+
+fn collect_element_ids(elements: &[ProcessElement], names: &mut Vec<String>) {
+    for element in elements {
+        match element {
+            ProcessElement::Task { id, .. } | ProcessElement::CallActivity { id, .. } => {
+                names.push(id.clone());
+            }
+            ProcessElement::Subprocess { id, elements, .. }
+            | ProcessElement::EventSubprocess { id, elements, .. } => {
+                names.push(id.clone());
+                collect_element_ids(elements, names);
+            }
+            ProcessElement::Pool {
+                lanes, elements, ..
+            } => {
+                for lane in lanes {
+                    collect_element_ids(&lane.elements, names);
+                }
+                collect_element_ids(elements, names);
+            }
+            ProcessElement::Group { elements, .. } => collect_element_ids(elements, names),
+            ProcessElement::StartEvent { .. }
+            | ProcessElement::EndEvent { .. }
+            | ProcessElement::Gateway { .. }
+            | ProcessElement::IntermediateEvent { .. }
+            | ProcessElement::Annotation { .. }
+            | ProcessElement::DataObject { .. } => {}
+        }
+    }
+}
+
+pub(crate) fn collect_flow_references<'a>(
+    elements: &'a [ProcessElement],
+    flows: &'a [Flow],
+    references: &mut Vec<(&'a str, &'a Span)>,
+) {
+    for flow in flows {
+        references.push((&flow.from, &flow.span));
+        references.push((&flow.to, &flow.span));
+    }
+
+    for element in elements {
+        match element {
+            ProcessElement::Gateway { branches, .. } => {
+                references.extend(
+                    branches
+                        .iter()
+                        .map(|branch| (branch.target.as_str(), &branch.span)),
+                );
+            }
+            ProcessElement::Subprocess {
+                elements, flows, ..
+            }
+            | ProcessElement::EventSubprocess {
+                elements, flows, ..
+            } => collect_flow_references(elements, flows, references),
+            ProcessElement::Pool {
+                lanes,
+                elements,
+                flows,
+                ..
+            } => {
+                for lane in lanes {
+                    collect_flow_references(&lane.elements, &[], references);
+                }
+                collect_flow_references(elements, flows, references);
+            }
+            ProcessElement::Group { elements, .. } => {
+                collect_flow_references(elements, &[], references);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn collect_calls<'a>(elements: &'a [ProcessElement], calls: &mut Vec<(&'a str, &'a Span)>) {
+    for element in elements {
+        match element {
+            ProcessElement::CallActivity {
+                called_element,
+                span,
+                ..
+            } => calls.push((called_element, span)),
+            ProcessElement::Subprocess { elements, .. }
+            | ProcessElement::EventSubprocess { elements, .. }
+            | ProcessElement::Group { elements, .. } => collect_calls(elements, calls),
+            ProcessElement::Pool {
+                lanes, elements, ..
+            } => {
+                for lane in lanes {
+                    collect_calls(&lane.elements, calls);
+                }
+                collect_calls(elements, calls);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn collect_endpoints<'a>(
+    elements: &'a [ProcessElement],
+    endpoints: &mut Vec<(&'a str, Option<&'a str>, &'a Span)>,
+) {
+    for element in elements {
+        match element {
+            ProcessElement::Task {
+                id,
+                task_type: TaskType::Service,
+                attributes,
+                span,
+                ..
+            } => {
+                if let Some(value) = attributes.get("endpoint") {
+                    let value = match value {
+                        AttributeValue::String(value) => Some(value.as_str()),
+                        _ => None,
+                    };
+                    endpoints.push((id, value, span));
+                }
+            }
+            ProcessElement::Subprocess { elements, .. }
+            | ProcessElement::EventSubprocess { elements, .. }
+            | ProcessElement::Group { elements, .. } => collect_endpoints(elements, endpoints),
+            ProcessElement::Pool {
+                lanes, elements, ..
+            } => {
+                for lane in lanes {
+                    collect_endpoints(&lane.elements, endpoints);
+                }
+                collect_endpoints(elements, endpoints);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn collect_subprocess_ids(elements: &[ProcessElement], names: &mut Vec<String>) {
+    for element in elements {
+        match element {
+            ProcessElement::Subprocess { id, elements, .. } => {
+                names.push(id.clone());
+                collect_subprocess_ids(elements, names);
+            }
+            ProcessElement::EventSubprocess { elements, .. }
+            | ProcessElement::Group { elements, .. } => collect_subprocess_ids(elements, names),
+            ProcessElement::Pool {
+                lanes, elements, ..
+            } => {
+                for lane in lanes {
+                    collect_subprocess_ids(&lane.elements, names);
+                }
+                collect_subprocess_ids(elements, names);
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,555 @@
+use std::{fmt, str::FromStr};
+
+use indexmap::IndexMap;
+
+use crate::{
+    lexer::Span,
+    parser::{
+        ast::{AttributeValue, ErrorSeverity, ProcessDeclaration, ProcessElement, TaskType},
+        validator::SyntaxError,
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Placement {
+    Process,
+    Activity,
+    Event,
+    Gateway,
+    Pool,
+}
+
+pub const ATTRIBUTE_PLACEMENTS: &[(&str, &[Placement])] = &[
+    ("async", &[Placement::Activity]),
+    ("asyncBefore", &[Placement::Activity]),
+    ("asyncAfter", &[Placement::Activity]),
+    ("loop", &[Placement::Activity]),
+    ("multiInstance", &[Placement::Activity]),
+    ("sequential", &[Placement::Activity]),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    String,
+    Number,
+    Boolean,
+    Duration,
+}
+
+pub const ATTRIBUTE_TYPES: &[(&str, ValueKind)] = &[
+    ("async", ValueKind::Boolean),
+    ("asyncBefore", ValueKind::Boolean),
+    ("asyncAfter", ValueKind::Boolean),
+    ("loop", ValueKind::Boolean),
+    ("multiInstance", ValueKind::Boolean),
+    ("sequential", ValueKind::Boolean),
+    ("retries", ValueKind::Number),
+    ("timeout", ValueKind::Duration),
+];
+
+pub const CAMUNDA7_SERVICE_IMPLEMENTATIONS: &[&str] = &[
+    "camunda:class",
+    "camunda:delegateExpression",
+    "camunda:expression",
+    "camunda:topic",
+];
+
+impl fmt::Display for Placement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Process => write!(f, "processes"),
+            Self::Activity => write!(f, "activities (tasks, subprocesses, call activities)"),
+            Self::Event => write!(f, "events"),
+            Self::Gateway => write!(f, "gateways"),
+            Self::Pool => write!(f, "pools"),
+        }
+    }
+}
+
+impl ValueKind {
+    #[must_use]
+    pub const fn of(value: &AttributeValue) -> Self {
+        match value {
+            AttributeValue::String(_) => Self::String,
+            AttributeValue::Number(_) => Self::Number,
+            AttributeValue::Boolean(_) => Self::Boolean,
+            AttributeValue::Duration(_) => Self::Duration,
+        }
+    }
+}
+
+impl fmt::Display for ValueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::String => write!(f, "a string"),
+            Self::Number => write!(f, "a number"),
+            Self::Boolean => write!(f, "a boolean"),
+            Self::Duration => write!(f, "a duration"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Profile {
+    #[default]
+    Generic,
+    Camunda7,
+    Zeebe,
+}
+
+impl Profile {
+    pub const ALL: &[Self] = &[Self::Generic, Self::Camunda7, Self::Zeebe];
+
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Generic => "generic",
+            Self::Camunda7 => "camunda7",
+            Self::Zeebe => "zeebe",
+        }
+    }
+
+    #[must_use]
+    pub const fn description(self) -> &'static str {
+        match self {
+            Self::Generic => "Plain BPMN 2.0 without engine-specific checks",
+            Self::Camunda7 => "Camunda Platform 7",
+            Self::Zeebe => "Camunda 8 (Zeebe)",
+        }
+    }
+
+    #[must_use]
+    pub fn rules(self) -> Vec<Box<dyn Rule>> {
+        let mut rules: Vec<Box<dyn Rule>> = vec![
+            Box::new(AttributePlacementRule),
+            Box::new(AttributeTypeRule),
+            Box::new(LoopMarkerRule),
+        ];
+
+        match self {
+            Self::Generic => {}
+            Self::Camunda7 => {
+                rules.push(Box::new(ServiceImplementationRule {
+                    engine: "Camunda 7",
+                    accepted: CAMUNDA7_SERVICE_IMPLEMENTATIONS,
+                }));
+                rules.push(Box::new(ForeignNamespaceRule {
+                    engine: "Camunda 7",
+                    namespace: "zeebe",
+                }));
+            }
+            Self::Zeebe => {
+                rules.push(Box::new(ServiceImplementationRule {
+                    engine: "Zeebe",
+                    accepted: &["zeebe:taskDefinition"],
+                }));
+                rules.push(Box::new(ForeignNamespaceRule {
+                    engine: "Zeebe",
+                    namespace: "camunda",
+                }));
+            }
+        }
+
+        rules
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Profile {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|profile| profile.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                let names: Vec<_> = Self::ALL.iter().map(|profile| profile.name()).collect();
+                format!(
+                    "Unknown profile '{name}', expected one of {}",
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+pub trait Rule {
+    fn name(&self) -> &'static str;
+
+    fn check_process(&self, _process: &ProcessDeclaration, _errors: &mut Vec<SyntaxError>) {}
+
+    fn check_element(&self, _element: &ProcessElement, _errors: &mut Vec<SyntaxError>) {}
+}
+
+pub struct AttributePlacementRule;
+
+impl Rule for AttributePlacementRule {
+    fn name(&self) -> &'static str {
+        "attribute-placement"
+    }
+
+    fn check_process(&self, process: &ProcessDeclaration, errors: &mut Vec<SyntaxError>) {
+        check_attribute_placement(
+            &process.attributes,
+            Placement::Process,
+            "process",
+            &process.span,
+            errors,
+        );
+    }
+
+    fn check_element(&self, element: &ProcessElement, errors: &mut Vec<SyntaxError>) {
+        let (attributes, placement, kind, span) = match element {
+            ProcessElement::StartEvent {
+                attributes, span, ..
+            } => (attributes, Placement::Event, "start event", span),
+            ProcessElement::EndEvent {
+                attributes, span, ..
+            } => (attributes, Placement::Event, "end event", span),
+            ProcessElement::IntermediateEvent {
+                attributes, span, ..
+            } => (attributes, Placement::Event, "intermediate event", span),
+            ProcessElement::Task {
+                attributes, span, ..
+            } => (attributes, Placement::Activity, "task", span),
+            ProcessElement::Subprocess {
+                attributes, span, ..
+            } => (attributes, Placement::Activity, "subprocess", span),
+            ProcessElement::CallActivity {
+                attributes, span, ..
+            } => (attributes, Placement::Activity, "call activity", span),
+            ProcessElement::EventSubprocess {
+                attributes, span, ..
+            } => (attributes, Placement::Activity, "event subprocess", span),
+            ProcessElement::Gateway {
+                attributes, span, ..
+            } => (attributes, Placement::Gateway, "gateway", span),
+            ProcessElement::Pool {
+                attributes, span, ..
+            } => (attributes, Placement::Pool, "pool", span),
+            ProcessElement::Group { .. }
+            | ProcessElement::Annotation { .. }
+            | ProcessElement::DataObject { .. } => return,
+        };
+
+        check_attribute_placement(attributes, placement, kind, span, errors);
+    }
+}
+
+fn check_attribute_placement(
+    attributes: &IndexMap<String, AttributeValue>,
+    placement: Placement,
+    kind: &str,
+    span: &Span,
+    errors: &mut Vec<SyntaxError>,
+) {
+    let mut misplaced: Vec<(&String, &[Placement])> = attributes
+        .keys()
+        .filter_map(|key| {
+            ATTRIBUTE_PLACEMENTS
+                .iter()
+                .find(|(name, _)| name == key)
+                .filter(|(_, allowed)| !allowed.contains(&placement))
+                .map(|(_, allowed)| (key, *allowed))
+        })
+        .collect();
+    misplaced.sort_by(|a, b| a.0.cmp(b.0));
+
+    for (key, allowed) in misplaced {
+        let allowed = allowed
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(" or ");
+
+        errors.push(SyntaxError {
+            message: format!(
+                "Attribute '{key}' is not valid on a {kind}, it only applies to {allowed}"
+            ),
+            span: span.clone(),
+            severity: ErrorSeverity::Warning,
+            lint: "attribute-placement",
+            element: None,
+            first_definition: None,
+        });
+    }
+}
+
+pub struct AttributeTypeRule;
+
+impl Rule for AttributeTypeRule {
+    fn name(&self) -> &'static str {
+        "attribute-type"
+    }
+
+    fn check_process(&self, process: &ProcessDeclaration, errors: &mut Vec<SyntaxError>) {
+        check_attribute_types(&process.attributes, &process.span, errors);
+    }
+
+    fn check_element(&self, element: &ProcessElement, errors: &mut Vec<SyntaxError>) {
+        match element {
+            ProcessElement::StartEvent {
+                attributes, span, ..
+            }
+            | ProcessElement::EndEvent {
+                attributes, span, ..
+            }
+            | ProcessElement::IntermediateEvent {
+                attributes, span, ..
+            }
+            | ProcessElement::Task {
+                attributes, span, ..
+            }
+            | ProcessElement::Subprocess {
+                attributes, span, ..
+            }
+            | ProcessElement::CallActivity {
+                attributes, span, ..
+            }
+            | ProcessElement::Gateway {
+                attributes, span, ..
+            }
+            | ProcessElement::EventSubprocess {
+                attributes, span, ..
+            }
+            | ProcessElement::Pool {
+                attributes, span, ..
+            } => check_attribute_types(attributes, span, errors),
+            ProcessElement::Group { .. }
+            | ProcessElement::Annotation { .. }
+            | ProcessElement::DataObject { .. } => {}
+        }
+    }
+}
+
+fn check_attribute_types(
+    attributes: &IndexMap<String, AttributeValue>,
+    span: &Span,
+    errors: &mut Vec<SyntaxError>,
+) {
+    let mut mismatched: Vec<(&String, ValueKind, ValueKind)> = attributes
+        .iter()
+        .filter_map(|(key, value)| {
+            ATTRIBUTE_TYPES
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, expected)| (key, *expected, ValueKind::of(value)))
+                .filter(|(_, expected, found)| expected != found)
+        })
+        .collect();
+    mismatched.sort_by(|a, b| a.0.cmp(b.0));
+
+    for (key, expected, found) in mismatched {
+        errors.push(SyntaxError {
+            message: format!("Attribute '{key}' expects {expected}, found {found}"),
+            span: span.clone(),
+            severity: ErrorSeverity::Error,
+            lint: "attribute-type",
+            element: None,
+            first_definition: None,
+        });
+    }
+}
+
+// `loop` and `multiInstance` mark an activity as standard loop or multi-instance,
+// `sequential` picks how the instances of a multi-instance activity run
+pub struct LoopMarkerRule;
+
+impl Rule for LoopMarkerRule {
+    fn name(&self) -> &'static str {
+        "loop-marker"
+    }
+
+    fn check_element(&self, element: &ProcessElement, errors: &mut Vec<SyntaxError>) {
+        let (ProcessElement::Task {
+            id,
+            attributes,
+            span,
+            ..
+        }
+        | ProcessElement::Subprocess {
+            id,
+            attributes,
+            span,
+            ..
+        }
+        | ProcessElement::CallActivity {
+            id,
+            attributes,
+            span,
+            ..
+        }
+        | ProcessElement::EventSubprocess {
+            id,
+            attributes,
+            span,
+            ..
+        }) = element
+        else {
+            return;
+        };
+
+        let enabled =
+            |key: &str| matches!(attributes.get(key), Some(AttributeValue::Boolean(true)));
+
+        if enabled("loop") && enabled("multiInstance") {
+            errors.push(SyntaxError {
+                message: format!(
+                    "Activity '{id}' cannot be both a loop and multi-instance, keep either `loop` or `multiInstance`"
+                ),
+                span: span.clone(),
+                severity: ErrorSeverity::Error,
+                lint: "loop-marker",
+                element: None,
+                first_definition: None,
+            });
+        }
+
+        if attributes.contains_key("sequential") && !enabled("multiInstance") {
+            errors.push(SyntaxError {
+                message: format!(
+                    "Attribute 'sequential' on '{id}' has no effect without `multiInstance=true`"
+                ),
+                span: span.clone(),
+                severity: ErrorSeverity::Warning,
+                lint: "loop-marker",
+                element: None,
+                first_definition: None,
+            });
+        }
+    }
+}
+
+pub struct ServiceImplementationRule {
+    pub engine: &'static str,
+    pub accepted: &'static [&'static str],
+}
+
+impl Rule for ServiceImplementationRule {
+    fn name(&self) -> &'static str {
+        "service-implementation"
+    }
+
+    fn check_element(&self, element: &ProcessElement, errors: &mut Vec<SyntaxError>) {
+        let ProcessElement::Task {
+            id,
+            task_type: TaskType::Service,
+            attributes,
+            span,
+            ..
+        } = element
+        else {
+            return;
+        };
+
+        if self
+            .accepted
+            .iter()
+            .any(|key| attributes.contains_key(*key))
+        {
+            return;
+        }
+
+        let expected = if self.accepted.len() == 1 {
+            format!("'{}'", self.accepted[0])
+        } else {
+            format!("one of {}", self.accepted.join(", "))
+        };
+
+        errors.push(SyntaxError {
+            message: format!(
+                "Service task '{id}' needs {expected} to be executable on {}",
+                self.engine
+            ),
+            span: span.clone(),
+            severity: ErrorSeverity::Warning,
+            lint: "service-implementation",
+            element: None,
+            first_definition: None,
+        });
+    }
+}
+
+pub struct ForeignNamespaceRule {
+    pub engine: &'static str,
+    pub namespace: &'static str,
+}
+
+impl Rule for ForeignNamespaceRule {
+    fn name(&self) -> &'static str {
+        "foreign-namespace"
+    }
+
+    fn check_process(&self, process: &ProcessDeclaration, errors: &mut Vec<SyntaxError>) {
+        self.check_attributes(&process.attributes, &process.span, errors);
+    }
+
+    fn check_element(&self, element: &ProcessElement, errors: &mut Vec<SyntaxError>) {
+        match element {
+            ProcessElement::StartEvent {
+                attributes, span, ..
+            }
+            | ProcessElement::EndEvent {
+                attributes, span, ..
+            }
+            | ProcessElement::IntermediateEvent {
+                attributes, span, ..
+            }
+            | ProcessElement::Task {
+                attributes, span, ..
+            }
+            | ProcessElement::Subprocess {
+                attributes, span, ..
+            }
+            | ProcessElement::CallActivity {
+                attributes, span, ..
+            }
+            | ProcessElement::Gateway {
+                attributes, span, ..
+            }
+            | ProcessElement::EventSubprocess {
+                attributes, span, ..
+            }
+            | ProcessElement::Pool {
+                attributes, span, ..
+            } => self.check_attributes(attributes, span, errors),
+            ProcessElement::Group { .. }
+            | ProcessElement::Annotation { .. }
+            | ProcessElement::DataObject { .. } => {}
+        }
+    }
+}
+
+impl ForeignNamespaceRule {
+    fn check_attributes(
+        &self,
+        attributes: &IndexMap<String, AttributeValue>,
+        span: &Span,
+        errors: &mut Vec<SyntaxError>,
+    ) {
+        let mut foreign: Vec<&String> = attributes
+            .keys()
+            .filter(|key| {
+                key.split_once(':')
+                    .is_some_and(|(namespace, _)| namespace == self.namespace)
+            })
+            .collect();
+        foreign.sort();
+
+        for key in foreign {
+            errors.push(SyntaxError {
+                message: format!("Attribute '{key}' is ignored by {}", self.engine),
+                span: span.clone(),
+                severity: ErrorSeverity::Warning,
+                lint: "foreign-namespace",
+                element: None,
+                first_definition: None,
+            });
+        }
+    }
+}
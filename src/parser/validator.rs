@@ -1,8 +1,16 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 use crate::{
-    lexer::Span,
-    parser::ast::{AstDocument, ErrorSeverity, Flow, FlowType, ParseError, ProcessElement},
+    lexer::{Span, invalid_number_suffix},
+    parser::{
+        analysis::{check_gateway_modes, check_reachability, check_termination, element_span},
+        ast::{
+            AstDocument, ErrorSeverity, EventType, Flow, FlowType, GatewayBranch, GatewayMode,
+            GatewayType, Lane, ParseError, ProcessElement, parse_duration_millis,
+        },
+        resolver::collect_flow_references,
+        rules::{Profile, Rule},
+    },
 };
 
 pub type SyntaxError = ParseError;
@@ -11,30 +19,60 @@ pub type ValidationResult = Result<(), Vec<SyntaxError>>;
 
 pub struct SyntaxValidator {
     errors: Vec<SyntaxError>,
+    rules: Vec<Box<dyn Rule>>,
+    synthetic_ids: BTreeSet<String>,
+    parse_error_offsets: Vec<usize>,
 }
 
 impl SyntaxValidator {
     #[must_use]
-    pub const fn new() -> Self {
-        Self { errors: Vec::new() }
+    pub fn new() -> Self {
+        Self::with_profile(Profile::Generic)
+    }
+
+    #[must_use]
+    pub fn with_profile(profile: Profile) -> Self {
+        Self {
+            errors: Vec::new(),
+            rules: profile.rules(),
+            synthetic_ids: BTreeSet::new(),
+            parse_error_offsets: Vec::new(),
+        }
     }
 
     pub fn validate(&mut self, document: &AstDocument) -> ValidationResult {
         self.errors.clear();
+        self.synthetic_ids.clone_from(&document.synthetic_ids);
+        self.parse_error_offsets = document
+            .errors
+            .iter()
+            .map(|error| error.span.start)
+            .collect();
+        let parse_failed = document.has_errors();
 
         for process in &document.processes {
-            let mut node_ids = HashMap::new();
-
-            for element in &process.elements {
-                self.validate_element(element, &mut node_ids);
+            for rule in &self.rules {
+                rule.check_process(process, &mut self.errors);
             }
 
-            for flow in &process.flows {
-                self.validate_flow(flow, &node_ids);
+            self.validate_scope(&process.elements, &process.flows);
+
+            // Recovery drops the flows it could not read, so the graph is incomplete
+            if !parse_failed {
+                let flow_errors = check_reachability(&process.elements, &process.flows)
+                    .into_iter()
+                    .chain(check_termination(&process.elements, &process.flows))
+                    .filter(|error| !self.names_synthetic(error))
+                    .collect::<Vec<_>>();
+                self.errors.extend(flow_errors);
             }
+            self.errors
+                .extend(check_gateway_modes(&process.elements, &process.flows));
         }
 
         self.validate_unknown_commands(document);
+        self.validate_shadowed_names(document);
+        self.validate_unused_imports(document);
 
         if self.errors.is_empty() {
             Ok(())
@@ -43,51 +81,199 @@ impl SyntaxValidator {
         }
     }
 
+    // A process and each subprocess body form a scope. Pools, lanes and groups are
+    // transparent, and a subprocess id belongs to the scope that declares it.
+    fn validate_scope(&mut self, elements: &[ProcessElement], flows: &[Flow]) {
+        let mut node_ids = HashMap::new();
+
+        for element in elements {
+            self.validate_element(element, &mut node_ids);
+        }
+
+        self.validate_gateway_targets(elements);
+
+        let mut scope_flows: Vec<&Flow> = flows.iter().collect();
+        collect_scope_flows(elements, &mut scope_flows);
+
+        let mut scope_elements = Vec::new();
+        collect_scope_elements(elements, &mut scope_elements);
+        scope_elements.sort_by_key(|element| element_span(element).start);
+        for (index, element) in scope_elements.iter().enumerate() {
+            let next = scope_elements.get(index + 1).copied();
+            self.validate_empty_gateway(element, next, &scope_flows);
+        }
+
+        let mut containers = HashSet::new();
+        collect_scope_containers(elements, &mut containers);
+
+        let mut pools = HashMap::new();
+        collect_pool_members(elements, None, &mut pools);
+
+        let mut event_subprocesses = HashSet::new();
+        collect_event_subprocesses(elements, &mut event_subprocesses);
+
+        let mut artifacts = HashMap::new();
+        collect_artifacts(elements, &mut artifacts);
+
+        // Flows name anonymous events by their keyword
+        let mut starts = HashSet::from(["start"]);
+        let mut ends = HashSet::from(["end"]);
+        collect_scope_events(elements, &mut starts, &mut ends);
+
+        for flow in &scope_flows {
+            self.validate_flow(flow, &node_ids, &containers);
+            self.validate_pool_crossing(flow, &pools, &containers);
+
+            // A message may still trigger a start event or leave an end event
+            if matches!(flow.flow_type, FlowType::Sequence | FlowType::Default) {
+                if starts.contains(flow.to.as_str()) {
+                    self.errors.push(SyntaxError {
+                        message: "Start event cannot have incoming flow".to_string(),
+                        span: flow.span.clone(),
+                        severity: ErrorSeverity::Error,
+                        lint: "start-incoming",
+                        element: None,
+                        first_definition: None,
+                    });
+                }
+                if ends.contains(flow.from.as_str()) {
+                    self.errors.push(SyntaxError {
+                        message: "End event cannot have outgoing flow".to_string(),
+                        span: flow.span.clone(),
+                        severity: ErrorSeverity::Error,
+                        lint: "end-outgoing",
+                        element: None,
+                        first_definition: None,
+                    });
+                }
+            }
+
+            if flow.flow_type != FlowType::Association
+                && event_subprocesses.contains(flow.to.as_str())
+            {
+                self.errors.push(SyntaxError {
+                    message: format!(
+                        "Event subprocess '{}' cannot have incoming flows, it starts from its trigger",
+                        flow.to
+                    ),
+                    span: flow.span.clone(),
+                    severity: ErrorSeverity::Warning,
+                    lint: "event-subprocess-flow",
+                    element: None,
+                    first_definition: None,
+                });
+            }
+
+            // Notes and data only attach through associations, never along the sequence
+            if flow.flow_type == FlowType::Association {
+                self.validate_association(flow, &node_ids, &artifacts);
+            } else {
+                for name in [&flow.from, &flow.to] {
+                    if let Some(kind) = artifacts.get(name.as_str()) {
+                        self.errors.push(SyntaxError {
+                            message: format!(
+                                "{kind} '{name}' can only be connected with an association `..>`"
+                            ),
+                            span: flow.span.clone(),
+                            severity: ErrorSeverity::Warning,
+                            lint: "data-flow",
+                            element: None,
+                            first_definition: None,
+                        });
+                    }
+                }
+            }
+        }
+
+        self.validate_duplicate_flows(&scope_flows);
+    }
+
     fn validate_element(&mut self, element: &ProcessElement, node_ids: &mut HashMap<String, Span>) {
+        for rule in &self.rules {
+            rule.check_element(element, &mut self.errors);
+        }
+
+        if let ProcessElement::Gateway {
+            id,
+            gateway_type,
+            branches,
+            span,
+            ..
+        } = element
+        {
+            self.validate_gateway_branches(id.as_deref(), gateway_type, branches, span);
+        }
+
+        self.validate_event_type(element);
+
         let (id_opt, span) = match element {
             ProcessElement::Gateway { id, span, .. }
             | ProcessElement::EndEvent { id, span, .. }
             | ProcessElement::StartEvent { id, span, .. }
-            | ProcessElement::IntermediateEvent { id, span, .. } => (id.as_ref(), span),
+            | ProcessElement::IntermediateEvent { id, span, .. }
+            | ProcessElement::Annotation { id, span, .. } => (id.as_ref(), span),
             ProcessElement::Subprocess {
-                id, span, elements, ..
+                id,
+                span,
+                elements,
+                flows,
+                ..
             } => {
-                let mut nested_ids = HashMap::new();
-                for nested_element in elements {
-                    self.validate_element(nested_element, &mut nested_ids);
-                }
+                self.validate_body(&format!("Subprocess '{id}'"), elements, Some(flows), span);
+                self.validate_scope(elements, flows);
+                (Some(id), span)
+            }
+            ProcessElement::EventSubprocess {
+                id,
+                span,
+                elements,
+                flows,
+                ..
+            } => {
+                self.validate_body(
+                    &format!("Event subprocess '{id}'"),
+                    elements,
+                    Some(flows),
+                    span,
+                );
+                self.validate_scope(elements, flows);
                 (Some(id), span)
             }
             ProcessElement::CallActivity { id, span, .. }
-            | ProcessElement::Task { id, span, .. } => (Some(id), span),
+            | ProcessElement::Task { id, span, .. }
+            | ProcessElement::DataObject { id, span, .. } => (Some(id), span),
             ProcessElement::Pool {
                 name,
                 span,
+                lanes,
                 elements,
                 ..
             } => {
-                let mut pool_ids = HashMap::new();
-                for pool_element in elements {
-                    self.validate_element(pool_element, &mut pool_ids);
+                let misplaced = self.validate_lane_membership(lanes, elements);
+                for lane_element in lanes.iter().flat_map(|lane| &lane.elements).chain(elements) {
+                    if !misplaced.contains(&element_span(lane_element)) {
+                        self.validate_element(lane_element, node_ids);
+                    }
                 }
                 (Some(name), span)
             }
             ProcessElement::Group { elements, span, .. } => {
-                let mut group_ids = HashMap::new();
                 for group_element in elements {
-                    self.validate_element(group_element, &mut group_ids);
+                    self.validate_element(group_element, node_ids);
                 }
                 (None, span)
             }
-            ProcessElement::Annotation { span, .. } => (None, span),
         };
 
         if let Some(id) = id_opt {
-            if let Some(_first_span) = node_ids.get(id) {
+            if let Some(first) = node_ids.get(id) {
                 self.errors.push(SyntaxError {
                     message: format!("Duplicate node id '{id}'"),
                     span: span.clone(),
                     severity: ErrorSeverity::Error,
+                    lint: "duplicate-id",
+                    element: Some(id.clone()),
+                    first_definition: Some(first.clone()),
                 });
             } else {
                 node_ids.insert(id.clone(), span.clone());
@@ -95,23 +281,235 @@ impl SyntaxValidator {
         }
     }
 
-    fn validate_flow(&mut self, flow: &Flow, node_ids: &HashMap<String, Span>) {
-        match flow.flow_type {
-            FlowType::Sequence => {
-                if !self.is_valid_sequence_flow(&flow.from, &flow.to, node_ids) {
+    fn validate_event_type(&mut self, element: &ProcessElement) {
+        if let ProcessElement::StartEvent {
+            event_type: Some(EventType::Timer(duration)),
+            span,
+            ..
+        }
+        | ProcessElement::IntermediateEvent {
+            event_type: EventType::Timer(duration),
+            span,
+            ..
+        } = element
+            && !duration.is_empty()
+            && parse_duration_millis(duration).is_none()
+            && invalid_number_suffix(duration).is_none()
+        {
+            self.errors.push(SyntaxError {
+                message: format!(
+                    "Invalid timer duration '{duration}', expected a duration like 30s, 5m or 1h30m"
+                ),
+                span: span.clone(),
+                severity: ErrorSeverity::Error,
+                lint: "timer-duration",
+                element: None,
+                first_definition: None,
+            });
+        }
+
+        if let ProcessElement::StartEvent {
+            event_type: Some(EventType::Conditional(expression)),
+            span,
+            ..
+        }
+        | ProcessElement::IntermediateEvent {
+            event_type: EventType::Conditional(expression),
+            span,
+            ..
+        }
+        | ProcessElement::EventSubprocess {
+            trigger: EventType::Conditional(expression),
+            span,
+            ..
+        } = element
+            && expression.trim().is_empty()
+        {
+            self.errors.push(SyntaxError {
+                message: "Conditional event needs a condition, e.g. @conditional \"amount > 100\""
+                    .to_string(),
+                span: span.clone(),
+                severity: ErrorSeverity::Error,
+                lint: "conditional-event",
+                element: None,
+                first_definition: None,
+            });
+        }
+    }
+
+    fn validate_gateway_branches(
+        &mut self,
+        id: Option<&str>,
+        gateway_type: &GatewayType,
+        branches: &[GatewayBranch],
+        span: &Span,
+    ) {
+        let gateway = id.map_or_else(|| "Gateway".to_string(), |id| format!("Gateway '{id}'"));
+        let defaults = branches.iter().filter(|branch| branch.is_default).count();
+
+        if defaults > 1 {
+            self.errors.push(SyntaxError {
+                message: format!("{gateway} has {defaults} default branches, at most one allowed"),
+                span: span.clone(),
+                severity: ErrorSeverity::Error,
+                lint: "multiple-defaults",
+                element: None,
+                first_definition: None,
+            });
+        } else if defaults == 0
+            && matches!(gateway_type, GatewayType::Exclusive)
+            && branches.iter().any(|branch| branch.condition.is_some())
+        {
+            // Nothing moves on when every condition is false, the token is stuck
+            let gateway = id.map_or_else(
+                || "Exclusive gateway".to_string(),
+                |id| format!("Exclusive gateway '{id}'"),
+            );
+            self.errors.push(SyntaxError {
+                message: format!(
+                    "{gateway} has no default branch, add `=> Fallback` to avoid a deadlock when no condition matches"
+                ),
+                span: span.clone(),
+                severity: ErrorSeverity::Warning,
+                lint: "missing-default",
+                element: None,
+                first_definition: None,
+            });
+        }
+
+        if matches!(gateway_type, GatewayType::Parallel) {
+            for branch in branches.iter().filter(|branch| branch.condition.is_some()) {
+                self.errors.push(SyntaxError {
+                    message: "Condition on parallel gateway branch is ignored".to_string(),
+                    span: branch.span.clone(),
+                    severity: ErrorSeverity::Warning,
+                    lint: "parallel-condition",
+                    element: None,
+                    first_definition: None,
+                });
+            }
+        }
+    }
+
+    // A split without branches or outgoing flows is a stub, unless its block failed to
+    // parse and the branches were dropped with a parse error before the next element
+    fn validate_empty_gateway(
+        &mut self,
+        element: &ProcessElement,
+        next: Option<&ProcessElement>,
+        flows: &[&Flow],
+    ) {
+        let ProcessElement::Gateway {
+            id,
+            mode,
+            branches,
+            span,
+            ..
+        } = element
+        else {
+            return;
+        };
+
+        let end = next.map_or(usize::MAX, |next| element_span(next).start);
+        if !branches.is_empty()
+            || *mode == GatewayMode::Join
+            || flows
+                .iter()
+                .any(|flow| Some(flow.from.as_str()) == id.as_deref())
+            || self
+                .parse_error_offsets
+                .iter()
+                .any(|offset| (span.start..end).contains(offset))
+        {
+            return;
+        }
+
+        self.errors.push(SyntaxError {
+            message: id.as_ref().map_or_else(
+                || "Gateway has no branches".to_string(),
+                |id| format!("Gateway '{id}' has no branches"),
+            ),
+            span: span.clone(),
+            severity: ErrorSeverity::Warning,
+            lint: "empty-block",
+            element: None,
+            first_definition: None,
+        });
+    }
+
+    // Elements error recovery invented would only echo the error that created them
+    fn names_synthetic(&self, error: &SyntaxError) -> bool {
+        error
+            .element
+            .as_ref()
+            .is_some_and(|id| self.synthetic_ids.contains(id))
+    }
+
+    fn validate_gateway_targets(&mut self, elements: &[ProcessElement]) {
+        let mut scope_ids = HashSet::new();
+        collect_scope_ids(elements, &mut scope_ids);
+
+        let mut gateways = Vec::new();
+        collect_scope_gateways(elements, &mut gateways);
+
+        for branch in gateways {
+            if branch.target != "end"
+                && !is_imported(&branch.target)
+                && !scope_ids.contains(branch.target.as_str())
+                && !self.synthetic_ids.contains(&branch.target)
+            {
+                self.errors.push(SyntaxError {
+                    message: format!("Unknown gateway target '{}'", branch.target),
+                    span: branch.span.clone(),
+                    severity: ErrorSeverity::Error,
+                    lint: "unknown-reference",
+                    element: None,
+                    first_definition: None,
+                });
+            }
+        }
+    }
+
+    fn validate_flow(
+        &mut self,
+        flow: &Flow,
+        node_ids: &HashMap<String, Span>,
+        containers: &HashSet<&str>,
+    ) {
+        // Message flows may connect whole pools, sequence flows need a concrete node
+        let mut from_container = false;
+        let mut to_container = false;
+        if matches!(flow.flow_type, FlowType::Sequence | FlowType::Default) {
+            from_container = containers.contains(flow.from.as_str());
+            to_container = containers.contains(flow.to.as_str());
+
+            for (name, direction, is_container) in [
+                (&flow.from, "from", from_container),
+                (&flow.to, "to", to_container),
+            ] {
+                if is_container {
                     self.errors.push(SyntaxError {
-                        message: format!("Invalid sequential arrow: {} -> {}", flow.from, flow.to),
+                        message: format!("Cannot draw a flow {direction} container '{name}'"),
                         span: flow.span.clone(),
                         severity: ErrorSeverity::Error,
+                        lint: "container-flow",
+                        element: None,
+                        first_definition: None,
                     });
                 }
             }
-            FlowType::Message => {
-                if !self.is_valid_message_flow(&flow.from, &flow.to) {
+        }
+
+        match flow.flow_type {
+            FlowType::Sequence => {
+                if !self.is_valid_sequence_flow(&flow.from, &flow.to, node_ids) {
                     self.errors.push(SyntaxError {
-                        message: format!("Invalid message arrow: {} --> {}", flow.from, flow.to),
+                        message: format!("Invalid sequential arrow: {} -> {}", flow.from, flow.to),
                         span: flow.span.clone(),
                         severity: ErrorSeverity::Error,
+                        lint: "syntax",
+                        element: None,
+                        first_definition: None,
                     });
                 }
             }
@@ -124,52 +522,332 @@ impl SyntaxValidator {
                         ),
                         span: flow.span.clone(),
                         severity: ErrorSeverity::Error,
+                        lint: "syntax",
+                        element: None,
+                        first_definition: None,
                     });
                 }
             }
-            FlowType::Association => {
-                if !self.is_valid_association(&flow.from, &flow.to) {
-                    self.errors.push(SyntaxError {
-                        message: format!("Invalid associative link: {} ..> {}", flow.from, flow.to),
-                        span: flow.span.clone(),
-                        severity: ErrorSeverity::Warning,
-                    });
-                }
-            }
+            FlowType::Message | FlowType::Association => {}
+        }
+
+        if flow.from == flow.to {
+            self.errors.push(SyntaxError {
+                message: format!("Self-loop on element '{}'", flow.from),
+                span: flow.span.clone(),
+                severity: ErrorSeverity::Warning,
+                lint: "self-loop",
+                element: None,
+                first_definition: None,
+            });
         }
 
-        if !node_ids.contains_key(&flow.from) && flow.from != "start" {
+        // Flows out of `end` or into `start` are reported as such by the caller
+        if !node_ids.contains_key(&flow.from)
+            && !matches!(flow.from.as_str(), "start" | "end")
+            && !from_container
+            && !is_imported(&flow.from)
+        {
             self.errors.push(SyntaxError {
                 message: format!("Unknown flow source: '{}'", flow.from),
                 span: flow.span.clone(),
                 severity: ErrorSeverity::Error,
+                lint: "unknown-reference",
+                element: None,
+                first_definition: None,
             });
         }
 
-        if !node_ids.contains_key(&flow.to) && flow.to != "end" {
+        if !node_ids.contains_key(&flow.to)
+            && !matches!(flow.to.as_str(), "start" | "end")
+            && !to_container
+            && !is_imported(&flow.to)
+            && !self.synthetic_ids.contains(&flow.to)
+        {
             self.errors.push(SyntaxError {
                 message: format!("Unknown flow target: '{}'", flow.to),
                 span: flow.span.clone(),
                 severity: ErrorSeverity::Error,
+                lint: "unknown-reference",
+                element: None,
+                first_definition: None,
             });
         }
     }
 
+    // One end must be a note or data object and the other a flow element. Unknown
+    // ends are reported by `validate_flow` already.
+    fn validate_association(
+        &mut self,
+        flow: &Flow,
+        node_ids: &HashMap<String, Span>,
+        artifacts: &HashMap<&str, &str>,
+    ) {
+        let known = |name: &str| node_ids.contains_key(name) || name == "start" || name == "end";
+        if !known(&flow.from) || !known(&flow.to) {
+            return;
+        }
+
+        if artifacts.contains_key(flow.from.as_str()) == artifacts.contains_key(flow.to.as_str()) {
+            self.errors.push(SyntaxError {
+                message: format!(
+                    "Association {} ..> {} must connect an element to a note or data object",
+                    flow.from, flow.to
+                ),
+                span: flow.span.clone(),
+                severity: ErrorSeverity::Warning,
+                lint: "association",
+                element: None,
+                first_definition: None,
+            });
+        }
+    }
+
+    // Placements of an element already declared in another lane of the pool, these are
+    // reported here instead of as duplicate ids. Elements written directly in the pool
+    // count as members of its default lane
+    fn validate_lane_membership<'a>(
+        &mut self,
+        lanes: &'a [Lane],
+        elements: &'a [ProcessElement],
+    ) -> Vec<&'a Span> {
+        let mut members: HashMap<&str, &str> = HashMap::new();
+        let mut misplaced = Vec::new();
+        if lanes.is_empty() {
+            return misplaced;
+        }
+
+        let placements = lanes
+            .iter()
+            .map(|lane| (lane.name.as_str(), lane.elements.as_slice()))
+            .chain([(Lane::DEFAULT, elements)]);
+        for (lane, elements) in placements {
+            let mut declarations = Vec::new();
+            collect_scope_declarations(elements, &mut declarations, &mut Vec::new());
+            for (id, span) in declarations {
+                match members.get(id) {
+                    Some(owner) if *owner != lane => {
+                        self.errors.push(SyntaxError {
+                            message: format!("Element '{id}' belongs to multiple lanes"),
+                            span: span.clone(),
+                            severity: ErrorSeverity::Error,
+                            lint: "duplicate-id",
+                            element: None,
+                            first_definition: None,
+                        });
+                        misplaced.push(span);
+                    }
+                    Some(_) => {}
+                    None => {
+                        members.insert(id, lane);
+                    }
+                }
+            }
+        }
+
+        misplaced
+    }
+
+    fn validate_duplicate_flows(&mut self, flows: &[&Flow]) {
+        for (index, flow) in flows.iter().enumerate() {
+            let first = flows[..index].iter().find(|earlier| {
+                earlier.from == flow.from
+                    && earlier.to == flow.to
+                    && earlier.flow_type == flow.flow_type
+                    && earlier.condition == flow.condition
+            });
+
+            if let Some(first) = first {
+                let arrow = match flow.flow_type {
+                    FlowType::Sequence => "->",
+                    FlowType::Message => "-->",
+                    FlowType::Default => "=>",
+                    FlowType::Association => "..>",
+                };
+                self.errors.push(SyntaxError {
+                    message: format!(
+                        "Duplicate flow {} {arrow} {} (first declared at line {}, column {})",
+                        flow.from, flow.to, first.span.line, first.span.column
+                    ),
+                    span: flow.span.clone(),
+                    severity: ErrorSeverity::Warning,
+                    lint: "duplicate-flow",
+                    element: None,
+                    first_definition: None,
+                });
+            }
+        }
+    }
+
     fn validate_unknown_commands(&mut self, document: &AstDocument) {
         for process in &document.processes {
-            let has_start = process
-                .elements
+            self.validate_body(
+                &format!("Process '{}'", process.name),
+                &process.elements,
+                None,
+                &process.span,
+            );
+        }
+    }
+
+    // Processes and subprocess bodies begin at their own start events. A subprocess,
+    // given with its flows, also has to finish inside its body before the outer flow
+    // continues
+    fn validate_body(
+        &mut self,
+        owner: &str,
+        elements: &[ProcessElement],
+        flows: Option<&[Flow]>,
+        span: &Span,
+    ) {
+        if elements.is_empty() {
+            self.errors.push(SyntaxError {
+                message: format!("{owner} is empty"),
+                span: span.clone(),
+                severity: ErrorSeverity::Warning,
+                lint: "empty-block",
+                element: None,
+                first_definition: None,
+            });
+            return;
+        }
+
+        let starts = elements
+            .iter()
+            .filter(|element| matches!(element, ProcessElement::StartEvent { .. }))
+            .count();
+
+        if starts > 1 {
+            self.errors.push(SyntaxError {
+                message: format!(
+                    "{owner} has {starts} start events, each one begins a separate path"
+                ),
+                span: span.clone(),
+                severity: ErrorSeverity::Info,
+                lint: "multiple-starts",
+                element: None,
+                first_definition: None,
+            });
+        }
+
+        if starts == 0 {
+            self.errors.push(SyntaxError {
+                message: format!("{owner} must contain at least one start event"),
+                span: span.clone(),
+                severity: ErrorSeverity::Warning,
+                lint: "missing-start",
+                element: None,
+                first_definition: None,
+            });
+        }
+
+        if flows.is_some_and(|flows| !has_end_event(elements, flows)) {
+            self.errors.push(SyntaxError {
+                message: format!("{owner} must contain at least one end event"),
+                span: span.clone(),
+                severity: ErrorSeverity::Warning,
+                lint: "missing-end",
+                element: None,
+                first_definition: None,
+            });
+        }
+    }
+
+    // `call X` cannot tell a process or subprocess apart from an element with its name.
+    // Declarations in the same scope as a subprocess are already duplicate ids, and a
+    // call activity is named after what it calls.
+    fn validate_shadowed_names(&mut self, document: &AstDocument) {
+        let mut scopes: Vec<&[ProcessElement]> = document
+            .processes
+            .iter()
+            .map(|process| process.elements.as_slice())
+            .collect();
+        let mut declarations = Vec::new();
+        let mut subprocesses = Vec::new();
+        let mut calls = Vec::new();
+        let mut index = 0;
+        while let Some(elements) = scopes.get(index).copied() {
+            let mut scope_declarations = Vec::new();
+            let mut bodies = Vec::new();
+            collect_scope_declarations(elements, &mut scope_declarations, &mut bodies);
+            collect_scope_callables(elements, index, &mut subprocesses, &mut calls);
+            declarations.extend(
+                scope_declarations
+                    .into_iter()
+                    .map(|(id, span)| (index, id, span)),
+            );
+            scopes.extend(bodies);
+            index += 1;
+        }
+
+        for (scope, id, span) in declarations {
+            if self.synthetic_ids.contains(id) || calls.contains(&span) {
+                continue;
+            }
+
+            let shadowed = if document.processes.iter().any(|process| process.name == id) {
+                "process"
+            } else if subprocesses
                 .iter()
-                .any(|element| matches!(element, ProcessElement::StartEvent { .. }));
+                .any(|(declared_in, name)| *name == id && *declared_in != scope)
+            {
+                "subprocess"
+            } else {
+                continue;
+            };
+
+            self.errors.push(SyntaxError {
+                message: format!("Element '{id}' shadows {shadowed} '{id}'"),
+                span: span.clone(),
+                severity: ErrorSeverity::Warning,
+                lint: "shadowed-name",
+                element: None,
+                first_definition: None,
+            });
+        }
+    }
+
+    fn validate_unused_imports(&mut self, document: &AstDocument) {
+        let mut calls = Vec::new();
+        let mut references = Vec::new();
+        for process in &document.processes {
+            collect_called_elements(&process.elements, &mut calls);
+            collect_flow_references(&process.elements, &process.flows, &mut references);
+        }
+        calls.extend(
+            references
+                .into_iter()
+                .map(|(name, _)| name)
+                .filter(|name| is_imported(name)),
+        );
 
-            if !has_start {
+        for import in &document.imports {
+            let unused: Vec<&String> = import.alias.as_ref().map_or_else(
+                || {
+                    import
+                        .items
+                        .iter()
+                        .filter(|item| !calls.contains(&item.as_str()))
+                        .collect()
+                },
+                |alias| {
+                    let prefix = format!("{alias}::");
+                    if calls.iter().any(|call| call.starts_with(&prefix)) {
+                        Vec::new()
+                    } else {
+                        vec![alias]
+                    }
+                },
+            );
+
+            for name in unused {
                 self.errors.push(SyntaxError {
-                    message: format!(
-                        "Process '{}' must contain at least one start event",
-                        process.name
-                    ),
-                    span: process.span.clone(),
+                    message: format!("Unused import '{name}'"),
+                    span: import.span.clone(),
                     severity: ErrorSeverity::Warning,
+                    lint: "unused-import",
+                    element: None,
+                    first_definition: None,
                 });
             }
         }
@@ -189,19 +867,365 @@ impl SyntaxValidator {
         true
     }
 
-    #[allow(clippy::unused_self)]
-    const fn is_valid_message_flow(&self, _from: &str, _to: &str) -> bool {
-        true
+    // Message flows travel between pools and sequence flows stay inside one. Endpoints
+    // outside every pool are left alone, the process itself is an implicit participant
+    fn validate_pool_crossing(
+        &mut self,
+        flow: &Flow,
+        pools: &HashMap<&str, &str>,
+        containers: &HashSet<&str>,
+    ) {
+        let (Some(from_pool), Some(to_pool)) =
+            (pools.get(flow.from.as_str()), pools.get(flow.to.as_str()))
+        else {
+            return;
+        };
+
+        match flow.flow_type {
+            FlowType::Message if from_pool == to_pool => {
+                self.errors.push(SyntaxError {
+                    message: format!(
+                        "Message flow {} --> {} stays inside pool '{from_pool}', use a sequence flow `->`",
+                        flow.from, flow.to
+                    ),
+                    span: flow.span.clone(),
+                    severity: ErrorSeverity::Warning,
+                    lint: "pool-boundary",
+                    element: None,
+                    first_definition: None,
+                });
+            }
+            // Sequence flows touching a pool or lane are already rejected outright
+            FlowType::Sequence | FlowType::Default
+                if from_pool != to_pool
+                    && !containers.contains(flow.from.as_str())
+                    && !containers.contains(flow.to.as_str()) =>
+            {
+                self.errors.push(SyntaxError {
+                    message: format!(
+                        "Sequence flow {} -> {} crosses from pool '{from_pool}' to pool '{to_pool}', use a message flow `-->`",
+                        flow.from, flow.to
+                    ),
+                    span: flow.span.clone(),
+                    severity: ErrorSeverity::Error,
+                    lint: "pool-boundary",
+                    element: None,
+                    first_definition: None,
+                });
+            }
+            _ => {}
+        }
     }
 
     #[allow(clippy::unused_self)]
     fn is_valid_default_flow(&self, from: &str, node_ids: &HashMap<String, Span>) -> bool {
         node_ids.contains_key(from) || from == "start"
     }
+}
 
-    #[allow(clippy::unused_self)]
-    const fn is_valid_association(&self, _from: &str, _to: &str) -> bool {
-        true
+// Qualified names point into imported files and are checked by the resolver
+fn is_imported(name: &str) -> bool {
+    name.contains("::")
+}
+
+fn collect_scope_ids<'a>(elements: &'a [ProcessElement], ids: &mut HashSet<&'a str>) {
+    for element in elements {
+        match element {
+            ProcessElement::Task { id, .. }
+            | ProcessElement::Subprocess { id, .. }
+            | ProcessElement::EventSubprocess { id, .. }
+            | ProcessElement::CallActivity { id, .. }
+            | ProcessElement::DataObject { id, .. } => {
+                ids.insert(id);
+            }
+            ProcessElement::Gateway { id, .. }
+            | ProcessElement::StartEvent { id, .. }
+            | ProcessElement::EndEvent { id, .. }
+            | ProcessElement::IntermediateEvent { id, .. }
+            | ProcessElement::Annotation { id, .. } => {
+                if let Some(id) = id {
+                    ids.insert(id);
+                }
+            }
+            ProcessElement::Pool {
+                lanes, elements, ..
+            } => {
+                for lane in lanes {
+                    collect_scope_ids(&lane.elements, ids);
+                }
+                collect_scope_ids(elements, ids);
+            }
+            ProcessElement::Group { elements, .. } => collect_scope_ids(elements, ids),
+        }
+    }
+}
+
+fn collect_called_elements<'a>(elements: &'a [ProcessElement], calls: &mut Vec<&'a str>) {
+    for element in elements {
+        match element {
+            ProcessElement::CallActivity { called_element, .. } => calls.push(called_element),
+            ProcessElement::Subprocess { elements, .. }
+            | ProcessElement::EventSubprocess { elements, .. }
+            | ProcessElement::Group { elements, .. } => collect_called_elements(elements, calls),
+            ProcessElement::Pool {
+                lanes, elements, ..
+            } => {
+                for lane in lanes {
+                    collect_called_elements(&lane.elements, calls);
+                }
+                collect_called_elements(elements, calls);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn collect_scope_elements<'a>(
+    elements: &'a [ProcessElement],
+    scope_elements: &mut Vec<&'a ProcessElement>,
+) {
+    for element in elements {
+        scope_elements.push(element);
+        match element {
+            ProcessElement::Pool {
+                lanes, elements, ..
+            } => {
+                for lane in lanes {
+                    collect_scope_elements(&lane.elements, scope_elements);
+                }
+                collect_scope_elements(elements, scope_elements);
+            }
+            ProcessElement::Group { elements, .. } => {
+                collect_scope_elements(elements, scope_elements);
+            }
+            _ => {}
+        }
+    }
+}
+
+// Flows may end at the anonymous `end` without declaring it
+fn has_end_event(elements: &[ProcessElement], flows: &[Flow]) -> bool {
+    let mut scope_elements = Vec::new();
+    collect_scope_elements(elements, &mut scope_elements);
+    let mut scope_flows: Vec<&Flow> = flows.iter().collect();
+    collect_scope_flows(elements, &mut scope_flows);
+
+    scope_elements
+        .iter()
+        .any(|element| matches!(element, ProcessElement::EndEvent { .. }))
+        || scope_flows.iter().any(|flow| flow.to == "end")
+}
+
+fn collect_scope_flows<'a>(elements: &'a [ProcessElement], flows: &mut Vec<&'a Flow>) {
+    for element in elements {
+        match element {
+            ProcessElement::Pool {
+                elements,
+                flows: pool_flows,
+                ..
+            } => {
+                flows.extend(pool_flows);
+                collect_scope_flows(elements, flows);
+            }
+            ProcessElement::Group { elements, .. } => collect_scope_flows(elements, flows),
+            _ => {}
+        }
+    }
+}
+
+fn collect_scope_containers<'a>(elements: &'a [ProcessElement], containers: &mut HashSet<&'a str>) {
+    for element in elements {
+        match element {
+            ProcessElement::Pool {
+                name,
+                lanes,
+                elements,
+                ..
+            } => {
+                containers.insert(name);
+                for lane in lanes {
+                    containers.insert(&lane.name);
+                    collect_scope_containers(&lane.elements, containers);
+                }
+                collect_scope_containers(elements, containers);
+            }
+            // A group's label is only drawn, it never names anything a flow could reach
+            ProcessElement::Group { elements, .. } => {
+                collect_scope_containers(elements, containers);
+            }
+            _ => {}
+        }
+    }
+}
+
+// The pool each element, lane and pool of a scope belongs to
+fn collect_pool_members<'a>(
+    elements: &'a [ProcessElement],
+    pool: Option<&'a str>,
+    members: &mut HashMap<&'a str, &'a str>,
+) {
+    for element in elements {
+        match element {
+            ProcessElement::Pool {
+                name,
+                lanes,
+                elements,
+                ..
+            } => {
+                members.insert(name, name);
+                for lane in lanes {
+                    members.insert(&lane.name, name);
+                    collect_pool_members(&lane.elements, Some(name), members);
+                }
+                collect_pool_members(elements, Some(name), members);
+            }
+            ProcessElement::Group { elements, .. } => {
+                collect_pool_members(elements, pool, members);
+            }
+            ProcessElement::Task { id, .. }
+            | ProcessElement::Subprocess { id, .. }
+            | ProcessElement::EventSubprocess { id, .. }
+            | ProcessElement::CallActivity { id, .. }
+            | ProcessElement::DataObject { id, .. } => {
+                if let Some(pool) = pool {
+                    members.insert(id, pool);
+                }
+            }
+            ProcessElement::Gateway { id, .. }
+            | ProcessElement::StartEvent { id, .. }
+            | ProcessElement::EndEvent { id, .. }
+            | ProcessElement::IntermediateEvent { id, .. }
+            | ProcessElement::Annotation { id, .. } => {
+                if let (Some(pool), Some(id)) = (pool, id) {
+                    members.insert(id, pool);
+                }
+            }
+        }
+    }
+}
+
+fn collect_event_subprocesses<'a>(elements: &'a [ProcessElement], ids: &mut HashSet<&'a str>) {
+    for element in elements {
+        match element {
+            ProcessElement::EventSubprocess { id, .. } => {
+                ids.insert(id);
+            }
+            ProcessElement::Pool {
+                lanes, elements, ..
+            } => {
+                for lane in lanes {
+                    collect_event_subprocesses(&lane.elements, ids);
+                }
+                collect_event_subprocesses(elements, ids);
+            }
+            ProcessElement::Group { elements, .. } => collect_event_subprocesses(elements, ids),
+            _ => {}
+        }
+    }
+}
+
+fn collect_scope_callables<'a>(
+    elements: &'a [ProcessElement],
+    scope: usize,
+    subprocesses: &mut Vec<(usize, &'a str)>,
+    calls: &mut Vec<&'a Span>,
+) {
+    for element in elements {
+        match element {
+            ProcessElement::Subprocess { id, .. } | ProcessElement::EventSubprocess { id, .. } => {
+                subprocesses.push((scope, id));
+            }
+            ProcessElement::CallActivity { span, .. } => calls.push(span),
+            ProcessElement::Pool {
+                lanes, elements, ..
+            } => {
+                for lane in lanes {
+                    collect_scope_callables(&lane.elements, scope, subprocesses, calls);
+                }
+                collect_scope_callables(elements, scope, subprocesses, calls);
+            }
+            ProcessElement::Group { elements, .. } => {
+                collect_scope_callables(elements, scope, subprocesses, calls);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn collect_scope_events<'a>(
+    elements: &'a [ProcessElement],
+    starts: &mut HashSet<&'a str>,
+    ends: &mut HashSet<&'a str>,
+) {
+    for element in elements {
+        match element {
+            ProcessElement::StartEvent { id: Some(id), .. } => {
+                starts.insert(id);
+            }
+            ProcessElement::EndEvent { id: Some(id), .. } => {
+                ends.insert(id);
+            }
+            ProcessElement::Pool {
+                lanes, elements, ..
+            } => {
+                for lane in lanes {
+                    collect_scope_events(&lane.elements, starts, ends);
+                }
+                collect_scope_events(elements, starts, ends);
+            }
+            ProcessElement::Group { elements, .. } => {
+                collect_scope_events(elements, starts, ends);
+            }
+            _ => {}
+        }
+    }
+}
+
+// Notes and data objects, keyed by id, with the name diagnostics use for them
+fn collect_artifacts<'a>(elements: &'a [ProcessElement], kinds: &mut HashMap<&'a str, &'a str>) {
+    for element in elements {
+        match element {
+            ProcessElement::DataObject { id, store, .. } => {
+                kinds.insert(id, if *store { "Data store" } else { "Data object" });
+            }
+            ProcessElement::Annotation { id: Some(id), .. } => {
+                kinds.insert(id, "Note");
+            }
+            ProcessElement::Pool {
+                lanes, elements, ..
+            } => {
+                for lane in lanes {
+                    collect_artifacts(&lane.elements, kinds);
+                }
+                collect_artifacts(elements, kinds);
+            }
+            ProcessElement::Group { elements, .. } => collect_artifacts(elements, kinds),
+            _ => {}
+        }
+    }
+}
+
+fn collect_scope_gateways<'a>(
+    elements: &'a [ProcessElement],
+    branches: &mut Vec<&'a GatewayBranch>,
+) {
+    for element in elements {
+        match element {
+            ProcessElement::Gateway {
+                branches: gateway_branches,
+                ..
+            } => branches.extend(gateway_branches),
+            ProcessElement::Pool {
+                lanes, elements, ..
+            } => {
+                for lane in lanes {
+                    collect_scope_gateways(&lane.elements, branches);
+                }
+                collect_scope_gateways(elements, branches);
+            }
+            ProcessElement::Group { elements, .. } => collect_scope_gateways(elements, branches),
+            _ => {}
+        }
     }
 }
 
@@ -212,6 +1236,60 @@ impl Default for SyntaxValidator {
 }
 
 pub fn validate_syntax(document: &AstDocument) -> ValidationResult {
-    let mut validator = SyntaxValidator::new();
+    validate_syntax_with_profile(document, Profile::Generic)
+}
+
+pub fn validate_syntax_with_profile(document: &AstDocument, profile: Profile) -> ValidationResult {
+    let mut validator = SyntaxValidator::with_profile(profile);
     validator.validate(document)
 }
+
+// Declarations in the order `validate_element` visits them, plus the subprocess
+// bodies that open scopes of their own
+fn collect_scope_declarations<'a>(
+    elements: &'a [ProcessElement],
+    declarations: &mut Vec<(&'a str, &'a Span)>,
+    bodies: &mut Vec<&'a [ProcessElement]>,
+) {
+    for element in elements {
+        match element {
+            ProcessElement::Gateway { id, span, .. }
+            | ProcessElement::EndEvent { id, span, .. }
+            | ProcessElement::StartEvent { id, span, .. }
+            | ProcessElement::IntermediateEvent { id, span, .. }
+            | ProcessElement::Annotation { id, span, .. } => {
+                if let Some(id) = id {
+                    declarations.push((id, span));
+                }
+            }
+            ProcessElement::Subprocess {
+                id, span, elements, ..
+            }
+            | ProcessElement::EventSubprocess {
+                id, span, elements, ..
+            } => {
+                bodies.push(elements);
+                declarations.push((id, span));
+            }
+            ProcessElement::CallActivity { id, span, .. }
+            | ProcessElement::Task { id, span, .. }
+            | ProcessElement::DataObject { id, span, .. } => declarations.push((id, span)),
+            ProcessElement::Pool {
+                name,
+                span,
+                lanes,
+                elements,
+                ..
+            } => {
+                for lane in lanes {
+                    collect_scope_declarations(&lane.elements, declarations, bodies);
+                }
+                collect_scope_declarations(elements, declarations, bodies);
+                declarations.push((name, span));
+            }
+            ProcessElement::Group { elements, .. } => {
+                collect_scope_declarations(elements, declarations, bodies);
+            }
+        }
+    }
+}
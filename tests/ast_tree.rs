@@ -0,0 +1,51 @@
+use bpmncode::{ast_tree::render_tree, lexer::Lexer, parser::Parser};
+
+fn tree(input: &str) -> String {
+    let tokens = Lexer::new(input, "test.bpmn").tokenize();
+    let document = Parser::new(tokens).parse();
+    assert!(!document.has_errors(), "Errors: {:?}", document.errors);
+    render_tree(&document)
+}
+
+#[test]
+fn test_tree_descends_into_nested_elements() {
+    let tree = tree(
+        r#"
+        process Orders {
+            start
+            xor Check {
+                [ok] -> Fulfil
+                => Reject
+            }
+            subprocess Fulfil "Fulfil order" {
+                task Pack
+            }
+            pool Shop {
+                lane Sales {
+                    user Reject
+                }
+            }
+            end @terminate
+
+            Fulfil -> end
+        }
+    "#,
+    );
+
+    assert_eq!(
+        tree,
+        r#"process Orders
+├── start
+├── xor Check
+│   ├── [ok] -> Fulfil
+│   └── => Reject
+├── subprocess Fulfil "Fulfil order"
+│   └── task Pack
+├── pool Shop
+│   └── lane Sales
+│       └── user Reject
+├── end @terminate
+└── Fulfil -> end
+"#
+    );
+}
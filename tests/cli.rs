@@ -0,0 +1,331 @@
+use std::{
+    fs,
+    io::Write,
+    process::{Command, Stdio},
+};
+
+#[test]
+fn test_check_json_over_several_files_is_one_document() {
+    let dir = tempfile::tempdir().unwrap();
+    let valid = dir.path().join("valid.bpmn");
+    let broken = dir.path().join("broken.bpmn");
+    fs::write(
+        &valid,
+        "process Valid {\n    start\n    task Ship\n    end\n    start -> Ship -> end\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        &broken,
+        "process Broken {\n    start\n    tsk Ship\n    end\n}\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_bpmncode"))
+        .arg("check")
+        .arg(&valid)
+        .arg(&broken)
+        .args(["--format", "json"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let reports: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let reports = reports.as_array().unwrap();
+
+    assert_eq!(reports.len(), 2);
+    assert!(reports[0]["file"].as_str().unwrap().ends_with("valid.bpmn"));
+    assert_eq!(reports[0]["summary"]["has_errors"], false);
+    assert_eq!(reports[1]["summary"]["has_errors"], true);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_check_json_counts_each_severity() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("twin.bpmn");
+    fs::write(
+        &path,
+        "process Twin {\n    start\n    start @message \"order\"\n    task Ship\n    end\n    start -> Ship -> end\n}\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_bpmncode"))
+        .arg("check")
+        .arg(&path)
+        .args(["--format", "json"])
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let reports: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let summary = &reports[0]["summary"];
+
+    assert_eq!(summary["error_count"], 0, "{stdout}");
+    assert_eq!(summary["warning_count"], 0, "{stdout}");
+    assert_eq!(summary["info_count"], 1, "{stdout}");
+    assert_eq!(summary["hint_count"], 0, "{stdout}");
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_check_reads_source_from_stdin() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_bpmncode"))
+        .args(["check", "-", "--format", "short"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"process Piped {\n    start\n    tsk Ship\n    end\n}\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("<stdin>:3:5"), "{stdout}");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_check_reads_stdin_once() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_bpmncode"))
+        .args(["check", "-", "--stdin", "-", "--format", "json"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"process Piped {\n    start\n    task Ship\n    end\n}\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let reports: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(reports.as_array().unwrap().len(), 1, "{stdout}");
+    assert_eq!(reports[0]["file"], "<stdin>", "{stdout}");
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_check_relative_path_omits_the_file_from_locations() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(
+        dir.path().join("p.bpmn"),
+        "process P {\n    start\n    tsk Ship\n    end\n}\n",
+    )
+    .unwrap();
+
+    for path in ["p.bpmn", "./p.bpmn"] {
+        let output = Command::new(env!("CARGO_BIN_EXE_bpmncode"))
+            .args(["check", "--no-color", path])
+            .current_dir(dir.path())
+            .output()
+            .unwrap();
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.contains("  error: 3:5 "), "{stdout}");
+    }
+}
+
+#[test]
+fn test_info_syntax_json_lists_the_vocabulary() {
+    let output = Command::new(env!("CARGO_BIN_EXE_bpmncode"))
+        .args(["info", "--syntax", "--format", "json"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let grammar: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let contains = |key: &str, value: &str| {
+        grammar[key]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|entry| entry == value)
+    };
+    assert!(contains("keywords", "process"));
+    assert!(contains("event_types", "timer"));
+    assert!(contains("flow_types", "->"));
+    assert!(contains("attribute_names", "assignee"));
+    assert!(contains("lints", "unreachable"));
+    assert_eq!(
+        grammar["error_codes"][3],
+        serde_json::json!({ "code": "E004", "kind": "duplicate identifier" })
+    );
+}
+
+#[test]
+fn test_stats_json_reports_each_process() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("order.bpmn");
+    fs::write(
+        &input,
+        "process Order {\n    start\n    user Review\n    xor Ok? {\n        [yes] -> Ship\n        => Reject\n    }\n    task Ship\n    task Reject\n    end\n}\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_bpmncode"))
+        .arg("stats")
+        .arg(&input)
+        .args(["--format", "json"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let metrics: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(metrics[0]["name"], "Order");
+    assert_eq!(metrics[0]["tasks"]["user"], 1);
+    assert_eq!(metrics[0]["gateways"]["xor"], 1);
+    assert_eq!(metrics[0]["complexity"], 2);
+}
+
+#[test]
+fn test_verbose_check_output_is_stable() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("golden.bpmn");
+    fs::write(
+        &input,
+        "process Golden {\n    start\n    xor Route {\n        [fast] -> Ship\n        => Hold\n    }\n    task Ship\n    task Hold\n    end\n    Ship -> end\n    Hold -> end\n}\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_bpmncode"))
+        .args(["check", "--verbose", "--no-color", "golden.bpmn"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+
+    let expected = "\
+✓ golden.bpmn - no issues found
+  📊 processes: 1
+  📦 imports: 0
+  🔄 'Golden' has 5 elements
+Debug: AST structure:
+  📄 Imports: 0
+  🔄 Processes: 1
+    - Golden (5 elements, 2 flows)
+      • end: 1
+      • start: 1
+      • task: 2
+      • xor: 1
+  process Golden
+  ├── start
+  ├── xor Route
+  │   ├── [fast] -> Ship
+  │   └── => Hold
+  ├── task Ship
+  ├── task Hold
+  ├── end
+  ├── Ship -> end
+  └── Hold -> end
+
+✓ All checks passed
+";
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), expected);
+}
+
+#[test]
+fn test_export_bpmndi_lays_out_each_process() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("order.bpmn");
+    fs::write(
+        &input,
+        "process Order {\n    start\n    task Ship\n    end\n    start -> Ship -> end\n}\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_bpmncode"))
+        .arg("export")
+        .arg(&input)
+        .args(["--format", "bpmndi"])
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success(), "{stdout}");
+    assert!(
+        stdout.starts_with("<bpmndi:BPMNDiagram id=\"Order_diagram\">\n"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("<bpmndi:BPMNShape id=\"Ship_di\" bpmnElement=\"Ship\">"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("bpmnElement=\"Flow_Ship_EndEvent_1\""),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn test_verbose_check_lists_imports_and_errors() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(
+        dir.path().join("shared.bpmn"),
+        "process Shared {\n    start\n    end\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("order.bpmn"),
+        "import \"shared.bpmn\" as shared\nprocess Order {\n    start\n    call shared::Shared\n    xor {\n        => Done\n    }\n    task Done\n    end\n}\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_bpmncode"))
+        .args(["check", "--verbose", "--no-color", "order.bpmn"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.contains("  📄 Imports: 1\n    - shared.bpmn as shared\n"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains(
+            "  ❌ Errors: 1\n    - Gateway has no name; flows cannot reference it, name it like `xor Decide`\n"
+        ),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn test_diff_json_lists_changes() {
+    let dir = tempfile::tempdir().unwrap();
+    let old = dir.path().join("old.bpmn");
+    let new = dir.path().join("new.bpmn");
+    fs::write(&old, "process Order {\n    start\n    user Validate\n}\n").unwrap();
+    fs::write(
+        &new,
+        "process Order {\n    start\n    service Validate\n}\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_bpmncode"))
+        .arg("diff")
+        .arg(&old)
+        .arg(&new)
+        .args(["--format", "json"])
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let changes: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(
+        changes,
+        serde_json::json!([{
+            "process": "Order",
+            "kind": "modified",
+            "subject": "Task 'Validate'",
+            "details": ["changed type user → service"],
+        }])
+    );
+    assert!(output.status.success());
+}
@@ -0,0 +1,632 @@
+use std::{collections::BTreeMap, fs};
+
+use bpmncode::{
+    CompileOptions, compile, compile_str, compile_str_with_options, compile_with_options,
+    diagnostics::{LintLevel, Severity},
+};
+
+#[test]
+fn test_compile_str_reports_pipeline_diagnostics() {
+    let report = compile_str(
+        r"
+        process Broken {
+            start
+            tsk Review
+            end
+        }
+    ",
+        "broken.bpmn",
+    );
+
+    assert_eq!(report.file_path, "broken.bpmn");
+    assert!(report.has_errors());
+    assert!(
+        report
+            .errors
+            .iter()
+            .any(|error| error.suggestions().contains(&"task".to_string())),
+        "Errors: {:?}",
+        report.errors
+    );
+}
+
+#[test]
+fn test_single_ampersand_in_condition_is_unknown_token() {
+    let report = compile_str(
+        r"
+        process Approval {
+            start
+            task Review
+            task Approve
+            end
+            start -> Review
+            Review -> Approve [approved & total >= 10]
+            Approve -> end
+        }
+    ",
+        "approval.bpmn",
+    );
+
+    let unknown: Vec<_> = report
+        .errors
+        .iter()
+        .filter(|error| error.to_string().contains("Unknown token"))
+        .collect();
+    assert_eq!(unknown.len(), 1, "Errors: {:?}", report.errors);
+    assert!(unknown[0].to_string().contains("'&'"));
+}
+
+#[test]
+fn test_negative_numbers_in_conditions_are_accepted() {
+    let report = compile_str(
+        r"
+        process Billing {
+            start
+            xor Balance {
+                [balance > -5] -> Charge
+                [balance >= -2.5] -> Remind
+                => Block
+            }
+            task Charge
+            task Remind
+            task Block
+            end
+            Charge -> end
+            Remind -> end
+        }
+    ",
+        "billing.bpmn",
+    );
+
+    assert!(!report.has_errors(), "Errors: {:?}", report.errors);
+
+    let report = compile_str(
+        "process Billing {\n    start\n    task Charge - 5\n    end\n}\n",
+        "billing.bpmn",
+    );
+    assert!(
+        report
+            .errors
+            .iter()
+            .any(|error| error.to_string().contains("Unknown token '-'")),
+        "Errors: {:?}",
+        report.errors
+    );
+}
+
+#[test]
+fn test_adjacent_unknown_characters_are_reported_once() {
+    let report = compile_str(
+        "process Order {\n    start\n    task Ship &%$ end\n}\n",
+        "order.bpmn",
+    );
+
+    let unexpected: Vec<_> = report
+        .errors
+        .iter()
+        .filter(|error| {
+            let message = error.to_string();
+            message.contains("Unknown token") || message.contains("Unexpected characters")
+        })
+        .collect();
+    assert_eq!(unexpected.len(), 1, "Errors: {:?}", report.errors);
+    assert_eq!(
+        unexpected[0].to_string(),
+        "Syntax error: Unexpected characters '&%$'"
+    );
+    assert_eq!(unexpected[0].span().column, 15);
+    assert_eq!(unexpected[0].span().end - unexpected[0].span().start, 3);
+}
+
+#[test]
+fn test_number_with_unknown_unit_is_reported_by_the_lexer_only() {
+    let report = compile_str(
+        "process Order {\n    start\n    task Ship @retries 10foo @timeout 1d12h\n    end\n    start -> Ship -> end\n}\n",
+        "order.bpmn",
+    );
+
+    let invalid: Vec<_> = report
+        .errors
+        .iter()
+        .filter(|error| error.to_string().contains("Invalid"))
+        .collect();
+    assert_eq!(invalid.len(), 1, "Errors: {:?}", report.errors);
+    assert_eq!(
+        invalid[0].to_string(),
+        "Syntax error: Invalid number suffix 'foo', expected one of ms, s, m, h or d"
+    );
+    assert_eq!(invalid[0].span().column, 24);
+    assert_eq!(invalid[0].span().end - invalid[0].span().start, 5);
+}
+
+#[test]
+fn test_namespaced_flow_source_is_not_a_keyword_typo() {
+    let report = compile_str(
+        "process Order {\n    start\n    validation::CheckData -> end\n}\n",
+        "order.bpmn",
+    );
+
+    // Only the missing import is reported, the name itself parses
+    assert_eq!(report.errors.len(), 1, "Errors: {:?}", report.errors);
+    assert!(
+        report.errors[0]
+            .to_string()
+            .contains("Unknown import namespace 'validation'")
+    );
+}
+
+#[test]
+fn test_compile_reads_file_from_disk() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("order.bpmn");
+    fs::write(
+        &path,
+        "process Order {\n    start\n    task Ship\n    end\n}\n",
+    )
+    .unwrap();
+
+    let report = compile(&path).unwrap();
+
+    assert!(report.errors.is_empty(), "Errors: {:?}", report.errors);
+    assert!(report.source_code.contains("task Ship"));
+}
+
+#[test]
+fn test_compilation_lists_the_files_it_read() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("order.bpmn");
+    fs::write(
+        dir.path().join("shared.bpmn"),
+        "process Shared {\n    start\n    end\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        &path,
+        "import \"shared.bpmn\" as shared\nimport \"missing.bpmn\" as missing\nprocess Order {\n    start\n    call shared::Shared\n    end\n}\n",
+    )
+    .unwrap();
+
+    let compilation = compile_with_options(&path, &CompileOptions::default()).unwrap();
+
+    let names: Vec<_> = compilation
+        .dependencies
+        .iter()
+        .map(|dependency| dependency.file_name().unwrap().to_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["missing.bpmn", "order.bpmn", "shared.bpmn"]);
+    assert!(
+        compilation
+            .dependencies
+            .iter()
+            .all(|dependency| dependency.is_absolute())
+    );
+}
+
+#[test]
+fn test_lexer_errors_in_imported_files_are_reported() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("order.bpmn");
+    fs::write(
+        dir.path().join("shared.bpmn"),
+        "process Shared {\n    start\n    task Pay\n    note \"oops\n    end\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        &path,
+        "import \"shared.bpmn\" as shared\nprocess Order {\n    start\n    call shared::Pay\n    end\n}\n",
+    )
+    .unwrap();
+
+    let report = compile(&path).unwrap();
+
+    assert_eq!(report.errors.len(), 1, "Errors: {:?}", report.errors);
+    let error = &report.errors[0];
+    assert!(error.to_string().contains("Unterminated string literal"));
+    assert!(error.span().file.ends_with("shared.bpmn"));
+    assert_eq!((error.span().line, error.span().column), (4, 10));
+}
+
+#[test]
+fn test_comprehensive_example_has_no_errors() {
+    let report = compile(std::path::Path::new("examples/comprehensive.bpmn")).unwrap();
+
+    let problems: Vec<_> = report
+        .errors
+        .iter()
+        .filter(|error| matches!(error.severity(), Severity::Error | Severity::Warning))
+        .collect();
+    assert!(problems.is_empty(), "{problems:?}");
+}
+
+#[test]
+fn test_every_example_checks_clean() {
+    let mut checked = 0;
+    for entry in fs::read_dir("examples").unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_none_or(|extension| extension != "bpmn") {
+            continue;
+        }
+
+        let report = compile(&path).unwrap();
+        let problems: Vec<_> = report
+            .errors
+            .iter()
+            .filter(|error| matches!(error.severity(), Severity::Error | Severity::Warning))
+            .collect();
+        assert!(problems.is_empty(), "{}: {problems:?}", path.display());
+        checked += 1;
+    }
+
+    assert!(checked > 1);
+}
+
+#[test]
+fn test_compile_missing_file_is_an_error() {
+    let dir = tempfile::tempdir().unwrap();
+
+    assert!(compile(&dir.path().join("missing.bpmn")).is_err());
+}
+
+#[test]
+fn test_unknown_gateway_target_suggests_existing_task() {
+    let report = compile_str(
+        r"
+        process Review {
+            start
+            task Approve
+            task Reject
+            xor Decision {
+                [yes] -> Aprove
+                [no] -> Reject
+            }
+            end
+        }
+    ",
+        "review.bpmn",
+    );
+
+    let error = report
+        .errors
+        .iter()
+        .find(|error| {
+            error
+                .to_string()
+                .contains("Unknown gateway target 'Aprove'")
+        })
+        .unwrap();
+
+    assert!(error.suggestions().contains(&"Approve".to_string()));
+}
+
+#[test]
+fn test_unterminated_string_points_at_opening_quote() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("note.bpmn");
+    fs::write(
+        &path,
+        "process Notes {\n    start\n    note \"oops\n    end\n}\n",
+    )
+    .unwrap();
+
+    let report = compile(&path).unwrap();
+
+    assert_eq!(report.errors.len(), 1, "Errors: {:?}", report.errors);
+    let error = &report.errors[0];
+    assert!(error.to_string().contains("Unterminated string literal"));
+    assert_eq!((error.span().line, error.span().column), (3, 10));
+}
+
+#[test]
+fn test_lint_levels_remap_severities() {
+    let source = r"
+        process Levels {
+            task Orphan
+            tsk Review
+        }
+    ";
+
+    let default = compile_str(source, "levels.bpmn");
+    let lints: Vec<_> = default
+        .errors
+        .iter()
+        .map(|error| (error.lint_name(), error.severity()))
+        .collect();
+    assert!(
+        lints.contains(&("missing-start", Severity::Warning)),
+        "{lints:?}"
+    );
+    assert!(
+        lints.contains(&("unexpected-token", Severity::Error)),
+        "{lints:?}"
+    );
+
+    let options = CompileOptions {
+        lint_levels: BTreeMap::from([
+            ("missing-start".to_string(), LintLevel::Error),
+            ("E002".to_string(), LintLevel::Allow),
+        ]),
+        ..CompileOptions::default()
+    };
+    let report = compile_str_with_options(source, "levels.bpmn", &options).report;
+    let lints: Vec<_> = report
+        .errors
+        .iter()
+        .map(|error| (error.lint_name(), error.severity()))
+        .collect();
+
+    assert_eq!(lints, vec![("missing-start", Severity::Error)]);
+    assert_eq!(report.warning_count(), 0);
+}
+
+#[test]
+fn test_undeclared_condition_variables_checked_once_a_var_is_declared() {
+    fn undeclared(source: &str, options: &CompileOptions) -> Vec<(String, usize, Vec<String>)> {
+        compile_str_with_options(source, "loan.bpmn", options)
+            .report
+            .errors
+            .iter()
+            .filter(|error| error.lint_name() == "undeclared-variable")
+            .map(|error| {
+                (
+                    error.to_string(),
+                    error.span().line,
+                    error.suggestions().to_vec(),
+                )
+            })
+            .collect()
+    }
+
+    let source = r"
+        process Loan {
+            var amount: number
+            var approved: boolean
+            start
+            xor Check {
+                [amout > 1000 && approved] -> Review
+                [customer.tier == 2] -> Review
+                => Done
+            }
+            task Review
+            task Done
+            Review -> end
+            Done -> end
+            end
+        }
+    ";
+
+    assert_eq!(
+        undeclared(source, &CompileOptions::default()),
+        vec![
+            (
+                "Syntax error: Undeclared variable 'amout' in condition".to_string(),
+                7,
+                vec!["amount".to_string()]
+            ),
+            (
+                "Syntax error: Undeclared variable 'customer' in condition".to_string(),
+                8,
+                vec![]
+            ),
+        ]
+    );
+
+    let allow = CompileOptions {
+        lint_levels: BTreeMap::from([("undeclared-variable".to_string(), LintLevel::Allow)]),
+        ..CompileOptions::default()
+    };
+    assert!(undeclared(source, &allow).is_empty());
+
+    // Without any `var` the process is only checked when the lint is raised
+    let source = source
+        .replace("var amount: number", "")
+        .replace("var approved: boolean", "");
+    assert!(undeclared(&source, &CompileOptions::default()).is_empty());
+
+    let warn = CompileOptions {
+        lint_levels: BTreeMap::from([("undeclared-variable".to_string(), LintLevel::Warning)]),
+        ..CompileOptions::default()
+    };
+    assert_eq!(undeclared(&source, &warn).len(), 3);
+}
+
+#[test]
+fn test_gateway_attribute_list_before_branches() {
+    let report = compile_str(
+        r"
+        process Order {
+            start
+            xor Check? (priority=high) {
+                [ok] -> Ship
+                => Ship
+            }
+            task Ship
+            end
+        }
+    ",
+        "order.bpmn",
+    );
+
+    assert!(report.errors.is_empty(), "{:?}", report.errors);
+}
+
+#[test]
+fn test_gateway_block_attributes_before_branches() {
+    let report = compile_str(
+        r#"
+        process Order {
+            start
+            xor Check @priority "high" {
+                [ok] -> Other
+                => Ship
+            }
+            xor Other (note=ab) {
+                [ok] -> Ship
+                => Ship
+            }
+            task Ship
+            end
+        }
+    "#,
+        "order.bpmn",
+    );
+
+    assert!(report.errors.is_empty(), "{:?}", report.errors);
+}
+
+#[test]
+fn test_join_gateway_needs_no_braces() {
+    let report = compile_str(
+        r"
+        process Order {
+            start
+            xor Pick {
+                [fast] -> Express
+                => Standard
+            }
+            task Express
+            task Standard
+            xor Merge join
+            xor Route {
+                [gift] -> Wrap
+                => Ship
+            }
+            task Wrap
+            task Ship
+            end
+            Express -> Merge
+            Wrap -> Ship
+        }
+    ",
+        "order.bpmn",
+    );
+
+    assert!(report.errors.is_empty(), "{:?}", report.errors);
+}
+
+#[test]
+fn test_gateway_keywords_inside_conditions_are_not_gateways() {
+    let report = compile_str(
+        r"
+        process Order {
+            start
+            xor Check? {
+                [approved or vip] -> A
+                [fast and cheap] -> A
+                => B
+            }
+            task A
+            task B
+            end
+            A -> B [approved or vip]
+        }
+    ",
+        "order.bpmn",
+    );
+
+    let braces: Vec<_> = report
+        .errors
+        .iter()
+        .map(ToString::to_string)
+        .filter(|message| message.contains("gateway missing"))
+        .collect();
+    assert!(braces.is_empty(), "{braces:?}");
+}
+
+#[test]
+fn test_service_endpoints_must_be_urls_or_paths() {
+    let source = r#"
+        process Billing {
+            start
+            service Charge(endpoint="htp://payments.local/charge")
+            service Refund(endpoint="payments")
+            service Notify(endpoint="https://mail.example.com:8443/send")
+            service Archive(endpoint="/api/archive")
+            service Audit(endpoint="${auditUrl}/events")
+            service Local(endpoint="http://[::1]:8080/x")
+            service Login(endpoint="http://user@host/x")
+            end
+        }
+    "#;
+
+    let endpoints = |report: &bpmncode::diagnostics::DiagnosticReport| -> Vec<_> {
+        report
+            .errors
+            .iter()
+            .filter(|error| error.code() == "E005")
+            .map(|error| {
+                (
+                    error.to_string(),
+                    error.severity(),
+                    error.suggestions().to_vec(),
+                )
+            })
+            .collect()
+    };
+
+    let report = compile_str(source, "billing.bpmn");
+    assert_eq!(
+        endpoints(&report),
+        vec![
+            (
+                "Invalid attribute 'endpoint=\"htp://payments.local/charge\"' for element 'Charge'"
+                    .to_string(),
+                Severity::Warning,
+                vec![
+                    "http://payments.local/charge".to_string(),
+                    "https://payments.local/charge".to_string()
+                ]
+            ),
+            (
+                "Invalid attribute 'endpoint=\"payments\"' for element 'Refund'".to_string(),
+                Severity::Warning,
+                vec![]
+            ),
+        ]
+    );
+
+    let options = CompileOptions {
+        lint_levels: BTreeMap::from([("service-endpoint".to_string(), LintLevel::Error)]),
+        ..CompileOptions::default()
+    };
+    let report = compile_str_with_options(source, "billing.bpmn", &options).report;
+    let severities: Vec<_> = endpoints(&report)
+        .into_iter()
+        .map(|(_, severity, _)| severity)
+        .collect();
+    assert_eq!(severities, vec![Severity::Error, Severity::Error]);
+}
+
+#[test]
+fn test_unnamed_gateway_warning_can_be_allowed() {
+    let source = r"
+        process Order {
+            start
+            and {
+                => Pack
+                => Bill
+            }
+            task Pack
+            task Bill
+            end
+        }
+    ";
+
+    let unnamed = |report: &bpmncode::diagnostics::DiagnosticReport| -> Vec<_> {
+        report
+            .errors
+            .iter()
+            .filter(|error| error.lint_name() == "unnamed-gateway")
+            .map(|error| (error.span().line, error.severity()))
+            .collect()
+    };
+
+    let report = compile_str(source, "order.bpmn");
+    assert_eq!(unnamed(&report), vec![(4, Severity::Warning)]);
+
+    let options = CompileOptions {
+        lint_levels: BTreeMap::from([("unnamed-gateway".to_string(), LintLevel::Allow)]),
+        ..CompileOptions::default()
+    };
+    let report = compile_str_with_options(source, "order.bpmn", &options).report;
+    assert!(unnamed(&report).is_empty(), "{:?}", report.errors);
+}
@@ -0,0 +1,64 @@
+use bpmncode::{
+    completion::{Completion, CompletionKind, complete_at},
+    lexer::Lexer,
+};
+
+fn complete(source_with_cursor: &str) -> Vec<Completion> {
+    let offset = source_with_cursor.find('|').unwrap();
+    let source = source_with_cursor.replacen('|', "", 1);
+    let tokens = Lexer::new(&source, "test.bpmn").tokenize();
+    complete_at(&tokens, offset)
+}
+
+fn insert_texts(completions: &[Completion]) -> Vec<&str> {
+    completions
+        .iter()
+        .map(|completion| completion.insert_text.as_str())
+        .collect()
+}
+
+#[test]
+fn test_statement_start_completes_keywords() {
+    let completions = complete("process P {\n    ta|\n}\n");
+
+    assert_eq!(insert_texts(&completions), vec!["task"]);
+    assert_eq!(completions[0].kind, CompletionKind::Keyword);
+}
+
+#[test]
+fn test_after_at_completes_event_types() {
+    let completions = complete("process P {\n    start @|\n}\n");
+
+    assert!(
+        completions
+            .iter()
+            .all(|completion| completion.kind == CompletionKind::EventType)
+    );
+    assert!(insert_texts(&completions).contains(&"timer"));
+}
+
+#[test]
+fn test_inside_parens_completes_attribute_names() {
+    let completions = complete("process P {\n    user Review (as|)\n}\n");
+
+    assert_eq!(insert_texts(&completions), vec!["assignee"]);
+    assert_eq!(completions[0].kind, CompletionKind::Attribute);
+}
+
+#[test]
+fn test_after_flow_arrow_completes_ids_of_enclosing_process() {
+    let source = "process Other {\n    task Elsewhere\n}\n\nprocess P {\n    task Review\n    task Ship\n    xor Check {\n        => Ship\n    }\n    Review -> |\n}\n";
+    let completions = complete(source);
+
+    assert_eq!(insert_texts(&completions), vec!["Review", "Ship", "Check"]);
+    assert!(
+        completions
+            .iter()
+            .all(|completion| completion.kind == CompletionKind::Element)
+    );
+}
+
+#[test]
+fn test_no_completions_after_element_name() {
+    assert!(complete("process P {\n    task Review |\n}\n").is_empty());
+}
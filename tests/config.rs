@@ -0,0 +1,53 @@
+use std::fs;
+
+use bpmncode::config::{Config, ConfigError, LintLevel};
+
+#[test]
+fn test_config_is_discovered_from_parent_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    let nested = dir.path().join("processes/billing");
+    fs::create_dir_all(&nested).unwrap();
+    fs::write(
+        dir.path().join(".bpmncode.toml"),
+        r#"
+format = "short"
+no_source = true
+max_nesting_depth = 32
+indent_width = 2
+
+[lints]
+unreachable = "error"
+missing-start = "allow"
+"#,
+    )
+    .unwrap();
+
+    let config = Config::load(&nested.join("invoice.bpmn")).unwrap();
+
+    assert_eq!(config.format.as_deref(), Some("short"));
+    assert_eq!(config.no_source, Some(true));
+    assert_eq!(config.max_nesting_depth, Some(32));
+    assert_eq!(config.indent_width, Some(2));
+    assert_eq!(config.lints.get("unreachable"), Some(&LintLevel::Error));
+    assert_eq!(config.lints.get("missing-start"), Some(&LintLevel::Allow));
+    assert_eq!(config.path, Some(dir.path().join(".bpmncode.toml")));
+}
+
+#[test]
+fn test_missing_config_is_default() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let config = Config::load(&dir.path().join("order.bpmn")).unwrap();
+
+    assert_eq!(config, Config::default());
+}
+
+#[test]
+fn test_invalid_config_is_an_error() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join(".bpmncode.toml"), "max_nesting = 3\n").unwrap();
+
+    let result = Config::load(&dir.path().join("order.bpmn"));
+
+    assert!(matches!(result, Err(ConfigError::Parse { .. })));
+}
@@ -0,0 +1,534 @@
+use std::path::PathBuf;
+
+use bpmncode::{
+    diagnostics::{
+        DiagnosticError, DiagnosticReport, ERROR_CODES, Severity, SourcedDiagnostic,
+        baseline::Baseline,
+        fix::{apply_fixes, collect_fixes},
+        formatter::DiagnosticFormatter,
+        is_known_lint,
+    },
+    lexer::Span,
+};
+use miette::Diagnostic;
+
+fn span_at(file: &str, line: usize, column: usize) -> Span {
+    Span {
+        start: 0,
+        end: 4,
+        line,
+        column,
+        file: PathBuf::from(file),
+    }
+}
+
+fn syntax_error(file: &str, line: usize, message: &str) -> DiagnosticError {
+    DiagnosticError::SyntaxError {
+        message: message.to_string(),
+        span: span_at(file, line, 5),
+        severity: Severity::Error,
+        suggestions: Vec::new(),
+        lint: "syntax",
+    }
+}
+
+#[test]
+fn test_review_format_lists_only_new_diagnostics() {
+    let mut old_report = DiagnosticReport::new("order.bpmn".to_string(), String::new());
+    old_report.add_error(syntax_error("order.bpmn", 3, "Known problem"));
+    let baseline = Baseline::from_reports(&[old_report]);
+
+    let mut report = DiagnosticReport::new("order.bpmn".to_string(), String::new());
+    report.add_error(syntax_error("order.bpmn", 3, "Known problem"));
+    report.add_error(syntax_error("order.bpmn", 7, "Fresh problem"));
+    baseline.retain_new(&mut report);
+
+    let untouched = DiagnosticReport::new("clean.bpmn".to_string(), String::new());
+
+    let formatter = DiagnosticFormatter::new(false, false);
+    let markdown = formatter.format_review(&[report, untouched]);
+
+    assert!(markdown.contains("1 new diagnostic"));
+    assert!(markdown.contains("<summary><code>order.bpmn</code>"));
+    assert!(markdown.contains("`E001`"));
+    assert!(markdown.contains("Fresh problem"));
+    assert!(!markdown.contains("Known problem"));
+    assert!(!markdown.contains("clean.bpmn"));
+}
+
+#[test]
+fn test_review_format_without_new_diagnostics() {
+    let formatter = DiagnosticFormatter::new(false, false);
+    let report = DiagnosticReport::new("order.bpmn".to_string(), String::new());
+
+    assert!(
+        formatter
+            .format_review(&[report])
+            .contains("no new diagnostics")
+    );
+}
+
+#[test]
+fn test_long_source_line_is_truncated_around_error() {
+    let condition = format!(
+        "xor Check -> [{} > 10] Done",
+        "customer_order_total_amount_".repeat(8)
+    );
+    let column = condition.find("> 10").unwrap() + 1;
+
+    let mut report = DiagnosticReport::new("order.bpmn".to_string(), condition.clone());
+    report.add_error(DiagnosticError::SyntaxError {
+        message: format!("Unexpected token in condition '{condition}'"),
+        span: Span {
+            start: column - 1,
+            end: column,
+            line: 1,
+            column,
+            file: PathBuf::from("order.bpmn"),
+        },
+        severity: Severity::Error,
+        suggestions: Vec::new(),
+        lint: "syntax",
+    });
+
+    let formatter = DiagnosticFormatter::new(false, true).with_width(Some(60));
+    let output = formatter.format_cli(&report);
+
+    for line in output.lines() {
+        assert!(line.chars().count() <= 60, "line too wide: {line}");
+    }
+
+    let source_line = output.lines().find(|line| line.contains("> 10")).unwrap();
+    let caret_line = output.lines().find(|line| line.contains('^')).unwrap();
+    let gt_column = source_line
+        .find("> 10")
+        .map(|index| source_line[..index].chars().count())
+        .unwrap();
+    let caret_column = caret_line.chars().position(|c| c == '^').unwrap();
+
+    assert!(source_line.contains('…'));
+    assert_eq!(gt_column, caret_column);
+}
+
+#[test]
+fn test_carets_stop_at_end_of_line_for_multiline_span() {
+    let source = "process Notes {\n    task Review /* first\n    second */\n}\n";
+    let start = source.find("/*").unwrap();
+    let end = source.find("*/").unwrap() + 2;
+
+    let mut report = DiagnosticReport::new("notes.bpmn".to_string(), source.to_string());
+    report.add_error(DiagnosticError::SyntaxError {
+        message: "Unexpected comment".to_string(),
+        span: Span {
+            start,
+            end,
+            line: 2,
+            column: 17,
+            file: PathBuf::from("notes.bpmn"),
+        },
+        severity: Severity::Error,
+        suggestions: Vec::new(),
+        lint: "syntax",
+    });
+
+    let formatter = DiagnosticFormatter::new(false, true);
+    let output = formatter.format_cli(&report);
+    let lines: Vec<&str> = output.lines().collect();
+    let source_index = lines
+        .iter()
+        .position(|line| line.ends_with("task Review /* first"))
+        .unwrap();
+
+    assert_eq!(lines[source_index + 1], "    |                 ^^^^^^^^...");
+    assert!(lines[source_index + 1].len() <= lines[source_index].len() + 3);
+}
+
+#[test]
+fn test_carets_are_clamped_to_line_length() {
+    let source = "task Review\n";
+
+    let mut report = DiagnosticReport::new("clamp.bpmn".to_string(), source.to_string());
+    report.add_error(DiagnosticError::SyntaxError {
+        message: "Bad column".to_string(),
+        span: Span {
+            start: 0,
+            end: 11,
+            line: 1,
+            column: 40,
+            file: PathBuf::from("clamp.bpmn"),
+        },
+        severity: Severity::Error,
+        suggestions: Vec::new(),
+        lint: "syntax",
+    });
+
+    let formatter = DiagnosticFormatter::new(false, true);
+    let output = formatter.format_cli(&report);
+    let caret_line = output.lines().find(|line| line.contains('^')).unwrap();
+
+    assert_eq!(caret_line, format!("    | {}^", " ".repeat(11)));
+}
+
+#[test]
+fn test_fancy_format_renders_source_snippet() {
+    let source = "process P {\n    start\n    tsk Review\n    end\n}\n";
+    let start = source.find("tsk").unwrap();
+
+    let mut report = DiagnosticReport::new("typo.bpmn".to_string(), source.to_string());
+    report.add_error(DiagnosticError::UnexpectedToken {
+        found: "tsk".to_string(),
+        expected: "keyword".to_string(),
+        span: Span {
+            start,
+            end: start + 3,
+            line: 3,
+            column: 5,
+            file: PathBuf::from("typo.bpmn"),
+        },
+        severity: Severity::Error,
+        suggestions: vec!["task".to_string()],
+    });
+
+    let output = DiagnosticFormatter::new(false, true).format_fancy(&report);
+
+    assert!(output.contains("[typo.bpmn:3:5]"), "{output}");
+    assert!(output.contains(" 3 |     tsk Review"), "{output}");
+    assert!(output.contains("help: Did you mean: task?"), "{output}");
+    assert!(!output.contains('\u{1b}'), "{output}");
+}
+
+#[test]
+fn test_sourced_diagnostic_exposes_report_source() {
+    let source = "process P {\n    tsk Review\n}\n";
+    let start = source.find("tsk").unwrap();
+
+    let mut report = DiagnosticReport::new("typo.bpmn".to_string(), source.to_string());
+    report.add_error(DiagnosticError::UnexpectedToken {
+        found: "tsk".to_string(),
+        expected: "keyword".to_string(),
+        span: Span {
+            start,
+            end: start + 3,
+            line: 2,
+            column: 5,
+            file: PathBuf::from("typo.bpmn"),
+        },
+        severity: Severity::Error,
+        suggestions: Vec::new(),
+    });
+
+    let named = report.named_source();
+    let diagnostic = SourcedDiagnostic {
+        error: &report.errors[0],
+        source_code: &named,
+    };
+
+    let label = diagnostic.labels().unwrap().next().unwrap();
+    let contents = diagnostic
+        .source_code()
+        .unwrap()
+        .read_span(label.inner(), 0, 0)
+        .unwrap();
+
+    assert_eq!(contents.data(), b"tsk");
+    assert_eq!(contents.name(), Some("typo.bpmn"));
+    assert!(report.errors[0].source_code().is_none());
+}
+
+#[test]
+fn test_errors_in_imported_files_show_that_file() {
+    let imported = "process Shared {\n    task Pay \"unterminated\n}\n";
+    let start = imported.find('"').unwrap();
+
+    let mut report = DiagnosticReport::new(
+        "order.bpmn".to_string(),
+        "import \"shared.bpmn\" as shared\nprocess Order {\n    start\n}\n".to_string(),
+    );
+    report
+        .imported_sources
+        .insert(PathBuf::from("/work/shared.bpmn"), imported.to_string());
+    report.add_error(DiagnosticError::SyntaxError {
+        message: "Unterminated string literal".to_string(),
+        span: Span {
+            start,
+            end: start + 1,
+            line: 2,
+            column: 14,
+            file: PathBuf::from("/work/shared.bpmn"),
+        },
+        severity: Severity::Error,
+        lint: "syntax",
+        suggestions: Vec::new(),
+    });
+
+    let cli = DiagnosticFormatter::new(false, true).format_cli(&report);
+    assert!(
+        cli.contains("    |     task Pay \"unterminated\n    |              ^"),
+        "{cli}"
+    );
+
+    let fancy = DiagnosticFormatter::new(false, true).format_fancy(&report);
+    assert!(fancy.contains("[/work/shared.bpmn:2:14]"), "{fancy}");
+    assert!(fancy.contains("task Pay"), "{fancy}");
+    assert!(!fancy.contains("process Order"), "{fancy}");
+}
+
+#[test]
+fn test_sarif_format_merges_reports_into_one_run() {
+    let mut order = DiagnosticReport::new("order.bpmn".to_string(), String::new());
+    order.add_error(syntax_error("order.bpmn", 3, "Broken flow"));
+    let mut invoice = DiagnosticReport::new("invoice.bpmn".to_string(), String::new());
+    invoice.add_error(
+        syntax_error("invoice.bpmn", 9, "Element 'Audit' is unreachable")
+            .with_severity(Severity::Warning),
+    );
+
+    let formatter = DiagnosticFormatter::new(false, false);
+    let sarif: serde_json::Value =
+        serde_json::from_str(&formatter.format_sarif(&[order, invoice]).unwrap()).unwrap();
+
+    assert_eq!(sarif["version"], "2.1.0");
+    assert_eq!(sarif["runs"].as_array().unwrap().len(), 1);
+
+    let results = sarif["runs"][0]["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0]["ruleId"], "E001");
+    assert_eq!(results[0]["level"], "error");
+    assert_eq!(results[1]["level"], "warning");
+
+    let location = &results[1]["locations"][0]["physicalLocation"];
+    assert_eq!(location["artifactLocation"]["uri"], "invoice.bpmn");
+    assert_eq!(location["region"]["startLine"], 9);
+    assert_eq!(location["region"]["startColumn"], 5);
+}
+
+#[test]
+fn test_github_format_emits_workflow_commands() {
+    let mut report = DiagnosticReport::new("flows/order, v2.bpmn".to_string(), String::new());
+    report.add_error(syntax_error(
+        "flows/order, v2.bpmn",
+        3,
+        "Broken flow\nsecond line",
+    ));
+    report.add_error(
+        syntax_error("flows/order, v2.bpmn", 8, "Gateway has no default")
+            .with_severity(Severity::Info),
+    );
+
+    let formatter = DiagnosticFormatter::new(false, false);
+    let lines: Vec<String> = formatter
+        .format_github(&report)
+        .lines()
+        .map(str::to_string)
+        .collect();
+
+    assert_eq!(
+        lines,
+        vec![
+            "::error file=flows/order%2C v2.bpmn,line=3,col=5,title=E001::Syntax error: Broken flow%0Asecond line",
+            "::notice file=flows/order%2C v2.bpmn,line=8,col=5,title=E001::Syntax error: Gateway has no default",
+        ]
+    );
+}
+
+#[test]
+fn test_github_and_sarif_locate_errors_in_imported_files() {
+    let mut report = DiagnosticReport::new("order.bpmn".to_string(), String::new());
+    report.add_error(syntax_error("order.bpmn", 3, "Broken flow"));
+    report.add_error(syntax_error("lib/shared.bpmn", 7, "Unknown task type"));
+
+    let formatter = DiagnosticFormatter::new(false, false);
+    let github = formatter.format_github(&report);
+    let files: Vec<_> = github
+        .lines()
+        .filter_map(|line| line.split("file=").nth(1)?.split(',').next())
+        .collect();
+    assert_eq!(files, vec!["order.bpmn", "lib/shared.bpmn"], "{github}");
+
+    let sarif: serde_json::Value =
+        serde_json::from_str(&formatter.format_sarif(&[report]).unwrap()).unwrap();
+    let uris: Vec<_> = sarif["runs"][0]["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|result| {
+            result["locations"][0]["physicalLocation"]["artifactLocation"]["uri"]
+                .as_str()
+                .unwrap()
+        })
+        .collect();
+    assert_eq!(uris, vec!["order.bpmn", "lib/shared.bpmn"]);
+}
+
+#[test]
+fn test_fixes_apply_single_suggestions_back_to_front() {
+    let source = "process Order {\n    strat\n    tsk Review\n    end\n}\n";
+    let report = bpmncode::compile_str(source, "order.bpmn");
+
+    let fixes = collect_fixes(&report);
+    assert_eq!(fixes.len(), 2, "Errors: {:?}", report.errors);
+
+    let (output, applied) = apply_fixes(source, fixes);
+    assert_eq!(applied, 2);
+    assert_eq!(
+        output,
+        "process Order {\n    start\n    task Review\n    end\n}\n"
+    );
+    assert!(!bpmncode::compile_str(&output, "order.bpmn").has_errors());
+}
+
+#[test]
+fn test_fixes_skip_ambiguous_and_non_word_spans() {
+    let source = "\"unterminated";
+    let mut report = DiagnosticReport::new("order.bpmn".to_string(), source.to_string());
+    report.add_error(
+        syntax_error("order.bpmn", 1, "Unterminated string literal")
+            .with_suggestion("\"".to_string()),
+    );
+    report.add_error(
+        syntax_error("order.bpmn", 1, "Unknown keyword")
+            .with_suggestions(vec!["task".to_string(), "tasks".to_string()]),
+    );
+
+    assert!(collect_fixes(&report).is_empty());
+}
+
+#[test]
+fn test_fixes_only_replace_the_named_token() {
+    let unnamed =
+        "process Order {\n    start\n    xor\n        [ok] -> A\n    }\n    task A\n    end\n}\n";
+    let misnamed_call = "process Invoicing {\n    start\n    end\n}\nprocess Order {\n    start\n    call Invoce\n    end\n}\n";
+
+    for source in [unnamed, misnamed_call] {
+        let report = bpmncode::compile_str(source, "order.bpmn");
+        assert!(
+            report
+                .errors
+                .iter()
+                .any(|error| error.suggestions().len() == 1),
+            "Errors: {:?}",
+            report.errors
+        );
+        assert!(collect_fixes(&report).is_empty(), "{source}");
+    }
+}
+
+#[test]
+fn test_duplicate_id_points_at_first_definition() {
+    let source = "process Order {\n    start\n    task Review\n    subprocess Billing {\n        task Review\n    }\n    user Review\n    end\n}\n";
+    let report = bpmncode::compile_str(source, "order.bpmn");
+
+    let duplicates: Vec<_> = report
+        .errors
+        .iter()
+        .filter(|error| error.code() == "E004")
+        .collect();
+    assert_eq!(duplicates.len(), 1, "Errors: {:?}", report.errors);
+    assert_eq!(duplicates[0].lint_name(), "duplicate-id");
+    assert_eq!(duplicates[0].span().line, 7);
+    assert_eq!(
+        duplicates[0].first_definition().map(|span| span.line),
+        Some(3)
+    );
+
+    let output = DiagnosticFormatter::new(false, false).format_cli(&report);
+    assert!(
+        output.contains("Duplicate identifier 'Review'\n    note: first defined at line 3"),
+        "{output}"
+    );
+}
+
+#[test]
+fn test_sort_orders_by_file_and_position() {
+    let mut report = DiagnosticReport::new("order.bpmn".to_string(), String::new());
+    report.add_error(syntax_error("order.bpmn", 9, "Late"));
+    report.add_error(syntax_error("common.bpmn", 4, "Imported"));
+    report.add_error(syntax_error("order.bpmn", 2, "First at line 2"));
+    report.add_error(syntax_error("order.bpmn", 2, "Second at line 2"));
+
+    report.sort();
+
+    let messages: Vec<_> = report.errors.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "Syntax error: Imported",
+            "Syntax error: First at line 2",
+            "Syntax error: Second at line 2",
+            "Syntax error: Late",
+        ]
+    );
+}
+
+#[test]
+fn test_max_errors_truncates_output_but_not_totals() {
+    let mut report = DiagnosticReport::new("order.bpmn".to_string(), String::new());
+    for line in 1..=5 {
+        report.add_error(syntax_error("order.bpmn", line, &format!("Problem {line}")));
+    }
+
+    let formatter = DiagnosticFormatter::new(false, false).with_max_errors(Some(2));
+
+    let short = formatter.format_short(&report);
+    assert_eq!(short.lines().count(), 3);
+    assert!(short.contains("Problem 2"));
+    assert!(!short.contains("Problem 3"));
+    assert!(short.ends_with("… and 3 more\n"));
+
+    let human = formatter.format_cli(&report);
+    assert!(human.contains("… and 3 more"));
+    assert!(human.contains("5 errors"), "{human}");
+
+    let json: serde_json::Value =
+        serde_json::from_str(&formatter.format_json(&report).unwrap()).unwrap();
+    assert_eq!(json["errors"].as_array().unwrap().len(), 2);
+    assert_eq!(json["summary"]["error_count"], 5);
+    assert_eq!(json["summary"]["omitted"], 3);
+}
+
+#[test]
+fn test_error_codes_are_known_lints() {
+    let error = syntax_error("order.bpmn", 1, "Broken flow");
+    assert!(ERROR_CODES.iter().any(|(code, _)| *code == error.code()));
+
+    for (index, (code, _)) in ERROR_CODES.iter().enumerate() {
+        assert!(is_known_lint(code));
+        assert!(!ERROR_CODES[..index].iter().any(|(other, _)| other == code));
+    }
+    assert!(!is_known_lint("E009"));
+    assert!(!is_known_lint("E00"));
+}
+
+#[test]
+fn test_single_input_locations_omit_the_path() {
+    let report = bpmncode::compile_str(
+        "process Order {\n    var total: text\n    start\n    end\n}\n",
+        "order.bpmn",
+    );
+    assert_eq!(
+        report.errors[0].to_string(),
+        "Syntax error: Unexpected token 'text', expected number, string or boolean"
+    );
+
+    let output = DiagnosticFormatter::new(false, false)
+        .with_paths(false)
+        .format_cli(&report);
+    assert!(output.contains("  error: 2:16 Syntax error"), "{output}");
+    assert!(!output.contains("order.bpmn:"), "{output}");
+
+    let output = DiagnosticFormatter::new(false, false).format_cli(&report);
+    assert!(
+        output.contains("  error: order.bpmn:2:16 Syntax error"),
+        "{output}"
+    );
+
+    let mut report = DiagnosticReport::new("order.bpmn".to_string(), String::new());
+    report.add_error(syntax_error("common.bpmn", 4, "Imported"));
+    let output = DiagnosticFormatter::new(false, false)
+        .with_paths(false)
+        .format_cli(&report);
+    assert!(
+        output.contains("  error: common.bpmn:4:5 Syntax error"),
+        "{output}"
+    );
+}
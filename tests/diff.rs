@@ -0,0 +1,126 @@
+use bpmncode::{
+    diff::{ChangeKind, diff_documents},
+    lexer::Lexer,
+    parser::{Parser, ast::AstDocument},
+};
+
+fn parse(input: &str) -> AstDocument {
+    let tokens = Lexer::new(input, "test.bpmn").tokenize();
+    let document = Parser::new(tokens).parse();
+    assert!(!document.has_errors(), "Errors: {:?}", document.errors);
+    document
+}
+
+fn diff(old: &str, new: &str) -> Vec<String> {
+    diff_documents(&parse(old), &parse(new))
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[test]
+fn test_diff_matches_elements_by_id() {
+    let changes = diff(
+        r#"
+        process Order {
+            start
+            user Validate "Validate order" (timeout=5m)
+            xor Check {
+                [amount > 100] -> Review
+                => Ship
+            }
+            task Review
+            task Ship
+            pool Shop {
+                lane Sales {
+                    task Quote
+                }
+            }
+            Validate -> Check
+            Review -> Ship
+        }
+        "#,
+        r#"
+        process Order {
+            start
+            task Audit
+            service Validate "Check order" (timeout=10m, retries=3)
+            xor Check {
+                [amount > 500] -> Review
+                => Ship
+            }
+            task Ship
+            task Review
+            pool Shop {
+                lane Sales {
+                }
+                task Quote
+            }
+            Validate -> Check
+            Ship -> Audit
+        }
+        process Refund {
+            start
+        }
+        "#,
+    );
+
+    assert_eq!(
+        changes,
+        vec![
+            "Task 'Validate' changed type user → service, changed label \"Validate order\" → \
+             \"Check order\", changed timeout 5m → 10m, added retries=3",
+            "Task 'Quote' moved from lane Sales to lane default",
+            "Task 'Audit' added",
+            "Flow Review -> Ship removed",
+            "Flow Check -> Review changed condition [amount > 100] → [amount > 500]",
+            "Flow Ship -> Audit added",
+            "Process 'Refund' added",
+        ]
+    );
+}
+
+#[test]
+fn test_diff_ignores_layout_and_order() {
+    let old = r"
+        process Order {
+            var amount: number
+            start
+            task A
+            task B
+            A -> B
+            A -> B [amount > 1]
+        }
+    ";
+    let new = r"
+        process Order {
+            var amount: number
+
+            task B
+            start
+
+
+            task A
+            A -> B [amount > 1]
+            A -> B
+        }
+    ";
+
+    assert!(diff(old, new).is_empty());
+
+    let changes = diff_documents(&parse(old), &parse("process Order { start }"));
+    let kinds: Vec<_> = changes
+        .iter()
+        .map(|change| (change.kind, change.subject.as_str()))
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            (ChangeKind::Removed, "Variable 'amount'"),
+            (ChangeKind::Removed, "Task 'A'"),
+            (ChangeKind::Removed, "Task 'B'"),
+            (ChangeKind::Removed, "Flow A -> B"),
+            (ChangeKind::Removed, "Flow A -> B"),
+        ]
+    );
+}
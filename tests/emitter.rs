@@ -0,0 +1,393 @@
+use bpmncode::{
+    emitter::{
+        dot::to_dot,
+        layout::{layout_process, to_bpmndi},
+        mermaid::to_mermaid,
+        to_iso8601_duration,
+    },
+    lexer::Lexer,
+    parser::{ast::ProcessDeclaration, parse_tokens},
+};
+
+fn parse_process(input: &str) -> ProcessDeclaration {
+    let mut lexer = Lexer::new(input, "test.bpmn");
+    let tokens = lexer.tokenize();
+    let document = parse_tokens(tokens);
+
+    assert!(!document.has_errors(), "Errors: {:?}", document.errors);
+    document.processes.into_iter().next().unwrap()
+}
+
+#[test]
+fn test_mermaid_flowchart_shapes_and_conditions() {
+    let process = parse_process(
+        r"
+        process Order {
+            start
+            task Validate
+            xor {
+                [valid] -> Ship
+                => Reject
+            }
+            task Ship
+            task Reject
+            end
+
+            Validate -> Ship
+            Ship -> end
+        }
+    ",
+    );
+
+    let mermaid = to_mermaid(&process);
+
+    assert!(mermaid.starts_with("flowchart TD\n"));
+    assert!(mermaid.contains("start((start))"));
+    assert!(mermaid.contains("end_event((end))"));
+    assert!(mermaid.contains("Validate[Validate]"));
+    assert!(mermaid.contains("gateway_5_13{X}"));
+    assert!(mermaid.contains("gateway_5_13 -->|valid| Ship"));
+    assert!(mermaid.contains("gateway_5_13 ==> Reject"));
+    assert!(mermaid.contains("Validate --> Ship"));
+    assert!(mermaid.contains("Ship --> end_event"));
+}
+
+#[test]
+fn test_mermaid_draws_implicit_flows() {
+    let process = parse_process(
+        r"
+        process SimpleOrder {
+            start
+            task ReceiveOrder
+            subprocess Packing {
+                task Pick
+                task Pack
+            }
+            end
+        }
+    ",
+    );
+
+    let mermaid = to_mermaid(&process);
+
+    for edge in [
+        "start --> ReceiveOrder",
+        "ReceiveOrder --> Packing",
+        "Packing --> end_event",
+        "Pick --> Pack",
+    ] {
+        assert!(mermaid.contains(&format!("    {edge}\n")), "{mermaid}");
+    }
+}
+
+#[test]
+fn test_mermaid_subprocess_becomes_subgraph() {
+    let process = parse_process(
+        r"
+        process Fulfillment {
+            start
+            subprocess Packing {
+                start
+                task Pick
+                end
+            }
+            end
+
+            Packing -> end
+        }
+    ",
+    );
+
+    let mermaid = to_mermaid(&process);
+
+    assert!(mermaid.contains("subgraph Packing [Packing]"));
+    assert!(mermaid.contains("Packing_start((start))"));
+    assert!(mermaid.contains("Packing_end((end))"));
+    assert!(mermaid.contains("Pick[Pick]"));
+    assert!(mermaid.contains("Packing --> end_event"));
+}
+
+#[test]
+fn test_dot_clusters_lanes_and_styles_edges() {
+    let process = parse_process(
+        r"
+        process Support {
+            start
+            pool Company {
+                lane Frontdesk {
+                    task Answer
+                }
+                lane Engineering {
+                    task Fix
+                }
+            }
+            end
+
+            Answer -> Fix
+            Answer --> Fix
+            Answer ..> Fix
+        }
+    ",
+    );
+
+    let dot = to_dot(&process);
+
+    assert!(dot.starts_with("digraph \"Support\" {"));
+    assert!(dot.contains("subgraph \"cluster_Company\" {"));
+    assert!(dot.contains("subgraph \"cluster_Company_Frontdesk\" {"));
+    assert!(dot.contains("subgraph \"cluster_Company_Engineering\" {"));
+    assert!(dot.contains("\"Answer\" -> \"Fix\" [style=solid];"));
+    assert!(dot.contains("\"Answer\" -> \"Fix\" [style=dashed];"));
+    assert!(dot.contains("\"Answer\" -> \"Fix\" [style=dotted, arrowhead=none];"));
+    assert!(dot.trim_end().ends_with('}'));
+}
+
+#[test]
+fn test_dot_draws_implicit_flows() {
+    let process = parse_process(
+        r"
+        process SimpleOrder {
+            start
+            task ReceiveOrder
+            subprocess Packing {
+                start
+                task Pick
+                end
+            }
+            end
+        }
+    ",
+    );
+
+    let dot = to_dot(&process);
+
+    for edge in [
+        "\"start\" -> \"ReceiveOrder\"",
+        "\"ReceiveOrder\" -> \"Packing\"",
+        "\"Packing\" -> \"end\"",
+        "\"Packing_start\" -> \"Pick\"",
+        "\"Pick\" -> \"Packing_end\"",
+    ] {
+        assert!(
+            dot.contains(&format!("    {edge} [style=solid];\n")),
+            "{dot}"
+        );
+    }
+}
+
+#[test]
+fn test_iso8601_durations() {
+    assert_eq!(to_iso8601_duration("5m").as_deref(), Some("PT5M"));
+    assert_eq!(to_iso8601_duration("90s").as_deref(), Some("PT1M30S"));
+    assert_eq!(to_iso8601_duration("2h").as_deref(), Some("PT2H"));
+    assert_eq!(to_iso8601_duration("1h30m").as_deref(), Some("PT1H30M"));
+    assert_eq!(to_iso8601_duration("1500ms").as_deref(), Some("PT1.5S"));
+    assert_eq!(to_iso8601_duration("0s").as_deref(), Some("PT0S"));
+    assert_eq!(to_iso8601_duration("30x"), None);
+}
+
+#[test]
+fn test_anonymous_start_events_get_distinct_ids() {
+    let process = parse_process(
+        r"
+        process Intake {
+            start
+            task Email
+            end
+            start
+            task Phone
+            end
+        }
+    ",
+    );
+
+    let mermaid = to_mermaid(&process);
+    assert!(mermaid.contains("    start((start))"));
+    assert!(mermaid.contains("    start_6_13((start))"));
+    assert!(mermaid.contains("    end_8_13((end))"));
+
+    let dot = to_dot(&process);
+    assert!(dot.contains("\"start_6_13\" [label=\"start\", shape=circle];"));
+}
+
+#[test]
+fn test_emitters_use_display_labels() {
+    let process = parse_process(
+        r#"
+        process Order {
+            start
+            task ValidateOrder "Validate Customer Order"
+            ValidateOrder -> end
+            end
+        }
+    "#,
+    );
+
+    let mermaid = to_mermaid(&process);
+    assert!(mermaid.contains("    ValidateOrder[Validate Customer Order]"));
+    assert!(mermaid.contains("ValidateOrder --> end_event"));
+
+    let dot = to_dot(&process);
+    assert!(dot.contains("\"ValidateOrder\" [label=\"Validate Customer Order\""));
+}
+
+#[test]
+fn test_layout_ranks_columns_and_lane_bands() {
+    let process = parse_process(
+        r"
+        process Order {
+            pool Shop {
+                lane Sales {
+                    task Receive
+                    xor Check {
+                        [ok] -> Ship
+                        => Reject
+                    }
+                }
+                lane Warehouse {
+                    task Ship
+                    task Reject
+                    end
+                }
+            }
+
+            Receive -> Check
+            Ship -> end
+            Reject -> end
+        }
+    ",
+    );
+
+    let layout = layout_process(&process);
+    let bounds = |id: &str| layout.shape(id).unwrap().bounds;
+
+    assert!(bounds("Receive").x < bounds("Check").x);
+    assert!(bounds("Check").x < bounds("Ship").x);
+    assert_eq!(bounds("Ship").x, bounds("Reject").x);
+    assert!(bounds("Ship").y < bounds("Reject").y);
+
+    let sales = bounds("Shop_Sales");
+    let warehouse = bounds("Shop_Warehouse");
+    assert_eq!(warehouse.y, sales.y + sales.height);
+    assert!(bounds("Receive").y < warehouse.y);
+    assert!(bounds("Ship").y >= warehouse.y);
+    assert_eq!(bounds("Shop").height, sales.height + warehouse.height);
+    assert!(layout.shape("Shop").unwrap().horizontal);
+
+    let edge = layout
+        .edges
+        .iter()
+        .find(|edge| edge.element == "Flow_Check_Ship")
+        .unwrap();
+    let check = bounds("Check");
+    assert_eq!(
+        edge.waypoints.first(),
+        Some(&(check.x + check.width, check.y + check.height / 2))
+    );
+    assert_eq!(edge.waypoints.last().unwrap().0, bounds("Ship").x);
+}
+
+#[test]
+fn test_direct_pool_elements_fill_the_default_lane() {
+    let process = parse_process(
+        r"
+        process Order {
+            pool Shop {
+                lane Sales {
+                    task Quote
+                }
+                task Pay
+            }
+            pool Bank {
+                task Settle
+            }
+
+            Quote -> Pay
+            Pay --> Settle
+        }
+    ",
+    );
+
+    let dot = to_dot(&process);
+    let default_lane = dot
+        .split("subgraph \"cluster_Shop_default\" {")
+        .nth(1)
+        .unwrap();
+    assert!(default_lane.contains("\"Pay\" [label="));
+    assert!(!default_lane.contains("\"Quote\" [label="));
+    assert!(!dot.contains("cluster_Bank_default"));
+
+    let layout = layout_process(&process);
+    let bounds = |id: &str| layout.shape(id).unwrap().bounds;
+    let sales = bounds("Shop_Sales");
+    let default = bounds("Shop_default");
+    assert_eq!(default.y, sales.y + sales.height);
+    assert!(bounds("Quote").y < default.y);
+    assert!(bounds("Pay").y >= default.y);
+    assert_eq!(bounds("Shop").height, sales.height + default.height);
+    assert!(layout.shape("Bank_default").is_none());
+    assert!(bounds("Settle").y >= bounds("Bank").y);
+}
+
+#[test]
+fn test_bpmndi_shapes_and_edges() {
+    let process = parse_process(
+        r"
+        process Loop {
+            task Draft
+            task Review
+            Draft -> Review
+            Review -> Draft
+        }
+    ",
+    );
+
+    let diagram = to_bpmndi(&process);
+
+    assert!(diagram.starts_with("<bpmndi:BPMNDiagram id=\"Loop_diagram\">\n"));
+    assert!(diagram.contains("<bpmndi:BPMNPlane id=\"Loop_plane\" bpmnElement=\"Loop\">"));
+    assert!(diagram.contains("<bpmndi:BPMNShape id=\"Draft_di\" bpmnElement=\"Draft\">"));
+    assert!(diagram.contains("<dc:Bounds x=\"140\" y=\"70\" width=\"100\" height=\"80\" />"));
+    assert!(diagram.contains(
+        "<bpmndi:BPMNEdge id=\"Flow_Draft_Review_di\" bpmnElement=\"Flow_Draft_Review\">"
+    ));
+    assert!(diagram.contains("<di:waypoint x=\"240\" y=\"110\" />"));
+    assert!(diagram.contains("<di:waypoint x=\"290\" y=\"110\" />"));
+    assert!(diagram.contains("bpmnElement=\"Flow_Review_Draft\""));
+    assert!(diagram.ends_with("</bpmndi:BPMNDiagram>\n"));
+}
+
+#[test]
+fn test_layout_follows_implicit_flows() {
+    let process = parse_process(
+        r"
+        process SimpleOrder {
+            start
+            task ReceiveOrder
+            task ShipProduct
+            end
+        }
+    ",
+    );
+
+    let layout = layout_process(&process);
+    let bounds = |id: &str| layout.shape(id).unwrap().bounds;
+
+    assert!(bounds("start").x < bounds("ReceiveOrder").x);
+    assert!(bounds("ReceiveOrder").x < bounds("ShipProduct").x);
+    assert!(bounds("ShipProduct").x < bounds("end").x);
+
+    let edges: Vec<_> = layout
+        .edges
+        .iter()
+        .map(|edge| edge.element.as_str())
+        .collect();
+    assert_eq!(
+        edges,
+        vec![
+            "Flow_start_ReceiveOrder",
+            "Flow_ReceiveOrder_ShipProduct",
+            "Flow_ShipProduct_end",
+        ]
+    );
+}
@@ -0,0 +1,132 @@
+use bpmncode::{
+    formatter::{
+        diff_lines,
+        printer::print_document,
+        source::{FormatOptions, format_source, format_source_with_options},
+    },
+    lexer::Lexer,
+    parser::Parser,
+};
+
+fn format(input: &str) -> String {
+    let tokens = Lexer::new(input, "test.bpmn").tokenize();
+    let document = Parser::new(tokens).parse();
+
+    assert!(!document.has_errors(), "Errors: {:?}", document.errors);
+    format_source(&document, input)
+}
+
+#[test]
+fn test_reindents_blocks_and_splits_elements() {
+    let input = "process Order {\nstart task Validate\n  xor   Valid? { [ok] -> Ship\n=> Reject }\n\n\n\n  task Ship task Reject\nend\n}\n";
+
+    assert_eq!(
+        format(input),
+        "process Order {\n    start\n    task Validate\n    xor Valid? {\n        [ok] -> Ship\n        => Reject\n    }\n\n    task Ship\n    task Reject\n    end\n}\n"
+    );
+}
+
+#[test]
+fn test_preserves_comments_and_aligns_attributes() {
+    let input = r#"
+// Order handling
+process Order {
+
+    start // entry point
+    task Validate( timeout = 30s ,retries=3 )
+    user Review (assignee="manager")
+    /* shipping is manual */
+    task Ship
+    end
+
+}
+"#;
+
+    let formatted = format(input);
+    assert_eq!(
+        formatted,
+        r#"// Order handling
+process Order {
+    start // entry point
+    task Validate(timeout=30s, retries=3)
+    user Review  (assignee="manager")
+    /* shipping is manual */
+    task Ship
+    end
+}
+"#
+    );
+    assert_eq!(format(&formatted), formatted);
+}
+
+#[test]
+fn test_printer_keeps_attribute_declaration_order() {
+    let input = "process Order @version \"2\" @author \"Ops\" {\n    start\n    task Validate (timeout=30s, retries=3, assignee=\"bot\")\n    end\n}\n";
+    let tokens = Lexer::new(input, "test.bpmn").tokenize();
+    let printed = print_document(&Parser::new(tokens).parse());
+
+    assert!(
+        printed.contains("process Order @version \"2\" @author \"Ops\" {"),
+        "{printed}"
+    );
+    assert!(
+        printed.contains("task Validate(timeout=30s, retries=3, assignee=\"bot\")"),
+        "{printed}"
+    );
+
+    let tokens = Lexer::new(&printed, "test.bpmn").tokenize();
+    assert_eq!(print_document(&Parser::new(tokens).parse()), printed);
+}
+
+#[test]
+fn test_keeps_event_subprocess_header_on_one_line() {
+    let input = "process Order {\nstart\nevent   subprocess Cancel @message \"Cancel\" {\nstart\nend }\n}\n";
+
+    assert_eq!(
+        format(input),
+        "process Order {\n    start\n    event subprocess Cancel @message \"Cancel\" {\n        start\n        end\n    }\n}\n"
+    );
+}
+
+#[test]
+fn test_indent_width_applies_to_every_level() {
+    let input = "process Order {\nsubprocess Ship {\nxor Ready? {\n[ok] -> Pack\n}\n}\n}\n";
+    let tokens = Lexer::new(input, "test.bpmn").tokenize();
+    let document = Parser::new(tokens).parse();
+
+    let indents = |indent_width| {
+        format_source_with_options(&document, input, &FormatOptions { indent_width })
+            .lines()
+            .map(|line| line.len() - line.trim_start().len())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(indents(2), vec![0, 2, 4, 6, 4, 2, 0]);
+    assert_eq!(indents(4), vec![0, 4, 8, 12, 8, 4, 0]);
+}
+
+#[test]
+fn test_diff_lists_changed_lines() {
+    let diff = diff_lines("a\nb\nc\n", "a\nB\nc\n");
+
+    assert_eq!(diff, "   2 - b\n   2 + B\n");
+}
+
+#[test]
+fn test_diff_of_large_files_stays_bounded() {
+    let lines: Vec<String> = (0..50_000).map(|line| format!("task T{line}")).collect();
+    let original = lines.join("\n");
+
+    let mut edited = lines.clone();
+    edited[40_000] = "task Changed".to_string();
+    assert_eq!(
+        diff_lines(&original, &edited.join("\n")),
+        "40001 - task T40000\n40001 + task Changed\n"
+    );
+
+    let rewritten: Vec<String> = lines.iter().map(|line| format!("    {line}")).collect();
+    assert_eq!(
+        diff_lines(&original, &rewritten.join("\n")),
+        "File differs from line 1 on, 50000 lines changed to 50000, too many to compare\n"
+    );
+}
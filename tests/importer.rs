@@ -0,0 +1,301 @@
+use bpmncode::{
+    compile_str,
+    formatter::printer::print_document,
+    importer::bpmn_xml::{ImportError, from_bpmn_xml},
+    parser::ast::{GatewayType, ProcessElement},
+};
+
+const ORDER_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<bpmn:definitions xmlns:bpmn="http://www.omg.org/spec/BPMN/20100524/MODEL" id="Defs">
+  <bpmn:process id="Process_1" name="Order handling" isExecutable="true">
+    <bpmn:startEvent id="StartEvent_1" />
+    <bpmn:userTask id="Activity_1" name="Review order" />
+    <bpmn:exclusiveGateway id="Gateway_1" name="Approved?" default="Flow_4" />
+    <bpmn:serviceTask id="Activity_2" name="Ship order" />
+    <bpmn:task id="Activity_3" name="Reject order" />
+    <bpmn:intermediateCatchEvent id="Event_9" name="Wait" />
+    <bpmn:endEvent id="EndEvent_1" />
+    <bpmn:sequenceFlow id="Flow_1" sourceRef="StartEvent_1" targetRef="Activity_1" />
+    <bpmn:sequenceFlow id="Flow_2" sourceRef="Activity_1" targetRef="Gateway_1" />
+    <bpmn:sequenceFlow id="Flow_3" sourceRef="Gateway_1" targetRef="Activity_2">
+      <bpmn:conditionExpression>${approved &amp;&amp; total &gt; 10}</bpmn:conditionExpression>
+    </bpmn:sequenceFlow>
+    <bpmn:sequenceFlow id="Flow_4" sourceRef="Gateway_1" targetRef="Activity_3" />
+    <bpmn:sequenceFlow id="Flow_5" sourceRef="Activity_2" targetRef="EndEvent_1" />
+  </bpmn:process>
+</bpmn:definitions>
+"#;
+
+#[test]
+fn test_imports_tasks_gateways_and_flows() {
+    let document = from_bpmn_xml(ORDER_XML).unwrap();
+    let process = &document.processes[0];
+
+    assert_eq!(process.name, "OrderHandling");
+
+    let ProcessElement::Gateway {
+        id,
+        gateway_type,
+        branches,
+        ..
+    } = &process.elements[2]
+    else {
+        panic!("Expected gateway, got {:?}", process.elements[2]);
+    };
+    assert_eq!(id.as_deref(), Some("Approved"));
+    assert_eq!(*gateway_type, GatewayType::Exclusive);
+    assert_eq!(branches.len(), 2);
+    assert_eq!(
+        branches[0]
+            .condition
+            .as_ref()
+            .map(ToString::to_string)
+            .as_deref(),
+        Some("approved && total > 10")
+    );
+    assert!(branches[1].is_default);
+    assert_eq!(branches[1].target, "RejectOrder");
+
+    let flows: Vec<_> = process
+        .flows
+        .iter()
+        .map(|flow| (flow.from.as_str(), flow.to.as_str()))
+        .collect();
+    assert_eq!(
+        flows,
+        vec![
+            ("start", "ReviewOrder"),
+            ("ReviewOrder", "Approved"),
+            ("ShipOrder", "end"),
+            ("RejectOrder", "end"),
+        ]
+    );
+
+    let ProcessElement::Task { id, label, .. } = &process.elements[1] else {
+        panic!("Expected task, got {:?}", process.elements[1]);
+    };
+    assert_eq!(id, "ReviewOrder");
+    assert_eq!(label.as_deref(), Some("Review order"));
+}
+
+#[test]
+fn test_unsupported_elements_become_notes() {
+    let document = from_bpmn_xml(ORDER_XML).unwrap();
+    let source = print_document(&document);
+
+    assert!(
+        source.contains("note \"Unsupported intermediateCatchEvent 'Wait'\""),
+        "Source: {source}"
+    );
+    assert!(source.contains("user ReviewOrder"));
+}
+
+#[test]
+fn test_intermediate_events_keep_their_flows_through_a_round_trip() {
+    let xml = r#"<bpmn:definitions xmlns:bpmn="http://www.omg.org/spec/BPMN/20100524/MODEL">
+  <bpmn:process id="Approval">
+    <bpmn:startEvent id="Start_1" />
+    <bpmn:task id="Submit" name="Submit" />
+    <bpmn:task id="Notify" name="Notify" />
+    <bpmn:intermediateCatchEvent id="Event_1" name="Wait">
+      <bpmn:timerEventDefinition />
+    </bpmn:intermediateCatchEvent>
+    <bpmn:intermediateThrowEvent id="Event_2" name="Ping" />
+    <bpmn:task id="Approve" name="Approve" />
+    <bpmn:endEvent id="End_1" />
+    <bpmn:boundaryEvent id="Event_3" name="Escalate" attachedToRef="Submit" />
+    <bpmn:sequenceFlow id="Flow_1" sourceRef="Start_1" targetRef="Submit" />
+    <bpmn:sequenceFlow id="Flow_2" sourceRef="Submit" targetRef="Event_1" />
+    <bpmn:sequenceFlow id="Flow_3" sourceRef="Event_1" targetRef="Event_2" />
+    <bpmn:sequenceFlow id="Flow_4" sourceRef="Event_2" targetRef="Approve" />
+    <bpmn:sequenceFlow id="Flow_5" sourceRef="Approve" targetRef="End_1" />
+    <bpmn:sequenceFlow id="Flow_6" sourceRef="Event_3" targetRef="Notify" />
+  </bpmn:process>
+</bpmn:definitions>
+"#;
+
+    let document = from_bpmn_xml(xml).unwrap();
+    let source = print_document(&document);
+
+    assert!(source.contains("    event Wait @timer\n"), "{source}");
+    for flow in [
+        "start -> Submit",
+        "Submit -> Wait",
+        "Wait -> Approve",
+        "Approve -> end",
+        "Notify -> end",
+    ] {
+        assert!(source.contains(&format!("    {flow}\n")), "{source}");
+    }
+
+    let warnings: Vec<_> = document
+        .errors
+        .iter()
+        .map(|warning| warning.message.as_str())
+        .collect();
+    assert_eq!(
+        warnings,
+        vec![
+            "Unsupported intermediateThrowEvent 'Ping' became a note, the flows through it now skip it",
+            "Unsupported boundaryEvent 'Escalate' became a note, its outgoing flows were dropped",
+        ]
+    );
+
+    let report = compile_str(&source, "approval.bpmn");
+    assert!(!report.has_errors(), "{:?}", report.errors);
+}
+
+#[test]
+fn test_data_references_become_data_objects() {
+    let xml = r#"<definitions>
+  <process id="P">
+    <dataObject id="DataObject_1" />
+    <dataObjectReference id="Ref_1" name="Order form" dataObjectRef="DataObject_1" />
+    <dataStoreReference id="Store_1" name="Inventory" />
+  </process>
+</definitions>"#;
+    let source = print_document(&from_bpmn_xml(xml).unwrap());
+
+    assert!(
+        source.contains("    data OrderForm \"Order form\"\n    data store Inventory\n"),
+        "Source: {source}"
+    );
+    assert!(!source.contains("Unsupported"), "Source: {source}");
+}
+
+#[test]
+fn test_conditional_event_definition_keeps_its_condition() {
+    let xml = r#"<bpmn:definitions xmlns:bpmn="http://www.omg.org/spec/BPMN/20100524/MODEL">
+  <bpmn:process id="Restock">
+    <bpmn:startEvent id="Low">
+      <bpmn:conditionalEventDefinition>
+        <bpmn:condition>${stock &lt; 10}</bpmn:condition>
+      </bpmn:conditionalEventDefinition>
+    </bpmn:startEvent>
+  </bpmn:process>
+</bpmn:definitions>
+"#;
+
+    let document = from_bpmn_xml(xml).unwrap();
+
+    assert!(
+        print_document(&document).contains(r#"start @conditional "stock < 10""#),
+        "{}",
+        print_document(&document)
+    );
+}
+
+#[test]
+fn test_loop_characteristics_become_markers() {
+    let xml = r#"<bpmn:definitions xmlns:bpmn="http://www.omg.org/spec/BPMN/20100524/MODEL">
+  <bpmn:process id="Billing">
+    <bpmn:task id="Retry" name="Retry">
+      <bpmn:standardLoopCharacteristics />
+    </bpmn:task>
+    <bpmn:callActivity id="Charge" name="Charge" calledElement="Payment">
+      <bpmn:multiInstanceLoopCharacteristics isSequential="true" />
+    </bpmn:callActivity>
+    <bpmn:serviceTask id="Notify" name="Notify">
+      <bpmn:multiInstanceLoopCharacteristics />
+    </bpmn:serviceTask>
+  </bpmn:process>
+</bpmn:definitions>
+"#;
+
+    let source = print_document(&from_bpmn_xml(xml).unwrap());
+
+    assert!(source.contains("task Retry(loop=true)"), "{source}");
+    assert!(
+        source.contains("call Charge(process=\"Payment\", multiInstance=true, sequential=true)"),
+        "{source}"
+    );
+    assert!(
+        source.contains("service Notify(multiInstance=true)"),
+        "{source}"
+    );
+}
+
+#[test]
+fn test_malformed_xml_is_rejected() {
+    let result = from_bpmn_xml("<definitions>\n  <process id=\"P\">\n</definitions>");
+    assert!(matches!(
+        result,
+        Err(ImportError::Malformed { line: 3, .. })
+    ));
+
+    let result = from_bpmn_xml("<definitions></definitions>");
+    assert!(matches!(result, Err(ImportError::NoProcess)));
+}
+
+#[test]
+fn test_character_references_are_decoded() {
+    let xml = r#"<bpmn:definitions xmlns:bpmn="http://www.omg.org/spec/BPMN/20100524/MODEL">
+  <bpmn:process id="Shipping">
+    <bpmn:task id="Activity_1" name="Pack&#32;box&#x21;" />
+    <bpmn:exclusiveGateway id="Gateway_1" name="Heavy" />
+    <bpmn:task id="Activity_2" name="Truck" />
+    <bpmn:sequenceFlow id="Flow_1" sourceRef="Gateway_1" targetRef="Activity_2">
+      <bpmn:conditionExpression>${weight &#62; 10}</bpmn:conditionExpression>
+    </bpmn:sequenceFlow>
+  </bpmn:process>
+</bpmn:definitions>
+"#;
+
+    let source = print_document(&from_bpmn_xml(xml).unwrap());
+
+    assert!(source.contains("task PackBox \"Pack box!\""), "{source}");
+    assert!(source.contains("[weight > 10] -> Truck"), "{source}");
+}
+
+#[test]
+fn test_prefixed_attributes_do_not_replace_plain_ones() {
+    let xml = r#"<bpmn:definitions xmlns:bpmn="http://www.omg.org/spec/BPMN/20100524/MODEL">
+  <bpmn:process id="Shipping">
+    <bpmn:task id="Activity_1" name="Pack" ext:name="Ignored" xsi:type="bpmn:tTask" />
+  </bpmn:process>
+</bpmn:definitions>
+"#;
+
+    let source = print_document(&from_bpmn_xml(xml).unwrap());
+
+    assert!(source.contains("task Pack\n"), "{source}");
+}
+
+#[test]
+fn test_several_start_and_end_events_keep_their_names() {
+    let xml = r#"<bpmn:definitions xmlns:bpmn="http://www.omg.org/spec/BPMN/20100524/MODEL">
+  <bpmn:process id="Orders">
+    <bpmn:startEvent id="Web" />
+    <bpmn:startEvent id="Phone">
+      <bpmn:messageEventDefinition />
+    </bpmn:startEvent>
+    <bpmn:task id="Take" name="Take" />
+    <bpmn:endEvent id="Done" />
+    <bpmn:endEvent id="Lost" />
+    <bpmn:sequenceFlow id="Flow_1" sourceRef="Web" targetRef="Take" />
+    <bpmn:sequenceFlow id="Flow_2" sourceRef="Phone" targetRef="Lost" />
+    <bpmn:sequenceFlow id="Flow_3" sourceRef="Take" targetRef="Done" />
+  </bpmn:process>
+</bpmn:definitions>
+"#;
+
+    let document = from_bpmn_xml(xml).unwrap();
+    let process = &document.processes[0];
+
+    let flows: Vec<_> = process
+        .flows
+        .iter()
+        .map(|flow| (flow.from.as_str(), flow.to.as_str()))
+        .collect();
+    assert_eq!(
+        flows,
+        vec![("Web", "Take"), ("Phone", "Lost"), ("Take", "Done")]
+    );
+
+    let source = print_document(&document);
+    assert!(
+        source.contains("    start Web\n    start Phone @message\n"),
+        "{source}"
+    );
+    assert!(source.contains("    end Done\n    end Lost\n"), "{source}");
+}
@@ -1,8 +1,8 @@
 #[cfg(test)]
 mod tests {
-    use bpmncode::lexer::{Lexer, TokenKind};
+    use bpmncode::lexer::{Lexer, TokenKind, error::LexerError};
 
-    use std::path::Path;
+    use std::{fmt::Write, path::Path, time::Instant};
 
     #[test]
     fn test_basic_keywords() {
@@ -41,6 +41,30 @@ mod tests {
         assert_eq!(tokens[3].kind, TokenKind::Association);
     }
 
+    #[test]
+    fn test_condition_operators() {
+        let input = "== != <= >= < > && || ! =>";
+        let mut lexer = Lexer::new(input, "test.bpmn");
+        let kinds: Vec<_> = lexer.tokenize().into_iter().map(|t| t.kind).collect();
+
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::EqualEqual,
+                TokenKind::NotEqual,
+                TokenKind::LessEqual,
+                TokenKind::GreaterEqual,
+                TokenKind::Less,
+                TokenKind::Greater,
+                TokenKind::AndAnd,
+                TokenKind::OrOr,
+                TokenKind::Bang,
+                TokenKind::DefaultFlow,
+                TokenKind::Eof,
+            ]
+        );
+    }
+
     #[test]
     fn test_string_literals() {
         let input = r#""Simple string" "String with spaces" "String with \"quotes\"""#;
@@ -125,6 +149,99 @@ mod tests {
         assert_eq!(comment_tokens[1].kind, TokenKind::BlockComment);
     }
 
+    #[test]
+    fn test_unterminated_string_literal() {
+        let input = "note \"oops\r\nend";
+        let mut lexer = Lexer::new(input, "test.bpmn");
+        let tokens = lexer.tokenize();
+
+        assert_eq!(tokens[1].kind, TokenKind::StringLiteral);
+        assert_eq!(tokens[1].text, "\"oops");
+        assert_eq!(tokens[2].kind, TokenKind::CarriageReturnNewline);
+        assert_eq!(tokens[3].kind, TokenKind::End);
+
+        match lexer.errors() {
+            [LexerError::UnterminatedString { span }] => {
+                assert_eq!((span.line, span.column), (1, 6));
+            }
+            errors => panic!("Expected an unterminated string, got {errors:?}"),
+        }
+    }
+
+    #[test]
+    fn test_mixed_line_endings_each_advance_one_line() {
+        let input = "start\rtask A // note\r\nend\n\"open\rtask B";
+        let mut lexer = Lexer::new(input, "test.bpmn");
+        let tokens = lexer.tokenize();
+
+        let positions: Vec<_> = tokens
+            .iter()
+            .filter(|t| {
+                !matches!(
+                    t.kind,
+                    TokenKind::Newline | TokenKind::CarriageReturnNewline
+                )
+            })
+            .map(|t| (t.kind.clone(), t.span.line, t.span.column))
+            .collect();
+        assert_eq!(
+            positions,
+            vec![
+                (TokenKind::Start, 1, 1),
+                (TokenKind::Task, 2, 1),
+                (TokenKind::Identifier, 2, 6),
+                (TokenKind::LineComment, 2, 8),
+                (TokenKind::End, 3, 1),
+                (TokenKind::StringLiteral, 4, 1),
+                (TokenKind::Task, 5, 1),
+                (TokenKind::Identifier, 5, 6),
+                (TokenKind::Eof, 5, 7),
+            ]
+        );
+        assert_eq!(tokens[1].kind, TokenKind::Newline);
+        assert_eq!(tokens[5].kind, TokenKind::CarriageReturnNewline);
+        assert!(!tokens.iter().any(|t| t.kind == TokenKind::Unknown));
+    }
+
+    #[test]
+    fn test_nested_block_comments() {
+        let input = "task A /* outer /* inner */ still comment */ end";
+        let mut lexer = Lexer::new(input, "test.bpmn");
+        let tokens = lexer.tokenize();
+
+        let kinds: Vec<_> = tokens.iter().map(|t| t.kind.clone()).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Task,
+                TokenKind::Identifier,
+                TokenKind::BlockComment,
+                TokenKind::End,
+                TokenKind::Eof,
+            ]
+        );
+        assert_eq!(tokens[2].text, "/* outer /* inner */ still comment */");
+        assert!(lexer.errors().is_empty());
+    }
+
+    #[test]
+    fn test_unterminated_block_comment() {
+        let input = "task A\n  /* never /* closed */\nend";
+        let mut lexer = Lexer::new(input, "test.bpmn");
+        let tokens = lexer.tokenize();
+
+        assert!(!tokens.iter().any(|t| t.kind == TokenKind::Unknown));
+        assert_eq!(tokens[tokens.len() - 2].kind, TokenKind::BlockComment);
+
+        match lexer.errors() {
+            [LexerError::UnterminatedComment { span }] => {
+                assert_eq!((span.line, span.column), (2, 3));
+                assert_eq!(&input[span.start..span.end], "/*");
+            }
+            errors => panic!("Expected an unterminated comment, got {errors:?}"),
+        }
+    }
+
     #[test]
     fn test_complete_process() {
         let input = r#"
@@ -280,6 +397,53 @@ mod tests {
         assert_eq!(end_token.span.column, 1);
     }
 
+    #[test]
+    fn test_position_tracking_counts_characters() {
+        let input = "задача\ntask";
+        let mut lexer = Lexer::new(input, "unicode.bpmn");
+        let tokens = lexer.tokenize();
+
+        let newline = tokens
+            .iter()
+            .find(|t| t.kind == TokenKind::Newline)
+            .unwrap();
+        assert_eq!(newline.span.line, 1);
+        assert_eq!(newline.span.column, 7);
+
+        let task_token = tokens.iter().find(|t| t.kind == TokenKind::Task).unwrap();
+        assert_eq!(task_token.span.line, 2);
+        assert_eq!(task_token.span.column, 1);
+
+        let eof = tokens.last().unwrap();
+        assert_eq!(eof.kind, TokenKind::Eof);
+        assert_eq!((eof.span.line, eof.span.column), (2, 5));
+
+        let tokens = Lexer::new("задача", "unicode.bpmn").tokenize();
+        let eof = tokens.last().unwrap();
+        assert_eq!((eof.span.line, eof.span.column), (1, 7));
+    }
+
+    #[test]
+    fn test_large_input_positions_are_linear() {
+        let mut input = String::from("process Large {\n    start\n");
+        for index in 0..10_000 {
+            let _ = writeln!(input, "    task Task{index}(retries=3) // step {index}");
+        }
+        input.push_str("    end\n}\n");
+
+        let start = Instant::now();
+        let tokens = Lexer::new(&input, "large.bpmn").tokenize();
+        let duration = start.elapsed();
+
+        let end_token = tokens.iter().find(|t| t.kind == TokenKind::End).unwrap();
+        assert_eq!(end_token.span.line, 10_003);
+        assert_eq!(end_token.span.column, 5);
+        assert!(
+            duration.as_secs() < 2,
+            "Tokenizing 10k lines took {duration:?}"
+        );
+    }
+
     #[test]
     fn test_file_tracking() {
         let input = "process Test { start -> end }";
@@ -394,6 +558,77 @@ xor PaymentValid? {
             3
         ); // 2 comments + EOF
     }
+
+    #[test]
+    fn test_random_input_yields_well_formed_tokens() {
+        const PIECES: &[&str] = &[
+            "a", "Z_9", "7", "1.5s", " ", "\t", "\n", "\r", "\r\n", "\"", "\\", "/", "*", "/*",
+            "*/", "//", "-", ">", "=", "!", "&", "|", ".", ":", "{", "}", "é", "ß", "日本", "🦀",
+            "\u{0}", "\u{feff}", "\u{301}", "process", "task",
+        ];
+
+        // xorshift keeps the inputs reproducible without a dependency
+        let mut state: u64 = 0x2545_F491_4F6C_DD1D;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            usize::try_from(state % 1_000_003).unwrap()
+        };
+
+        let mut inputs: Vec<String> = (0..1000)
+            .map(|_| {
+                (0..next() % 80)
+                    .map(|_| PIECES[next() % PIECES.len()])
+                    .collect()
+            })
+            .collect();
+        inputs.push("x".repeat(100_000));
+        inputs.push(format!("\"{}", "é".repeat(50_000)));
+        inputs.push(format!("/*{}", "\\é".repeat(50_000)));
+
+        for input in &inputs {
+            let mut lexer = Lexer::new(input, "random.bpmn");
+            let tokens = lexer.tokenize();
+
+            let last = tokens.last().unwrap();
+            assert_eq!(last.kind, TokenKind::Eof);
+            assert_eq!(last.span.start, input.len());
+
+            let mut previous_end = 0;
+            for token in &tokens {
+                assert!(token.span.start >= previous_end, "{input:?}: {token:?}");
+                assert!(token.span.start <= token.span.end && token.span.end <= input.len());
+                assert_eq!(
+                    input.get(token.span.start..token.span.end),
+                    Some(token.text.as_str())
+                );
+                previous_end = token.span.end;
+            }
+
+            for error in lexer.errors() {
+                let span = error.span();
+                assert!(
+                    span.start <= span.end && span.end <= input.len(),
+                    "{input:?}"
+                );
+                assert!(input.is_char_boundary(span.start) && input.is_char_boundary(span.end));
+            }
+        }
+    }
+
+    #[test]
+    fn test_tokens_serialize_to_json() {
+        let tokens = Lexer::new("task Review", "test.bpmn").tokenize();
+        let json = serde_json::to_value(&tokens).unwrap();
+
+        assert_eq!(json[0]["kind"], "Task");
+        assert_eq!(json[1]["kind"], "Identifier");
+        assert_eq!(json[1]["text"], "Review");
+        assert_eq!(json[1]["span"]["column"], 6);
+        assert_eq!(json[1]["span"]["start"], 5);
+        assert_eq!(json[2]["kind"], "Eof");
+    }
 }
 
 #[cfg(test)]
@@ -458,4 +693,67 @@ mod multi_file_tests {
         let result = lexer.tokenize_file(temp_dir.path().join("nonexistent.bpmn").as_path());
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_incremental_edits_match_full_tokenize() {
+        use bpmncode::lexer::{Lexer, incremental::IncrementalLexer};
+
+        let snippets = [
+            "",
+            " ",
+            "\n",
+            "\r\n",
+            "task Extra",
+            "/*",
+            "*/",
+            "\"",
+            "->",
+            "-",
+            "{",
+            "}",
+            "// note",
+            "30s",
+            "\n    user Review\n",
+        ];
+        let mut incremental =
+            IncrementalLexer::new(include_str!("../examples/comprehensive.bpmn"), "test.bpmn");
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = |bound: usize| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            usize::try_from(seed % bound as u64).unwrap()
+        };
+
+        for _ in 0..500 {
+            let source = incremental.source();
+            let boundaries: Vec<usize> = source
+                .char_indices()
+                .map(|(index, _)| index)
+                .chain(std::iter::once(source.len()))
+                .collect();
+            let start = boundaries[next(boundaries.len())];
+            let end = boundaries
+                .iter()
+                .copied()
+                .filter(|&boundary| boundary >= start)
+                .nth(next(8))
+                .unwrap_or(start);
+            let replacement = snippets[next(snippets.len())];
+
+            incremental.edit(start..end, replacement);
+
+            let mut lexer = Lexer::new(incremental.source(), "test.bpmn");
+            let expected = lexer.tokenize();
+            assert_eq!(
+                incremental.tokens(),
+                expected.as_slice(),
+                "Mismatch after replacing {start}..{end} with {replacement:?}"
+            );
+            assert_eq!(
+                format!("{:?}", incremental.errors()),
+                format!("{:?}", lexer.errors())
+            );
+        }
+    }
 }
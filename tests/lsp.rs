@@ -0,0 +1,152 @@
+#![cfg(feature = "lsp")]
+
+use std::thread;
+
+use bpmncode::lsp::serve;
+use lsp_server::{Connection, Message, Notification, Request, RequestId, Response};
+use serde_json::{Value, json};
+
+const URI: &str = "file:///orders.bpmn";
+const SOURCE: &str = "process Orders {\n    start\n    task Review\n    tsk Ship\n    end\n    start -> Review -> end\n}\n";
+
+fn request(client: &Connection, id: i32, method: &str, params: Value) -> Response {
+    client
+        .sender
+        .send(Message::Request(Request::new(
+            RequestId::from(id),
+            method.to_string(),
+            params,
+        )))
+        .unwrap();
+
+    loop {
+        if let Message::Response(response) = client.receiver.recv().unwrap() {
+            return response;
+        }
+    }
+}
+
+fn notify(client: &Connection, method: &str, params: Value) {
+    client
+        .sender
+        .send(Message::Notification(Notification::new(
+            method.to_string(),
+            params,
+        )))
+        .unwrap();
+}
+
+fn position(line: u32, character: u32) -> Value {
+    json!({ "textDocument": { "uri": URI }, "position": { "line": line, "character": character } })
+}
+
+#[test]
+fn test_server_publishes_diagnostics_and_answers_requests() {
+    let (client, server) = Connection::memory();
+    let server = thread::spawn(move || serve(&server).unwrap());
+
+    let initialized = request(&client, 1, "initialize", json!({ "capabilities": {} }));
+    assert!(initialized.result.unwrap()["capabilities"]["definitionProvider"] == true);
+    notify(&client, "initialized", json!({}));
+
+    notify(
+        &client,
+        "textDocument/didOpen",
+        json!({ "textDocument": { "uri": URI, "languageId": "bpmncode", "version": 1, "text": SOURCE } }),
+    );
+    let Message::Notification(published) = client.receiver.recv().unwrap() else {
+        panic!("Expected diagnostics");
+    };
+    assert_eq!(published.method, "textDocument/publishDiagnostics");
+    let diagnostics = published.params["diagnostics"].as_array().unwrap();
+    assert!(
+        diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic["range"]["start"] == json!({ "line": 3, "character": 4 })),
+        "{diagnostics:?}"
+    );
+
+    let completion = request(&client, 2, "textDocument/completion", position(3, 5));
+    let labels: Vec<_> = completion
+        .result
+        .unwrap()
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["label"].as_str().unwrap().to_string())
+        .collect();
+    assert!(labels.contains(&"task".to_string()), "{labels:?}");
+
+    let definition = request(&client, 3, "textDocument/definition", position(5, 15));
+    assert_eq!(definition.result.unwrap()["range"]["start"]["line"], 2);
+
+    let references = request(
+        &client,
+        4,
+        "textDocument/references",
+        json!({ "textDocument": { "uri": URI }, "position": { "line": 2, "character": 10 }, "context": { "includeDeclaration": false } }),
+    );
+    assert_eq!(references.result.unwrap().as_array().unwrap().len(), 1);
+
+    let hover = request(&client, 5, "textDocument/hover", position(5, 15));
+    assert_eq!(
+        hover.result.unwrap()["contents"]["value"],
+        "**Task** `Review`"
+    );
+
+    request(&client, 6, "shutdown", Value::Null);
+    notify(&client, "exit", Value::Null);
+    server.join().unwrap();
+}
+
+#[test]
+fn test_server_survives_malformed_notifications_and_decodes_uris() {
+    let dir = tempfile::tempdir().unwrap();
+    let folder = dir.path().join("my orders");
+    std::fs::create_dir(&folder).unwrap();
+    std::fs::write(
+        folder.join("shared.bpmn"),
+        "process Shared {\n    start\n    end\n}\n",
+    )
+    .unwrap();
+    let uri = format!(
+        "file://{}/orders.bpmn",
+        folder.display().to_string().replace(' ', "%20")
+    );
+    let source = "import \"shared.bpmn\" as shared\nprocess Orders {\n    start\n    call shared::Shared\n    task Pack \"📦\" -> Ghost\n    end\n}\n";
+
+    let (client, server) = Connection::memory();
+    let server = thread::spawn(move || serve(&server).unwrap());
+    request(&client, 1, "initialize", json!({ "capabilities": {} }));
+    notify(&client, "initialized", json!({}));
+
+    notify(
+        &client,
+        "textDocument/didOpen",
+        json!({ "textDocument": { "uri": uri } }),
+    );
+    notify(
+        &client,
+        "textDocument/didOpen",
+        json!({ "textDocument": { "uri": uri, "languageId": "bpmncode", "version": 1, "text": source } }),
+    );
+    let Message::Notification(published) = client.receiver.recv().unwrap() else {
+        panic!("Expected diagnostics");
+    };
+    let diagnostics = published.params["diagnostics"].as_array().unwrap();
+    let errors: Vec<_> = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic["severity"] == 1)
+        .collect();
+
+    assert_eq!(errors.len(), 1, "{diagnostics:?}");
+    assert_eq!(
+        errors[0]["range"]["start"],
+        json!({ "line": 4, "character": 19 }),
+        "{diagnostics:?}"
+    );
+
+    request(&client, 2, "shutdown", Value::Null);
+    notify(&client, "exit", Value::Null);
+    server.join().unwrap();
+}
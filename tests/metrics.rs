@@ -0,0 +1,55 @@
+use bpmncode::{
+    lexer::Lexer,
+    metrics::{ProcessMetrics, document_metrics},
+    parser::Parser,
+};
+
+fn metrics(input: &str) -> Vec<ProcessMetrics> {
+    let tokens = Lexer::new(input, "test.bpmn").tokenize();
+    let document = Parser::new(tokens).parse();
+    assert!(!document.has_errors(), "Errors: {:?}", document.errors);
+    document_metrics(&document)
+}
+
+#[test]
+fn test_counts_nested_elements_and_complexity() {
+    let metrics = metrics(
+        r#"
+        process Orders {
+            start
+            service Charge
+            and Fork {
+                => Pack
+                => Bill
+            }
+            subprocess Fulfil {
+                start
+                task Pack
+                group "Shipping" {
+                    task Bill
+                }
+                end
+            }
+            task Orphan
+            end
+
+            Charge -> Fork
+            Fork -> Fulfil
+            Fulfil -> end
+        }
+    "#,
+    );
+
+    let process = &metrics[0];
+    assert_eq!(process.name, "Orders");
+    assert_eq!(
+        process.tasks.iter().collect::<Vec<_>>(),
+        vec![(&"service", &1), (&"subprocess", &1), (&"task", &3)]
+    );
+    assert_eq!(process.gateways.get("and"), Some(&1));
+    assert_eq!(process.events.get("start"), Some(&2));
+    assert_eq!(process.flows, 5);
+    assert_eq!(process.complexity, 2);
+    assert_eq!(process.max_depth, 2);
+    assert_eq!(process.unreachable, 1);
+}
@@ -0,0 +1,113 @@
+use bpmncode::{
+    lexer::Lexer,
+    navigation::{definition_span, hover_at, references},
+    parser::{ast::AstDocument, parse_tokens},
+};
+
+const SOURCE: &str = r"process Orders {
+    start
+    task Review
+    xor Decision {
+        [approved] -> Ship
+        => Review
+    }
+    subprocess Fulfilment {
+        start
+        task Ship
+        end
+        start -> Ship -> end
+    }
+    pool Warehouse {
+        lane Packing {
+            task Pack
+        }
+        Pack -> Ship
+    }
+    end
+    start -> Review -> Decision
+    Fulfilment -> end
+}
+";
+
+fn parse(source: &str) -> AstDocument {
+    parse_tokens(Lexer::new(source, "test.bpmn").tokenize())
+}
+
+#[test]
+fn test_definition_span_finds_nested_declarations() {
+    let document = parse(SOURCE);
+
+    assert_eq!(definition_span(&document, "Review").unwrap().line, 3);
+    assert_eq!(definition_span(&document, "Ship").unwrap().line, 10);
+    assert_eq!(definition_span(&document, "Pack").unwrap().line, 16);
+    assert_eq!(definition_span(&document, "Packing").unwrap().line, 15);
+    assert_eq!(definition_span(&document, "Orders").unwrap().line, 1);
+    assert!(definition_span(&document, "Missing").is_none());
+}
+
+#[test]
+fn test_references_cover_flows_and_branches_in_containers() {
+    let document = parse(SOURCE);
+
+    let lines = |name: &str| {
+        references(&document, name)
+            .iter()
+            .map(|span| span.line)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(lines("Ship"), vec![5, 12, 18]);
+    assert_eq!(lines("Review"), vec![6, 21]);
+    assert!(lines("Pack").contains(&18));
+}
+
+#[test]
+fn test_references_point_at_the_name() {
+    let document = parse(SOURCE);
+
+    let ship: Vec<_> = references(&document, "Ship")
+        .iter()
+        .map(|span| &SOURCE[span.start..span.end])
+        .collect();
+    assert_eq!(ship, vec!["Ship"; 3]);
+    let columns: Vec<_> = references(&document, "Review")
+        .iter()
+        .map(|span| (span.line, span.column))
+        .collect();
+    assert_eq!(columns, vec![(6, 12), (21, 14)]);
+}
+
+#[test]
+fn test_hover_describes_innermost_element() {
+    let source = r#"process Orders {
+    start
+    user Review "Review order" (assignee="alice", priority=2)
+    call Billing::Invoice
+    subprocess Fulfilment {
+        start
+        service Ship
+        end
+    }
+    end
+}
+"#;
+    let document = parse(source);
+    let offset_of = |needle: &str| source.find(needle).unwrap();
+
+    let review = hover_at(&document, offset_of("user")).unwrap();
+    assert_eq!(
+        review,
+        "**User task** `Review`\n\n- label: Review order\n- @assignee: `\"alice\"`\n- @priority: `2`"
+    );
+
+    let call = hover_at(&document, offset_of("call")).unwrap();
+    assert!(call.contains("- calls: `Billing::Invoice`"), "{call}");
+
+    let ship = hover_at(&document, offset_of("service")).unwrap();
+    assert!(ship.starts_with("**Service task** `Ship`"), "{ship}");
+
+    let fulfilment = hover_at(&document, offset_of("subprocess")).unwrap();
+    assert!(fulfilment.contains("- elements: 3"), "{fulfilment}");
+
+    assert!(hover_at(&document, offset_of("Orders")).is_none());
+}
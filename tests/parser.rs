@@ -1,7 +1,8 @@
 #[cfg(test)]
 mod tests {
-    use bpmncode::lexer::Lexer;
-    use bpmncode::parser::{ast::*, parse_tokens};
+    use bpmncode::diagnostics::DiagnosticError;
+    use bpmncode::lexer::{Lexer, TokenKind};
+    use bpmncode::parser::{Parser, ast::*, ids, parse_tokens};
 
     fn parse_input(input: &str) -> AstDocument {
         let mut lexer = Lexer::new(input, "test.bpmn");
@@ -197,11 +198,17 @@ mod tests {
             assert_eq!(branches.len(), 3);
 
             // Проверяем ветки
-            assert_eq!(branches[0].condition.as_ref().unwrap(), "condition1");
+            assert_eq!(
+                branches[0].condition.as_ref().unwrap().to_string(),
+                "condition1"
+            );
             assert_eq!(branches[0].target, "Task1");
             assert!(!branches[0].is_default);
 
-            assert_eq!(branches[1].condition.as_ref().unwrap(), "condition2");
+            assert_eq!(
+                branches[1].condition.as_ref().unwrap().to_string(),
+                "condition2"
+            );
             assert_eq!(branches[1].target, "Task2");
             assert!(!branches[1].is_default);
 
@@ -256,6 +263,49 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_inclusive_gateway() {
+        let input = r"
+            process Shipping {
+                start
+                or Options? {
+                    [giftWrap] -> Wrap
+                    [express] -> Courier
+                    => Pack
+                }
+                task Wrap
+                task Courier
+                task Pack
+                end
+            }
+        ";
+
+        let ast = parse_input(input);
+        assert!(ast.errors.is_empty(), "{:?}", ast.errors);
+
+        let gateway = ast.processes[0]
+            .elements
+            .iter()
+            .find(|e| matches!(e, ProcessElement::Gateway { .. }))
+            .expect("Should have gateway");
+
+        if let ProcessElement::Gateway {
+            id,
+            gateway_type,
+            branches,
+            ..
+        } = gateway
+        {
+            assert_eq!(id.as_deref(), Some("Options"));
+            assert_eq!(*gateway_type, GatewayType::Inclusive);
+            assert_eq!(branches.len(), 3);
+            assert!(branches[2].is_default);
+        }
+
+        let printed = bpmncode::formatter::printer::print_document(&ast);
+        assert!(printed.contains("    or Options {\n"), "{printed}");
+    }
+
     #[test]
     fn test_flows() {
         let input = r"
@@ -295,6 +345,151 @@ mod tests {
         assert_eq!(process.flows[2].to, "end");
     }
 
+    #[test]
+    fn test_flows_naming_event_keywords() {
+        let input = r"
+            process FlowTest {
+                start
+                task Ship
+                end
+                Ship -> start
+                end -> Ship
+            }
+        ";
+
+        let ast = parse_input(input);
+        assert!(ast.errors.is_empty(), "{:?}", ast.errors);
+
+        let process = &ast.processes[0];
+        assert_eq!(process.elements.len(), 3);
+        let flows: Vec<_> = process
+            .flows
+            .iter()
+            .map(|flow| (flow.from.as_str(), flow.to.as_str()))
+            .collect();
+        assert_eq!(flows, vec![("Ship", "start"), ("end", "Ship")]);
+    }
+
+    #[test]
+    fn test_chained_flows() {
+        let input = r#"
+            process Chain {
+                start -> task A "Do A" -> xor Done? {
+                    [ok] -> Notify
+                    => A
+                }
+                Done -> service Notify -> end
+                A -> task B -> end
+                B -> end
+                Notify -> user Review
+            }
+        "#;
+
+        let ast = parse_input(input);
+        assert!(ast.errors.is_empty(), "{:?}", ast.errors);
+
+        let process = &ast.processes[0];
+        let ids: Vec<_> = process
+            .elements
+            .iter()
+            .map(|element| match element {
+                ProcessElement::StartEvent { .. } => "start",
+                ProcessElement::EndEvent { .. } => "end",
+                ProcessElement::Task { id, .. } => id.as_str(),
+                ProcessElement::Gateway { id, .. } => id.as_deref().unwrap(),
+                _ => "?",
+            })
+            .collect();
+        assert_eq!(
+            ids,
+            vec!["start", "A", "Done", "Notify", "end", "B", "Review"]
+        );
+
+        // The chained `B -> end` gives way to the one written out
+        let flows: Vec<_> = process
+            .flows
+            .iter()
+            .map(|flow| (flow.from.as_str(), flow.to.as_str(), flow.span.line))
+            .collect();
+        assert_eq!(
+            flows,
+            vec![
+                ("start", "A", 3),
+                ("A", "Done", 3),
+                ("Done", "Notify", 7),
+                ("Notify", "end", 7),
+                ("A", "B", 8),
+                ("B", "end", 9),
+                ("Notify", "Review", 10),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_chain_from_start_reuses_the_declared_start() {
+        let input = r"
+            process Twice {
+                start
+                task A
+                task B
+                start -> A -> end
+                start -> B
+            }
+        ";
+
+        let ast = parse_input(input);
+        assert!(ast.errors.is_empty(), "{:?}", ast.errors);
+
+        let process = &ast.processes[0];
+        let starts = process
+            .elements
+            .iter()
+            .filter(|element| matches!(element, ProcessElement::StartEvent { .. }))
+            .count();
+        assert_eq!(starts, 1);
+        let flows: Vec<_> = process
+            .flows
+            .iter()
+            .map(|flow| (flow.from.as_str(), flow.to.as_str()))
+            .collect();
+        assert_eq!(flows, vec![("start", "A"), ("A", "end"), ("start", "B")]);
+    }
+
+    #[test]
+    fn test_chain_to_end_declares_the_end_event() {
+        let input = r"
+            process Straight {
+                start -> task A -> task B -> end
+                subprocess Inner {
+                    start -> task C -> end
+                }
+                task D
+                D -> end
+            }
+        ";
+
+        let ast = parse_input(input);
+        assert!(ast.errors.is_empty(), "{:?}", ast.errors);
+
+        let process = &ast.processes[0];
+        let ends = |elements: &[ProcessElement]| {
+            elements
+                .iter()
+                .filter(|element| matches!(element, ProcessElement::EndEvent { id: None, .. }))
+                .count()
+        };
+        assert_eq!(ends(&process.elements), 1);
+        assert!(matches!(
+            process.elements[3],
+            ProcessElement::EndEvent { ref span, .. } if span.line == 3
+        ));
+
+        let ProcessElement::Subprocess { elements, .. } = &process.elements[4] else {
+            panic!("Expected subprocess, got {:?}", process.elements[4]);
+        };
+        assert_eq!(ends(elements), 1);
+    }
+
     #[test]
     fn test_different_flow_types() {
         let input = r"
@@ -366,11 +561,11 @@ mod tests {
 
         // Проверяем первое условие (может содержать пробелы)
         let first_condition = conditional_flows[0].condition.as_ref().unwrap();
-        assert!(first_condition.contains("amount") && first_condition.contains("1000"));
+        assert_eq!(first_condition.to_string(), "amount > 1000");
 
         // Проверяем второе условие
         let second_condition = conditional_flows[1].condition.as_ref().unwrap();
-        assert!(second_condition.contains("status") && second_condition.contains("approved"));
+        assert_eq!(second_condition.to_string(), r#"status == "approved""#);
     }
 
     #[test]
@@ -543,6 +738,234 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_intermediate_event_direction() {
+        let input = r#"
+            process Escalate {
+                start
+                event throw @escalation "LateDelivery"
+                event catch @signal "Ready"
+                event @compensation "Refund"
+                end
+            }
+        "#;
+
+        let ast = parse_input(input);
+
+        assert_eq!(
+            ast.errors.len(),
+            0,
+            "Should have no errors: {:?}",
+            ast.errors
+        );
+
+        let events: Vec<_> = ast.processes[0]
+            .elements
+            .iter()
+            .filter_map(|element| match element {
+                ProcessElement::IntermediateEvent {
+                    event_type,
+                    direction,
+                    ..
+                } => Some((event_type.clone(), *direction)),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(
+            events,
+            vec![
+                (
+                    EventType::Escalation("LateDelivery".to_string()),
+                    EventDirection::Throw
+                ),
+                (
+                    EventType::Signal("Ready".to_string()),
+                    EventDirection::Catch
+                ),
+                (
+                    EventType::Compensation("Refund".to_string()),
+                    EventDirection::Catch
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_named_intermediate_events() {
+        let input = r#"
+            process Payment {
+                start
+                event WaitForPayment @message "PaymentConfirmed"
+                event throw Reminder @signal "Remind"
+                WaitForPayment -> Reminder
+                end
+            }
+        "#;
+
+        let ast = parse_input(input);
+        assert!(ast.errors.is_empty(), "{:?}", ast.errors);
+
+        let names: Vec<_> = ast.processes[0]
+            .elements
+            .iter()
+            .filter_map(|element| match element {
+                ProcessElement::IntermediateEvent { id, direction, .. } => {
+                    Some((id.as_deref(), *direction))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                (Some("WaitForPayment"), EventDirection::Catch),
+                (Some("Reminder"), EventDirection::Throw),
+            ]
+        );
+
+        let printed = bpmncode::formatter::printer::print_document(&ast);
+        assert!(
+            printed.contains("    event throw Reminder @signal \"Remind\"\n"),
+            "{printed}"
+        );
+    }
+
+    #[test]
+    fn test_conditional_events() {
+        let input = r#"
+            process Restock {
+                start @conditional "stock < 10"
+                event @conditional "supplier.ready == true"
+                end
+            }
+        "#;
+
+        let ast = parse_input(input);
+        assert!(ast.errors.is_empty(), "{:?}", ast.errors);
+
+        let process = &ast.processes[0];
+        let ProcessElement::StartEvent { event_type, .. } = &process.elements[0] else {
+            panic!("Expected StartEvent, got: {:?}", process.elements[0]);
+        };
+        assert_eq!(
+            event_type,
+            &Some(EventType::Conditional("stock < 10".to_string()))
+        );
+        let ProcessElement::IntermediateEvent { event_type, .. } = &process.elements[1] else {
+            panic!("Expected IntermediateEvent, got: {:?}", process.elements[1]);
+        };
+        assert_eq!(
+            event_type,
+            &EventType::Conditional("supplier.ready == true".to_string())
+        );
+
+        let printed = bpmncode::formatter::printer::print_document(&ast);
+        assert!(
+            printed.contains("    start @conditional \"stock < 10\"\n"),
+            "{printed}"
+        );
+        assert!(
+            printed.contains("    event @conditional \"supplier.ready == true\"\n"),
+            "{printed}"
+        );
+    }
+
+    #[test]
+    fn test_process_variables() {
+        let input = r"
+            process Loan {
+                var amount: number
+                var approved: boolean
+                var region: text
+                start
+                end
+            }
+        ";
+
+        let ast = parse_input(input);
+        let process = &ast.processes[0];
+        let variables: Vec<_> = process
+            .variables
+            .iter()
+            .map(|variable| (variable.name.as_str(), variable.variable_type))
+            .collect();
+        assert_eq!(
+            variables,
+            vec![
+                ("amount", VariableType::Number),
+                ("approved", VariableType::Boolean)
+            ]
+        );
+        assert_eq!(process.elements.len(), 2);
+
+        let messages: Vec<_> = ast
+            .errors
+            .iter()
+            .map(|e| (e.message.as_str(), e.span.line, e.span.column))
+            .collect();
+        assert_eq!(
+            messages,
+            vec![(
+                "Unexpected token 'text', expected number, string or boolean",
+                5,
+                29
+            )]
+        );
+
+        let printed = bpmncode::formatter::printer::print_document(&ast);
+        assert!(
+            printed.contains("    var amount: number\n    var approved: boolean\n"),
+            "{printed}"
+        );
+    }
+
+    #[test]
+    fn test_unexpected_end_of_file_is_named() {
+        let ast = parse_input("process Loan {\n    var amount:");
+        let messages: Vec<_> = ast.errors.iter().map(|e| e.message.as_str()).collect();
+        assert!(
+            messages
+                .contains(&"Unexpected token 'end of file', expected number, string or boolean"),
+            "{messages:?}"
+        );
+
+        let ast = parse_input("process Loan");
+        let messages: Vec<_> = ast.errors.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec!["Unexpected token 'end of file', expected '{'"]
+        );
+        let error = DiagnosticError::from_parse_error(&ast.errors[0], &ast);
+        assert!(error.suggestions().is_empty(), "{:?}", error.suggestions());
+    }
+
+    #[test]
+    fn test_ast_json_round_trip() {
+        let input = r#"
+            process Billing @version "1.0" {
+                start
+                task Charge(retries=3, timeout=30s, async=true, queue="billing")
+                end
+            }
+        "#;
+
+        let ast = parse_input(input);
+        let json = serde_json::to_value(&ast).unwrap();
+
+        let attributes = &json["processes"][0]["elements"][1]["Task"]["attributes"];
+        assert_eq!(attributes["retries"], serde_json::json!(3.0));
+        assert_eq!(
+            attributes["timeout"],
+            serde_json::json!({ "duration": "30s" })
+        );
+        assert_eq!(attributes["async"], serde_json::json!(true));
+        assert_eq!(attributes["queue"], serde_json::json!("billing"));
+
+        let restored: AstDocument = serde_json::from_value(json).unwrap();
+        assert_eq!(restored, ast);
+    }
+
     #[test]
     fn test_imports() {
         let input = r#"
@@ -630,6 +1053,68 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_named_annotation() {
+        let ast = parse_input(
+            "process P {\n    task Ship\n    note Rules \"Fragile first\"\n    Ship ..> Rules\n}",
+        );
+        assert!(ast.errors.is_empty(), "{:?}", ast.errors);
+
+        let ProcessElement::Annotation { id, text, .. } = &ast.processes[0].elements[1] else {
+            panic!("Expected Annotation");
+        };
+        assert_eq!(id.as_deref(), Some("Rules"));
+        assert_eq!(text, "Fragile first");
+
+        let printed = bpmncode::formatter::printer::print_document(&ast);
+        assert!(
+            printed.contains("    note Rules \"Fragile first\"\n"),
+            "{printed}"
+        );
+    }
+
+    #[test]
+    fn test_data_objects() {
+        let input = r#"
+            process Orders {
+                start
+                task Receive
+                data OrderForm "Order form"
+                data store Inventory
+                end
+
+                Receive ..> OrderForm
+            }
+        "#;
+
+        let ast = parse_input(input);
+        assert!(ast.errors.is_empty(), "{:?}", ast.errors);
+
+        let data: Vec<_> = ast.processes[0]
+            .elements
+            .iter()
+            .filter_map(|element| match element {
+                ProcessElement::DataObject {
+                    id, label, store, ..
+                } => Some((id.as_str(), label.as_deref(), *store)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            data,
+            vec![
+                ("OrderForm", Some("Order form"), false),
+                ("Inventory", None, true)
+            ]
+        );
+
+        let printed = bpmncode::formatter::printer::print_document(&ast);
+        assert!(
+            printed.contains("    data OrderForm \"Order form\"\n    data store Inventory\n"),
+            "{printed}"
+        );
+    }
+
     #[test]
     #[allow(clippy::float_cmp)]
     fn test_attribute_values() {
@@ -699,23 +1184,218 @@ mod tests {
     }
 
     #[test]
-    fn test_complex_process() {
+    fn test_container_and_gateway_attributes() {
         let input = r#"
-            process ComplexOrder @version "2.0" @author "Business Analyst" {
-                start @message "OrderReceived"
-                
-                task ValidateOrder (timeout=5m assignee="validator")
-                
-                xor OrderValid? {
-                    [validation_result == "valid"] -> ProcessOrder
-                    [validation_result == "invalid"] -> RejectOrder
-                    => ReviewManually
+            process Order {
+                xor Check? (priority=high) {
+                    [ok] -> Ship
+                    => Reject
                 }
-                
-                task ProcessOrder
-                task RejectOrder
-                task ReviewManually
-                
+                and Merge join @timeout 5m
+                pool Shop @owner "sales" {
+                    task Ship
+                    task Reject
+                }
+                event subprocess Cancel @error "E1" (retries=2) {
+                    start
+                }
+            }
+        "#;
+
+        let ast = parse_input(input);
+        assert!(ast.errors.is_empty(), "Errors: {:?}", ast.errors);
+
+        let attributes: Vec<_> = ast.processes[0]
+            .elements
+            .iter()
+            .map(|element| match element {
+                ProcessElement::Gateway { attributes, .. }
+                | ProcessElement::Pool { attributes, .. }
+                | ProcessElement::EventSubprocess { attributes, .. } => attributes
+                    .iter()
+                    .map(|(key, value)| (key.as_str(), value.clone()))
+                    .collect::<Vec<_>>(),
+                _ => panic!("Unexpected element: {element:?}"),
+            })
+            .collect();
+        assert_eq!(
+            attributes,
+            vec![
+                vec![("priority", AttributeValue::String("high".to_string()))],
+                vec![("timeout", AttributeValue::Duration("5m".to_string()))],
+                vec![("owner", AttributeValue::String("sales".to_string()))],
+                vec![("retries", AttributeValue::Number(2.0))],
+            ]
+        );
+
+        let printed = bpmncode::formatter::printer::print_document(&ast);
+        assert!(
+            printed.contains("    xor Check(priority=\"high\") {\n"),
+            "{printed}"
+        );
+        assert!(
+            printed.contains("    and Merge join(timeout=5m)\n"),
+            "{printed}"
+        );
+        assert!(
+            printed.contains("    pool Shop(owner=\"sales\") {\n"),
+            "{printed}"
+        );
+        assert!(
+            printed.contains("    event subprocess Cancel @error \"E1\"(retries=2) {\n"),
+            "{printed}"
+        );
+        assert_eq!(
+            bpmncode::formatter::printer::print_document(&parse_input(&printed)),
+            printed
+        );
+    }
+
+    #[test]
+    fn test_keywords_as_attribute_keys() {
+        let input = r"
+            process Order {
+                xor Other (note=ab) {
+                    [ok] -> Ship
+                    => Ship
+                }
+                task Ship
+            }
+        ";
+
+        let ast = parse_input(input);
+        assert!(ast.errors.is_empty(), "Errors: {:?}", ast.errors);
+
+        let ProcessElement::Gateway { attributes, .. } = &ast.processes[0].elements[0] else {
+            panic!("Expected gateway: {:?}", ast.processes[0].elements[0]);
+        };
+        assert_eq!(
+            attributes.get("note"),
+            Some(&AttributeValue::String("ab".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_duration_attributes_compute_millis() {
+        let input = r"
+            process Durations {
+                task Wait (short=250ms, medium=90s, long=1h30m, mixed=2s500ms, days=1d12h, half=1.5h)
+                end
+            }
+        ";
+
+        let ast = parse_input(input);
+        assert!(ast.errors.is_empty(), "Unexpected errors: {:?}", ast.errors);
+
+        let ProcessElement::Task { attributes, .. } = &ast.processes[0].elements[0] else {
+            panic!("Expected Task");
+        };
+        let millis = |key: &str| attributes.get(key).and_then(AttributeValue::as_millis);
+
+        assert_eq!(millis("short"), Some(250));
+        assert_eq!(millis("medium"), Some(90_000));
+        assert_eq!(millis("long"), Some(5_400_000));
+        assert_eq!(millis("mixed"), Some(2_500));
+        assert_eq!(millis("days"), Some(129_600_000));
+        assert_eq!(millis("half"), Some(5_400_000));
+
+        for (raw, expected) in [
+            ("3.5h", 12_600_000),
+            ("1.25s", 1_250),
+            ("1h0.5m", 3_630_000),
+            ("1.5ms", 2),
+            ("0.0004s", 0),
+        ] {
+            assert_eq!(parse_duration_millis(raw), Some(expected), "{raw}");
+        }
+
+        for raw in ["30x", "30m1h", "5s5s", "ms", "3.h", ".5h", "1.2.3s"] {
+            assert_eq!(parse_duration_millis(raw), None, "{raw} should be rejected");
+        }
+
+        let ast = parse_input("process P { task T (timeout=30m1h) }");
+        assert!(
+            ast.errors
+                .iter()
+                .any(|e| e.message.contains("Invalid attribute value '30m1h'")),
+            "Expected invalid value error: {:?}",
+            ast.errors
+        );
+    }
+
+    #[test]
+    fn test_element_behind_a_failed_link_is_parsed_once() {
+        let ast = parse_input(
+            "process P {\n    start\n    task A -> group \"g\" {\n        task C(x=1, x=2)\n    }\n    task B -> task D(y=1, y=2) []\n    end\n}",
+        );
+
+        let messages: Vec<_> = ast.errors.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Attribute 'x' specified twice",
+                "Attribute 'y' specified twice",
+                "Unexpected token ']', expected condition expression",
+            ]
+        );
+
+        let names: Vec<_> = ast.processes[0]
+            .elements
+            .iter()
+            .filter_map(|element| match element {
+                ProcessElement::Task { id, .. } => Some(id.as_str()),
+                ProcessElement::Group { .. } => Some("group"),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(names, vec!["A", "group", "B", "D"]);
+    }
+
+    #[test]
+    fn test_attribute_given_twice_is_reported() {
+        let ast = parse_input("process P {\n    task Wait @timeout 5m (timeout=10m)\n}");
+
+        let warnings: Vec<_> = ast
+            .errors
+            .iter()
+            .map(|e| (e.message.as_str(), e.span.column, e.severity.clone()))
+            .collect();
+        assert_eq!(
+            warnings,
+            vec![(
+                "Attribute 'timeout' specified twice",
+                28,
+                ErrorSeverity::Warning
+            )]
+        );
+
+        let ProcessElement::Task { attributes, .. } = &ast.processes[0].elements[0] else {
+            panic!("Expected Task");
+        };
+        assert_eq!(
+            attributes.get("timeout"),
+            Some(&AttributeValue::Duration("10m".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_complex_process() {
+        let input = r#"
+            process ComplexOrder @version "2.0" @author "Business Analyst" {
+                start @message "OrderReceived"
+                
+                task ValidateOrder (timeout=5m assignee="validator")
+                
+                xor OrderValid? {
+                    [validation_result == "valid"] -> ProcessOrder
+                    [validation_result == "invalid"] -> RejectOrder
+                    => ReviewManually
+                }
+                
+                task ProcessOrder
+                task RejectOrder
+                task ReviewManually
+                
                 and PrepareShipment {
                     branch1 -> PackItems
                     branch2 -> GenerateInvoice
@@ -783,6 +1463,40 @@ mod tests {
         assert!(process.flows.len() >= 5);
     }
 
+    #[test]
+    fn test_junk_between_processes_is_skipped() {
+        let input = r"
+            process First {
+                start
+                end
+            }
+
+            this line is junk { }
+            }
+
+            process Second {
+                start
+                end
+            }
+        ";
+
+        let tokens = Lexer::new(input, "test.bpmn").tokenize();
+        for ast in [parse_tokens(tokens.clone()), Parser::new(tokens).parse()] {
+            let names: Vec<_> = ast.processes.iter().map(|p| p.name.as_str()).collect();
+            assert_eq!(names, vec!["First", "Second"]);
+
+            let messages: Vec<_> = ast
+                .errors
+                .iter()
+                .map(|e| (e.message.as_str(), e.span.line))
+                .collect();
+            assert_eq!(
+                messages,
+                vec![("Unexpected token 'this', expected 'process' or 'import'", 7)]
+            );
+        }
+    }
+
     #[test]
     fn test_error_recovery() {
         let input = r"
@@ -980,6 +1694,59 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_carriage_return_separated_statements() {
+        let ast = parse_input(
+            "process Legacy {\r    start\r    task Review\r    task Ship\r\r    Review -> Ship\r}\r",
+        );
+
+        assert!(ast.errors.is_empty(), "{:?}", ast.errors);
+        let process = &ast.processes[0];
+        assert_eq!(process.elements.len(), 3);
+        assert_eq!(process.flows.len(), 1);
+        assert_eq!(process.flows[0].span.line, 6);
+    }
+
+    #[test]
+    fn test_string_literal_unicode_escapes() {
+        let input = r#"
+            process StringTest {
+                task MyTask @description "Caf\u00e9 \u{1F69A}\r\nline\\u00e9"
+                end
+            }
+        "#;
+
+        let ast = parse_input(input);
+        assert!(ast.errors.is_empty(), "{:?}", ast.errors);
+
+        let ProcessElement::Task { attributes, .. } = &ast.processes[0].elements[0] else {
+            panic!("Expected task");
+        };
+        assert_eq!(
+            attributes["description"],
+            AttributeValue::String("Café 🚚\r\nline\\u00e9".to_string())
+        );
+    }
+
+    #[test]
+    fn test_malformed_unicode_escape_is_reported() {
+        let input = r#"
+            process StringTest {
+                task MyTask @description "bad \uZZZZ"
+                task Other "short \u{e9"
+                end
+            }
+        "#;
+
+        let ast = parse_input(input);
+
+        let messages: Vec<_> = ast.errors.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages.len(), 2, "{messages:?}");
+        assert_eq!(messages[0], "Invalid escape sequence '\\u'");
+        assert_eq!(messages[1], "Invalid escape sequence '\\u{e9'");
+        assert_eq!(ast.processes[0].elements.len(), 3);
+    }
+
     #[test]
     fn test_nested_subprocess_flows() {
         let input = r"
@@ -1039,6 +1806,477 @@ mod tests {
             panic!("Expected Subprocess");
         }
     }
+
+    #[test]
+    fn test_display_labels() {
+        let ast = parse_input(
+            r#"
+            process Order {
+                task ValidateOrder "Validate Customer Order" (retries=3)
+                user Review
+                call billing::Charge "Charge card"
+                subprocess Ship "Ship \"express\"" {
+                    task Pack
+                }
+            }
+        "#,
+        );
+        assert!(ast.errors.is_empty(), "Errors: {:?}", ast.errors);
+
+        let labels: Vec<_> = ast.processes[0]
+            .elements
+            .iter()
+            .map(|element| match element {
+                ProcessElement::Task { id, label, .. }
+                | ProcessElement::CallActivity { id, label, .. }
+                | ProcessElement::Subprocess { id, label, .. } => (id.as_str(), label.as_deref()),
+                other => panic!("Unexpected element {other:?}"),
+            })
+            .collect();
+
+        assert_eq!(
+            labels,
+            vec![
+                ("ValidateOrder", Some("Validate Customer Order")),
+                ("Review", None),
+                ("billing", Some("Charge card")),
+                ("Ship", Some("Ship \"express\"")),
+            ]
+        );
+
+        let ProcessElement::Task { attributes, .. } = &ast.processes[0].elements[0] else {
+            panic!("Expected Task");
+        };
+        assert_eq!(
+            attributes.get("retries"),
+            Some(&AttributeValue::Number(3.0))
+        );
+    }
+
+    #[test]
+    fn test_assign_ids_names_anonymous_elements() {
+        let mut ast = parse_input(
+            r"
+            process Order {
+                start
+                task Gateway_1
+                xor {
+                    [ok] -> Ship
+                    => Reject
+                }
+                task Reject
+                Reject -> end
+                subprocess Ship {
+                    start
+                    task Pack
+                    Pack -> end
+                    end
+                }
+                event @timer 5m
+                end
+            }
+        ",
+        );
+        let messages: Vec<_> = ast.errors.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec!["Gateway has no name; flows cannot reference it, name it like `xor Decide`"]
+        );
+
+        ids::assign_ids(&mut ast);
+        let elements = &ast.processes[0].elements;
+
+        let ids: Vec<_> = elements
+            .iter()
+            .filter_map(|element| match element {
+                ProcessElement::StartEvent { id, .. }
+                | ProcessElement::EndEvent { id, .. }
+                | ProcessElement::Gateway { id, .. }
+                | ProcessElement::IntermediateEvent { id, .. } => id.clone(),
+                ProcessElement::Task { id, .. } | ProcessElement::Subprocess { id, .. } => {
+                    Some(id.clone())
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            ids,
+            vec![
+                "StartEvent_1",
+                "Gateway_1",
+                "Gateway_2",
+                "Reject",
+                "Ship",
+                "IntermediateEvent_1",
+                "EndEvent_2",
+            ]
+        );
+
+        assert_eq!(ast.processes[0].flows[0].to, "EndEvent_2");
+
+        let ProcessElement::Subprocess {
+            elements: inner,
+            flows,
+            ..
+        } = &elements[4]
+        else {
+            panic!("Expected Subprocess");
+        };
+        assert!(
+            matches!(&inner[0], ProcessElement::StartEvent { id: Some(id), .. } if id == "StartEvent_2")
+        );
+        assert_eq!(flows[0].to, "EndEvent_1");
+    }
+
+    #[test]
+    fn test_event_subprocess() {
+        let input = r#"
+        process Orders {
+            start
+            event @timer 5m
+            event subprocess HandleFailure @error "E1" {
+                start @error "E1"
+                task Notify
+                end
+                Notify -> end
+            }
+            end
+        }
+    "#;
+
+        let ast = parse_input(input);
+        assert!(ast.errors.is_empty(), "{:?}", ast.errors);
+
+        let elements = &ast.processes[0].elements;
+        assert!(matches!(
+            elements[1],
+            ProcessElement::IntermediateEvent { .. }
+        ));
+
+        let ProcessElement::EventSubprocess {
+            id,
+            trigger,
+            elements: inner,
+            flows,
+            ..
+        } = &elements[2]
+        else {
+            panic!("Expected event subprocess, got {:?}", elements[2]);
+        };
+        assert_eq!(id, "HandleFailure");
+        assert_eq!(*trigger, EventType::Error("E1".to_string()));
+        assert_eq!(inner.len(), 3);
+        assert_eq!(flows.len(), 1);
+    }
+
+    #[test]
+    fn test_gateway_modes() {
+        let input = r"
+        process Orders {
+            start
+            and Fork split {
+                => Pack
+            }
+            and Merge join
+            xor Review mixed? {
+                => Pack
+            }
+            xor join {
+                => Pack
+            }
+            task Pack
+            end
+        }
+    ";
+
+        let ast = parse_input(input);
+        let messages: Vec<_> = ast.errors.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec!["Gateway has no name; flows cannot reference it, name it like `xor Decide`"]
+        );
+
+        let modes: Vec<_> = ast.processes[0]
+            .elements
+            .iter()
+            .filter_map(|element| match element {
+                ProcessElement::Gateway {
+                    id, mode, branches, ..
+                } => Some((id.as_deref(), *mode, branches.len())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            modes,
+            vec![
+                (Some("Fork"), GatewayMode::Split, 1),
+                (Some("Merge"), GatewayMode::Join, 0),
+                (Some("Review"), GatewayMode::Mixed, 1),
+                (None, GatewayMode::Join, 1),
+            ]
+        );
+
+        let printed = bpmncode::formatter::printer::print_document(&ast);
+        assert!(printed.contains("    and Merge join\n"), "{printed}");
+        assert!(printed.contains("    xor Review mixed {\n"), "{printed}");
+    }
+
+    #[test]
+    fn test_message_and_signal_definitions_are_deduplicated() {
+        let input = r#"
+        process Orders {
+            start @message "OrderReceived"
+            event throw @signal "Restock"
+            pool Warehouse {
+                event @message "OrderReceived"
+                event @signal "Restock"
+            }
+            event subprocess Failure @error "E1" {
+                start @error "E1"
+                end @message "Apology"
+            }
+            end @error "E2"
+        }
+    "#;
+
+        let ast = parse_input(input);
+        assert!(ast.errors.is_empty(), "{:?}", ast.errors);
+
+        assert_eq!(ast.messages, vec!["OrderReceived", "Apology"]);
+        assert_eq!(ast.signals, vec!["Restock"]);
+        assert_eq!(ast.error_codes, vec!["E1", "E2"]);
+    }
+
+    #[test]
+    fn test_parser_terminates_on_token_soup() {
+        const PIECES: &[&str] = &[
+            "process",
+            "import",
+            "from",
+            "as",
+            "subprocess",
+            "start",
+            "end",
+            "task",
+            "user",
+            "call",
+            "xor",
+            "and",
+            "event",
+            "group",
+            "pool",
+            "lane",
+            "note",
+            "->",
+            "-->",
+            "=>",
+            "..>",
+            "::",
+            ":",
+            "{",
+            "}",
+            "(",
+            ")",
+            "[",
+            "]",
+            ",",
+            "=",
+            "@",
+            "?",
+            "\"s\"",
+            "42",
+            "5m",
+            "Name",
+            "==",
+            "&&",
+            "!",
+            "<",
+            "\n",
+            "// c",
+            "/* c */",
+            "#",
+            "-",
+        ];
+
+        // xorshift keeps the soup reproducible without a dependency
+        let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        for _ in 0..500 {
+            let length = usize::try_from(next() % 60).unwrap();
+            let input: Vec<&str> = (0..length)
+                .map(|_| PIECES[usize::try_from(next()).unwrap() % PIECES.len()])
+                .collect();
+            let input = input.join(" ");
+
+            let tokens = Lexer::new(&input, "soup.bpmn").tokenize();
+            let _ = parse_tokens(tokens.clone());
+            let _ = Parser::new(tokens).parse_with_recovery();
+        }
+
+        let stray = Lexer::new("} process P { } } }", "stray.bpmn").tokenize();
+        assert!(
+            !Parser::new(stray.clone())
+                .parse_with_recovery()
+                .errors
+                .is_empty()
+        );
+        assert!(!parse_tokens(stray).errors.is_empty());
+    }
+
+    #[test]
+    fn test_conditions_are_printed_canonically() {
+        let input = r#"
+        process Canonical {
+            task Source
+            task Target
+            Source -> Target [amount>=1000&&currency=="USD"]
+            Source -> Target [(a || b) && !c]
+            Source -> Target [a || b && c]
+        }
+    "#;
+
+        let ast = parse_input(input);
+        assert!(ast.errors.is_empty(), "{:?}", ast.errors);
+
+        let conditions: Vec<String> = ast.processes[0]
+            .flows
+            .iter()
+            .filter_map(|flow| flow.condition.as_ref().map(ToString::to_string))
+            .collect();
+        assert_eq!(
+            conditions,
+            vec![
+                r#"amount >= 1000 && currency == "USD""#,
+                "(a || b) && !c",
+                "a || b && c",
+            ]
+        );
+
+        let ConditionExpr::Or(_, right) = ast.processes[0].flows[2].condition.as_ref().unwrap()
+        else {
+            panic!("Expected || at the top");
+        };
+        assert!(matches!(**right, ConditionExpr::And(..)));
+    }
+
+    #[test]
+    fn test_invalid_condition_is_reported() {
+        let input = r"
+        process Broken {
+            task Source
+            task Target
+            Source -> Target [amount >]
+        }
+    ";
+
+        let ast = parse_input(input);
+        assert!(
+            ast.errors
+                .iter()
+                .any(|error| error.message.contains("Invalid condition 'amount >'")),
+            "{:?}",
+            ast.errors
+        );
+    }
+
+    #[test]
+    fn test_deeply_nested_condition_is_reported_not_overflowed() {
+        for condition in ["(".repeat(5_000) + "ok", "!".repeat(100_000) + "ok"] {
+            let input = format!(
+                "process Deep {{\n    task Source\n    task Target\n    Source -> Target [{condition}]\n}}\n"
+            );
+
+            let ast = parse_input(&input);
+            assert!(
+                ast.errors
+                    .iter()
+                    .any(|error| error.message.ends_with("nested deeper than 256 levels")),
+                "{:?}",
+                ast.errors
+            );
+        }
+    }
+
+    #[test]
+    fn test_long_condition_chain_is_reported_not_overflowed() {
+        for operator in [" && ", " || "] {
+            let condition = vec!["ok"; 20_000].join(operator);
+            let input = format!(
+                "process Long {{\n    task Source\n    task Target\n    Source -> Target [{condition}]\n}}\n"
+            );
+
+            let ast = parse_input(&input);
+            assert!(
+                ast.errors
+                    .iter()
+                    .any(|error| error.message.ends_with("more than 256 operators in a row")),
+                "{:?}",
+                ast.errors
+            );
+        }
+    }
+
+    #[test]
+    fn test_each_malformed_gateway_branch_is_reported() {
+        let input = r"
+        process Broken {
+            task Approve
+            task Reject
+            task Review
+            xor Decision {
+                [approved] Approve
+                [rejected] -> Reject
+                [unclear] -> 
+                => Review
+            }
+        }
+    ";
+
+        let ast = parse_input(input);
+        let branch_errors: Vec<_> = ast
+            .errors
+            .iter()
+            .filter(|error| error.severity == ErrorSeverity::Error)
+            .collect();
+        assert_eq!(branch_errors.len(), 2, "{:?}", ast.errors);
+        assert!(branch_errors[0].message.contains("expected -> or =>"));
+        assert_eq!(branch_errors[0].span.line, 7);
+        assert_eq!(branch_errors[1].span.line, 9);
+
+        let ProcessElement::Gateway { branches, .. } = &ast.processes[0].elements[3] else {
+            panic!("Expected gateway, got {:?}", ast.processes[0].elements[3]);
+        };
+        assert_eq!(branches.len(), 2);
+        assert_eq!(branches[0].target, "Reject");
+        assert!(branches[1].is_default);
+    }
+
+    #[test]
+    fn test_missing_brace_reports_expected_spelling() {
+        let input = r"
+        process Broken
+            task A
+        }
+    ";
+
+        let ast = parse_input(input);
+        assert!(
+            ast.errors
+                .iter()
+                .any(|error| error.message.contains("expected '{'")),
+            "{:?}",
+            ast.errors
+        );
+        assert_eq!(TokenKind::RightBrace.to_string(), "}");
+        assert_eq!(TokenKind::SequenceFlow.to_string(), "->");
+        assert_eq!(TokenKind::Eof.to_string(), "end of file");
+    }
 }
 
 #[cfg(test)]
@@ -1142,6 +2380,57 @@ mod integration_tests {
         assert_eq!(ast.processes.len(), 1);
     }
 
+    #[test]
+    fn test_nesting_depth_limit() {
+        use bpmncode::parser::Parser;
+        use std::fmt::Write;
+
+        let depth = 50_000;
+        let mut input = String::from("process TooDeep {\n    start\n");
+        for i in 0..depth {
+            writeln!(input, "subprocess Level{i} {{").unwrap();
+        }
+        input.push_str(&"}\n".repeat(depth));
+        input.push_str("    end\n}\n");
+
+        // Unoptimised builds use much larger stack frames than release builds,
+        // so give the parser the same stack the CLI main thread gets
+        let ast = std::thread::Builder::new()
+            .stack_size(8 * 1024 * 1024)
+            .spawn(move || parse_input(&input))
+            .unwrap()
+            .join()
+            .unwrap();
+
+        let depth_errors: Vec<_> = ast
+            .errors
+            .iter()
+            .filter(|e| e.message == "Maximum nesting depth exceeded")
+            .collect();
+        assert_eq!(depth_errors.len(), 1, "Errors: {:?}", ast.errors);
+        assert_eq!(depth_errors[0].span.line, 259);
+        assert_eq!(ast.processes.len(), 1);
+        assert_eq!(ast.processes[0].elements.len(), 3);
+
+        let mut lexer = Lexer::new(
+            "process P { group \"a\" { group \"b\" { task T } } }",
+            "test.bpmn",
+        );
+        let ast = Parser::new(lexer.tokenize()).with_max_depth(1).parse();
+        assert!(
+            ast.errors
+                .iter()
+                .any(|e| e.message == "Maximum nesting depth exceeded")
+        );
+        let ProcessElement::Group { elements, .. } = &ast.processes[0].elements[0] else {
+            panic!("Expected Group");
+        };
+        assert!(matches!(
+            &elements[0],
+            ProcessElement::Group { elements, .. } if elements.is_empty()
+        ));
+    }
+
     #[test]
     fn test_edge_cases() {
         // Тест различных граничных случаев
@@ -0,0 +1,187 @@
+use std::fs;
+
+use bpmncode::{
+    diagnostics::DiagnosticError,
+    lexer::Lexer,
+    lexer::multi_file::MultiFileLexer,
+    parser::{
+        parse_tokens,
+        resolver::{check_local_calls, resolve_imports},
+    },
+};
+
+fn resolve(main_source: &str, files: &[(&str, &str)]) -> Vec<DiagnosticError> {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("shared")).unwrap();
+    for (name, content) in files {
+        fs::write(dir.path().join(name), content).unwrap();
+    }
+    fs::write(dir.path().join("shared/main.bpmn"), main_source).unwrap();
+
+    // The base directory deliberately differs from the importing file's directory
+    let mut lexer = MultiFileLexer::new(dir.path());
+    let tokens = lexer.tokenize_file("shared/main.bpmn".as_ref()).unwrap();
+    let document = parse_tokens(tokens);
+
+    resolve_imports(&document, &mut lexer)
+}
+
+#[test]
+fn test_namespaced_call_resolves_against_imported_file() {
+    let errors = resolve(
+        r#"
+        import "billing.bpmn" as billing
+
+        process Order {
+            start
+            call billing::Invoice
+            end
+        }
+    "#,
+        &[(
+            "shared/billing.bpmn",
+            "process Invoice {\n    start\n    task Charge\n    end\n}\n",
+        )],
+    );
+
+    assert!(errors.is_empty(), "Unexpected errors: {errors:?}");
+}
+
+#[test]
+fn test_missing_imported_element_suggests_similar_name() {
+    let errors = resolve(
+        r#"
+        import "billing.bpmn" as billing
+
+        process Order {
+            start
+            call billing::Invoise
+            end
+        }
+    "#,
+        &[(
+            "shared/billing.bpmn",
+            "process Invoice {\n    start\n    task Charge\n    end\n}\n",
+        )],
+    );
+
+    assert_eq!(errors.len(), 1, "Errors: {errors:?}");
+    match &errors[0] {
+        DiagnosticError::UndefinedReference {
+            name, suggestions, ..
+        } => {
+            assert_eq!(name, "billing::Invoise");
+            assert!(suggestions.contains(&"billing::Invoice".to_string()));
+        }
+        other => panic!("Expected undefined reference, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_flows_and_branches_resolve_namespaced_targets() {
+    let errors = resolve(
+        r#"
+        import "validation.bpmn" as validation
+
+        process Order {
+            start
+            task Receive
+            xor Valid? {
+                [checked] -> validation::CheckData
+                => validation::Rejct
+            }
+            end
+            Receive -> validation::CheckData
+            validation::CheckData -> end
+        }
+    "#,
+        &[(
+            "shared/validation.bpmn",
+            "process Validation {\n    start\n    task CheckData\n    task Reject\n    end\n}\n",
+        )],
+    );
+
+    assert_eq!(errors.len(), 1, "Errors: {errors:?}");
+    match &errors[0] {
+        DiagnosticError::UndefinedReference {
+            name, suggestions, ..
+        } => {
+            assert_eq!(name, "validation::Rejct");
+            assert!(suggestions.contains(&"validation::Reject".to_string()));
+        }
+        other => panic!("Expected undefined reference, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_unknown_namespace_and_missing_file_are_import_errors() {
+    let errors = resolve(
+        r#"
+        import "missing.bpmn" as missing
+
+        process Order {
+            start
+            call shipping::Dispatch
+            call missing::Anything
+            end
+        }
+    "#,
+        &[],
+    );
+
+    assert_eq!(errors.len(), 2, "Errors: {errors:?}");
+    assert!(
+        errors
+            .iter()
+            .all(|error| matches!(error, DiagnosticError::ImportError { .. }))
+    );
+    assert!(
+        errors
+            .iter()
+            .any(|error| error.to_string().contains("missing.bpmn"))
+    );
+    assert!(
+        errors
+            .iter()
+            .any(|error| error.to_string().contains("'shipping'"))
+    );
+}
+
+#[test]
+fn test_local_call_must_name_process_or_subprocess() {
+    let source = r#"
+        import Refund from "payments.bpmn"
+
+        process Order {
+            start
+            subprocess Packing {
+                start
+                end
+            }
+            call Invoicing
+            call Packing
+            call Refund
+            call Invoce
+            call billing::Charge
+            end
+        }
+
+        process Invoicing {
+            start
+            end
+        }
+    "#;
+    let document = parse_tokens(Lexer::new(source, "main.bpmn").tokenize());
+
+    let errors = check_local_calls(&document);
+
+    assert_eq!(errors.len(), 1, "Errors: {errors:?}");
+    let DiagnosticError::UndefinedReference {
+        name, suggestions, ..
+    } = &errors[0]
+    else {
+        panic!("Expected an undefined reference, got {:?}", errors[0]);
+    };
+    assert_eq!(name, "Invoce");
+    assert_eq!(suggestions, &vec!["Invoicing".to_string()]);
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use indexmap::IndexMap;
 use std::path::PathBuf;
 
 use bpmncode::{
@@ -8,7 +8,9 @@ use bpmncode::{
             AstDocument, ErrorSeverity, Flow, FlowType, ProcessDeclaration, ProcessElement,
             TaskType,
         },
-        validator::validate_syntax,
+        condition::parse_condition_str,
+        rules::Profile,
+        validator::{validate_syntax, validate_syntax_with_profile},
     },
 };
 
@@ -28,23 +30,26 @@ fn test_duplicate_id_validation() {
 
     let task1 = ProcessElement::Task {
         id: "task1".to_string(),
+        label: None,
         task_type: TaskType::Generic,
-        attributes: HashMap::new(),
+        attributes: IndexMap::new(),
         span: span.clone(),
     };
 
     let task2 = ProcessElement::Task {
         id: "task1".to_string(),
+        label: None,
         task_type: TaskType::User,
-        attributes: HashMap::new(),
+        attributes: IndexMap::new(),
         span: span.clone(),
     };
 
     let process = ProcessDeclaration {
         name: "TestProcess".to_string(),
-        attributes: HashMap::new(),
+        attributes: IndexMap::new(),
         elements: vec![task1, task2],
         flows: vec![],
+        variables: vec![],
         span,
     };
 
@@ -52,6 +57,7 @@ fn test_duplicate_id_validation() {
         imports: vec![],
         processes: vec![process],
         errors: vec![],
+        ..AstDocument::default()
     };
 
     let result = validate_syntax(&document);
@@ -69,8 +75,9 @@ fn test_invalid_flow_validation() {
 
     let task1 = ProcessElement::Task {
         id: "task1".to_string(),
+        label: None,
         task_type: TaskType::Generic,
-        attributes: HashMap::new(),
+        attributes: IndexMap::new(),
         span: span.clone(),
     };
 
@@ -81,13 +88,16 @@ fn test_invalid_flow_validation() {
         flow_type: FlowType::Sequence,
         condition: None,
         span: span.clone(),
+        from_span: span.clone(),
+        to_span: span.clone(),
     };
 
     let process = ProcessDeclaration {
         name: "TestProcess".to_string(),
-        attributes: HashMap::new(),
+        attributes: IndexMap::new(),
         elements: vec![task1],
         flows: vec![invalid_flow],
+        variables: vec![],
         span,
     };
 
@@ -95,6 +105,7 @@ fn test_invalid_flow_validation() {
         imports: vec![],
         processes: vec![process],
         errors: vec![],
+        ..AstDocument::default()
     };
 
     let result = validate_syntax(&document);
@@ -116,21 +127,22 @@ fn test_valid_document() {
     let start = ProcessElement::StartEvent {
         id: None,
         event_type: None,
-        attributes: HashMap::new(),
+        attributes: IndexMap::new(),
         span: span.clone(),
     };
 
     let task1 = ProcessElement::Task {
         id: "task1".to_string(),
+        label: None,
         task_type: TaskType::Generic,
-        attributes: HashMap::new(),
+        attributes: IndexMap::new(),
         span: span.clone(),
     };
 
     let end = ProcessElement::EndEvent {
         id: None,
         event_type: None,
-        attributes: HashMap::new(),
+        attributes: IndexMap::new(),
         span: span.clone(),
     };
 
@@ -140,6 +152,8 @@ fn test_valid_document() {
         flow_type: FlowType::Sequence,
         condition: None,
         span: span.clone(),
+        from_span: span.clone(),
+        to_span: span.clone(),
     };
 
     let flow2 = Flow {
@@ -148,13 +162,16 @@ fn test_valid_document() {
         flow_type: FlowType::Sequence,
         condition: None,
         span: span.clone(),
+        from_span: span.clone(),
+        to_span: span.clone(),
     };
 
     let process = ProcessDeclaration {
         name: "ValidProcess".to_string(),
-        attributes: HashMap::new(),
+        attributes: IndexMap::new(),
         elements: vec![start, task1, end],
         flows: vec![flow1, flow2],
+        variables: vec![],
         span,
     };
 
@@ -162,6 +179,7 @@ fn test_valid_document() {
         imports: vec![],
         processes: vec![process],
         errors: vec![],
+        ..AstDocument::default()
     };
 
     let result = validate_syntax(&document);
@@ -174,16 +192,18 @@ fn test_missing_start_event_warning() {
 
     let task1 = ProcessElement::Task {
         id: "task1".to_string(),
+        label: None,
         task_type: TaskType::Generic,
-        attributes: HashMap::new(),
+        attributes: IndexMap::new(),
         span: span.clone(),
     };
 
     let process = ProcessDeclaration {
         name: "ProcessWithoutStart".to_string(),
-        attributes: HashMap::new(),
+        attributes: IndexMap::new(),
         elements: vec![task1],
         flows: vec![],
+        variables: vec![],
         span,
     };
 
@@ -191,6 +211,7 @@ fn test_missing_start_event_warning() {
         imports: vec![],
         processes: vec![process],
         errors: vec![],
+        ..AstDocument::default()
     };
 
     let result = validate_syntax(&document);
@@ -236,28 +257,32 @@ fn test_complex_flow_validation() {
     let start = ProcessElement::StartEvent {
         id: None,
         event_type: None,
-        attributes: HashMap::new(),
+        attributes: IndexMap::new(),
         span: span.clone(),
     };
 
     let gateway = ProcessElement::Gateway {
         id: Some("decision".to_string()),
         gateway_type: bpmncode::parser::ast::GatewayType::Exclusive,
+        mode: bpmncode::parser::ast::GatewayMode::Split,
         branches: vec![],
+        attributes: IndexMap::new(),
         span: span.clone(),
     };
 
     let task1 = ProcessElement::Task {
         id: "approve".to_string(),
+        label: None,
         task_type: TaskType::User,
-        attributes: HashMap::new(),
+        attributes: IndexMap::new(),
         span: span.clone(),
     };
 
     let task2 = ProcessElement::Task {
         id: "reject".to_string(),
+        label: None,
         task_type: TaskType::User,
-        attributes: HashMap::new(),
+        attributes: IndexMap::new(),
         span: span.clone(),
     };
 
@@ -268,6 +293,8 @@ fn test_complex_flow_validation() {
         flow_type: FlowType::Default,
         condition: None,
         span: span.clone(),
+        from_span: span.clone(),
+        to_span: span.clone(),
     };
 
     // Обычный поток к второй задаче
@@ -275,15 +302,18 @@ fn test_complex_flow_validation() {
         from: "decision".to_string(),
         to: "reject".to_string(),
         flow_type: FlowType::Sequence,
-        condition: Some("amount > 1000".to_string()),
+        condition: Some(parse_condition_str("amount > 1000").unwrap()),
         span: span.clone(),
+        from_span: span.clone(),
+        to_span: span.clone(),
     };
 
     let process = ProcessDeclaration {
         name: "ApprovalProcess".to_string(),
-        attributes: HashMap::new(),
+        attributes: IndexMap::new(),
         elements: vec![start, gateway, task1, task2],
         flows: vec![default_flow, conditional_flow],
+        variables: vec![],
         span,
     };
 
@@ -291,9 +321,1430 @@ fn test_complex_flow_validation() {
         imports: vec![],
         processes: vec![process],
         errors: vec![],
+        ..AstDocument::default()
     };
 
     let result = validate_syntax(&document);
 
     assert!(result.is_ok());
 }
+
+fn validate_source(input: &str) -> Vec<bpmncode::parser::ast::ParseError> {
+    validate_source_with_profile(input, Profile::Generic)
+}
+
+fn validate_source_with_profile(
+    input: &str,
+    profile: Profile,
+) -> Vec<bpmncode::parser::ast::ParseError> {
+    let mut lexer = Lexer::new(input, "test.bpmn");
+    let tokens = lexer.tokenize();
+    let document = bpmncode::parser::parse_tokens(tokens);
+
+    validate_syntax_with_profile(&document, profile)
+        .err()
+        .unwrap_or_default()
+}
+
+#[test]
+fn test_async_attribute_on_event_warns() {
+    let errors = validate_source(
+        r"
+        process AsyncStart {
+            start @async
+            task T
+            end
+        }
+    ",
+    );
+
+    assert!(errors.iter().any(|e| {
+        e.message.contains("Attribute 'async'")
+            && e.message.contains("start event")
+            && e.severity == ErrorSeverity::Warning
+    }));
+}
+
+#[test]
+fn test_async_attribute_on_task_is_clean() {
+    let errors = validate_source(
+        r"
+        process AsyncTask {
+            start
+            task T (async=true)
+            end
+        }
+    ",
+    );
+
+    assert!(
+        !errors.iter().any(|e| e.message.contains("Attribute")),
+        "Unexpected placement errors: {errors:?}"
+    );
+}
+
+#[test]
+fn test_gateway_and_pool_attributes_are_checked() {
+    let errors = validate_source(
+        r"
+        process Routing {
+            start
+            xor Route (async=true, retries=high) {
+                [express] -> Fast
+                => Slow
+            }
+            pool Shop @asyncBefore {
+                task Fast
+                task Slow
+            }
+            end
+        }
+    ",
+    );
+
+    let messages: Vec<_> = errors
+        .iter()
+        .filter(|e| e.message.contains("Attribute"))
+        .map(|e| e.message.as_str())
+        .collect();
+    assert_eq!(
+        messages,
+        vec![
+            "Attribute 'async' is not valid on a gateway, it only applies to activities (tasks, \
+             subprocesses, call activities)",
+            "Attribute 'retries' expects a number, found a string",
+            "Attribute 'asyncBefore' is not valid on a pool, it only applies to activities \
+             (tasks, subprocesses, call activities)",
+        ]
+    );
+}
+
+#[test]
+fn test_loop_markers() {
+    let errors = validate_source(
+        r#"
+        process Billing {
+            start
+            call Payment (multiInstance=true, sequential=false)
+            task Retry (loop=true)
+            task Both (loop=true, multiInstance=true)
+            service Notify (sequential=true, multiInstance="yes")
+            Payment -> Retry -> Both -> Notify
+            end
+        }
+        process Payment {
+            start
+            end
+        }
+    "#,
+    );
+
+    let messages: Vec<_> = errors
+        .iter()
+        .filter(|e| e.message.contains("loop") || e.message.contains("multiInstance"))
+        .map(|e| (e.message.as_str(), e.severity.clone()))
+        .collect();
+    assert_eq!(
+        messages,
+        vec![
+            (
+                "Activity 'Both' cannot be both a loop and multi-instance, keep either `loop` or \
+                 `multiInstance`",
+                ErrorSeverity::Error
+            ),
+            (
+                "Attribute 'multiInstance' expects a boolean, found a string",
+                ErrorSeverity::Error
+            ),
+            (
+                "Attribute 'sequential' on 'Notify' has no effect without `multiInstance=true`",
+                ErrorSeverity::Warning
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_profiles_parse_from_their_names() {
+    for profile in Profile::ALL {
+        assert_eq!(profile.name().parse::<Profile>(), Ok(*profile));
+    }
+    assert_eq!("Zeebe".parse::<Profile>(), Ok(Profile::Zeebe));
+    assert!("camunda".parse::<Profile>().is_err());
+}
+
+#[test]
+fn test_zeebe_profile_requires_task_definition() {
+    let input = r#"
+        process Payment {
+            start
+            service Charge
+            service Refund @zeebe:taskDefinition "refund"
+            end
+        }
+    "#;
+
+    let zeebe_errors = validate_source_with_profile(input, Profile::Zeebe);
+    let charge_warnings: Vec<_> = zeebe_errors
+        .iter()
+        .filter(|e| e.message.contains("zeebe:taskDefinition"))
+        .collect();
+
+    assert_eq!(charge_warnings.len(), 1, "Errors: {zeebe_errors:?}");
+    assert!(charge_warnings[0].message.contains("'Charge'"));
+    assert_eq!(charge_warnings[0].severity, ErrorSeverity::Warning);
+
+    let generic_errors = validate_source(input);
+    assert!(
+        !generic_errors
+            .iter()
+            .any(|e| e.message.contains("zeebe:taskDefinition")),
+        "Unexpected engine errors: {generic_errors:?}"
+    );
+}
+
+#[test]
+fn test_unknown_gateway_target_is_reported() {
+    let errors = validate_source(
+        r"
+        process Review {
+            start
+            task Approve
+            task Reject
+            xor Decision {
+                [yes] -> Aprove
+                [no] -> Reject
+            }
+            end
+        }
+    ",
+    );
+
+    let unknown: Vec<_> = errors
+        .iter()
+        .filter(|e| e.message.contains("Unknown gateway target"))
+        .collect();
+
+    assert_eq!(unknown.len(), 1, "Errors: {errors:?}");
+    assert!(unknown[0].message.contains("'Aprove'"));
+    assert_eq!(unknown[0].severity, ErrorSeverity::Error);
+    assert_eq!(unknown[0].span.line, 7);
+}
+
+#[test]
+fn test_unreachable_element_warns() {
+    let errors = validate_source(
+        r"
+        process Orders {
+            start
+            task Receive
+            task Ship
+            end
+            task Forgotten
+
+            Receive -> Ship
+            Ship -> end
+        }
+    ",
+    );
+
+    let unreachable: Vec<_> = errors
+        .iter()
+        .filter(|e| e.message.contains("is unreachable"))
+        .collect();
+
+    assert_eq!(unreachable.len(), 1, "Errors: {errors:?}");
+    assert!(unreachable[0].message.contains("'Forgotten'"));
+    assert_eq!(unreachable[0].severity, ErrorSeverity::Warning);
+}
+
+#[test]
+fn test_pool_elements_reached_through_flows() {
+    let errors = validate_source(
+        r"
+        process Support {
+            start
+            task Triage
+            pool Company {
+                lane Engineering {
+                    task Fix
+                }
+            }
+            end
+
+            Triage -> Fix
+            Fix -> end
+        }
+    ",
+    );
+
+    assert!(
+        !errors.iter().any(|e| e.message.contains("is unreachable")),
+        "Unexpected reachability errors: {errors:?}"
+    );
+}
+
+#[test]
+fn test_reachability_skipped_when_parsing_failed() {
+    let errors = validate_source(
+        r"
+        process Broken {
+            start
+            xor Route? {
+                [ok] Ship
+                => Hold
+            }
+            task Ship
+            task Hold
+            end
+            Ship -> end
+            Hold -> end
+        }
+    ",
+    );
+
+    assert!(
+        !errors.iter().any(|e| e.message.contains("is unreachable")
+            || e.message.contains("cannot reach an end event")),
+        "Unexpected flow errors: {errors:?}"
+    );
+}
+
+#[test]
+fn test_reachability_skipped_without_start_event() {
+    let errors = validate_source(
+        r"
+        process Fragment {
+            task Alone
+            task Other
+        }
+    ",
+    );
+
+    assert!(!errors.iter().any(|e| e.message.contains("is unreachable")));
+}
+
+#[test]
+fn test_gateway_looping_forever_cannot_reach_end() {
+    let errors = validate_source(
+        r"
+        process Retry {
+            start
+            task Work
+            xor Again {
+                [failed] -> Work
+                [flaky] -> Work
+            }
+            end
+
+            Work -> Again
+        }
+    ",
+    );
+
+    let stuck: Vec<_> = errors
+        .iter()
+        .filter(|e| e.message.contains("cannot reach an end event"))
+        .map(|e| e.message.as_str())
+        .collect();
+
+    assert_eq!(
+        stuck,
+        vec![
+            "Element 'start' cannot reach an end event",
+            "Element 'Work' cannot reach an end event",
+            "Element 'Again' cannot reach an end event",
+        ]
+    );
+}
+
+#[test]
+fn test_loop_without_end_event_cannot_terminate() {
+    let errors = validate_source(
+        r"
+        process Loop {
+            start
+            task A
+            task B
+            B -> A
+        }
+
+        process Open {
+            start
+            task Ship
+        }
+    ",
+    );
+
+    let stuck: Vec<_> = errors
+        .iter()
+        .filter(|e| e.message.contains("cannot reach an end event"))
+        .map(|e| e.message.as_str())
+        .collect();
+
+    assert_eq!(
+        stuck,
+        vec![
+            "Element 'start' cannot reach an end event",
+            "Element 'A' cannot reach an end event",
+            "Element 'B' cannot reach an end event",
+        ]
+    );
+}
+
+#[test]
+fn test_flow_to_end_keyword_terminates_without_end_event() {
+    let errors = validate_source(
+        r"
+        process Quick {
+            start
+            task Done
+
+            Done -> end
+        }
+    ",
+    );
+
+    assert!(
+        !errors
+            .iter()
+            .any(|e| e.message.contains("cannot reach an end event")),
+        "Unexpected termination errors: {errors:?}"
+    );
+}
+
+#[test]
+fn test_gateway_with_several_default_branches() {
+    let errors = validate_source(
+        r"
+        process Routing {
+            start
+            xor Route {
+                [express] -> Fast
+                => Slow
+                => Manual
+            }
+            task Fast
+            task Slow
+            task Manual
+            end
+        }
+    ",
+    );
+
+    let error = errors
+        .iter()
+        .find(|e| e.message.contains("default branches"))
+        .unwrap();
+    assert_eq!(
+        error.message,
+        "Gateway 'Route' has 2 default branches, at most one allowed"
+    );
+    assert_eq!(error.severity, ErrorSeverity::Error);
+    assert_eq!(error.span.line, 4);
+}
+
+#[test]
+fn test_exclusive_gateway_without_default_warns_once() {
+    let mut lexer = Lexer::new(
+        r"
+        process Routing {
+            start
+            xor Route {
+                [express] -> Fast
+                [standard] -> Slow
+            }
+            and Split {
+                [left] -> Fast
+                [right] -> Slow
+            }
+            task Fast
+            task Slow
+            end
+        }
+    ",
+        "test.bpmn",
+    );
+    let mut document = bpmncode::parser::parse_tokens(lexer.tokenize());
+
+    // Sources always give a branch a condition or make it the default, an unconditional
+    // one only comes from building the tree directly
+    let ProcessElement::Gateway { branches, .. } = &mut document.processes[0].elements[1] else {
+        panic!("Expected gateway");
+    };
+    branches[1].condition = None;
+
+    let errors = validate_syntax(&document).err().unwrap_or_default();
+    let notes: Vec<_> = errors
+        .iter()
+        .filter(|e| e.message.contains("no default"))
+        .collect();
+    assert_eq!(notes.len(), 1, "Errors: {errors:?}");
+    assert!(notes[0].message.starts_with("Exclusive gateway 'Route'"));
+    assert_eq!(notes[0].severity, ErrorSeverity::Warning);
+    assert_eq!(notes[0].lint, "missing-default");
+}
+
+#[test]
+fn test_exclusive_gateway_with_only_conditions_warns_of_deadlock() {
+    let errors = validate_source(
+        r"
+        process Routing {
+            start
+            xor Route {
+                [express] -> Fast
+                [standard] -> Slow
+            }
+            xor Retry {
+                [failed] -> Fast
+                => Slow
+            }
+            and Split {
+                [left] -> Fast
+                [right] -> Slow
+            }
+            task Fast
+            task Slow
+            end
+        }
+    ",
+    );
+
+    let notes: Vec<_> = errors
+        .iter()
+        .filter(|e| e.message.contains("no default"))
+        .map(|e| (e.message.as_str(), e.severity.clone(), e.lint))
+        .collect();
+    assert_eq!(
+        notes,
+        vec![(
+            "Exclusive gateway 'Route' has no default branch, add `=> Fallback` to avoid a \
+             deadlock when no condition matches",
+            ErrorSeverity::Warning,
+            "missing-default"
+        )],
+        "Errors: {errors:?}"
+    );
+}
+
+#[test]
+fn test_data_objects_connect_through_associations() {
+    let errors = validate_source(
+        r"
+        process Orders {
+            start
+            task Receive
+            data OrderForm
+            task Ship
+            end
+
+            Receive ..> OrderForm
+            OrderForm -> Ship
+        }
+    ",
+    );
+
+    let warnings: Vec<_> = errors
+        .iter()
+        .filter(|e| e.message.contains("OrderForm"))
+        .map(|e| (e.message.as_str(), e.span.line))
+        .collect();
+    assert_eq!(
+        warnings,
+        vec![(
+            "Data object 'OrderForm' can only be connected with an association `..>`",
+            10
+        )]
+    );
+}
+
+#[test]
+fn test_associations_attach_notes_and_data() {
+    let errors = validate_source(
+        r#"
+        process Orders {
+            start
+            task Receive
+            task Ship
+            note Rules "Fragile first"
+            note "Unnamed"
+            data OrderForm
+            end
+
+            Ship ..> Rules
+            Receive ..> OrderForm
+            Receive ..> Ship
+            Rules ..> OrderForm
+            Ship -> Rules
+        }
+    "#,
+    );
+
+    let warnings: Vec<_> = errors
+        .iter()
+        .filter(|e| e.message.contains("..>") || e.message.contains("Unknown"))
+        .map(|e| (e.message.as_str(), e.span.line))
+        .collect();
+    assert_eq!(
+        warnings,
+        vec![
+            (
+                "Association Receive ..> Ship must connect an element to a note or data object",
+                13
+            ),
+            (
+                "Association Rules ..> OrderForm must connect an element to a note or data object",
+                14
+            ),
+            (
+                "Note 'Rules' can only be connected with an association `..>`",
+                15
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_flows_into_start_and_out_of_end_events() {
+    let errors = validate_source(
+        r#"
+        process Orders {
+            start
+            task Ship
+            end
+            pool Partner {
+                start @message "Order"
+            }
+
+            Ship -> start
+            end -> Ship
+            Ship --> start
+        }
+    "#,
+    );
+
+    let misplaced: Vec<_> = errors
+        .iter()
+        .filter(|e| e.message.contains("event cannot have"))
+        .map(|e| (e.message.as_str(), e.span.line, &e.severity))
+        .collect();
+    assert_eq!(
+        misplaced,
+        vec![
+            (
+                "Start event cannot have incoming flow",
+                10,
+                &ErrorSeverity::Error
+            ),
+            (
+                "End event cannot have outgoing flow",
+                11,
+                &ErrorSeverity::Error
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_flows_into_start_or_out_of_end_report_one_error() {
+    let errors = validate_source(
+        r"
+        process Orders {
+            start
+            task Ship
+            end
+
+            Ship -> start
+            end -> Ship
+        }
+    ",
+    );
+
+    let reported: Vec<_> = errors
+        .iter()
+        .filter(|e| e.severity == ErrorSeverity::Error)
+        .map(|e| (e.message.as_str(), e.span.line))
+        .collect();
+    assert_eq!(
+        reported,
+        vec![
+            ("Start event cannot have incoming flow", 7),
+            ("End event cannot have outgoing flow", 8),
+        ]
+    );
+}
+
+#[test]
+fn test_inclusive_gateway_defaults() {
+    let errors = validate_source(
+        r"
+        process Shipping {
+            start
+            or Options {
+                [giftWrap] -> Wrap
+                [express] -> Courier
+            }
+            or Extras {
+                [insured] -> Wrap
+                => Courier
+                => Wrap
+            }
+            task Wrap
+            task Courier
+            end
+        }
+    ",
+    );
+
+    assert!(
+        !errors.iter().any(|e| e.message.contains("no default")),
+        "Errors: {errors:?}"
+    );
+    let error = errors
+        .iter()
+        .find(|e| e.message.contains("default branches"))
+        .unwrap();
+    assert_eq!(
+        error.message,
+        "Gateway 'Extras' has 2 default branches, at most one allowed"
+    );
+}
+
+#[test]
+fn test_conditions_on_parallel_branches_are_ignored() {
+    let input = r"
+        process Fulfilment {
+            start
+            and Split {
+                [in_stock] -> Pick
+            }
+            task Pick
+            task Invoice
+            end
+
+            Split -> Invoice
+        }
+    ";
+
+    let document = bpmncode::parser::parse_tokens(Lexer::new(input, "test.bpmn").tokenize());
+    assert!(document.errors.is_empty(), "{:?}", document.errors);
+
+    let errors = validate_source(input);
+    let warnings: Vec<_> = errors
+        .iter()
+        .filter(|e| e.message == "Condition on parallel gateway branch is ignored")
+        .collect();
+    assert_eq!(warnings.len(), 1, "Errors: {errors:?}");
+    assert_eq!(warnings[0].severity, ErrorSeverity::Warning);
+    assert_eq!(warnings[0].span.line, 5);
+}
+
+#[test]
+fn test_duplicate_flows_are_reported_once() {
+    let errors = validate_source(
+        r"
+        process Shipping {
+            start
+            task Pack
+            task Ship
+            task Audit
+            end
+
+            Pack -> Ship
+            Pack -> Ship
+            Ship -> Audit [express]
+            Ship -> Audit [standard]
+            Audit -> end
+        }
+    ",
+    );
+
+    let duplicates: Vec<_> = errors
+        .iter()
+        .filter(|e| e.message.starts_with("Duplicate flow"))
+        .collect();
+
+    assert_eq!(duplicates.len(), 1, "Errors: {errors:?}");
+    assert_eq!(
+        duplicates[0].message,
+        "Duplicate flow Pack -> Ship (first declared at line 9, column 13)"
+    );
+    assert_eq!(duplicates[0].severity, ErrorSeverity::Warning);
+    assert_eq!(duplicates[0].span.line, 10);
+}
+
+#[test]
+fn test_self_loop_flows() {
+    let errors = validate_source(
+        r"
+        process Retry {
+            start
+            task Attempt
+            task Check
+            task Poll
+            end
+
+            Attempt -> Check
+            Check -> Attempt
+            Poll -> Poll
+            Check -> end
+        }
+    ",
+    );
+
+    let loops: Vec<_> = errors.iter().filter(|e| e.lint == "self-loop").collect();
+
+    assert_eq!(loops.len(), 1, "Errors: {errors:?}");
+    assert_eq!(loops[0].message, "Self-loop on element 'Poll'");
+    assert_eq!(loops[0].severity, ErrorSeverity::Warning);
+}
+
+#[test]
+fn test_attribute_values_are_type_checked() {
+    let errors = validate_source(
+        r#"
+        process Billing {
+            start
+            service Charge(timeout="soon", retries=true, async=false)
+            task Refund(timeout=30s, retries=3)
+            end
+        }
+    "#,
+    );
+
+    let mismatches: Vec<_> = errors
+        .iter()
+        .filter(|e| e.message.contains("expects"))
+        .map(|e| (e.message.as_str(), e.span.line))
+        .collect();
+
+    assert_eq!(
+        mismatches,
+        vec![
+            ("Attribute 'retries' expects a number, found a boolean", 4),
+            ("Attribute 'timeout' expects a duration, found a string", 4),
+        ]
+    );
+}
+
+#[test]
+fn test_invalid_timer_duration() {
+    let errors = validate_source(
+        r"
+        process Reminder {
+            start @timer 5m
+            event @timer soon
+            end
+        }
+    ",
+    );
+
+    let timer_errors: Vec<_> = errors
+        .iter()
+        .filter(|e| e.message.contains("timer duration"))
+        .collect();
+
+    assert_eq!(timer_errors.len(), 1, "Errors: {errors:?}");
+    assert!(timer_errors[0].message.contains("'soon'"));
+    assert_eq!(timer_errors[0].span.line, 4);
+}
+
+#[test]
+fn test_subprocess_scoping_of_flow_targets() {
+    let errors = validate_source(
+        r"
+        process Outer {
+            start
+            task OuterTask
+            subprocess InnerSub {
+                start
+                task InnerTask
+                task Shared
+                InnerTask -> Missing
+                end
+            }
+            subprocess OtherSub {
+                start
+                task Shared
+                end
+            }
+            pool Team {
+                lane Support {
+                    task Answer
+                }
+            }
+            OuterTask -> InnerSub
+            OuterTask -> InnerTask
+            OuterTask -> Answer
+            end
+        }
+    ",
+    );
+
+    let unknown: Vec<_> = errors
+        .iter()
+        .filter(|e| e.message.starts_with("Unknown flow"))
+        .map(|e| e.message.as_str())
+        .collect();
+    assert_eq!(
+        unknown,
+        vec![
+            "Unknown flow target: 'Missing'",
+            "Unknown flow target: 'InnerTask'",
+        ]
+    );
+    assert!(
+        !errors
+            .iter()
+            .any(|e| e.message.contains("Duplicate node id")),
+        "Ids in sibling subprocesses should not clash: {errors:?}"
+    );
+}
+
+#[test]
+fn test_unused_imports() {
+    let errors = validate_source(
+        r#"
+        import Billing, Shipping from "common.bpmn"
+        import "payments.bpmn" as pay
+        import "audit.bpmn" as audit
+
+        process Order {
+            start
+            call Billing
+            subprocess Settle {
+                start
+                call pay::Charge
+                end
+            }
+            end
+        }
+    "#,
+    );
+
+    let unused: Vec<_> = errors
+        .iter()
+        .filter(|e| e.message.starts_with("Unused import"))
+        .map(|e| (e.message.as_str(), e.span.line, e.severity.clone()))
+        .collect();
+
+    assert_eq!(
+        unused,
+        vec![
+            ("Unused import 'Shipping'", 2, ErrorSeverity::Warning),
+            ("Unused import 'audit'", 4, ErrorSeverity::Warning),
+        ]
+    );
+}
+
+#[test]
+fn test_multiple_start_events_are_reported_as_info() {
+    let errors = validate_source(
+        r"
+        process Intake {
+            start
+            task Email
+            end
+            start
+            task Phone
+            end
+        }
+    ",
+    );
+
+    let diagnostics: Vec<_> = errors
+        .iter()
+        .map(|e| (e.message.as_str(), e.severity.clone()))
+        .collect();
+
+    assert_eq!(
+        diagnostics,
+        vec![(
+            "Process 'Intake' has 2 start events, each one begins a separate path",
+            ErrorSeverity::Info
+        )]
+    );
+}
+
+#[test]
+fn test_flows_cannot_connect_containers() {
+    let errors = validate_source(
+        r"
+        process Order {
+            start
+            pool Customer {
+                lane Online {
+                    task PlaceOrder
+                }
+            }
+            pool Supplier {
+                task CheckStock
+            }
+            PlaceOrder -> Supplier
+            Online -> CheckStock
+            PlaceOrder -> CheckStock
+            PlaceOrder --> Supplier
+            end
+        }
+    ",
+    );
+
+    let messages: Vec<_> = errors
+        .iter()
+        .filter(|e| e.severity == ErrorSeverity::Error)
+        .map(|e| (e.message.as_str(), e.span.line))
+        .collect();
+
+    assert_eq!(
+        messages,
+        vec![
+            ("Cannot draw a flow to container 'Supplier'", 12),
+            ("Cannot draw a flow from container 'Online'", 13),
+            (
+                "Sequence flow PlaceOrder -> CheckStock crosses from pool 'Customer' to pool 'Supplier', use a message flow `-->`",
+                14
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_event_subprocess_takes_no_incoming_flows() {
+    let errors = validate_source(
+        r#"
+        process Orders {
+            start
+            task Ship
+            end
+
+            event subprocess HandleFailure @error "E1" {
+                start @error "E1"
+                task Notify
+                end
+            }
+
+            Ship -> end
+            Ship -> HandleFailure
+        }
+    "#,
+    );
+
+    let warnings: Vec<_> = errors
+        .iter()
+        .filter(|e| e.severity == ErrorSeverity::Warning)
+        .map(|e| (e.message.as_str(), e.span.line))
+        .collect();
+
+    assert_eq!(
+        warnings,
+        vec![(
+            "Event subprocess 'HandleFailure' cannot have incoming flows, it starts from its trigger",
+            14
+        )]
+    );
+}
+
+#[test]
+fn test_gateway_mode_must_match_flow_directions() {
+    let errors = validate_source(
+        r"
+        process Orders {
+            start
+            and Fork {
+                => Pack
+                => Bill
+            }
+            task Pack
+            task Bill
+            xor Merge {
+                => Ship
+            }
+            and Done join {
+                => Ship
+                => Pack
+            }
+            task Ship
+            end
+
+            Pack -> Merge
+            Bill -> Merge
+            Ship -> Done
+        }
+    ",
+    );
+
+    let warnings: Vec<_> = errors
+        .iter()
+        .filter(|e| e.message.contains("or `mixed`"))
+        .map(|e| (e.message.as_str(), e.span.line))
+        .collect();
+
+    assert_eq!(
+        warnings,
+        vec![
+            (
+                "Split gateway 'Merge' has 2 incoming flows, mark it `join` or `mixed`",
+                10
+            ),
+            (
+                "Join gateway 'Done' has 2 outgoing flows, mark it `split` or `mixed`",
+                13
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_recovery_placeholders_are_not_reported_again() {
+    let errors = validate_source(
+        r"
+        process Orders {
+            start
+            task Review
+            task (retries=3)
+            xor Approved {
+                [ok] ->
+            }
+            end
+            start -> Review
+            Review -> Approved
+        }
+    ",
+    );
+
+    let messages: Vec<_> = errors.iter().map(|e| e.message.as_str()).collect();
+    assert!(
+        messages
+            .iter()
+            .all(|message| !message.contains("Task_") && !message.contains("UnknownTarget_")),
+        "{messages:?}"
+    );
+}
+
+#[test]
+fn test_namespaced_flow_endpoints_are_left_to_the_resolver() {
+    let errors = validate_source(
+        r#"
+        import "validation.bpmn" as validation
+
+        process Orders {
+            start
+            task Receive
+            xor Valid? {
+                [ok] -> validation::CheckData
+                => Receive
+            }
+            Receive -> Valid
+            validation::CheckData -> end
+        }
+    "#,
+    );
+
+    assert!(
+        errors.iter().all(|e| !e.message.contains("validation")),
+        "{errors:?}"
+    );
+}
+
+#[test]
+fn test_element_shadowing_process_or_subprocess_name() {
+    let errors = validate_source(
+        r"
+        process OrderFlow {
+            start
+            task Billing
+            subprocess Review {
+                start
+                task Review
+                end
+            }
+            end
+        }
+
+        process Billing {
+            start
+            call OrderFlow
+            end
+        }
+    ",
+    );
+
+    let shadowing: Vec<_> = errors
+        .iter()
+        .filter(|e| e.message.contains("shadows"))
+        .map(|e| (e.message.as_str(), e.span.line, &e.severity))
+        .collect();
+    assert_eq!(
+        shadowing,
+        vec![
+            (
+                "Element 'Billing' shadows process 'Billing'",
+                4,
+                &ErrorSeverity::Warning
+            ),
+            (
+                "Element 'Review' shadows subprocess 'Review'",
+                7,
+                &ErrorSeverity::Warning
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_empty_process_and_gateway_blocks() {
+    let errors = validate_source(
+        r"
+        process Empty {}
+
+        process Stubs {
+            start
+            xor Stub {}
+            xor Broken {
+                [ok] ->
+            }
+            and Merge join
+            xor Routed {}
+            pool Partner {
+                or Waiting {}
+            }
+            end
+
+            Routed -> end
+        }
+    ",
+    );
+
+    let empty: Vec<_> = errors
+        .iter()
+        .filter(|e| e.message.contains("is empty") || e.message.contains("no branches"))
+        .map(|e| (e.message.as_str(), e.span.line, &e.severity))
+        .collect();
+    assert_eq!(
+        empty,
+        vec![
+            ("Gateway 'Stub' has no branches", 6, &ErrorSeverity::Warning),
+            (
+                "Gateway 'Waiting' has no branches",
+                13,
+                &ErrorSeverity::Warning
+            ),
+            ("Process 'Empty' is empty", 2, &ErrorSeverity::Warning),
+        ]
+    );
+    assert!(!errors.iter().any(|e| {
+        e.message
+            .contains("'Empty' must contain at least one start event")
+    }));
+}
+
+#[test]
+fn test_conditional_event_needs_an_expression() {
+    let errors = validate_source(
+        r#"
+        process Restock {
+            start @conditional
+            event @conditional "  "
+            event @conditional "stock < 10"
+            end
+        }
+    "#,
+    );
+
+    let lines: Vec<_> = errors
+        .iter()
+        .filter(|e| e.message.starts_with("Conditional event needs a condition"))
+        .map(|e| (e.span.line, &e.severity))
+        .collect();
+    assert_eq!(
+        lines,
+        vec![(3, &ErrorSeverity::Error), (4, &ErrorSeverity::Error)]
+    );
+}
+
+#[test]
+fn test_element_in_two_lanes_of_a_pool() {
+    let errors = validate_source(
+        r"
+        process Order {
+            pool Shop {
+                lane Sales {
+                    task Quote
+                }
+                lane Billing {
+                    task Invoice
+                    task Quote
+                }
+            }
+            Quote -> Invoice
+        }
+    ",
+    );
+
+    let messages: Vec<_> = errors
+        .iter()
+        .filter(|e| e.severity == ErrorSeverity::Error)
+        .map(|e| (e.message.as_str(), e.span.line, e.span.column))
+        .collect();
+
+    assert_eq!(
+        messages,
+        vec![("Element 'Quote' belongs to multiple lanes", 9, 21)]
+    );
+}
+
+#[test]
+fn test_direct_pool_element_also_in_a_lane() {
+    let errors = validate_source(
+        r"
+        process Order {
+            pool Shop {
+                lane Sales {
+                    task Quote
+                }
+                task Invoice
+                task Quote
+            }
+            pool Bank {
+                task Settle
+                task Settle
+            }
+            Quote -> Invoice
+        }
+    ",
+    );
+
+    let messages: Vec<_> = errors
+        .iter()
+        .filter(|e| e.severity == ErrorSeverity::Error)
+        .map(|e| (e.message.as_str(), e.span.line, e.span.column))
+        .collect();
+
+    assert_eq!(
+        messages,
+        vec![
+            ("Element 'Quote' belongs to multiple lanes", 8, 17),
+            ("Duplicate node id 'Settle'", 12, 17),
+        ]
+    );
+}
+
+#[test]
+fn test_message_and_sequence_flows_respect_pool_boundaries() {
+    let errors = validate_source(
+        r"
+        process Order {
+            pool Customer {
+                lane Online {
+                    task PlaceOrder
+                }
+                task Pay
+            }
+            pool Supplier {
+                task CheckStock
+                task Ship
+            }
+            task Archive
+
+            PlaceOrder --> CheckStock
+            PlaceOrder --> Pay
+            PlaceOrder -> Pay
+            Pay -> Ship
+            Ship --> Customer
+            Ship -> Archive
+        }
+    ",
+    );
+
+    let messages: Vec<_> = errors
+        .iter()
+        .filter(|e| e.message.contains(" pool '"))
+        .map(|e| (e.message.as_str(), e.span.line, &e.severity))
+        .collect();
+
+    assert_eq!(
+        messages,
+        vec![
+            (
+                "Message flow PlaceOrder --> Pay stays inside pool 'Customer', use a sequence flow `->`",
+                16,
+                &ErrorSeverity::Warning
+            ),
+            (
+                "Sequence flow Pay -> Ship crosses from pool 'Customer' to pool 'Supplier', use a message flow `-->`",
+                18,
+                &ErrorSeverity::Error
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_subprocess_needs_its_own_start_and_end() {
+    let errors = validate_source(
+        r#"
+        process Order {
+            start -> Fulfil -> Close -> end
+            subprocess Fulfil {
+                task Pick
+                task Pack
+                Pick -> Pack
+            }
+            subprocess Close {
+                start -> Archive
+                task Archive
+                Archive -> end
+            }
+            event subprocess Cancel @error "E1" {
+                start
+                task Refund
+                start -> Refund
+            }
+            subprocess Later {
+            }
+        }
+    "#,
+    );
+
+    let messages: Vec<_> = errors
+        .iter()
+        .filter(|e| e.message.contains("at least one") || e.message.contains("is empty"))
+        .map(|e| (e.message.as_str(), e.span.line))
+        .collect();
+    assert_eq!(
+        messages,
+        vec![
+            (
+                "Subprocess 'Fulfil' must contain at least one start event",
+                4
+            ),
+            ("Subprocess 'Fulfil' must contain at least one end event", 4),
+            (
+                "Event subprocess 'Cancel' must contain at least one end event",
+                14
+            ),
+            ("Subprocess 'Later' is empty", 19),
+        ]
+    );
+}
+
+#[test]
+fn test_group_label_does_not_hide_an_element_of_the_same_name() {
+    let errors = validate_source(
+        r#"
+        process Order {
+            start
+            group "Review" {
+                task Review
+            }
+            start -> Review
+            end
+        }
+    "#,
+    );
+
+    assert!(
+        errors.iter().all(|e| e.severity != ErrorSeverity::Error),
+        "{errors:?}"
+    );
+}